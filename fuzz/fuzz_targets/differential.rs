@@ -0,0 +1,17 @@
+#![no_main]
+
+use chumsky::{error::Cheap, Parser};
+use eberban::{
+    lexer::{lexer, preprocess},
+    parser::ParserBackend,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    let Ok(words) = lexer::<Cheap<_>>().parse(preprocess(s)) else {
+        return;
+    };
+    let combinator = ParserBackend::Combinator.parse_sentence(words.clone()).ok();
+    let handwritten = ParserBackend::Handwritten.parse_sentence(words).ok();
+    assert_eq!(combinator, handwritten);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use chumsky::{error::Cheap, Parser};
+use eberban::{
+    dfa,
+    lexer::{preprocess, spanned_lexer},
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    let combinator = spanned_lexer::<Cheap<_>>().parse(preprocess(s)).ok();
+    assert_eq!(combinator, dfa::lex(s));
+});
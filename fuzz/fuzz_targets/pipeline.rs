@@ -0,0 +1,20 @@
+#![no_main]
+
+use chumsky::{error::Cheap, Parser};
+use eberban::{
+    expr::to_expr,
+    lexer::{lexer, preprocess},
+    parser::parser,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    let Ok(words) = lexer::<Cheap<_>>().parse(preprocess(s)) else {
+        return;
+    };
+    let Ok(tree) = parser::<Cheap<_>>().parse(words) else {
+        return;
+    };
+    let (expr, _) = to_expr(tree);
+    let _ = expr.to_string();
+});
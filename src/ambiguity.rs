@@ -0,0 +1,133 @@
+//! Runs of letters written without pauses that spell other sequences of words than the one the
+//! lexer reads, so that writers can add pauses where the text is genuinely ambiguous.
+//!
+//! The lexer reads a run in a single way, since words starting with a vowel or a sonorant can only
+//! follow a pause. Where such a word was meant but its pause forgotten, the letters are silently
+//! read as other words instead: `kavdanakla` reads as `kavdana kla` whether `kavda'na'kla` or
+//! `kavdan'a'kla` was meant. An ambiguity is such a reading of a run, with pauses added, that has a
+//! predicate the lexer does not read, so that the boundary between a predicate and a particle
+//! moves.
+
+use std::ops::Range;
+
+use crate::{
+    dfa,
+    lexer::{
+        assemble_spanned_numbers, lex_with_warnings, LexError, ParticleFamily, SpannedWords, Word,
+    },
+};
+
+/// The largest number of other readings reported for a run of letters.
+pub const MAX_ALTERNATIVES: usize = 8;
+
+/// A run of letters without pauses and the ways to read it other than the lexer's. Spans are in
+/// characters of the text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ambiguity {
+    pub span: Range<usize>,
+    /// The words the lexer reads.
+    pub read: SpannedWords,
+    /// Other sequences of words spelled by the same letters once pauses are added before the
+    /// words needing them, at most [`MAX_ALTERNATIVES`].
+    pub alternatives: Vec<SpannedWords>,
+}
+
+/// The single word `text` lexes as after a pause, if it lexes as one.
+fn lex_one(text: &str) -> Option<Word> {
+    let mut words = dfa::lex(&format!(" {text}"))?.into_iter();
+    match (words.next(), words.next()) {
+        (Some((word, _)), None) => Some(word),
+        _ => None,
+    }
+}
+
+/// The ambiguities of `s`, in the order of their spans, or why it fails to lex. Runs containing
+/// a quote are not checked, as the lexer reads quotes before anything else, and particles the
+/// lexer does not classify are not read in other sequences.
+pub fn ambiguities(s: &str) -> Result<Vec<Ambiguity>, Vec<LexError>> {
+    let (words, _) = lex_with_warnings(s)?;
+    let chars: Vec<char> = s.chars().collect();
+
+    // The words of each run, between pauses.
+    let mut runs: Vec<SpannedWords> = Vec::new();
+    for (word, span) in words {
+        match runs.last_mut() {
+            Some(run) if run.last().is_some_and(|(_, last)| last.end == span.start) => {
+                run.push((word, span))
+            }
+            _ => runs.push(vec![(word, span)]),
+        }
+    }
+
+    let mut ambiguities = Vec::new();
+    for read in runs {
+        if read
+            .iter()
+            .any(|(word, _)| matches!(word, Word::Particle(ParticleFamily::Quote(_))))
+        {
+            continue;
+        }
+        let (Some((_, first)), Some((_, last))) = (read.first(), read.last()) else {
+            continue;
+        };
+        let span = first.start..last.end;
+        let letters = chars.get(span.clone()).unwrap_or_default();
+        let mut alternatives = Vec::new();
+        readings(
+            letters,
+            span.start,
+            &mut Vec::new(),
+            &mut |reading: &SpannedWords| {
+                let reading = assemble_spanned_numbers(reading.clone());
+                let new_predicate = reading
+                    .iter()
+                    .any(|w| matches!(w.0, Word::Predicate(..)) && !read.contains(w));
+                if new_predicate && !alternatives.contains(&reading) {
+                    alternatives.push(reading);
+                }
+                alternatives.len() < MAX_ALTERNATIVES
+            },
+        );
+        if !alternatives.is_empty() {
+            ambiguities.push(Ambiguity {
+                span,
+                read,
+                alternatives,
+            });
+        }
+    }
+    Ok(ambiguities)
+}
+
+/// Calls `found` with every reading of `letters`, starting at `offset` in the text, as words
+/// following `words`, until `found` returns false. Returns false if it did.
+fn readings(
+    letters: &[char],
+    offset: usize,
+    words: &mut SpannedWords,
+    found: &mut impl FnMut(&SpannedWords) -> bool,
+) -> bool {
+    let start = words.last().map_or(offset, |(_, span)| span.end);
+    if start - offset == letters.len() {
+        return found(words);
+    }
+    for end in start + 1..=offset + letters.len() {
+        let text: String = letters[start - offset..end - offset].iter().collect();
+        let Some(word) = lex_one(&text) else {
+            continue;
+        };
+        if matches!(
+            word,
+            Word::Particle(ParticleFamily::Quote(_) | ParticleFamily::Other(_))
+        ) {
+            continue;
+        }
+        words.push((word, start..end));
+        let more = readings(letters, offset, words, found);
+        words.pop();
+        if !more {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,77 @@
+//! Sentences whose elided terminators leave open what their predicates attach to, so that
+//! writers can add `vei` or `pei` where the parse is not the one they meant.
+//!
+//! The parser only closes a `vi` or `fi` clause or a `pe` group when the words after it cannot
+//! continue it, so a predicate after an elided terminator attaches to the innermost clause or
+//! group: `mi dona vi mian vi tcu` chains `tcu` to `mian` rather than to `dona`. A reading is a
+//! parse with terminators written out earlier than the parser elides them, giving a different
+//! predicate tree.
+
+use std::collections::VecDeque;
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    lexer::{ParticleFamily, Word},
+    parser::{parser, PredicateTree},
+};
+
+/// The largest number of other readings reported for a sentence.
+pub const MAX_READINGS: usize = 8;
+
+/// The elidable terminators whose placement changes what predicates attach to.
+const TERMINATORS: [ParticleFamily; 2] = [ParticleFamily::Vei, ParticleFamily::Pei];
+
+/// A parse of a sentence other than the parser's, made explicit by inserting terminators.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reading {
+    /// The words of the sentence with the terminators inserted.
+    pub words: Vec<Word>,
+    /// The positions of the inserted terminators among `words`, in increasing order.
+    pub inserted: Vec<usize>,
+    pub tree: PredicateTree,
+}
+
+/// The readings of `words` that differ from the parser's and from each other, at most
+/// [`MAX_READINGS`], with the fewest inserted terminators first. A sentence that does not parse
+/// has none.
+pub fn readings(words: &[Word]) -> Vec<Reading> {
+    let parser = parser::<Cheap<Word>>();
+    let Ok(tree) = parser.parse(words.to_vec()) else {
+        return Vec::new();
+    };
+    let mut trees = vec![tree];
+    let mut readings = Vec::new();
+    let mut queue = VecDeque::from([(words.to_vec(), Vec::new())]);
+    while let Some((words, inserted)) = queue.pop_front() {
+        for at in 0..=words.len() {
+            for terminator in &TERMINATORS {
+                let mut candidate = words.clone();
+                candidate.insert(at, Word::Particle(terminator.clone()));
+                let Ok(tree) = parser.parse(candidate.clone()) else {
+                    continue;
+                };
+                if trees.contains(&tree) {
+                    continue;
+                }
+                trees.push(tree.clone());
+                let mut inserted: Vec<usize> = inserted
+                    .iter()
+                    .map(|i| if *i >= at { i + 1 } else { *i })
+                    .collect();
+                inserted.push(at);
+                inserted.sort_unstable();
+                readings.push(Reading {
+                    words: candidate.clone(),
+                    inserted: inserted.clone(),
+                    tree,
+                });
+                if readings.len() == MAX_READINGS {
+                    return readings;
+                }
+                queue.push_back((candidate, inserted));
+            }
+        }
+    }
+    readings
+}
@@ -2,14 +2,214 @@ type Res<T> = Result<T, Box<dyn std::error::Error>>;
 
 use std::time::Instant;
 
-use chumsky::{error::Cheap, Parser};
 use eberban::{
-    expr::{to_expr, Predicate},
-    lexer::{lexer, preprocess},
-    parser::parser,
+    coinage::{self, Shape},
+    compat::to_compat,
+    conformance, decompose,
+    diagnostics::{self, Code},
+    dictionary::Dictionary,
+    expr::{to_expr, to_expr_canonical, Predicate},
+    format::FormatOptions,
+    gloss::Labelled,
+    grammar,
+    lexer::lex_words,
+    parser::{ParserBackend, Sentence},
+    render::Colored,
+    stats::CorpusStats,
+    stream,
 };
 
 fn main() -> Res<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("conformance") => conformance(&args[1..]),
+        Some("coin") => coin(&args[1..]),
+        Some("check") => check(&args[1..]),
+        Some("stats") => stats(&args[1..]),
+        Some("decompose") => decompose(&args[1..]),
+        Some("collisions") => collisions(&args[1..]),
+        Some("format") => format(&args[1..]),
+        Some("grammar") => {
+            println!("{}", grammar::grammar().to_json());
+            Ok(())
+        }
+        _ => examples(&args),
+    }
+}
+
+fn conformance(args: &[String]) -> Res<()> {
+    let [path] = args else {
+        return Err("usage: parser conformance <suite file>".into());
+    };
+    let suite = conformance::load(&std::fs::read_to_string(path)?)?;
+    print!("{}", conformance::run(suite));
+    Ok(())
+}
+
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find_map(|a| a.strip_prefix(name)?.strip_prefix('='))
+}
+
+fn dictionary(args: &[String]) -> Res<Option<Dictionary>> {
+    Ok(match option(args, "--dictionary") {
+        Some(path) if path.ends_with(".csv") => {
+            Some(Dictionary::from_csv(&std::fs::read_to_string(path)?)?)
+        }
+        Some(path) => Some(Dictionary::from_json(&std::fs::read_to_string(path)?)?),
+        None => None,
+    })
+}
+
+fn coin(args: &[String]) -> Res<()> {
+    let Some(Ok(length)) = args.first().map(|l| l.parse()) else {
+        return Err(
+            "usage: parser coin <length> [--require=<letters>] [--ending=<letter>] \
+                    [--count=<n>] [--seed=<n>] [--dictionary=<file>]"
+                .into(),
+        );
+    };
+    let shape = Shape {
+        length,
+        required: option(args, "--require")
+            .map(|r| r.chars().collect())
+            .unwrap_or_default(),
+        ending: option(args, "--ending").and_then(|e| e.chars().next()),
+    };
+    let count = option(args, "--count").map(str::parse).transpose()?;
+    let seed = option(args, "--seed").map(str::parse).transpose()?;
+    let dictionary = dictionary(args)?.unwrap_or_default();
+    for word in coinage::coin(&shape, &dictionary, count.unwrap_or(10), seed.unwrap_or(1)) {
+        println!("{word}");
+    }
+    Ok(())
+}
+
+fn collisions(args: &[String]) -> Res<()> {
+    let Some(dictionary) = dictionary(args)? else {
+        return Err("usage: parser collisions --dictionary=<file>".into());
+    };
+    for collision in dictionary.collisions() {
+        println!("{collision}");
+    }
+    Ok(())
+}
+
+fn decompose(args: &[String]) -> Res<()> {
+    let Some(word) = args.iter().find(|a| !a.starts_with("--")) else {
+        return Err("usage: parser decompose <word> [--dictionary=<file>]".into());
+    };
+    let segmentations = match dictionary(args)? {
+        Some(dictionary) => decompose::decompose_in(word, &dictionary),
+        None => decompose::decompose(word),
+    };
+    for segmentation in segmentations {
+        println!("{segmentation}");
+    }
+    Ok(())
+}
+
+fn stats(args: &[String]) -> Res<()> {
+    if args.is_empty() {
+        return Err("usage: parser stats <file>...".into());
+    }
+    let mut stats = CorpusStats::new();
+    for path in args {
+        let text = std::fs::read_to_string(path)?;
+        let words = stream::tokens(&text)
+            .map(|token| token.map(|(word, _)| word))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("{path}: {e}"))?;
+        stats.add(&words);
+    }
+    println!("{}", stats.to_json());
+    Ok(())
+}
+
+fn format(args: &[String]) -> Res<()> {
+    let Some(path) = args.iter().find(|a| !a.starts_with("--")) else {
+        return Err(
+            "usage: parser format <file> [--terminators=keep|elide|write] [--width=<n>]".into(),
+        );
+    };
+    let mut options = FormatOptions::new();
+    if let Some(terminators) = option(args, "--terminators") {
+        options = options.terminators(terminators.parse()?);
+    }
+    if let Some(width) = option(args, "--width") {
+        options = options.width(width.parse()?);
+    }
+    let text = std::fs::read_to_string(path)?;
+    println!(
+        "{}",
+        options.format(&text).map_err(|e| format!("{path}: {e}"))?
+    );
+    Ok(())
+}
+
+fn check(args: &[String]) -> Res<()> {
+    let Some(path) = args.iter().find(|a| !a.starts_with("--")) else {
+        return Err(
+            "usage: parser check <file> [--format=human|json] [--allow=<code>,...] \
+                    [--dictionary=<file>] [--ambiguities] [--attachments]"
+                .into(),
+        );
+    };
+    let json = match option(args, "--format") {
+        None | Some("human") => false,
+        Some("json") => true,
+        Some(format) => return Err(format!("unknown format `{format}`").into()),
+    };
+    let allowed = option(args, "--allow")
+        .map(|codes| {
+            codes
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<Code>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let dictionary = dictionary(args)?;
+    let ambiguities = args.iter().any(|a| a == "--ambiguities");
+    let attachments = args.iter().any(|a| a == "--attachments");
+    let text = std::fs::read_to_string(path)?;
+    let mut errors = 0;
+    for (line, sentence) in text.lines().enumerate() {
+        if sentence.trim().is_empty() {
+            continue;
+        }
+        let mut diagnostics = diagnostics::check_with_dictionary(sentence, dictionary.as_ref());
+        if ambiguities {
+            diagnostics.extend(diagnostics::check_ambiguities(sentence));
+        }
+        if attachments {
+            diagnostics.extend(diagnostics::check_attachments(sentence));
+        }
+        for diagnostic in diagnostics {
+            if allowed.contains(&diagnostic.code) {
+                continue;
+            }
+            if diagnostic.severity() == diagnostics::Severity::Error {
+                errors += 1;
+            }
+            if json {
+                let mut value = diagnostic.to_json();
+                if let eberban::json::Value::Object(fields) = &mut value {
+                    fields.push(("line".into(), (line + 1).into()));
+                }
+                println!("{value}");
+            } else {
+                println!("{path}:{}: {diagnostic}", line + 1);
+            }
+        }
+    }
+    if errors > 0 {
+        return Err(format!("{errors} errors").into());
+    }
+    Ok(())
+}
+
+fn examples(args: &[String]) -> Res<()> {
     let example_sentences = [
         "zi mio tiho a ol ahu nu",
         "zimiotiho'a'ol'ahu'nu",
@@ -67,23 +267,57 @@ fn main() -> Res<()> {
         "mi zi pe zi pe bure meon",
         "zi pe mai vi zi ke fi zi ka",
         "mao dona ve mi vei mian",
+        "mi dona mian e",
+        "bure meon u",
     ];
 
-    let lexer = lexer::<Cheap<_>>();
-    let parser = parser::<Cheap<_>>();
+    let compat = args.iter().any(|a| a == "--compat");
+    let canonical = args.iter().any(|a| a == "--canonical");
+    let backend: ParserBackend = option(args, "--backend")
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or_default();
+    let roles = args.iter().any(|a| a == "--roles");
+    let gloss = args.iter().any(|a| a == "--gloss");
+    let color = args.iter().any(|a| a == "--color");
+    let dictionary = dictionary(args)?;
+
     for s in example_sentences {
         println!("{s}");
         let start = Instant::now();
-        let s = lexer.parse(preprocess(s)).unwrap();
+        let s = lex_words(s).unwrap();
         let lexing = start.elapsed();
-        if let Ok(tree) = parser.parse(s) {
-            let (expr, vars) = to_expr(tree);
+        let words = if color { s.clone() } else { Vec::new() };
+        if let Ok(Sentence { tree, mood, .. }) = backend.parse_sentence(s) {
+            if compat {
+                println!("{}", to_compat(&tree));
+            }
+            let (expr, vars) = if canonical {
+                to_expr_canonical(tree)
+            } else {
+                to_expr(tree)
+            };
             let parsing = start.elapsed();
             let expr = Predicate::Lambda {
                 vars,
-                pred: Box::new(expr),
+                pred: Box::new(expr.with_mood(mood)),
             };
-            println!("{expr}");
+            if color {
+                let colored = Colored {
+                    words: &words,
+                    pred: &expr,
+                };
+                println!("{colored}");
+            } else if roles || gloss {
+                let labelled = Labelled {
+                    pred: &expr,
+                    dictionary: dictionary.as_ref(),
+                    gloss,
+                };
+                println!("{labelled}");
+            } else {
+                println!("{expr}");
+            }
             println!(
                 "lexed in {} µs, parsed in {} µs",
                 lexing.as_micros(),
@@ -0,0 +1,172 @@
+//! Building predicate trees in code, without writing and parsing their text, as in
+//! `TreeBuilder::leaf("mian").share(0, TreeBuilder::leaf("mi")).build()`.
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    lexer::{
+        chaining_of, lex_words, AbstractionKind, ChainingFamily, ConnectiveKind, PredicateWord, Tag,
+    },
+    parser::{parser, PredicateTree},
+    Argument, Exposure, GrammarVar, Negation, PredicateChaining, Quantifier,
+};
+
+/// A predicate tree under construction. Each method returns the tree it makes of the one built
+/// so far, as the parser would make it of the corresponding words.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TreeBuilder {
+    tree: PredicateTree,
+}
+
+impl TreeBuilder {
+    /// The tree of a single word, as the parser reads it: a predicate, a pronoun, a number or a
+    /// name. Anything else is taken to be a root, chaining as its spelling says.
+    pub fn leaf(word: &str) -> Self {
+        let tree = match lex_words(word) {
+            Ok(words) if words.len() == 1 => parser::<Cheap<_>>().parse(words).ok(),
+            _ => None,
+        };
+        tree.unwrap_or_else(|| PredicateTree::Leaf {
+            word: PredicateWord {
+                word: word.to_string(),
+                chaining: chaining_of(word, ChainingFamily::Root),
+            },
+            negation: Negation::None,
+            free: Vec::new(),
+        })
+        .into()
+    }
+
+    pub fn number(value: u64) -> Self {
+        PredicateTree::Number {
+            value,
+            negation: Negation::None,
+            free: Vec::new(),
+        }
+        .into()
+    }
+
+    /// A subordinate clause of `kind` describing `tree`.
+    pub fn abstraction(kind: AbstractionKind, tree: impl Into<PredicateTree>) -> Self {
+        PredicateTree::Abstraction {
+            kind,
+            negation: Negation::None,
+            tree: Box::new(tree.into()),
+        }
+        .into()
+    }
+
+    /// Chains `sharer` to share its chaining place with `place` of this tree, as after `va`, `ve`
+    /// or `vo`.
+    pub fn share(self, place: GrammarVar, sharer: impl Into<PredicateTree>) -> Self {
+        self.chain(place, PredicateChaining::Sharing, sharer.into())
+    }
+
+    /// Chains `sharer` to be equated with `place` of this tree, as after `via`, `vie` or `vio`.
+    pub fn equate(self, place: GrammarVar, sharer: impl Into<PredicateTree>) -> Self {
+        self.chain(place, PredicateChaining::Equivalence, sharer.into())
+    }
+
+    /// Chains `tree` after the whole of this tree, as after a bare `vi`.
+    pub fn and(self, tree: impl Into<PredicateTree>) -> Self {
+        let mut binding = self.binding();
+        if let PredicateTree::Binding { and, .. } = &mut binding {
+            and.push(tree.into());
+        }
+        binding.into()
+    }
+
+    /// Attaches `tree` to this tree with `tag`, as after a tag particle such as `ta` or `tohe`.
+    pub fn tag(self, tag: Tag, tree: impl Into<PredicateTree>) -> Self {
+        let mut binding = self.binding();
+        if let PredicateTree::Binding { tags, .. } = &mut binding {
+            tags.push((tag, tree.into()));
+        }
+        binding.into()
+    }
+
+    fn chain(
+        self,
+        place: GrammarVar,
+        chain_with: PredicateChaining,
+        sharer: PredicateTree,
+    ) -> Self {
+        let mut binding = self.binding();
+        if let PredicateTree::Binding { sharers, .. } = &mut binding {
+            while sharers.len() <= place as usize {
+                sharers.push(Vec::new());
+            }
+            sharers[place as usize].push((chain_with, sharer));
+        }
+        binding.into()
+    }
+
+    /// This tree as the root of a binding, unless it already is one.
+    fn binding(self) -> PredicateTree {
+        match self.tree {
+            b @ PredicateTree::Binding { .. } => b,
+            root => PredicateTree::Binding {
+                chaining: root.chaining_behavior(),
+                root: Box::new(root),
+                negation: Negation::None,
+                exposure: Exposure::Standard,
+                sharers: Vec::new(),
+                and: Vec::new(),
+                tags: Vec::new(),
+            },
+        }
+    }
+
+    /// Negates this tree, `Short` as by `zi` and `Long` as by `bi`.
+    pub fn negate(self, negation: Negation) -> Self {
+        self.tree.negate(negation).into()
+    }
+
+    /// Joins this tree and `right` with a connective.
+    pub fn connect(self, kind: ConnectiveKind, right: impl Into<PredicateTree>) -> Self {
+        PredicateTree::Connective {
+            kind,
+            negation: Negation::None,
+            left: Box::new(self.tree),
+            right: Box::new(right.into()),
+        }
+        .into()
+    }
+
+    /// Quantifies this tree over the place it is chained to.
+    pub fn quantify(self, quantifier: Quantifier) -> Self {
+        PredicateTree::Quantified {
+            quantifier,
+            tree: Box::new(self.tree),
+        }
+        .into()
+    }
+
+    /// Exposes the places of this tree as `si` would, or as an argument list does for
+    /// [`Exposure::Explicit`].
+    pub fn expose(self, exposure: Exposure) -> Self {
+        let chaining = self.tree.chaining_behavior();
+        self.tree.with_exposure(exposure, chaining).into()
+    }
+
+    /// Gives this tree the argument list `args`, as written after `vi` before `be`.
+    pub fn arguments(self, args: impl IntoIterator<Item = (Argument, PredicateChaining)>) -> Self {
+        self.expose(Exposure::Explicit(args.into_iter().collect()))
+    }
+
+    pub fn build(self) -> PredicateTree {
+        self.tree
+    }
+}
+
+impl From<PredicateTree> for TreeBuilder {
+    fn from(tree: PredicateTree) -> Self {
+        TreeBuilder { tree }
+    }
+}
+
+impl From<TreeBuilder> for PredicateTree {
+    fn from(builder: TreeBuilder) -> Self {
+        builder.tree
+    }
+}
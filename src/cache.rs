@@ -0,0 +1,256 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    dictionary::{to_expr_with_metadata, Dictionary, LeafTable},
+    expr::to_expr,
+    incremental::{ExprResult, LexResult, ParseResult},
+    lexer::lex_words,
+    parser::ParserBackend,
+};
+
+/// The results of running the whole pipeline on a sentence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    /// The normalized sentence that was analysed. Error spans index into it.
+    pub sentence: String,
+    pub tokens: LexResult,
+    pub tree: ParseResult,
+    pub expr: ExprResult,
+    /// Metadata of the leaves of `expr`, when a dictionary is configured.
+    pub leaves: LeafTable,
+}
+
+/// Where an [`AnalysisCache`] keeps its analyses, by hash of the normalized sentence.
+pub trait CacheStorage {
+    fn get(&self, key: u64) -> Option<Arc<Analysis>>;
+    fn insert(&mut self, key: u64, analysis: Arc<Analysis>);
+    fn clear(&mut self);
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Keeps every analysis in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    entries: HashMap<u64, Arc<Analysis>>,
+}
+
+impl CacheStorage for MemoryStorage {
+    fn get(&self, key: u64) -> Option<Arc<Analysis>> {
+        self.entries.get(&key).cloned()
+    }
+
+    fn insert(&mut self, key: u64, analysis: Arc<Analysis>) {
+        self.entries.insert(key, analysis);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Keeps at most `capacity` analyses in memory, evicting the oldest first.
+#[derive(Debug, Clone)]
+pub struct BoundedStorage {
+    capacity: usize,
+    entries: HashMap<u64, Arc<Analysis>>,
+    order: VecDeque<u64>,
+}
+
+impl BoundedStorage {
+    pub fn new(capacity: usize) -> Self {
+        BoundedStorage {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+}
+
+impl CacheStorage for BoundedStorage {
+    fn get(&self, key: u64) -> Option<Arc<Analysis>> {
+        self.entries.get(&key).cloned()
+    }
+
+    fn insert(&mut self, key: u64, analysis: Arc<Analysis>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key, analysis).is_none() {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// How many times the cache was emptied, explicitly or by a change of configuration.
+    pub invalidations: u64,
+    pub entries: usize,
+}
+
+/// The form of a sentence that decides its analysis: letters are lowercased and runs of the same
+/// letter collapsed, as preprocessing does, and every run of whitespace becomes a single space.
+pub fn normalize(sentence: &str) -> String {
+    sentence
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() {
+                ' '
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .dedup()
+        .collect()
+}
+
+fn key(normalized: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Memoizes analyses of sentences by hash of their normalized form, so that sentences repeated
+/// across a corpus are only lexed, parsed and interpreted once. Changing the parser backend or
+/// the dictionary empties the cache.
+pub struct AnalysisCache<S = MemoryStorage> {
+    storage: S,
+    backend: ParserBackend,
+    dictionary: Option<Dictionary>,
+    stats: CacheStats,
+}
+
+impl Default for AnalysisCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisCache {
+    pub fn new() -> Self {
+        Self::with_storage(MemoryStorage::default())
+    }
+}
+
+impl<S: CacheStorage> AnalysisCache<S> {
+    pub fn with_storage(storage: S) -> Self {
+        AnalysisCache {
+            storage,
+            backend: ParserBackend::default(),
+            dictionary: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn backend(&self) -> ParserBackend {
+        self.backend
+    }
+
+    pub fn set_backend(&mut self, backend: ParserBackend) {
+        if self.backend != backend {
+            self.backend = backend;
+            self.invalidate();
+        }
+    }
+
+    pub fn dictionary(&self) -> Option<&Dictionary> {
+        self.dictionary.as_ref()
+    }
+
+    pub fn set_dictionary(&mut self, dictionary: Option<Dictionary>) {
+        if self.dictionary != dictionary {
+            self.dictionary = dictionary;
+            self.invalidate();
+        }
+    }
+
+    /// Empties the cache.
+    pub fn invalidate(&mut self) {
+        self.storage.clear();
+        self.stats.invalidations += 1;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.storage.len(),
+            ..self.stats
+        }
+    }
+
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// The analysis of `sentence`, from the cache if a sentence with the same normalized form
+    /// was analysed since the last invalidation.
+    pub fn analyse(&mut self, sentence: &str) -> Arc<Analysis> {
+        let normalized = normalize(sentence);
+        let key = key(&normalized);
+        if let Some(analysis) = self.storage.get(key) {
+            if analysis.sentence == normalized {
+                self.stats.hits += 1;
+                return analysis;
+            }
+        }
+        self.stats.misses += 1;
+        let analysis = Arc::new(self.run(normalized));
+        self.storage.insert(key, analysis.clone());
+        analysis
+    }
+
+    fn run(&self, sentence: String) -> Analysis {
+        let tokens = lex_words(&sentence);
+        let tree = tokens
+            .as_ref()
+            .ok()
+            .map(|words| self.backend.parse(words.clone()));
+        let (expr, leaves) = match tree.as_ref().and_then(|tree| tree.as_ref().ok()) {
+            Some(tree) => match &self.dictionary {
+                Some(dictionary) => {
+                    let (pred, vars, leaves) = to_expr_with_metadata(tree.clone(), dictionary);
+                    (Some((pred, vars)), leaves)
+                }
+                None => (Some(to_expr(tree.clone())), LeafTable::new()),
+            },
+            None => (None, LeafTable::new()),
+        };
+        Analysis {
+            sentence,
+            tokens,
+            tree,
+            expr,
+            leaves,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use chumsky::{error::Cheap, prelude::empty, Error, Parser};
+
+use crate::{
+    expr::{to_expr_cancellable, Predicate, Var},
+    lexer::{cancellable_lexer, preprocess, PreProcessed, Word},
+    parser::cancellable_parser,
+};
+
+/// A cooperative cancellation signal, checked while input is consumed during lexing and parsing
+/// and at every node during conversion. Clones share the same signal, so a host can keep one
+/// and hand another to the thread doing the analysis.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that also cancels itself once `timeout` has elapsed from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::new().deadline(Instant::now() + timeout)
+    }
+
+    /// Sets a wall-clock deadline after which the token counts as cancelled.
+    pub fn deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "analysis cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    Lex(Vec<Cheap<PreProcessed>>),
+    Parse(Vec<Cheap<Word>>),
+    Cancelled(Cancelled),
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::Lex(errors) => write!(f, "{} lexing error(s)", errors.len()),
+            AnalysisError::Parse(errors) => write!(f, "{} parsing error(s)", errors.len()),
+            AnalysisError::Cancelled(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+impl From<Cancelled> for AnalysisError {
+    fn from(c: Cancelled) -> Self {
+        AnalysisError::Cancelled(c)
+    }
+}
+
+/// A parser that consumes nothing and fails once `cancel` is cancelled, making every
+/// alternative that starts with it fail fast.
+pub(crate) fn checkpoint<I: Clone, E: Error<I>>(
+    cancel: CancellationToken,
+) -> impl Parser<I, (), Error = E> + Clone {
+    empty().try_map(move |(), span| {
+        if cancel.is_cancelled() {
+            Err(E::expected_input_found(span, None, None))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Runs the whole pipeline on `s`, giving up with [`AnalysisError::Cancelled`] once `cancel` is
+/// cancelled or its deadline has passed.
+pub fn analyse(
+    s: &str,
+    cancel: &CancellationToken,
+) -> Result<(Predicate, Vec<Var>), AnalysisError> {
+    cancel.check()?;
+    let words = cancellable_lexer::<Cheap<_>>(cancel.clone()).parse(preprocess(s));
+    cancel.check()?;
+    let words = words.map_err(AnalysisError::Lex)?;
+    let tree = cancellable_parser::<Cheap<_>>(cancel.clone()).parse(words);
+    cancel.check()?;
+    let tree = tree.map_err(AnalysisError::Parse)?;
+    Ok(to_expr_cancellable(tree, cancel)?)
+}
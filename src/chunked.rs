@@ -0,0 +1,160 @@
+use std::{iter, num::NonZeroUsize, str::CharIndices, thread};
+
+use chumsky::{error::Cheap, Error};
+use itertools::Itertools;
+
+use crate::lexer::{assemble_spanned_numbers, lex_spanned, PreProcessed, SpannedWords};
+
+/// The size in bytes of the chunks lexed by [`lex_chunked`] when none is given.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A run of letters between pauses, as preprocessing spells it.
+fn normalized(field: &str) -> String {
+    field
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .dedup()
+        .collect()
+}
+
+/// Splits `s` into chunks of about `chunk_size` bytes, returning each with the offset in
+/// characters of its start. Every chunk but the first starts at a run of whitespace outside of
+/// any quote: no other word contains a pause and every word may begin with one, so the words of
+/// each chunk are the same as they would be in the whole text.
+///
+/// Whether a `cu` starts a quote depends on whether its delimiter occurs again, so every run of
+/// letters ending in `ce` or `cu` is taken to start a quote, and the text is only split once all
+/// of those have been closed. This may keep a long text from being split, but never splits a
+/// quote.
+pub fn chunks(s: &str, chunk_size: usize) -> Vec<(usize, &str)> {
+    Chunks::new(s, chunk_size).collect()
+}
+
+/// The chunks of [`chunks`], found as they are iterated over.
+pub struct Chunks<'a> {
+    s: &'a str,
+    chunk_size: usize,
+    chars: iter::Enumerate<iter::Chain<CharIndices<'a>, iter::Once<(usize, char)>>>,
+    /// The start of the next chunk, in bytes and in characters.
+    start: (usize, usize),
+    /// The delimiters of the foreign quotes that may be open, `None` until it is read.
+    open: Vec<Option<String>>,
+    field_start: Option<usize>,
+    finished: bool,
+}
+
+impl<'a> Chunks<'a> {
+    pub fn new(s: &'a str, chunk_size: usize) -> Self {
+        Chunks {
+            s,
+            chunk_size,
+            chars: s
+                .char_indices()
+                .chain(iter::once((s.len(), ' ')))
+                .enumerate(),
+            start: (0, 0),
+            open: Vec::new(),
+            field_start: None,
+            finished: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.s;
+        for (chars, (i, c)) in self.chars.by_ref() {
+            match (c.is_whitespace() || c == '\'', self.field_start) {
+                (false, None) => self.field_start = Some(i),
+                (true, Some(field)) => {
+                    let field = normalized(&s[field..i]);
+                    self.open
+                        .retain(|delimiter| delimiter.as_ref() != Some(&field));
+                    for delimiter in self.open.iter_mut().filter(|d| d.is_none()) {
+                        *delimiter = Some(field.clone());
+                    }
+                    if field.ends_with("cu") {
+                        self.open.push(None);
+                    }
+                    let quoted_next = field.ends_with("ce");
+                    self.field_start = None;
+                    let splittable = c.is_whitespace() && self.open.is_empty() && !quoted_next;
+                    let (start, start_chars) = self.start;
+                    if splittable && i - start >= self.chunk_size && i < s.len() {
+                        self.start = (i, chars);
+                        return Some((start_chars, &s[start..i]));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+        let (start, start_chars) = self.start;
+        Some((start_chars, &s[start..]))
+    }
+}
+
+/// Lexes `s` like [`lex_spanned`], splitting it into chunks with [`chunks`] and lexing them on as
+/// many threads as are available. Spans of words and errors index into `s` as a whole; only the
+/// errors of the first chunk that fails to lex are reported. Trace events emitted while lexing
+/// are lost, as subscribers are per thread.
+pub fn lex_chunked(s: &str, chunk_size: usize) -> Result<SpannedWords, Vec<Cheap<PreProcessed>>> {
+    let chunks = chunks(s, chunk_size.max(1));
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(chunks.len());
+    let per_thread = chunks.len().div_ceil(threads.max(1));
+
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .chunks(per_thread.max(1))
+            .map(|batch| {
+                scope.spawn(move || {
+                    batch
+                        .iter()
+                        .map(|(offset, chunk)| {
+                            lex_spanned(chunk)
+                                .map(|words| offset_words(words, *offset))
+                                .map_err(|errors| offset_errors(errors, *offset))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| match handle.join() {
+                Ok(results) => results,
+                Err(_) => vec![Err(vec![Cheap::expected_input_found(0..0, None, None)])],
+            })
+            .collect()
+    });
+
+    let mut words = Vec::new();
+    for result in results {
+        words.extend(result?);
+    }
+    // A number may straddle a chunk boundary.
+    Ok(assemble_spanned_numbers(words))
+}
+
+fn offset_words(words: SpannedWords, offset: usize) -> SpannedWords {
+    words
+        .into_iter()
+        .map(|(word, span)| (word, span.start + offset..span.end + offset))
+        .collect()
+}
+
+fn offset_errors(errors: Vec<Cheap<PreProcessed>>, offset: usize) -> Vec<Cheap<PreProcessed>> {
+    errors
+        .into_iter()
+        .map(|e| {
+            Cheap::expected_input_found(e.span().start + offset..e.span().end + offset, None, None)
+        })
+        .collect()
+}
@@ -0,0 +1,104 @@
+//! Coining new roots of a wanted shape for proposing words to the dictionary.
+//!
+//! Scope cut: borrowings are not coined. The lexer reads no borrowings, as no spelling marks a
+//! word as one, so a coined borrowing could not be used in a text. Borrowing shapes belong here
+//! once the lexer reads them.
+
+use std::collections::BTreeSet;
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    collisions::collisions,
+    dictionary::Dictionary,
+    generate::Rng,
+    lexer::{lexer, preprocess, PredicateFamily, Word, NON_SONORANT, SONORANT, VOWELS},
+};
+
+/// The shape wanted for new roots. Freeform words are not coined either, as they are names
+/// rather than words of the dictionary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Shape {
+    /// The number of letters.
+    pub length: usize,
+    /// Letters that must appear in the word.
+    pub required: Vec<char>,
+    /// The last letter, which decides the chaining behavior of the word: `i` chains by
+    /// equivalence, another vowel by sharing its second place, and a sonorant by sharing its
+    /// first place.
+    pub ending: Option<char>,
+}
+
+fn consonants() -> Vec<char> {
+    NON_SONORANT
+        .iter()
+        .chain(&SONORANT)
+        .chain(&['h'])
+        .copied()
+        .collect()
+}
+
+/// A random string alternating clusters of one or two consonants with vowels.
+fn candidate(shape: &Shape, rng: &mut Rng) -> String {
+    let consonants = consonants();
+    let mut word = String::new();
+    let mut cluster = 0;
+    while word.chars().count() < shape.length {
+        let consonant = cluster == 0 || (cluster == 1 && rng.below(3) == 0);
+        if consonant {
+            word.push(rng.pick(&consonants));
+            cluster += 1;
+        } else {
+            word.push(rng.pick(&VOWELS));
+            cluster = 0;
+        }
+    }
+    if let Some(ending) = shape.ending {
+        word.pop();
+        word.push(ending);
+    }
+    word
+}
+
+/// Coins up to `count` new roots of the given shape that lex as a single root and that
+/// [`collisions`] finds easily confused with none of the words of `dictionary` nor with each
+/// other. The same seed gives the same words.
+pub fn coin(shape: &Shape, dictionary: &Dictionary, count: usize, seed: u64) -> Vec<String> {
+    let lexer = lexer::<Cheap<_>>();
+    let mut rng = Rng::new(seed);
+    let mut coined = BTreeSet::new();
+    let mut words = Vec::new();
+    for _ in 0..count.saturating_mul(10_000) {
+        if words.len() >= count {
+            break;
+        }
+        let word = candidate(shape, &mut rng);
+        if !shape.required.iter().all(|c| word.contains(*c))
+            || dictionary.get(&word).is_some()
+            || coined.contains(&word)
+        {
+            continue;
+        }
+        let padded = format!(" {word}");
+        let is_root = matches!(
+            lexer.parse(preprocess(&padded)).as_deref(),
+            Ok([Word::Predicate(pw, PredicateFamily::Root)]) if pw.word == word
+        );
+        if !is_root {
+            continue;
+        }
+        // A word listed twice is not reported as colliding, hence the checks above.
+        let taken = dictionary
+            .iter()
+            .map(|e| &*e.word)
+            .chain(words.iter().map(String::as_str));
+        let collides = collisions(taken.chain([word.as_str()]))
+            .iter()
+            .any(|c| c.first == word || c.second == word);
+        if !collides {
+            coined.insert(word.clone());
+            words.push(word);
+        }
+    }
+    words
+}
@@ -0,0 +1,146 @@
+//! Pairs of words of a word list, such as a dictionary, that are easily confused: words read as
+//! the same word once case is folded and repeated letters merged, and words differing only by
+//! letters that sound alike or by the order of two consonants.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::lexer::VOWELS;
+
+/// Pairs of letters differing only by voicing, or sonorants easily misheard as each other.
+pub const CONFUSABLE: [(char, char); 8] = [
+    ('p', 'b'),
+    ('t', 'd'),
+    ('k', 'g'),
+    ('f', 'v'),
+    ('s', 'z'),
+    ('c', 'j'),
+    ('m', 'n'),
+    ('l', 'r'),
+];
+
+/// How two words collide. Positions are in the letters of the words as preprocessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CollisionKind {
+    /// The words are read as the same word.
+    Identical,
+    /// The words differ only by a pair of [`CONFUSABLE`] letters at this position.
+    Confusable { at: usize, letters: (char, char) },
+    /// The words differ only by the order of the two consonants at this position and the next.
+    Transposed { at: usize },
+}
+
+/// Two words of a list that are easily confused, in the order of the list.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Collision {
+    pub first: String,
+    pub second: String,
+    pub kind: CollisionKind,
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first, second) = (&self.first, &self.second);
+        match self.kind {
+            CollisionKind::Identical => write!(f, "`{first}` and `{second}` are read the same"),
+            CollisionKind::Confusable {
+                letters: (a, b), ..
+            } => write!(
+                f,
+                "`{first}` and `{second}` differ only by the easily confused `{a}` and `{b}`"
+            ),
+            CollisionKind::Transposed { .. } => write!(
+                f,
+                "`{first}` and `{second}` differ only by the order of two consonants"
+            ),
+        }
+    }
+}
+
+/// The letters `word` is read as: ASCII case folded and repeated letters merged, as by
+/// [`preprocess`](crate::lexer::preprocess).
+fn letters(word: &str) -> Vec<char> {
+    let mut letters: Vec<char> = word.chars().map(|c| c.to_ascii_lowercase()).collect();
+    letters.dedup();
+    letters
+}
+
+/// The first letter of the [`CONFUSABLE`] pair `c` belongs to, or `c` itself.
+fn confusable_class(c: char) -> char {
+    CONFUSABLE
+        .iter()
+        .find(|(a, b)| *a == c || *b == c)
+        .map_or(c, |(a, _)| *a)
+}
+
+fn is_consonant(c: char) -> bool {
+    c.is_ascii_alphabetic() && !VOWELS.contains(&c) && c != 'h'
+}
+
+/// The pairs of words of `words` that are easily confused, in the order of the first word of each
+/// pair in the list, then of the second. A word listed twice is not reported.
+pub fn collisions<'a>(words: impl IntoIterator<Item = &'a str>) -> Vec<Collision> {
+    let mut seen = BTreeSet::new();
+    let words: Vec<&str> = words.into_iter().filter(|w| seen.insert(*w)).collect();
+    let read: Vec<Vec<char>> = words.iter().map(|w| letters(w)).collect();
+
+    // Words by their letters, and by their letters with confusable letters merged.
+    let mut by_letters: BTreeMap<&[char], Vec<usize>> = BTreeMap::new();
+    let mut by_class: BTreeMap<Vec<char>, Vec<usize>> = BTreeMap::new();
+    for (i, letters) in read.iter().enumerate() {
+        by_letters.entry(letters).or_default().push(i);
+        by_class
+            .entry(letters.iter().copied().map(confusable_class).collect())
+            .or_default()
+            .push(i);
+    }
+
+    let mut found = BTreeMap::new();
+    let mut report = |i: usize, j: usize, kind: CollisionKind| {
+        found.entry((i.min(j), i.max(j))).or_insert(kind);
+    };
+    for group in by_letters.values() {
+        for (n, &i) in group.iter().enumerate() {
+            for &j in &group[n + 1..] {
+                report(i, j, CollisionKind::Identical);
+            }
+        }
+    }
+    for group in by_class.values() {
+        for (n, &i) in group.iter().enumerate() {
+            for &j in &group[n + 1..] {
+                let differences: Vec<usize> = (0..read[i].len())
+                    .filter(|at| read[i][*at] != read[j][*at])
+                    .collect();
+                if let [at] = differences[..] {
+                    let letters = (read[i][at], read[j][at]);
+                    report(i, j, CollisionKind::Confusable { at, letters });
+                }
+            }
+        }
+    }
+    for (i, letters) in read.iter().enumerate() {
+        for at in 1..letters.len() {
+            let (a, b) = (letters[at - 1], letters[at]);
+            if a == b || !is_consonant(a) || !is_consonant(b) {
+                continue;
+            }
+            let mut swapped = letters.clone();
+            swapped.swap(at - 1, at);
+            for &j in by_letters.get(&swapped[..]).into_iter().flatten() {
+                report(i, j, CollisionKind::Transposed { at: at - 1 });
+            }
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|((i, j), kind)| Collision {
+            first: words[i].to_string(),
+            second: words[j].to_string(),
+            kind,
+        })
+        .collect()
+}
@@ -0,0 +1,224 @@
+use crate::{
+    json::Value,
+    lexer::{place_text, si_text, PredicateWord, QuantifierKind, DIGITS},
+    parser::PredicateTree,
+    Argument, ChainingBehavior, Exposure, Negation, PredicateChaining, Quantifier,
+};
+
+/// Serializes a parse in the structural format of the reference Eberban parser: every rule is
+/// an array whose first element is the rule name followed by its children, and every terminal is
+/// a `[FAMILY, word]` pair.
+///
+/// Particles that the tree does not retain (which of `vi`/`fi` introduced a chain, elided
+/// terminators) are rendered in their canonical form, so that two parses with the same structure
+/// always serialize identically.
+pub fn to_compat(tree: &PredicateTree) -> Value {
+    Value::Array(vec![
+        "text".into(),
+        Value::Array(vec!["sentence".into(), chaining(tree)]),
+    ])
+}
+
+fn terminal(family: &str, word: impl Into<String>) -> Value {
+    Value::Array(vec![family.into(), Value::String(word.into())])
+}
+
+fn rule(name: &str, children: impl IntoIterator<Item = Value>) -> Value {
+    Value::Array(
+        std::iter::once(name.into())
+            .chain(children)
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn negations(negation: Negation) -> impl Iterator<Item = Value> {
+    let long = negation.long().then(|| terminal("BI", "bi"));
+    let short = negation.short().then(|| terminal("ZI", "zi"));
+    long.into_iter().chain(short)
+}
+
+pub(crate) fn word_family(word: &str) -> &'static str {
+    let mut chars = word.chars();
+    let first = chars.next();
+    let is_particle = chars.all(|c| c == 'h' || crate::lexer::VOWELS.contains(&c));
+    match first {
+        Some('k') if is_particle => "KI",
+        Some('g') if is_particle => "GI",
+        Some('m') if is_particle => "MI",
+        _ => "ROOT",
+    }
+}
+
+fn predicate(word: &PredicateWord) -> Value {
+    rule(
+        "predicate",
+        [terminal(word_family(&word.word), word.word.clone())],
+    )
+}
+
+fn exposure(exposure: &Exposure, chaining: ChainingBehavior) -> Option<Value> {
+    match exposure {
+        Exposure::Standard => None,
+        Exposure::Transparent | Exposure::Modified(_) => {
+            Some(terminal("SI", si_text(exposure, chaining)))
+        }
+        Exposure::Explicit(args) => Some(rule(
+            "arguments",
+            args.iter()
+                .map(|(argument, chain_with)| match (argument, chain_with) {
+                    (Argument::Number(n), _) => terminal("JI", number_text(*n)),
+                    (Argument::Name(name), _) => terminal("FREEFORM", name.clone()),
+                    (Argument::Quote(quote), _) => terminal("ROOT", quote.leaf_name()),
+                    (Argument::Word(word), PredicateChaining::Sharing) => {
+                        terminal("KI", word.clone())
+                    }
+                    (Argument::Word(word), PredicateChaining::Equivalence) => {
+                        terminal("GI", word.clone())
+                    }
+                })
+                .chain(std::iter::once(terminal("BE", "be"))),
+        )),
+    }
+}
+
+fn element(tree: &PredicateTree) -> Value {
+    match tree {
+        PredicateTree::Leaf { word, negation, .. } => {
+            rule("element", negations(*negation).chain([predicate(word)]))
+        }
+        PredicateTree::Number {
+            value, negation, ..
+        } => rule(
+            "element",
+            negations(*negation).chain([rule("predicate", [terminal("JI", number_text(*value))])]),
+        ),
+        PredicateTree::Abstraction {
+            kind,
+            negation,
+            tree,
+        } => rule(
+            "element",
+            negations(*negation).chain([rule(
+                "abstraction",
+                [
+                    terminal("DA", kind.text()),
+                    chaining(tree),
+                    terminal("PEI", "pei"),
+                ],
+            )]),
+        ),
+        PredicateTree::Name { word, negation, .. } => rule(
+            "element",
+            negations(*negation)
+                .chain([rule("predicate", [terminal("FREEFORM", word.word.clone())])]),
+        ),
+        PredicateTree::Binding { .. }
+        | PredicateTree::Connective { .. }
+        | PredicateTree::Quantified { .. } => rule(
+            "element",
+            [rule(
+                "group",
+                [terminal("PE", "pe"), chaining(tree), terminal("PEI", "pei")],
+            )],
+        ),
+    }
+}
+
+fn chaining(tree: &PredicateTree) -> Value {
+    match tree {
+        PredicateTree::Leaf { .. }
+        | PredicateTree::Number { .. }
+        | PredicateTree::Name { .. }
+        | PredicateTree::Abstraction { .. } => rule("chaining", [element(tree)]),
+        PredicateTree::Binding {
+            chaining: chain,
+            root,
+            negation,
+            exposure: exp,
+            sharers,
+            and,
+            tags,
+        } => {
+            let mut children: Vec<Value> = negations(*negation).collect();
+            if let Some(e) = exposure(exp, *chain) {
+                children.push(e);
+            }
+            children.push(element(root));
+            let links = sharers
+                .iter()
+                .enumerate()
+                .flat_map(|(var, set)| {
+                    set.iter().map(move |(chain_with, p)| {
+                        let word = match chain_with {
+                            PredicateChaining::Sharing => format!("v{}", place_text(var as u8)),
+                            PredicateChaining::Equivalence => {
+                                format!("vi{}", place_text(var as u8))
+                            }
+                        };
+                        (word, p)
+                    })
+                })
+                .chain(and.iter().map(|p| ("vi".to_string(), p)))
+                .collect::<Vec<_>>();
+            let count = links.len();
+            for (i, (word, p)) in links.into_iter().enumerate() {
+                let mut link = vec![terminal("VI", word), chaining(p)];
+                // The tags follow the links, so the last link is closed as the others are.
+                if (i + 1 < count || !tags.is_empty())
+                    && matches!(
+                        p,
+                        PredicateTree::Binding { .. } | PredicateTree::Quantified { .. }
+                    )
+                {
+                    link.push(terminal("VEI", "vei"));
+                }
+                children.push(rule("chain", link));
+            }
+            for (tag, p) in tags {
+                children.push(rule(
+                    "adjunct",
+                    [
+                        terminal("TA", tag.text()),
+                        chaining(p),
+                        terminal("VEI", "vei"),
+                    ],
+                ));
+            }
+            rule("chaining", children)
+        }
+        PredicateTree::Connective {
+            kind,
+            negation,
+            left,
+            right,
+        } => rule(
+            "chaining",
+            negations(*negation).chain([rule(
+                "connective",
+                [chaining(left), terminal("BA", kind.text()), chaining(right)],
+            )]),
+        ),
+        PredicateTree::Quantified { quantifier, tree } => {
+            let mut children = match quantifier {
+                Quantifier::All => vec![terminal("DU", QuantifierKind::All.text())],
+                Quantifier::Some => vec![terminal("DU", QuantifierKind::Some.text())],
+                Quantifier::Most => vec![terminal("DU", QuantifierKind::Most.text())],
+                Quantifier::Exactly(n) => vec![
+                    terminal("DU", QuantifierKind::Exactly.text()),
+                    terminal("JI", number_text(*n)),
+                ],
+            };
+            children.push(chaining(tree));
+            rule("quantified", children)
+        }
+    }
+}
+
+/// The digit particles spelling `n`.
+fn number_text(n: u64) -> String {
+    n.to_string()
+        .bytes()
+        .filter_map(|d| DIGITS.get(usize::from(d - b'0')))
+        .copied()
+        .collect()
+}
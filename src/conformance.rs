@@ -0,0 +1,162 @@
+use std::fmt;
+
+use crate::testcorpus::{analyse, CorpusError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceCase {
+    /// The line of the case in the suite file, starting from 1.
+    pub line: usize,
+    pub sentence: String,
+    pub grammatical: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub cases: Vec<ConformanceCase>,
+}
+
+/// Loads a conformance suite: `## Section` headings, each followed by sentences prefixed with
+/// `+` if they are grammatical or `-` if they must be rejected. Blank lines and lines starting
+/// with a single `#` are ignored. Cases before the first heading form an unnamed section.
+pub fn load(s: &str) -> Result<Vec<Section>, CorpusError> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("##") {
+            sections.push(Section {
+                name: name.trim().to_string(),
+                cases: Vec::new(),
+            });
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (grammatical, sentence) = if let Some(s) = line.strip_prefix('+') {
+            (true, s)
+        } else if let Some(s) = line.strip_prefix('-') {
+            (false, s)
+        } else {
+            return Err(CorpusError {
+                line: i + 1,
+                message: "expected a heading or a case starting with `+` or `-`".to_string(),
+            });
+        };
+        if sections.is_empty() {
+            sections.push(Section {
+                name: String::new(),
+                cases: Vec::new(),
+            });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.cases.push(ConformanceCase {
+                line: i + 1,
+                sentence: sentence.trim().to_string(),
+                grammatical,
+            });
+        }
+    }
+    Ok(sections)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseResult {
+    pub case: ConformanceCase,
+    pub accepted: bool,
+}
+
+impl CaseResult {
+    pub fn conforms(&self) -> bool {
+        self.accepted == self.case.grammatical
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionResult {
+    pub name: String,
+    pub results: Vec<CaseResult>,
+}
+
+impl SectionResult {
+    pub fn conforming(&self) -> usize {
+        self.results.iter().filter(|r| r.conforms()).count()
+    }
+
+    /// Whether the construction covered by the section is fully supported.
+    pub fn supported(&self) -> bool {
+        self.conforming() == self.results.len()
+    }
+}
+
+/// Which sections of a conformance suite the crate supports, case by case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConformanceMatrix {
+    pub sections: Vec<SectionResult>,
+}
+
+impl ConformanceMatrix {
+    pub fn conforming(&self) -> usize {
+        self.sections.iter().map(SectionResult::conforming).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.sections.iter().map(|s| s.results.len()).sum()
+    }
+}
+
+impl fmt::Display for ConformanceMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .sections
+            .iter()
+            .map(|s| s.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("section".len());
+        writeln!(f, "{:width$}  cases  status", "section")?;
+        for section in &self.sections {
+            let status = if section.supported() {
+                "supported"
+            } else if section.conforming() == 0 {
+                "unsupported"
+            } else {
+                "partial"
+            };
+            let cases = format!("{}/{}", section.conforming(), section.results.len());
+            writeln!(f, "{:width$}  {cases:>5}  {status}", section.name)?;
+            for result in section.results.iter().filter(|r| !r.conforms()) {
+                let expectation = if result.case.grammatical {
+                    "rejected"
+                } else {
+                    "accepted"
+                };
+                writeln!(
+                    f,
+                    "{:width$}    line {}: {expectation} `{}`",
+                    "", result.case.line, result.case.sentence
+                )?;
+            }
+        }
+        writeln!(f, "{}/{} cases conform", self.conforming(), self.total())
+    }
+}
+
+pub fn run(sections: impl IntoIterator<Item = Section>) -> ConformanceMatrix {
+    ConformanceMatrix {
+        sections: sections
+            .into_iter()
+            .map(|section| SectionResult {
+                name: section.name,
+                results: section
+                    .cases
+                    .into_iter()
+                    .map(|case| CaseResult {
+                        accepted: analyse(&case.sentence).is_some(),
+                        case,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
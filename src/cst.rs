@@ -0,0 +1,720 @@
+//! A concrete syntax tree of a sentence, keeping every word in order with its span, the
+//! free modifiers after each word, the terminators elided from the text and the pauses between
+//! words. The sentence parsers build it and derive the [`Sentence`] and its [`PredicateTree`]
+//! from it, so that formatters, linters and refactoring tools can work on the syntax without
+//! losing anything written.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use chumsky::{error::Cheap, prelude::end, Error, Parser, Stream};
+
+use crate::{
+    cancel::CancellationToken,
+    lexer::{FiVar, ParticleFamily, PredicateFamily, QuantifierKind, Word, ZiParticle},
+    parser::{
+        bind, connect, element, keep_free, leaf, quantify, syntax_in, Clause, Depth, Modifier,
+        ParserOptions, PredicateTree, Sentence, Sharer, DEFAULT_MAX_DEPTH,
+    },
+    Argument, GrammarVar, Negation, PredicateChaining, Quantifier,
+};
+
+/// The construction a node of the syntax tree stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeKind {
+    /// A whole sentence, with its illocution and addressees.
+    Sentence,
+    /// A vocative and the leaf or group it addresses.
+    Addressee,
+    /// Predicate trees joined by connectives, inside a group or at the top of a sentence.
+    Connected,
+    /// `bi` negations, elements, the `vi` clauses bound to them and the tree chained after them.
+    PredicateTree,
+    /// Elements joined by connectives.
+    Elements,
+    /// `zi` negations, an optional `si` and a leaf, group or abstraction.
+    Element,
+    /// Connected predicate trees between `pe` and `pei`.
+    Group,
+    /// Connected predicate trees after `da`, `de` or `do`, closed by `pei`.
+    Abstraction,
+    /// A `vi` clause and the `fi` clauses after it, closed by `vei`.
+    Sharers,
+    /// A `vi` or `fi`, its arguments and the tree it chains.
+    Sharer,
+    /// A tag and the tree it attaches, closed by `vei`.
+    Adjunct,
+    /// The words of an argument list, closed by `be`.
+    Arguments,
+    /// A predicate tree chained to a place, after an optional quantifier.
+    Chained,
+    /// A quantifier and the number it takes.
+    Quantifier,
+}
+
+/// A word of the sentence, with its index among the words and its span.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub word: Word,
+    pub index: usize,
+    pub span: Range<usize>,
+}
+
+/// A terminator the text leaves out, before the word at `index`, or at the end of the sentence
+/// if there is none. `at` is the end of the word before it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Elided {
+    pub particle: ParticleFamily,
+    pub index: usize,
+    pub at: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Child {
+    Node(Node),
+    Token(Token),
+    Elided(Elided),
+    /// The pauses and spaces between two words, or before the first and after the last.
+    Trivia(Range<usize>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Node {
+    kind: NodeKind,
+    children: Vec<Child>,
+}
+
+impl Node {
+    pub(crate) fn new(kind: NodeKind, children: Vec<Child>) -> Self {
+        Self { kind, children }
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    pub fn children(&self) -> &[Child] {
+        &self.children
+    }
+
+    /// The nodes directly under this one.
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.children.iter().filter_map(|child| match child {
+            Child::Node(node) => Some(node),
+            _ => None,
+        })
+    }
+
+    /// Every word under this node, in order.
+    pub fn tokens(&self) -> Vec<&Token> {
+        let mut tokens = Vec::new();
+        self.collect(&mut |child| {
+            if let Child::Token(token) = child {
+                tokens.push(token);
+            }
+        });
+        tokens
+    }
+
+    /// Every terminator elided under this node, in order.
+    pub fn elided(&self) -> Vec<&Elided> {
+        let mut elided = Vec::new();
+        self.collect(&mut |child| {
+            if let Child::Elided(e) = child {
+                elided.push(e);
+            }
+        });
+        elided
+    }
+
+    /// From the start of the first word under this node to the end of the last.
+    pub fn span(&self) -> Option<Range<usize>> {
+        let tokens = self.tokens();
+        Some(tokens.first()?.span.start..tokens.last()?.span.end)
+    }
+
+    fn collect<'a>(&'a self, f: &mut impl FnMut(&'a Child)) {
+        for child in &self.children {
+            match child {
+                Child::Node(node) => node.collect(f),
+                child => f(child),
+            }
+        }
+    }
+
+    /// The words directly under this node, other than interjections and illocutions.
+    fn words(&self) -> impl Iterator<Item = &Word> {
+        self.children.iter().filter_map(|child| match child {
+            Child::Token(Token {
+                word:
+                    Word::Particle(ParticleFamily::Interjection(_) | ParticleFamily::Illocution(_)),
+                ..
+            }) => None,
+            Child::Token(token) => Some(&token.word),
+            _ => None,
+        })
+    }
+
+    /// The nodes directly under this one, other than addressees.
+    fn parts(&self) -> impl Iterator<Item = &Node> {
+        self.nodes().filter(|node| node.kind != NodeKind::Addressee)
+    }
+
+    fn node(&self, kind: NodeKind) -> Option<&Node> {
+        self.nodes().find(|node| node.kind == kind)
+    }
+}
+
+/// The syntax tree of a sentence, with the sentence derived from it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxTree {
+    root: Node,
+    sentence: Sentence,
+}
+
+impl SyntaxTree {
+    /// The [`NodeKind::Sentence`] node.
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn sentence(&self) -> &Sentence {
+        &self.sentence
+    }
+
+    pub fn tree(&self) -> &PredicateTree {
+        &self.sentence.tree
+    }
+
+    /// What bound each tree chained to the elements of a predicate tree, in the order they are
+    /// written: the place it fills and the particle that chose it, so that tools can tell which
+    /// place of a binding a word fills and why.
+    pub fn places(&self) -> Vec<PlaceBinding> {
+        let mut places = Vec::new();
+        // The nodes still to visit, in reverse order.
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            if node.kind == NodeKind::PredicateTree {
+                bound_places(node, &mut places);
+            }
+            stack.extend(node.nodes().collect::<Vec<_>>().into_iter().rev());
+        }
+        places
+    }
+}
+
+/// What chose the place a tree is bound to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlaceCause {
+    /// The `vi` particle at the span.
+    Vi(Range<usize>),
+    /// The `fi` particle at the span.
+    Fi(Range<usize>),
+    /// The chaining behavior of the elements, for a tree chained directly after them.
+    Chaining,
+}
+
+/// A tree bound to a place of the elements of a predicate tree, or after the whole binding.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlaceBinding {
+    /// From the start of the first element to the end of the last.
+    pub elements: Range<usize>,
+    /// The place filled, which is the slot of the binding's `sharers` the tree is in, or `None`
+    /// for a tree chained after the whole binding.
+    pub place: Option<GrammarVar>,
+    pub chain_with: PredicateChaining,
+    pub cause: PlaceCause,
+    /// The span of the tree bound, with its quantifier.
+    pub span: Range<usize>,
+}
+
+/// Pushes the places bound by the clauses and the chained tree of a [`NodeKind::PredicateTree`]
+/// node, numbered as [`bind`] numbers them.
+fn bound_places(node: &Node, places: &mut Vec<PlaceBinding>) {
+    let Some(elements) = node.node(NodeKind::Elements) else {
+        return;
+    };
+    let Some(span) = elements.span() else {
+        return;
+    };
+    let chained = node.node(NodeKind::Chained);
+    // The tree chained directly is numbered first, at the chaining place of the elements.
+    let chaining = match chained {
+        Some(_) => match derive_joined(elements, &Attached::new(), derive_element) {
+            Some(l) => Some(l.chaining_behavior()),
+            None => return,
+        },
+        None => None,
+    };
+    let mut v = chaining.map_or(0, |chaining| chaining.var);
+    let direct = chained
+        .zip(chaining)
+        .map(|(chained, chaining)| PlaceBinding {
+            elements: span.clone(),
+            place: Some(chaining.var),
+            chain_with: chaining.chain_with,
+            cause: PlaceCause::Chaining,
+            span: chained.span().unwrap_or(span.end..span.end),
+        });
+    for sharer in node
+        .nodes()
+        .filter(|n| n.kind == NodeKind::Sharers)
+        .flat_map(Node::parts)
+    {
+        let Some((var, chain_with, cause)) = sharer.children.iter().find_map(|child| match child {
+            Child::Token(Token {
+                word: Word::Particle(ParticleFamily::Vi { var, chain_with }),
+                span,
+                ..
+            }) => Some((
+                var.map_or(FiVar::None, FiVar::Var),
+                *chain_with,
+                PlaceCause::Vi(span.clone()),
+            )),
+            Child::Token(Token {
+                word: Word::Particle(ParticleFamily::Fi { var, chain_with }),
+                span,
+                ..
+            }) => Some((var.clone(), *chain_with, PlaceCause::Fi(span.clone()))),
+            _ => None,
+        }) else {
+            continue;
+        };
+        v = match var {
+            FiVar::Same | FiVar::None => v,
+            FiVar::Next => v.saturating_add(1),
+            FiVar::Var(v) => v,
+        };
+        let tree = sharer.node(NodeKind::Chained).and_then(Node::span);
+        places.push(PlaceBinding {
+            elements: span.clone(),
+            place: (var != FiVar::None).then_some(v),
+            chain_with,
+            cause,
+            span: tree.unwrap_or(span.end..span.end),
+        });
+    }
+    places.extend(direct);
+}
+
+/// Parses the words of a sentence, as [`parser`](crate::parser::parser) does, into its syntax
+/// tree. The span of each word is its index, and errors point at the word that could not be
+/// accepted.
+pub fn parse(words: &[Word]) -> Result<SyntaxTree, Vec<Cheap<Word>>> {
+    let spanned: Vec<_> = words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| (word.clone(), i..i + 1))
+        .collect();
+    parse_spanned(&spanned, words.len())
+}
+
+/// Like [`parse`], for words with their spans in a text of `len` characters as lexed by
+/// [`lex_spanned`](crate::lexer::lex_spanned), so that the text between the words is kept as
+/// trivia. Errors point at the text.
+pub fn parse_spanned(
+    words: &[(Word, Range<usize>)],
+    len: usize,
+) -> Result<SyntaxTree, Vec<Cheap<Word>>> {
+    let depth = Depth::new(DEFAULT_MAX_DEPTH);
+    // The parser reads the words spanned by their index, which the tokens keep.
+    let indexed = words
+        .iter()
+        .enumerate()
+        .map(|(i, (word, _))| (word.clone(), i..i + 1));
+    let parsed =
+        syntax_in::<Cheap<Word>>(CancellationToken::new(), ParserOptions::default(), &depth)
+            .then_ignore(end())
+            .parse(Stream::from_iter(words.len()..words.len(), indexed));
+    let mut root = match parsed {
+        Ok(root) => root,
+        Err(errors) => {
+            let at = match depth.exceeded() {
+                Some(span) => span.start,
+                None => errors.first().map_or(words.len(), |e| e.span().start),
+            };
+            return Err(error(words, len, at));
+        }
+    };
+    let Some(sentence) = derive_sentence(&root) else {
+        return Err(error(words, len, words.len()));
+    };
+    let mut end = 0;
+    add_spans(&mut root, words, &mut end);
+    if end < len {
+        root.children.push(Child::Trivia(end..len));
+    }
+    Ok(SyntaxTree { root, sentence })
+}
+
+/// The error of a sentence that could not be parsed at the word at `at`.
+fn error(words: &[(Word, Range<usize>)], len: usize, at: usize) -> Vec<Cheap<Word>> {
+    let span = words.get(at).map_or(len..len, |(_, span)| span.clone());
+    let found = words.get(at).map(|(word, _)| word.clone());
+    vec![Cheap::expected_input_found(span, None, found)]
+}
+
+/// Spans the words under the node and the terminators elided there in the text, and inserts
+/// the text between consecutive words before the second of them, `end` being the end of the word
+/// before the node.
+fn add_spans(node: &mut Node, words: &[(Word, Range<usize>)], end: &mut usize) {
+    let mut children = Vec::with_capacity(node.children.len());
+    for mut child in std::mem::take(&mut node.children) {
+        match &mut child {
+            Child::Node(node) => add_spans(node, words, end),
+            Child::Token(token) => {
+                if let Some((_, span)) = words.get(token.index) {
+                    token.span = span.clone();
+                }
+                if *end < token.span.start {
+                    children.push(Child::Trivia(*end..token.span.start));
+                }
+                *end = token.span.end;
+            }
+            Child::Elided(elided) => elided.at = *end,
+            Child::Trivia(_) => {}
+        }
+        children.push(child);
+    }
+    node.children = children;
+}
+
+pub(crate) fn argument(word: &Word) -> Option<(Argument, PredicateChaining)> {
+    match word {
+        Word::Particle(ParticleFamily::Ki(word)) => {
+            Some((Argument::Word(word.clone()), PredicateChaining::Sharing))
+        }
+        Word::Particle(ParticleFamily::Gi(pw)) => Some((
+            Argument::Word(pw.word.clone()),
+            PredicateChaining::Equivalence,
+        )),
+        Word::Predicate(pw, PredicateFamily::Freeform) => {
+            Some((Argument::Name(pw.word.clone()), PredicateChaining::Sharing))
+        }
+        Word::Particle(ParticleFamily::Quote(quote)) => {
+            Some((Argument::Quote(quote.clone()), PredicateChaining::Sharing))
+        }
+        word => count(word).map(|n| (Argument::Number(n), PredicateChaining::Sharing)),
+    }
+}
+
+pub(crate) fn count(word: &Word) -> Option<u64> {
+    match word {
+        Word::Particle(ParticleFamily::Number(number)) => number.value(),
+        _ => None,
+    }
+}
+
+/// The free modifiers of a syntax tree, the interjection and illocution tokens and the addressee
+/// nodes, by the index of the leaf, number or name they follow. Those before the first leaf,
+/// number or name of a sentence or addressee are on it, and those between a vocative and what it
+/// addresses are on the vocative.
+type Attached<'a> = BTreeMap<usize, Vec<&'a Child>>;
+
+fn attach(root: &Node) -> Attached<'_> {
+    let mut attached = Attached::new();
+    // The sentence and each addressee are walked on their own, with the index of the vocative
+    // opening an addressee.
+    let mut scopes = vec![(root, None)];
+    while let Some((scope, vocative)) = scopes.pop() {
+        let (mut owner, mut first, mut last) = (vocative, None, None);
+        let mut leading = Vec::new();
+        // The children still to visit, in reverse order, with the kind of their node.
+        let mut stack: Vec<_> = scope
+            .children
+            .iter()
+            .rev()
+            .map(|c| (c, scope.kind))
+            .collect();
+        while let Some((child, parent)) = stack.pop() {
+            let free = match child {
+                Child::Node(node) if node.kind == NodeKind::Addressee => {
+                    let index = node.children.iter().find_map(|child| match child {
+                        Child::Token(token) => Some(token.index),
+                        _ => None,
+                    });
+                    scopes.push((node, index));
+                    true
+                }
+                Child::Node(node) => {
+                    stack.extend(node.children.iter().rev().map(|c| (c, node.kind)));
+                    false
+                }
+                Child::Token(Token {
+                    word:
+                        Word::Particle(ParticleFamily::Interjection(_) | ParticleFamily::Illocution(_)),
+                    ..
+                }) => true,
+                Child::Token(Token {
+                    word: Word::Particle(ParticleFamily::Vocative(_)),
+                    index,
+                    ..
+                }) => {
+                    owner = Some(*index);
+                    false
+                }
+                Child::Token(Token { word, index, .. })
+                    if matches!(parent, NodeKind::Element | NodeKind::Addressee)
+                        && leaf(word).is_some() =>
+                {
+                    first.get_or_insert(*index);
+                    (owner, last) = (Some(*index), Some(*index));
+                    false
+                }
+                Child::Token(_) => {
+                    owner = last;
+                    false
+                }
+                Child::Elided(_) | Child::Trivia(_) => false,
+            };
+            if free {
+                match owner {
+                    Some(owner) => attached.entry(owner).or_default().push(child),
+                    None => leading.push(child),
+                }
+            }
+        }
+        if let Some(first) = first.or(vocative) {
+            let free = attached.entry(first).or_default();
+            leading.append(free);
+            *free = leading;
+        }
+    }
+    attached
+}
+
+/// The sentence of the syntax tree: its predicate tree, every leaf or group a vocative addresses
+/// wherever the vocative is, and the mood set by its first illocution.
+pub(crate) fn derive_sentence(node: &Node) -> Option<Sentence> {
+    let attached = attach(node);
+    let mut addressees = Vec::new();
+    let mut mood = None;
+    // The children still to visit, in reverse order.
+    let mut stack: Vec<&Child> = node.children.iter().rev().collect();
+    while let Some(child) = stack.pop() {
+        match child {
+            Child::Node(node) => {
+                if node.kind == NodeKind::Addressee {
+                    addressees.push(derive_addressee(node, &attached)?);
+                }
+                stack.extend(node.children.iter().rev());
+            }
+            Child::Token(Token {
+                word: Word::Particle(ParticleFamily::Illocution(m)),
+                ..
+            }) => {
+                mood.get_or_insert(*m);
+            }
+            _ => {}
+        }
+    }
+    Some(Sentence {
+        addressees,
+        tree: derive_connected(node.node(NodeKind::Connected)?, &attached)?,
+        mood,
+    })
+}
+
+fn derive_addressee(node: &Node, attached: &Attached) -> Option<PredicateTree> {
+    match node.node(NodeKind::Group) {
+        Some(group) => derive_connected(group.node(NodeKind::Connected)?, attached),
+        None => derive_leaf(node, attached),
+    }
+}
+
+/// The leaf, number or name directly under the node, with the free modifiers after it.
+fn derive_leaf(node: &Node, attached: &Attached) -> Option<PredicateTree> {
+    let (index, tree) = node.children.iter().find_map(|child| match child {
+        Child::Token(token) => Some((token.index, leaf(&token.word)?)),
+        _ => None,
+    })?;
+    Some(keep_free(tree, derive_free(index, attached)?))
+}
+
+/// The free modifiers after the word at `index`.
+fn derive_free(index: usize, attached: &Attached) -> Option<Vec<Modifier>> {
+    let Some(free) = attached.get(&index) else {
+        return Some(Vec::new());
+    };
+    free.iter()
+        .map(|child| match child {
+            Child::Token(Token {
+                word: Word::Particle(ParticleFamily::Interjection(interjection)),
+                ..
+            }) => Some(Modifier::Interjection(interjection.clone())),
+            Child::Token(Token {
+                word: Word::Particle(ParticleFamily::Illocution(mood)),
+                ..
+            }) => Some(Modifier::Illocution(*mood)),
+            Child::Node(node) => {
+                let (word, index) = node.children.iter().find_map(|child| match child {
+                    Child::Token(Token {
+                        word: Word::Particle(ParticleFamily::Vocative(word)),
+                        index,
+                        ..
+                    }) => Some((word.clone(), *index)),
+                    _ => None,
+                })?;
+                Some(Modifier::Vocative {
+                    word,
+                    free: derive_free(index, attached)?,
+                    addressee: derive_addressee(node, attached)?,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The nodes of a [`NodeKind::Connected`] or [`NodeKind::Elements`] node, derived by `item`,
+/// joined by their connectives.
+fn derive_joined(
+    node: &Node,
+    attached: &Attached,
+    item: fn(&Node, &Attached) -> Option<PredicateTree>,
+) -> Option<PredicateTree> {
+    let mut items = Vec::new();
+    for part in node.parts() {
+        items.push(item(part, attached)?);
+    }
+    join(node, items)
+}
+
+/// Joins the trees derived from the nodes of a [`NodeKind::Connected`] or
+/// [`NodeKind::Elements`] node by their connectives. Deriving the trees nested in each other
+/// recurses as deep as they are, so the joining is kept out of the frames of the recursion.
+fn join(node: &Node, items: Vec<PredicateTree>) -> Option<PredicateTree> {
+    let mut kinds = node.words().filter_map(|w| match w {
+        Word::Particle(ParticleFamily::Connective(kind)) => Some(*kind),
+        _ => None,
+    });
+    let mut items = items.into_iter();
+    let first = items.next()?;
+    items.try_fold(first, |left, right| {
+        Some(connect(left, (kinds.next()?, right)))
+    })
+}
+
+fn derive_connected(node: &Node, attached: &Attached) -> Option<PredicateTree> {
+    derive_joined(node, attached, derive_predicate_tree)
+}
+
+fn derive_predicate_tree(node: &Node, attached: &Attached) -> Option<PredicateTree> {
+    let l = derive_joined(node.node(NodeKind::Elements)?, attached, derive_element)?;
+    let clauses = derive_clauses(node, attached)?;
+    let r = match node.node(NodeKind::Chained) {
+        Some(chained) => Some(derive_chained(chained, attached)?),
+        None => None,
+    };
+    let bi = node
+        .words()
+        .filter(|w| **w == Word::Particle(ParticleFamily::Bi))
+        .count();
+    Some(bind(bi, l, clauses, r))
+}
+
+/// The clauses of a [`NodeKind::PredicateTree`] node.
+fn derive_clauses(node: &Node, attached: &Attached) -> Option<Vec<Clause>> {
+    let mut clauses = Vec::new();
+    for n in node.nodes() {
+        match n.kind {
+            NodeKind::Sharers => {
+                let mut group = Vec::new();
+                for sharer in n.parts() {
+                    group.push(derive_sharer(sharer, attached)?);
+                }
+                clauses.push(Clause::Sharers(group));
+            }
+            NodeKind::Adjunct => clauses.push(derive_adjunct(n, attached)?),
+            _ => {}
+        }
+    }
+    Some(clauses)
+}
+
+fn derive_element(node: &Node, attached: &Attached) -> Option<PredicateTree> {
+    let p = if let Some(group) = node.node(NodeKind::Group) {
+        derive_connected(group.node(NodeKind::Connected)?, attached)?
+    } else if let Some(abstraction) = node.node(NodeKind::Abstraction) {
+        derive_abstraction(abstraction, attached)?
+    } else {
+        derive_leaf(node, attached)?
+    };
+    Some(negate_element(node, p))
+}
+
+/// The element `p` derived from a [`NodeKind::Element`] node stands for, with its `zi` and
+/// `si`.
+fn negate_element(node: &Node, p: PredicateTree) -> PredicateTree {
+    let zi = node
+        .words()
+        .filter(|w| **w == Word::Particle(ParticleFamily::Zi(ZiParticle::Negation)))
+        .count();
+    let si = node.words().find_map(|w| match w {
+        Word::Particle(ParticleFamily::Si { exposure, chaining }) => {
+            Some((exposure.clone(), *chaining))
+        }
+        _ => None,
+    });
+    element(zi, si, p)
+}
+
+fn derive_abstraction(node: &Node, attached: &Attached) -> Option<PredicateTree> {
+    let kind = node.words().find_map(|w| match w {
+        Word::Particle(ParticleFamily::Subordinator(kind)) => Some(*kind),
+        _ => None,
+    })?;
+    let tree = derive_connected(node.node(NodeKind::Connected)?, attached)?;
+    Some(PredicateTree::Abstraction {
+        kind,
+        negation: Negation::None,
+        tree: Box::new(tree),
+    })
+}
+
+fn derive_adjunct(node: &Node, attached: &Attached) -> Option<Clause> {
+    let tag = node.words().find_map(|w| match w {
+        Word::Particle(ParticleFamily::Tag(tag)) => Some(tag.clone()),
+        _ => None,
+    })?;
+    Some(Clause::Adjunct(
+        tag,
+        derive_chained(node.node(NodeKind::Chained)?, attached)?,
+    ))
+}
+
+fn derive_sharer(node: &Node, attached: &Attached) -> Option<(Sharer, PredicateTree)> {
+    let binding = node.words().find_map(|w| match w {
+        Word::Particle(ParticleFamily::Vi { var, chain_with }) => {
+            Some((var.map_or(FiVar::None, FiVar::Var), *chain_with))
+        }
+        Word::Particle(ParticleFamily::Fi { var, chain_with }) => Some((var.clone(), *chain_with)),
+        _ => None,
+    })?;
+    let args = node
+        .node(NodeKind::Arguments)
+        .map(|args| args.words().filter_map(argument).collect());
+    let p = derive_chained(node.node(NodeKind::Chained)?, attached)?;
+    Some(((binding, args), p))
+}
+
+fn derive_chained(node: &Node, attached: &Attached) -> Option<PredicateTree> {
+    let q = match node.node(NodeKind::Quantifier) {
+        Some(quantifier) => Some(derive_quantifier(quantifier)?),
+        None => None,
+    };
+    let p = derive_predicate_tree(node.node(NodeKind::PredicateTree)?, attached)?;
+    Some(quantify(q, p))
+}
+
+fn derive_quantifier(node: &Node) -> Option<Quantifier> {
+    let mut words = node.words();
+    match words.next()? {
+        Word::Particle(ParticleFamily::Quantifier(kind)) => match kind {
+            QuantifierKind::All => Some(Quantifier::All),
+            QuantifierKind::Some => Some(Quantifier::Some),
+            QuantifierKind::Most => Some(Quantifier::Most),
+            QuantifierKind::Exactly => words.next().and_then(count).map(Quantifier::Exactly),
+        },
+        _ => None,
+    }
+}
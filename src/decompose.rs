@@ -0,0 +1,111 @@
+//! Readings of a long predicate word as a compound: every way to split it into consecutive
+//! roots. The lexer has no affixes, so every component is a root in its own right.
+
+use std::{fmt, ops::Range};
+
+use crate::{dictionary::Dictionary, phonotactics::is_valid_root};
+
+/// A root within a compound, with its span in characters of the word.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Component {
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+/// A split of a word into two roots or more, in order, spelling the word back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Segmentation(pub Vec<Component>);
+
+impl Segmentation {
+    /// The texts of the components, in order.
+    pub fn texts(&self) -> Vec<&str> {
+        self.0.iter().map(|c| &*c.text).collect()
+    }
+}
+
+impl fmt::Display for Segmentation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{}", component.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every segmentation of `word` into roots, fewest components first, then in alphabetical order.
+/// A word that does not split into roots, including a word too short to, has none.
+pub fn decompose(word: &str) -> Vec<Segmentation> {
+    let mut segmentations = segmentations(word);
+    segmentations.sort_by(|a, b| {
+        a.0.len()
+            .cmp(&b.0.len())
+            .then_with(|| a.texts().cmp(&b.texts()))
+    });
+    segmentations
+}
+
+/// Like [`decompose`], ranking first the segmentations with the fewest components missing from
+/// `dictionary`.
+pub fn decompose_in(word: &str, dictionary: &Dictionary) -> Vec<Segmentation> {
+    let unknown = |s: &Segmentation| {
+        s.0.iter()
+            .filter(|c| dictionary.get(&c.text).is_none())
+            .count()
+    };
+    let mut segmentations = segmentations(word);
+    segmentations.sort_by(|a, b| {
+        unknown(a)
+            .cmp(&unknown(b))
+            .then_with(|| a.0.len().cmp(&b.0.len()))
+            .then_with(|| a.texts().cmp(&b.texts()))
+    });
+    segmentations
+}
+
+fn segmentations(word: &str) -> Vec<Segmentation> {
+    let letters: Vec<char> = word.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let n = letters.len();
+    // Whether the letters from i to j spell a root, for every i < j.
+    let roots: Vec<Vec<bool>> = (0..n)
+        .map(|i| {
+            (0..=n)
+                .map(|j| i < j && is_valid_root(&letters[i..j].iter().collect::<String>()).is_ok())
+                .collect()
+        })
+        .collect();
+
+    let mut segmentations = Vec::new();
+    let mut components = Vec::new();
+    extend(&letters, &roots, 0, &mut components, &mut segmentations);
+    segmentations
+}
+
+/// Adds every segmentation of the letters from `start` on following `components`.
+fn extend(
+    letters: &[char],
+    roots: &[Vec<bool>],
+    start: usize,
+    components: &mut Vec<Component>,
+    segmentations: &mut Vec<Segmentation>,
+) {
+    if start == letters.len() {
+        if components.len() > 1 {
+            segmentations.push(Segmentation(components.clone()));
+        }
+        return;
+    }
+    for end in start + 1..=letters.len() {
+        if !roots[start][end] {
+            continue;
+        }
+        components.push(Component {
+            text: letters[start..end].iter().collect(),
+            span: start..end,
+        });
+        extend(letters, roots, end, components, segmentations);
+        components.pop();
+    }
+}
@@ -0,0 +1,622 @@
+//! A table-driven implementation of the morphology of [`crate::lexer`]. Letters are classified
+//! and consonant pairs checked by lookup tables, and each rule is a function over the
+//! preprocessed letters that makes the same ordered choices as the combinators, so that it
+//! accepts the same texts and produces the same words. It only reports whether a text lexes;
+//! errors are left to the combinator lexer, which explains them.
+
+use std::ops::Range;
+
+use itertools::Itertools;
+
+use crate::{
+    explain::MorphRule,
+    lexer::{
+        assemble_spanned_numbers, chaining_of, is_freeform, nonsonorant_family, pronoun,
+        sonorant_or_vowel_family, ChainingFamily, FiVar, ParticleFamily, PhonologyTables,
+        PredicateFamily, PredicateWord, Quote, Word, ARG_VOWELS, NON_SONORANT, SONORANT, VOWELS,
+    },
+    ChainingBehavior, Exposure, GrammarVar, PredicateChaining,
+};
+
+const OTHER: u8 = 0;
+const VOWEL: u8 = 1;
+const NON_SONORANT_CLASS: u8 = 2;
+const SONORANT_CLASS: u8 = 3;
+const H: u8 = 4;
+const PAUSE: u8 = 5;
+
+/// The class of every ASCII character.
+const CLASSES: [u8; 128] = {
+    let mut classes = [OTHER; 128];
+    let mut i = 0;
+    while i < VOWELS.len() {
+        classes[VOWELS[i] as usize] = VOWEL;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < NON_SONORANT.len() {
+        classes[NON_SONORANT[i] as usize] = NON_SONORANT_CLASS;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < SONORANT.len() {
+        classes[SONORANT[i] as usize] = SONORANT_CLASS;
+        i += 1;
+    }
+    classes[b'h' as usize] = H;
+    classes[b'\'' as usize] = PAUSE;
+    // The ASCII characters for which `char::is_whitespace` holds.
+    let mut i = 0x09;
+    while i <= 0x0d {
+        classes[i] = PAUSE;
+        i += 1;
+    }
+    classes[b' ' as usize] = PAUSE;
+    classes
+};
+
+fn class(c: char) -> u8 {
+    match CLASSES.get(c as usize) {
+        Some(class) => *class,
+        None if c.is_whitespace() => PAUSE,
+        None => OTHER,
+    }
+}
+
+fn arg_var(c: char) -> Option<GrammarVar> {
+    ARG_VOWELS
+        .iter()
+        .position(|v| *v == c)
+        .map(|i| i as GrammarVar)
+}
+
+/// Like the lexer's `place` rule: a place vowel, then as many `h` and place vowel pairs as follow.
+/// Fails, rather than stopping early, past the last place a `GrammarVar` holds.
+fn place(chars: &[char], i: usize) -> Option<(GrammarVar, usize)> {
+    let mut var = arg_var(*chars.get(i)?)?;
+    let mut j = i + 1;
+    while let (Some('h'), Some(digit)) = (chars.get(j), chars.get(j + 1).and_then(|c| arg_var(*c)))
+    {
+        var = var
+            .checked_add(1)?
+            .checked_mul(ARG_VOWELS.len() as GrammarVar)?
+            .checked_add(digit)?;
+        j += 2;
+    }
+    Some((var, j))
+}
+
+fn chaining(var: GrammarVar, chain_with: PredicateChaining) -> ChainingBehavior {
+    ChainingBehavior { var, chain_with }
+}
+
+fn equivalence(equivalent: bool) -> PredicateChaining {
+    if equivalent {
+        PredicateChaining::Equivalence
+    } else {
+        PredicateChaining::Sharing
+    }
+}
+
+/// The preprocessed letters of a text. Every rule takes the position to start at and returns
+/// the position after what it matched.
+struct Letters<'a> {
+    chars: Vec<char>,
+    tables: &'a PhonologyTables,
+}
+
+impl Letters<'_> {
+    fn class(&self, i: usize) -> u8 {
+        self.chars.get(i).map_or(OTHER, |c| class(*c))
+    }
+
+    fn is(&self, i: usize, class: u8) -> bool {
+        self.chars.get(i).is_some_and(|c| self::class(*c) == class)
+    }
+
+    fn is_char(&self, i: usize, c: char) -> bool {
+        self.chars.get(i) == Some(&c)
+    }
+
+    fn literal(&self, i: usize, s: &str) -> Option<usize> {
+        let mut j = i;
+        for c in s.chars() {
+            if !self.is_char(j, c) {
+                return None;
+            }
+            j += 1;
+        }
+        Some(j)
+    }
+
+    fn text(&self, range: Range<usize>) -> String {
+        self.chars.get(range).unwrap_or_default().iter().collect()
+    }
+
+    fn pauses(&self, mut i: usize) -> usize {
+        while self.is(i, PAUSE) {
+            i += 1;
+        }
+        i
+    }
+
+    fn vowels(&self, i: usize) -> Option<usize> {
+        let mut j = i;
+        while self.is(j, VOWEL) {
+            j += 1;
+        }
+        (j > i).then_some(j)
+    }
+
+    fn medial_pair(&self, i: usize) -> bool {
+        match (self.chars.get(i), self.chars.get(i + 1)) {
+            (Some(a), Some(b)) => self.tables.is_medial_pair(*a, *b),
+            _ => false,
+        }
+    }
+
+    /// A medial pair or a sonorant, then vowels.
+    fn required_string(&self, i: usize) -> Option<usize> {
+        if self.medial_pair(i) {
+            self.vowels(i + 2)
+        } else if self.is(i, SONORANT_CLASS) {
+            self.vowels(i + 1)
+        } else {
+            None
+        }
+    }
+
+    /// A medial pair, a sonorant or `h`, then vowels.
+    fn root_mix(&self, i: usize) -> Option<usize> {
+        if self.medial_pair(i) {
+            self.vowels(i + 2)
+        } else if self.is(i, SONORANT_CLASS) || self.is(i, H) {
+            self.vowels(i + 1)
+        } else {
+            None
+        }
+    }
+
+    fn root_mixes(&self, mut i: usize) -> usize {
+        while let Some(j) = self.root_mix(i) {
+            i = j;
+        }
+        i
+    }
+
+    fn sonorant_or_not(&self, i: usize) -> usize {
+        if self.is(i, SONORANT_CLASS) {
+            i + 1
+        } else {
+            i
+        }
+    }
+
+    fn nonsonorant_root(&self, i: usize) -> Option<usize> {
+        if !self.is(i, NON_SONORANT_CLASS) {
+            return None;
+        }
+        let j = self.vowels(i + 1)?;
+        let strings = self
+            .required_string(j)
+            .or_else(|| self.required_string(self.root_mixes(j)))
+            .map(|k| self.sonorant_or_not(self.root_mixes(k)));
+        match strings {
+            Some(end) => Some(end),
+            None if self.is(j, SONORANT_CLASS) => Some(j + 1),
+            None => None,
+        }
+    }
+
+    fn initial_pair_root(&self, i: usize) -> Option<usize> {
+        match (self.chars.get(i), self.chars.get(i + 1)) {
+            (Some(a), Some(b)) if self.tables.is_initial_pair(*a, *b) => {}
+            _ => return None,
+        }
+        let j = self.vowels(i + 2)?;
+        Some(self.sonorant_or_not(self.root_mixes(j)))
+    }
+
+    /// A whole run of letters ending in a non-sonorant.
+    fn freeform(&self, i: usize) -> Option<usize> {
+        let mut j = i;
+        while matches!(
+            self.class(j),
+            VOWEL | NON_SONORANT_CLASS | SONORANT_CLASS | H
+        ) {
+            j += 1;
+        }
+        let ends_run = j == self.chars.len() || self.is(j, PAUSE);
+        (ends_run && is_freeform(&self.text(i..j))).then_some(j)
+    }
+
+    fn root(&self, word: String) -> Word {
+        let chaining = chaining_of(&word, ChainingFamily::Root);
+        Word::Predicate(PredicateWord { word, chaining }, PredicateFamily::Root)
+    }
+
+    /// A word of non-pause characters.
+    fn quoted_word(&self, i: usize) -> Option<usize> {
+        let mut j = i;
+        while j < self.chars.len() && !self.is(j, PAUSE) {
+            j += 1;
+        }
+        (j > i).then_some(j)
+    }
+
+    fn ce(&self, i: usize) -> Option<(ParticleFamily, usize)> {
+        let j = self.literal(i, "ce")?;
+        let k = self.pauses(j);
+        let end = self.quoted_word(k).filter(|_| k > j)?;
+        Some((ParticleFamily::Quote(Quote::Word(self.text(k..end))), end))
+    }
+
+    fn cu(&self, i: usize) -> Option<(ParticleFamily, usize)> {
+        let j = self.literal(i, "cu")?;
+        let k = self.pauses(j);
+        let start = self.quoted_word(k).filter(|_| k > j)?;
+        let delimiter = self.text(k..start);
+        for content_end in start..self.chars.len() {
+            let m = self.pauses(content_end);
+            if m == content_end {
+                continue;
+            }
+            let Some(end) = self.literal(m, &delimiter) else {
+                continue;
+            };
+            if end < self.chars.len() && !self.is(end, PAUSE) {
+                continue;
+            }
+            let content = self.pauses(start).min(content_end);
+            return Some((
+                ParticleFamily::Quote(Quote::Foreign {
+                    content: self.text(content..content_end),
+                    delimiter,
+                }),
+                end,
+            ));
+        }
+        None
+    }
+
+    fn vi(&self, i: usize) -> Option<(ParticleFamily, usize)> {
+        if self.is_char(i, 'v') {
+            let j = if self.is_char(i + 1, 'i') {
+                i + 2
+            } else {
+                i + 1
+            };
+            if let Some((var, end)) = place(&self.chars, j) {
+                return Some((
+                    ParticleFamily::Vi {
+                        var: Some(var),
+                        chain_with: equivalence(j == i + 2),
+                    },
+                    end,
+                ));
+            }
+        }
+        let end = self.literal(i, "vi")?;
+        Some((
+            ParticleFamily::Vi {
+                var: None,
+                chain_with: PredicateChaining::Sharing,
+            },
+            end,
+        ))
+    }
+
+    fn fi(&self, i: usize) -> Option<(ParticleFamily, usize)> {
+        let fixed = [
+            ("feu", FiVar::Same, PredicateChaining::Sharing),
+            ("fau", FiVar::Next, PredicateChaining::Sharing),
+            ("fei", FiVar::Same, PredicateChaining::Equivalence),
+            ("fai", FiVar::Next, PredicateChaining::Equivalence),
+        ];
+        for (s, var, chain_with) in fixed {
+            if let Some(end) = self.literal(i, s) {
+                return Some((ParticleFamily::Fi { var, chain_with }, end));
+            }
+        }
+        if self.is_char(i, 'f') {
+            let j = if self.is_char(i + 1, 'i') {
+                i + 2
+            } else {
+                i + 1
+            };
+            if let Some((var, end)) = place(&self.chars, j) {
+                return Some((
+                    ParticleFamily::Fi {
+                        var: FiVar::Var(var),
+                        chain_with: equivalence(j == i + 2),
+                    },
+                    end,
+                ));
+            }
+        }
+        let end = self.literal(i, "fi")?;
+        Some((
+            ParticleFamily::Fi {
+                var: FiVar::None,
+                chain_with: PredicateChaining::Sharing,
+            },
+            end,
+        ))
+    }
+
+    fn mi(&self, i: usize) -> Option<(ParticleFamily, usize)> {
+        if !self.is_char(i, 'm') {
+            return None;
+        }
+        let end = self.vowels(i + 1)?;
+        Some((ParticleFamily::Mi(pronoun(self.text(i..end))), end))
+    }
+
+    fn si(&self, i: usize) -> Option<(ParticleFamily, usize)> {
+        if !self.is_char(i, 's') {
+            return None;
+        }
+        let arg = |j: usize| self.chars.get(j).and_then(|c| arg_var(*c));
+        let j = i + 1;
+        if self.is_char(j, 'i') {
+            if let Some(var) = arg(j + 1) {
+                return Some((
+                    ParticleFamily::Si {
+                        exposure: Exposure::Transparent,
+                        chaining: chaining(var, PredicateChaining::Equivalence),
+                    },
+                    j + 2,
+                ));
+            }
+        }
+        let modified = |vars: Vec<GrammarVar>, chained: Option<GrammarVar>, equivalent: bool| {
+            ParticleFamily::Si {
+                chaining: chaining(
+                    chained.or(vars.last().copied()).unwrap_or(0),
+                    equivalence(equivalent),
+                ),
+                exposure: Exposure::Modified(vars),
+            }
+        };
+        if self.is_char(j, 'i') {
+            // `sih` and an argument vowel, then `i`.
+            return match arg(j + 2) {
+                Some(var) if self.is_char(j + 1, 'h') && self.is_char(j + 3, 'i') => {
+                    Some((modified(Vec::new(), Some(var), true), j + 4))
+                }
+                _ => None,
+            };
+        }
+        let mut vars = Vec::new();
+        let mut k = j;
+        while let Some(var) = arg(k) {
+            vars.push(var);
+            k += 1;
+        }
+        if vars.is_empty() {
+            return None;
+        }
+        let chained = match arg(k + 1) {
+            Some(var) if self.is_char(k, 'h') => {
+                k += 2;
+                Some(var)
+            }
+            _ => None,
+        };
+        let equivalent = self.is_char(k, 'i');
+        if equivalent {
+            k += 1;
+        }
+        Some((modified(vars, chained, equivalent), k))
+    }
+
+    fn specific_particle(&self, i: usize) -> Option<(ParticleFamily, usize)> {
+        let fixed = |s: &str, family: ParticleFamily| Some((family, self.literal(i, s)?));
+        self.ce(i)
+            .or_else(|| self.cu(i))
+            .or_else(|| fixed("pei", ParticleFamily::Pei))
+            .or_else(|| fixed("pe", ParticleFamily::Pe))
+            .or_else(|| fixed("be", ParticleFamily::Be))
+            .or_else(|| fixed("vei", ParticleFamily::Vei))
+            .or_else(|| self.vi(i))
+            .or_else(|| self.fi(i))
+            .or_else(|| self.mi(i))
+            .or_else(|| self.si(i))
+            .or_else(|| fixed("bi", ParticleFamily::Bi))
+    }
+
+    fn nonsonorant_particle(&self, i: usize) -> Option<usize> {
+        if !self.is(i, NON_SONORANT_CLASS) {
+            return None;
+        }
+        let mut end = self.vowels(i + 1)?;
+        while let Some(j) = self.is(end, H).then(|| self.vowels(end + 1)).flatten() {
+            end = j;
+        }
+        Some(end)
+    }
+
+    /// A particle starting with a sonorant or a vowel, which must follow a pause.
+    fn sonorant_or_vowel_particle(&self, i: usize) -> Option<usize> {
+        let mut end = match self.class(i) {
+            SONORANT_CLASS if self.is(i + 1, VOWEL) => i + 2,
+            VOWEL => i + 1,
+            _ => return None,
+        };
+        while self.is(end, H) || self.is(end, SONORANT_CLASS) {
+            match self.vowels(end + 1) {
+                Some(j) => end = j,
+                None => break,
+            }
+        }
+        Some(self.sonorant_or_not(end))
+    }
+
+    /// The rule matching the word after the pauses at `i`, the position the word starts at, and
+    /// the position after it.
+    fn matched(&self, i: usize) -> Option<(Rule, usize, usize)> {
+        let start = self.pauses(i);
+        let matched = |rule: Rule, end: Option<usize>| end.map(|end| (rule, start, end));
+        matched(Rule::Freeform, self.freeform(start))
+            .or_else(|| matched(Rule::NonsonorantRoot, self.nonsonorant_root(start)))
+            .or_else(|| matched(Rule::InitialPairRoot, self.initial_pair_root(start)))
+            .or_else(|| {
+                let (particle, end) = self.specific_particle(start)?;
+                Some((Rule::Particle(particle), start, end))
+            })
+            .or_else(|| matched(Rule::NonsonorantParticle, self.nonsonorant_particle(start)))
+            .or_else(|| {
+                let end = self.sonorant_or_vowel_particle(start).filter(|_| start > i);
+                matched(Rule::SonorantOrVowelParticle, end)
+            })
+    }
+
+    fn word(&self, rule: Rule, range: Range<usize>) -> Word {
+        match rule {
+            Rule::Freeform => {
+                let word = self.text(range);
+                let chaining = chaining_of(&word, ChainingFamily::Freeform);
+                Word::Predicate(PredicateWord { word, chaining }, PredicateFamily::Freeform)
+            }
+            Rule::NonsonorantRoot | Rule::InitialPairRoot => self.root(self.text(range)),
+            Rule::Particle(particle) => Word::Particle(particle),
+            Rule::NonsonorantParticle => Word::Particle(nonsonorant_family(self.text(range))),
+            Rule::SonorantOrVowelParticle => {
+                Word::Particle(sonorant_or_vowel_family(self.text(range)))
+            }
+        }
+    }
+}
+
+/// The rule a word was matched by. Only the particles with their own rules are built while
+/// matching; other words are built from their letters once they are known to be needed.
+enum Rule {
+    Freeform,
+    NonsonorantRoot,
+    InitialPairRoot,
+    Particle(ParticleFamily),
+    NonsonorantParticle,
+    SonorantOrVowelParticle,
+}
+
+/// A word matched by a rule, by its range in the preprocessed letters and its span in the
+/// characters of the text.
+struct Match {
+    rule: Rule,
+    letters: Range<usize>,
+    span: Range<usize>,
+}
+
+/// The preprocessed letters of `s`, with the span of each in the characters of `s`.
+fn letters<'a>(s: &str, tables: &'a PhonologyTables) -> (Letters<'a>, Vec<Range<usize>>) {
+    let (chars, spans) = s
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .dedup_with_count()
+        .scan(0, |i, (n, c)| {
+            let start = *i;
+            *i += n;
+            Some((c, start..*i))
+        })
+        .unzip();
+    (Letters { chars, tables }, spans)
+}
+
+/// The words of `s` by the rules that match them, with their spans, or `None` if it does not
+/// lex.
+fn matches<'a>(s: &str, tables: &'a PhonologyTables) -> Option<(Letters<'a>, Vec<Match>)> {
+    let (letters, spans) = letters(s, tables);
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while let Some((rule, start, end)) = letters.matched(i) {
+        let span = match (spans.get(start), spans.get(end - 1)) {
+            (Some(start), Some(end)) => start.start..end.end,
+            _ => return None,
+        };
+        matches.push(Match {
+            rule,
+            letters: start..end,
+            span,
+        });
+        i = end;
+    }
+    (letters.pauses(i) == letters.chars.len()).then_some((letters, matches))
+}
+
+/// Lexes `s` like [`spanned_lexer`](crate::lexer::spanned_lexer), or returns `None` if it does
+/// not lex.
+pub fn lex(s: &str) -> Option<Vec<(Word, Range<usize>)>> {
+    lex_with_tables(s, &PhonologyTables::STANDARD)
+}
+
+/// Like [`lex`], but allowing the consonant pairs of `tables` instead of the standard ones.
+pub fn lex_with_tables(s: &str, tables: &PhonologyTables) -> Option<Vec<(Word, Range<usize>)>> {
+    let (letters, matches) = matches(s, tables)?;
+    let words = matches
+        .into_iter()
+        .map(
+            |Match {
+                 rule,
+                 letters: range,
+                 span,
+             }| {
+                let word = letters.word(rule, range);
+                trace!(Token { text: word.text() });
+                (word, span)
+            },
+        )
+        .collect();
+    Some(assemble_spanned_numbers(words))
+}
+
+/// The words of `s` with their spans and the rule each was read by, as [`lex`] would find them
+/// but without joining digits into numbers, or `None` if `s` does not lex.
+pub(crate) fn lex_with_rules(s: &str) -> Option<Vec<(Word, Range<usize>, MorphRule)>> {
+    let (letters, matches) = matches(s, &PhonologyTables::STANDARD)?;
+    Some(
+        matches
+            .into_iter()
+            .map(
+                |Match {
+                     rule,
+                     letters: range,
+                     span,
+                 }| {
+                    let morph_rule = match &rule {
+                        Rule::Freeform => MorphRule::Freeform,
+                        Rule::NonsonorantRoot => MorphRule::NonsonorantRoot,
+                        Rule::InitialPairRoot => MorphRule::InitialPairRoot,
+                        Rule::Particle(particle) => MorphRule::Particle(particle.name()),
+                        Rule::NonsonorantParticle => MorphRule::NonsonorantParticle,
+                        Rule::SonorantOrVowelParticle => MorphRule::SonorantOrVowelParticle,
+                    };
+                    (letters.word(rule, range), span, morph_rule)
+                },
+            )
+            .collect(),
+    )
+}
+
+/// The spans of the words of `s` in its characters, as [`lex`] would find them, without
+/// building the words. Consecutive digits are separate words. Returns `None` if `s` does not
+/// lex.
+pub fn boundaries(s: &str) -> Option<Vec<Range<usize>>> {
+    let (_, matches) = matches(s, &PhonologyTables::STANDARD)?;
+    Some(matches.into_iter().map(|m| m.span).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{preprocess, spanned_lexer};
+    use chumsky::{error::Cheap, Parser};
+    use proptest::{prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn lexes_as_the_combinators(s in "(ce|cu|vi|fe|ge|ie|mo|bure|dona|mian|alis|pe|pei|be|ba|zi|ni|pa|re|a|e|o|[a-z]| |')*") {
+            let combinator = spanned_lexer::<Cheap<_>>().parse(preprocess(&s)).ok();
+            prop_assert_eq!(lex(&s), combinator, "{:?}", s);
+        }
+    }
+}
@@ -0,0 +1,974 @@
+use std::{fmt, ops::Range, str::FromStr};
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    ambiguity::{ambiguities, Ambiguity},
+    attachment::readings,
+    cancel::Cancelled,
+    cst::{self, NodeKind},
+    dictionary::Dictionary,
+    fixit::{parse_spanned_with_suggestions, ParseDiagnostic},
+    json::Value,
+    lexer::{
+        lex_recovering, lex_spanned, lex_with_warnings, lex_words, place_text, LexError,
+        LexErrorReason, LexWarning, LexWarningReason, ParticleFamily, PreProcessed,
+        PredicateFamily, PredicateWord, SpannedWords, Text, Word,
+    },
+    normalize::NormalizeAction,
+    parser::{parser, PredicateTree},
+    sanitize::SanitizeAction,
+    spelling::{suggest, suggest_in},
+    visit::Order,
+    Exposure, GrammarVar,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// The stable code of each kind of diagnostic. Codes are never reused or renumbered, so that
+/// tools can filter and suppress diagnostics by code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Code {
+    UnclosedPe,
+    MissingBe,
+    UnclosedVi,
+    MissingPe,
+    UnexpectedToken,
+    SyntaxError,
+    InvalidCharacter,
+    InvalidInitialPair,
+    MalformedWord,
+    IncompleteWord,
+    InvalidMedialPair,
+    StrayConsonant,
+    MissingVowel,
+    Cancelled,
+    RedundantBe,
+    RedundantVei,
+    RedundantPei,
+    InvisibleCharacter,
+    PunctuationReplaced,
+    EmojiRemoved,
+    ApostropheReplaced,
+    FullWidthFolded,
+    DiacriticStripped,
+    LetterWithDiacritic,
+    MissingPause,
+    RepeatedLetter,
+    VowelRun,
+    AmbiguousSegmentation,
+    TextTooLong,
+    WordTooLong,
+    AmbiguousAttachment,
+    OpenVi,
+    MissingPlace,
+    LeadingFi,
+    ExtraPlace,
+}
+
+impl Code {
+    pub const ALL: [Code; 35] = [
+        Code::UnclosedPe,
+        Code::MissingBe,
+        Code::UnclosedVi,
+        Code::MissingPe,
+        Code::UnexpectedToken,
+        Code::SyntaxError,
+        Code::InvalidCharacter,
+        Code::InvalidInitialPair,
+        Code::MalformedWord,
+        Code::IncompleteWord,
+        Code::InvalidMedialPair,
+        Code::StrayConsonant,
+        Code::MissingVowel,
+        Code::Cancelled,
+        Code::RedundantBe,
+        Code::RedundantVei,
+        Code::RedundantPei,
+        Code::InvisibleCharacter,
+        Code::PunctuationReplaced,
+        Code::EmojiRemoved,
+        Code::ApostropheReplaced,
+        Code::FullWidthFolded,
+        Code::DiacriticStripped,
+        Code::LetterWithDiacritic,
+        Code::MissingPause,
+        Code::RepeatedLetter,
+        Code::VowelRun,
+        Code::AmbiguousSegmentation,
+        Code::TextTooLong,
+        Code::WordTooLong,
+        Code::AmbiguousAttachment,
+        Code::OpenVi,
+        Code::MissingPlace,
+        Code::LeadingFi,
+        Code::ExtraPlace,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Code::UnclosedPe => "E001",
+            Code::MissingBe => "E002",
+            Code::UnclosedVi => "E003",
+            Code::MissingPe => "E004",
+            Code::UnexpectedToken => "E005",
+            Code::SyntaxError => "E006",
+            Code::InvalidCharacter => "E010",
+            Code::InvalidInitialPair => "E011",
+            Code::MalformedWord => "E012",
+            Code::IncompleteWord => "E013",
+            Code::InvalidMedialPair => "E014",
+            Code::StrayConsonant => "E015",
+            Code::MissingVowel => "E016",
+            Code::LetterWithDiacritic => "E017",
+            Code::TextTooLong => "E018",
+            Code::WordTooLong => "E019",
+            Code::Cancelled => "E020",
+            Code::RedundantBe => "W001",
+            Code::RedundantVei => "W002",
+            Code::RedundantPei => "W003",
+            Code::InvisibleCharacter => "W010",
+            Code::PunctuationReplaced => "W011",
+            Code::EmojiRemoved => "W012",
+            Code::ApostropheReplaced => "W013",
+            Code::FullWidthFolded => "W014",
+            Code::DiacriticStripped => "W015",
+            Code::MissingPause => "W016",
+            Code::RepeatedLetter => "W017",
+            Code::VowelRun => "W018",
+            Code::AmbiguousSegmentation => "W019",
+            Code::AmbiguousAttachment => "W020",
+            Code::OpenVi => "W021",
+            Code::MissingPlace => "W022",
+            Code::LeadingFi => "W023",
+            Code::ExtraPlace => "W024",
+        }
+    }
+
+    pub fn severity(self) -> Severity {
+        match self.as_str().as_bytes().first() {
+            Some(b'W') => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// A one-line explanation of the code.
+    pub fn description(self) -> &'static str {
+        match self {
+            Code::UnclosedPe => "a `pe` group is not closed by `pei`",
+            Code::MissingBe => "a list of arguments is not terminated by `be`",
+            Code::UnclosedVi => "a `vi` or `fi` clause is not closed by `vei`",
+            Code::MissingPe => "a group is closed without being opened by `pe`",
+            Code::UnexpectedToken => "a word appears where it cannot",
+            Code::SyntaxError => "the sentence does not parse",
+            Code::InvalidCharacter => "a character is not part of the alphabet",
+            Code::InvalidInitialPair => "a word starts with a consonant pair that is not allowed",
+            Code::MalformedWord => "letters do not form a valid word",
+            Code::IncompleteWord => "the text ends in the middle of a word",
+            Code::InvalidMedialPair => "a consonant pair inside a word is not allowed",
+            Code::StrayConsonant => "a consonant cannot attach to the letters around it",
+            Code::MissingVowel => "a word has no vowel",
+            Code::Cancelled => "the analysis was cancelled",
+            Code::RedundantBe => "a `be` can be elided",
+            Code::RedundantVei => "a `vei` can be elided",
+            Code::RedundantPei => "a `pei` can be elided",
+            Code::InvisibleCharacter => "an invisible character was removed",
+            Code::PunctuationReplaced => "a punctuation mark was replaced by a pause",
+            Code::EmojiRemoved => "an emoji was removed",
+            Code::ApostropheReplaced => "a typographic apostrophe was replaced by a pause",
+            Code::FullWidthFolded => "a full-width character was replaced by its ASCII form",
+            Code::DiacriticStripped => "the diacritics of a letter were stripped",
+            Code::LetterWithDiacritic => "a letter has diacritics",
+            Code::TextTooLong => "the text is longer than the limit",
+            Code::WordTooLong => "a word is longer than the limit",
+            Code::MissingPause => "a word is not separated from the one before it by a pause",
+            Code::RepeatedLetter => "a repeated letter is read as a single one",
+            Code::VowelRun => "three vowels or more in a row are easily misread",
+            Code::AmbiguousSegmentation => {
+                "letters without pauses are read as other words than a forgotten pause would give"
+            }
+            Code::AmbiguousAttachment => {
+                "elided terminators leave open what a predicate is chained to"
+            }
+            Code::OpenVi => {
+                "a `vi` or `fi` clause left open takes in a clause that a `vei` would attach to \
+                 the predicate before it"
+            }
+            Code::MissingPlace => "a chain binds a place its predicate does not have",
+            Code::LeadingFi => "a chain starts with a `fi` clause rather than a `vi` clause",
+            Code::ExtraPlace => {
+                "a chain binds a place past the last place of its predicate, which is filled by \
+                 a variable of its own"
+            }
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Code {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Code::ALL
+            .into_iter()
+            .find(|code| code.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown diagnostic code `{s}`"))
+    }
+}
+
+/// What a diagnostic points at: characters of the source text or tokens of the lexed sentence.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Span {
+    Chars(Range<usize>),
+    Tokens(Range<usize>),
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Span::Chars(r) => write!(f, "characters {}..{}", r.start, r.end),
+            Span::Tokens(r) => write!(f, "tokens {}..{}", r.start, r.end),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub code: Code,
+    pub message: String,
+    pub span: Option<Span>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: Code, message: impl Into<String>, span: Option<Span>) -> Self {
+        Diagnostic {
+            code,
+            message: message.into(),
+            span,
+            help: Vec::new(),
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.code.severity()
+    }
+
+    /// Classifies the errors of the lexer on `text`.
+    pub fn from_lex_errors(text: &str, errors: &[Cheap<PreProcessed>]) -> Vec<Self> {
+        errors
+            .iter()
+            .map(|e| Diagnostic::from(&LexError::classify(text, e.span())))
+            .collect()
+    }
+
+    /// Classifies a parse failure by the repair closest to the error.
+    pub fn from_parse(diagnostic: &ParseDiagnostic) -> Self {
+        let span = diagnostic.errors.first().map(|e| Span::Tokens(e.span()));
+        let insertion = diagnostic
+            .suggestions
+            .first()
+            .filter(|s| s.span.is_empty())
+            .map(|s| s.replacement.as_str());
+        let (code, message) = match insertion {
+            Some("pei") => (Code::UnclosedPe, "unclosed `pe`"),
+            Some("be") => (Code::MissingBe, "missing `be`"),
+            Some("vei") => (Code::UnclosedVi, "unclosed `vi` or `fi`"),
+            Some("pe") => (Code::MissingPe, "missing `pe`"),
+            _ if !diagnostic.suggestions.is_empty() => (Code::UnexpectedToken, "unexpected word"),
+            _ => (Code::SyntaxError, "syntax error"),
+        };
+        Diagnostic {
+            help: diagnostic
+                .suggestions
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            ..Diagnostic::new(code, message, span)
+        }
+    }
+
+    /// Like [`from_parse`](Self::from_parse), pointing at the `pe` left open when `words` end
+    /// inside a group that a `pei` at the end would close, or at a `pei` closing no group, rather
+    /// than at the end of the sentence.
+    pub fn from_parse_in(words: &[Word], diagnostic: &ParseDiagnostic) -> Self {
+        let diagnostic = Diagnostic::from_parse(diagnostic);
+        let Some(Span::Tokens(span)) = &diagnostic.span else {
+            return diagnostic;
+        };
+        let at = span.start.min(words.len());
+        let closes = |words: &[Word]| {
+            let mut closed = words.to_vec();
+            closed.push(Word::Particle(ParticleFamily::Pei));
+            parser::<Cheap<Word>>().parse(closed).is_ok()
+        };
+        let mut open = Vec::new();
+        for (i, word) in words[..at].iter().enumerate() {
+            match word {
+                Word::Particle(ParticleFamily::Pe) => open.push(i),
+                Word::Particle(ParticleFamily::Pei) => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+        let (code, message, span) = match (words.get(at), open.last()) {
+            (Some(Word::Particle(ParticleFamily::Pei)), None) => {
+                (Code::MissingPe, "`pei` closes no `pe`", at..at + 1)
+            }
+            (None, Some(pe)) if closes(words) => (Code::UnclosedPe, "unclosed `pe`", *pe..*pe + 1),
+            _ => return diagnostic,
+        };
+        Diagnostic {
+            code,
+            message: message.to_string(),
+            span: Some(Span::Tokens(span)),
+            ..diagnostic
+        }
+    }
+
+    /// A rustc-style JSON representation.
+    pub fn to_json(&self) -> Value {
+        let spans = match &self.span {
+            Some(Span::Chars(r)) => vec![span_json("char", r)],
+            Some(Span::Tokens(r)) => vec![span_json("token", r)],
+            None => Vec::new(),
+        };
+        let children = self
+            .help
+            .iter()
+            .map(|help| {
+                Value::Object(vec![
+                    ("message".into(), help.as_str().into()),
+                    ("level".into(), "help".into()),
+                    ("spans".into(), Value::Array(Vec::new())),
+                ])
+            })
+            .collect::<Vec<_>>();
+        Value::Object(vec![
+            ("$message_type".into(), "diagnostic".into()),
+            ("message".into(), self.message.as_str().into()),
+            (
+                "code".into(),
+                Value::Object(vec![
+                    ("code".into(), self.code.as_str().into()),
+                    ("explanation".into(), self.code.description().into()),
+                ]),
+            ),
+            ("level".into(), self.severity().to_string().into()),
+            ("spans".into(), spans.into()),
+            ("children".into(), children.into()),
+            ("rendered".into(), self.to_string().into()),
+        ])
+    }
+}
+
+fn span_json(unit: &str, r: &Range<usize>) -> Value {
+    Value::Object(vec![
+        ("unit".into(), unit.into()),
+        ("start".into(), r.start.into()),
+        ("end".into(), r.end.into()),
+        ("is_primary".into(), true.into()),
+    ])
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity(), self.code, self.message)?;
+        if let Some(span) = &self.span {
+            write!(f, "\n  --> {span}")?;
+        }
+        for help in &self.help {
+            write!(f, "\n  = help: {help}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&SanitizeAction> for Diagnostic {
+    fn from(action: &SanitizeAction) -> Self {
+        let (code, offset, message) = match *action {
+            SanitizeAction::ZeroWidthRemoved { offset, character } => (
+                Code::InvisibleCharacter,
+                offset,
+                format!("removed invisible U+{:04X}", u32::from(character)),
+            ),
+            SanitizeAction::PunctuationToPause { offset, character } => (
+                Code::PunctuationReplaced,
+                offset,
+                format!("replaced `{character}` with a pause"),
+            ),
+            SanitizeAction::EmojiRemoved { offset, character } => {
+                (Code::EmojiRemoved, offset, format!("removed `{character}`"))
+            }
+        };
+        Diagnostic::new(code, message, Some(Span::Chars(offset..offset + 1)))
+    }
+}
+
+impl From<&NormalizeAction> for Diagnostic {
+    fn from(action: &NormalizeAction) -> Self {
+        let (code, offset, message) = match *action {
+            NormalizeAction::ApostropheToPause { offset, character } => (
+                Code::ApostropheReplaced,
+                offset,
+                format!("replaced `{character}` with `'`"),
+            ),
+            NormalizeAction::FullWidthFolded { offset, character } => (
+                Code::FullWidthFolded,
+                offset,
+                format!("replaced full-width `{character}`"),
+            ),
+            NormalizeAction::DiacriticStripped { offset, character } => (
+                Code::DiacriticStripped,
+                offset,
+                format!("stripped the diacritics of `{character}`"),
+            ),
+            NormalizeAction::DiacriticFlagged { offset, character } => (
+                Code::LetterWithDiacritic,
+                offset,
+                format!("`{character}` has diacritics"),
+            ),
+        };
+        Diagnostic::new(code, message, Some(Span::Chars(offset..offset + 1)))
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(error: &LexError) -> Self {
+        let code = match error.reason {
+            LexErrorReason::InvalidCharacter(_) => Code::InvalidCharacter,
+            LexErrorReason::InvalidInitialPair(..) => Code::InvalidInitialPair,
+            LexErrorReason::InvalidMedialPair(..) => Code::InvalidMedialPair,
+            LexErrorReason::StrayConsonant(_) => Code::StrayConsonant,
+            LexErrorReason::MissingVowel => Code::MissingVowel,
+            LexErrorReason::UnexpectedEnd => Code::IncompleteWord,
+            LexErrorReason::Malformed => Code::MalformedWord,
+            LexErrorReason::TextTooLong(_) => Code::TextTooLong,
+            LexErrorReason::WordTooLong(_) => Code::WordTooLong,
+        };
+        Diagnostic::new(
+            code,
+            error.reason.to_string(),
+            Some(Span::Chars(error.span.clone())),
+        )
+    }
+}
+
+impl From<&LexWarning> for Diagnostic {
+    fn from(warning: &LexWarning) -> Self {
+        let code = match warning.reason {
+            LexWarningReason::MissingPause => Code::MissingPause,
+            LexWarningReason::RepeatedLetter(_) => Code::RepeatedLetter,
+            LexWarningReason::VowelRun => Code::VowelRun,
+        };
+        Diagnostic::new(
+            code,
+            warning.reason.to_string(),
+            Some(Span::Chars(warning.span.clone())),
+        )
+    }
+}
+
+impl From<&Ambiguity> for Diagnostic {
+    fn from(ambiguity: &Ambiguity) -> Self {
+        let words = |words: &SpannedWords, separator: &str| {
+            words
+                .iter()
+                .map(|(word, _)| word.text())
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        Diagnostic {
+            help: ambiguity
+                .alternatives
+                .iter()
+                .map(|alternative| format!("write `{}` if that is meant", words(alternative, " ")))
+                .collect(),
+            ..Diagnostic::new(
+                Code::AmbiguousSegmentation,
+                format!(
+                    "`{}` is read as `{}`",
+                    words(&ambiguity.read, ""),
+                    words(&ambiguity.read, " ")
+                ),
+                Some(Span::Chars(ambiguity.span.clone())),
+            )
+        }
+    }
+}
+
+impl From<&Cancelled> for Diagnostic {
+    fn from(cancelled: &Cancelled) -> Self {
+        Diagnostic::new(Code::Cancelled, cancelled.to_string(), None)
+    }
+}
+
+/// Terminators that can be elided, with the code reported for them.
+const ELIDABLE: [(ParticleFamily, Code); 3] = [
+    (ParticleFamily::Be, Code::RedundantBe),
+    (ParticleFamily::Vei, Code::RedundantVei),
+    (ParticleFamily::Pei, Code::RedundantPei),
+];
+
+/// The number of spelling suggestions given for a malformed word.
+const SPELLING_SUGGESTIONS: usize = 3;
+
+/// The word of `text` containing the character at `at`, between the pauses around it.
+fn word_at(text: &str, at: usize) -> String {
+    let is_pause = |c: &char| c.is_whitespace() || *c == '\'';
+    let chars: Vec<char> = text.chars().collect();
+    let at = at.min(chars.len());
+    let start = chars[..at].iter().rposition(is_pause).map_or(0, |i| i + 1);
+    chars[start..].iter().take_while(|c| !is_pause(c)).collect()
+}
+
+/// The runs of letters of a sentence that a forgotten pause would read as other words, as found
+/// by [`ambiguities`]. Nothing is reported for a sentence that does not lex, as [`check`] reports
+/// why.
+pub fn check_ambiguities(text: &str) -> Vec<Diagnostic> {
+    ambiguities(text)
+        .map(|ambiguities| ambiguities.iter().map(Diagnostic::from).collect())
+        .unwrap_or_default()
+}
+
+/// The other ways to chain the predicates of a sentence that writing terminators would give, as
+/// found by [`readings`]. Nothing is reported for a sentence that does not lex or parse, as
+/// [`check`] reports why.
+pub fn check_attachments(text: &str) -> Vec<Diagnostic> {
+    let Ok(words) = lex_words(text) else {
+        return Vec::new();
+    };
+    let readings = readings(&words);
+    let Some(start) = readings.iter().filter_map(|r| r.inserted.first()).min() else {
+        return Vec::new();
+    };
+    vec![Diagnostic {
+        help: readings
+            .iter()
+            .map(|reading| format!("write `{}` if that is meant", Text(&reading.words)))
+            .collect(),
+        ..Diagnostic::new(
+            Code::AmbiguousAttachment,
+            "predicates after elided terminators can be chained elsewhere",
+            Some(Span::Tokens(*start..words.len())),
+        )
+    }]
+}
+
+/// The chains of a sentence binding a place that `dictionary` does not give the predicate they
+/// are chained to, or chaining a predicate by a place it does not have, and those
+/// [`extra_places`] finds. Words without places in the dictionary are not checked, and nothing
+/// is reported for a sentence that does not lex or parse, as [`check`] reports why.
+pub fn check_places(text: &str, dictionary: &Dictionary) -> Vec<Diagnostic> {
+    let Ok(words) = lex_words(text) else {
+        return Vec::new();
+    };
+    let mut diagnostics = place_diagnostics(&words, dictionary);
+    if let Ok(syntax) = cst::parse(&words) {
+        extend_places(&mut diagnostics, extra_places(&syntax, Some(dictionary)));
+    }
+    diagnostics
+}
+
+/// Adds the places past the last of a predicate that [`extra_places`] finds to `diagnostics`,
+/// dropping those [`place_diagnostics`] reports at the same word.
+fn extend_places(diagnostics: &mut Vec<Diagnostic>, extra: Vec<Diagnostic>) {
+    diagnostics.retain(|d| {
+        d.code != Code::MissingPlace || !extra.iter().any(|extra| extra.span == d.span)
+    });
+    diagnostics.extend(extra);
+}
+
+fn place_diagnostics(words: &[Word], dictionary: &Dictionary) -> Vec<Diagnostic> {
+    // Predicate words are renamed to their token index, so that each leaf of the tree tells which
+    // token it was read from.
+    let mark = |i: usize, pw: &PredicateWord| PredicateWord {
+        word: i.to_string(),
+        chaining: pw.chaining,
+    };
+    let marked: Vec<Word> = words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| match word {
+            Word::Predicate(pw, family) => Word::Predicate(mark(i, pw), *family),
+            Word::Particle(ParticleFamily::Gi(pw)) => {
+                Word::Particle(ParticleFamily::Gi(mark(i, pw)))
+            }
+            Word::Particle(ParticleFamily::Mi(pw)) => {
+                Word::Particle(ParticleFamily::Mi(mark(i, pw)))
+            }
+            word => word.clone(),
+        })
+        .collect();
+    let Ok(tree) = parser::<Cheap<Word>>().parse(marked) else {
+        return Vec::new();
+    };
+    let places = |token: usize| {
+        let word = words.get(token)?.text();
+        let entry = dictionary.get(&word).filter(|e| !e.places.is_empty())?;
+        Some((word, entry))
+    };
+    let mut diagnostics = Vec::new();
+    let mut missing = |token: usize, link: usize, var: GrammarVar, chained: bool| {
+        let Some((word, entry)) = places(token) else {
+            return;
+        };
+        if entry.places.iter().any(|p| p.var == var) {
+            return;
+        }
+        let message = if chained {
+            format!(
+                "`{word}` is chained by place {}, which it does not have",
+                place_name(var)
+            )
+        } else {
+            format!("`{word}` has no place {}", place_name(var))
+        };
+        let has: Vec<String> = entry.places.iter().map(|p| place_name(p.var)).collect();
+        diagnostics.push(Diagnostic {
+            help: vec![format!("`{word}` has places {}", has.join(", "))],
+            ..Diagnostic::new(
+                Code::MissingPlace,
+                message,
+                Some(Span::Tokens(link..link + 1)),
+            )
+        });
+    };
+    tree.walk(Order::Pre, &mut |t| {
+        let PredicateTree::Binding { root, sharers, .. } = t else {
+            return;
+        };
+        let root_token = chained_token(root);
+        for (var, set) in sharers.iter().enumerate() {
+            for (_, sharer) in set {
+                let first = sharer.leaves().filter_map(|l| token_of(l.leaf)).min();
+                if let (Some(token), Some(first)) = (root_token, first) {
+                    // The particle binding the place, unless the sharer is chained directly.
+                    let link = (token + 1..first)
+                        .rev()
+                        .find(|&j| {
+                            matches!(
+                                words[j],
+                                Word::Particle(
+                                    ParticleFamily::Vi { .. } | ParticleFamily::Fi { .. }
+                                )
+                            )
+                        })
+                        .unwrap_or(first);
+                    missing(token, link, var as GrammarVar, false);
+                }
+                if let Some(token) = chained_token(sharer) {
+                    missing(token, token, sharer.chaining_behavior().var, true);
+                }
+            }
+        }
+    });
+    diagnostics
+}
+
+/// The token a leaf marked by [`place_diagnostics`] was read from.
+fn token_of(leaf: &PredicateTree) -> Option<usize> {
+    match leaf {
+        PredicateTree::Leaf { word, .. } => word.word.parse().ok(),
+        _ => None,
+    }
+}
+
+/// The token of the word whose places a tree is chained by, if it is chained by the places of a
+/// word.
+fn chained_token(tree: &PredicateTree) -> Option<usize> {
+    match tree {
+        PredicateTree::Leaf { .. } => token_of(tree),
+        PredicateTree::Binding {
+            root,
+            exposure: Exposure::Standard,
+            ..
+        } => chained_token(root),
+        PredicateTree::Quantified { tree, .. } => chained_token(tree),
+        _ => None,
+    }
+}
+
+/// A place as definitions write it, such as `[A]` or `[EHE]`.
+fn place_name(var: GrammarVar) -> String {
+    format!("[{}]", place_text(var).to_ascii_uppercase())
+}
+
+/// The tokens of the `fi` particles starting a chain in `node`.
+fn leading_fi(node: &cst::Node, leading: &mut Vec<usize>) {
+    if node.kind() == NodeKind::Sharers {
+        let first = node
+            .nodes()
+            .next()
+            .and_then(|sharer| sharer.tokens().first().copied());
+        if let Some(token) = first {
+            if let Word::Particle(ParticleFamily::Fi { .. }) = token.word {
+                leading.push(token.index);
+            }
+        }
+    }
+    for child in node.nodes() {
+        leading_fi(child, leading);
+    }
+}
+
+/// The tokens of the `vi` and `fi` particles whose chained tree takes in a clause of its own,
+/// each with the first token of that clause. The clause could be meant for the predicate before
+/// the particle, which a `vei` before it would attach it to.
+fn clauses_taken_in(node: &cst::Node, taken_in: &mut Vec<(usize, usize)>) {
+    fn chained_tree(node: &cst::Node) -> Option<&cst::Node> {
+        let child = node.nodes().find(|n| n.kind() == NodeKind::Chained)?;
+        child.nodes().find(|n| n.kind() == NodeKind::PredicateTree)
+    }
+    if node.kind() == NodeKind::Sharer {
+        let particle = node.tokens().first().map(|token| token.index);
+        let mut tree = chained_tree(node);
+        while let (Some(particle), Some(t)) = (particle, tree) {
+            let clause = t
+                .nodes()
+                .find(|n| matches!(n.kind(), NodeKind::Sharers | NodeKind::Adjunct));
+            if let Some(token) = clause.and_then(|c| c.tokens().first().copied()) {
+                taken_in.push((particle, token.index));
+                break;
+            }
+            tree = chained_tree(t);
+        }
+    }
+    for child in node.nodes() {
+        clauses_taken_in(child, taken_in);
+    }
+}
+
+/// The chains binding a place past the last of the element they are bound to: past the one
+/// place of a number, name, quote or abstraction, or past the places `dictionary` gives a
+/// predicate word, by a `si` exposing that place, or by a clause or a tree chained directly to
+/// it. Such a place is filled by a variable of its own, which nothing else in the sentence refers
+/// to.
+fn extra_places(syntax: &cst::SyntaxTree, dictionary: Option<&Dictionary>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut extra = |what: &str, has: &[GrammarVar], var: GrammarVar, at: usize| {
+        let has = match has {
+            [place] => format!("only place {}", place_name(*place)),
+            has => {
+                let names: Vec<String> = has.iter().map(|p| place_name(*p)).collect();
+                format!("places {}", names.join(", "))
+            }
+        };
+        diagnostics.push(Diagnostic {
+            help: vec![format!(
+                "{what} has {has}; place {} is filled by a variable of its own",
+                place_name(var)
+            )],
+            ..Diagnostic::new(
+                Code::ExtraPlace,
+                format!("{what} has no place {}", place_name(var)),
+                Some(Span::Tokens(at..at + 1)),
+            )
+        });
+    };
+    let places = syntax.places();
+    // The nodes still to visit, in reverse order.
+    let mut stack = vec![syntax.root()];
+    while let Some(node) = stack.pop() {
+        stack.extend(node.nodes().collect::<Vec<_>>().into_iter().rev());
+        if node.kind() != NodeKind::PredicateTree {
+            continue;
+        }
+        let Some(elements) = node.nodes().find(|n| n.kind() == NodeKind::Elements) else {
+            continue;
+        };
+        let [element] = elements.nodes().collect::<Vec<_>>()[..] else {
+            continue;
+        };
+        let tokens = element.tokens();
+        let (what, has) = if let Some(abstraction) =
+            element.nodes().find(|n| n.kind() == NodeKind::Abstraction)
+        {
+            match abstraction.tokens().first() {
+                Some(token) => (
+                    format!("the abstraction by `{}`", token.word.text()),
+                    vec![0],
+                ),
+                None => continue,
+            }
+        } else if let Some(token) = tokens.iter().find(|token| {
+            matches!(
+                token.word,
+                Word::Predicate(_, PredicateFamily::Freeform)
+                    | Word::Particle(ParticleFamily::Number(_) | ParticleFamily::Quote(_))
+            )
+        }) {
+            (format!("`{}`", token.word.text()), vec![0])
+        } else {
+            let entry = tokens.iter().find_map(|token| match token.word {
+                Word::Predicate(..)
+                | Word::Particle(ParticleFamily::Gi(_) | ParticleFamily::Mi(_)) => {
+                    let word = token.word.text();
+                    let entry = dictionary?.get(&word).filter(|e| !e.places.is_empty())?;
+                    Some((word, entry))
+                }
+                _ => None,
+            });
+            match entry {
+                Some((word, entry)) => (
+                    format!("`{word}`"),
+                    entry.places.iter().map(|p| p.var).collect(),
+                ),
+                None => continue,
+            }
+        };
+        let Some(last) = has.iter().max().copied() else {
+            continue;
+        };
+        // A `si` exposing another place chains the element by it.
+        let si = tokens.iter().find_map(|token| match &token.word {
+            Word::Particle(ParticleFamily::Si { chaining, .. }) => Some((chaining.var, token)),
+            _ => None,
+        });
+        if let Some((var, token)) = si.filter(|(var, _)| *var > last) {
+            extra(&what, &has, var, token.index);
+        }
+        let Some(span) = elements.span() else {
+            continue;
+        };
+        for place in places.iter().filter(|p| p.elements == span) {
+            let Some(var) = place.place.filter(|var| *var > last) else {
+                continue;
+            };
+            let at = match &place.cause {
+                cst::PlaceCause::Vi(at) | cst::PlaceCause::Fi(at) => at.start,
+                cst::PlaceCause::Chaining => place.span.start,
+            };
+            extra(&what, &has, var, at);
+        }
+    }
+    diagnostics
+}
+
+/// Lexes and parses a sentence, reporting every malformed word, or else the warnings about its
+/// spelling followed by the parse error or every terminator whose removal gives the same parse.
+pub fn check(text: &str) -> Vec<Diagnostic> {
+    check_with_dictionary(text, None)
+}
+
+/// Like [`check`], ranking the words of `dictionary` first among the spelling suggestions for
+/// malformed words, and reporting the chains [`check_places`] finds.
+pub fn check_with_dictionary(text: &str, dictionary: Option<&Dictionary>) -> Vec<Diagnostic> {
+    let (words, errors) = lex_recovering(text);
+    if !errors.is_empty() {
+        return errors
+            .iter()
+            .map(|error| {
+                let word = word_at(text, error.span.start);
+                let suggestions = match dictionary {
+                    Some(dictionary) => suggest_in(&word, SPELLING_SUGGESTIONS, dictionary),
+                    None => suggest(&word, SPELLING_SUGGESTIONS),
+                };
+                Diagnostic {
+                    help: suggestions
+                        .iter()
+                        .map(|s| format!("did you mean `{s}`?"))
+                        .collect(),
+                    ..Diagnostic::from(error)
+                }
+            })
+            .collect();
+    }
+    let mut diagnostics: Vec<Diagnostic> = lex_with_warnings(text)
+        .map(|(_, warnings)| warnings.iter().map(Diagnostic::from).collect())
+        .unwrap_or_default();
+    let spanned = lex_spanned(text).unwrap_or_default();
+    let tree = match parse_spanned_with_suggestions(&spanned) {
+        Ok(tree) => tree,
+        Err(diagnostic) => {
+            diagnostics.push(Diagnostic::from_parse_in(&words, &diagnostic));
+            return diagnostics;
+        }
+    };
+    let parser = parser::<Cheap<Word>>();
+    let syntax = cst::parse(&words).ok();
+    let mut taken_in = Vec::new();
+    if let Some(syntax) = &syntax {
+        clauses_taken_in(syntax.root(), &mut taken_in);
+    }
+    for (i, at) in taken_in {
+        let mut candidate = words.clone();
+        candidate.insert(at, Word::Particle(ParticleFamily::Vei));
+        if parser.parse(candidate).is_ok_and(|t| t != tree) {
+            diagnostics.push(Diagnostic {
+                help: vec![format!(
+                    "insert `vei` before token {at} to attach the clause there to the predicate \
+                     before `{}`",
+                    words[i].text()
+                )],
+                ..Diagnostic::new(
+                    Code::OpenVi,
+                    format!("`{}` left open", words[i].text()),
+                    Some(Span::Tokens(i..i + 1)),
+                )
+            });
+        }
+    }
+    let mut extra = Vec::new();
+    if let Some(syntax) = syntax {
+        extra = extra_places(&syntax, dictionary);
+        let mut leading = Vec::new();
+        leading_fi(syntax.root(), &mut leading);
+        for i in leading {
+            diagnostics.push(Diagnostic {
+                help: vec![
+                    "parsers with strict chains only accept a chain starting with `vi`".to_string(),
+                ],
+                ..Diagnostic::new(
+                    Code::LeadingFi,
+                    format!("chain starts with `{}`", words[i].text()),
+                    Some(Span::Tokens(i..i + 1)),
+                )
+            });
+        }
+    }
+    for (i, word) in words.iter().enumerate() {
+        let Some((particle, code)) = ELIDABLE
+            .iter()
+            .find(|(p, _)| *word == Word::Particle(p.clone()))
+        else {
+            continue;
+        };
+        let mut candidate = words.clone();
+        candidate.remove(i);
+        if parser.parse(candidate).is_ok_and(|t| t == tree) {
+            diagnostics.push(Diagnostic {
+                help: vec![format!("remove tokens {i}..{}", i + 1)],
+                ..Diagnostic::new(
+                    *code,
+                    format!("redundant `{}`", Word::Particle(particle.clone()).text()),
+                    Some(Span::Tokens(i..i + 1)),
+                )
+            });
+        }
+    }
+    if let Some(dictionary) = dictionary {
+        diagnostics.extend(place_diagnostics(&words, dictionary));
+    }
+    extend_places(&mut diagnostics, extra);
+    diagnostics
+}
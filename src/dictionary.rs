@@ -0,0 +1,522 @@
+use std::{collections::BTreeMap, fmt};
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    collisions::{collisions, Collision},
+    expr::{ExprBackend, Predicate, Var},
+    json::{self, Value},
+    lexer::{
+        lexer, parse_place, preprocess, AbstractionKind, PredicateFamily, Word, PARTICLE_FAMILIES,
+    },
+    parser::PredicateTree,
+    semantics::{interpret, SemanticsBackend},
+    GrammarVar, Quantifier,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Family {
+    Root,
+    Borrowing,
+    Freeform,
+    Particle(String),
+}
+
+impl Family {
+    pub fn parse(s: &str) -> Self {
+        match &*s.trim().to_ascii_lowercase() {
+            "root" => Family::Root,
+            "borrowing" => Family::Borrowing,
+            "freeform" => Family::Freeform,
+            _ => Family::Particle(s.trim().to_ascii_uppercase()),
+        }
+    }
+
+    /// Whether a lexed word belongs to this family. Particles the lexer does not classify yet
+    /// match any particle family it does not know about.
+    pub fn matches(&self, word: &Word) -> bool {
+        match (self, word) {
+            (Family::Root, Word::Predicate(_, PredicateFamily::Root)) => true,
+            (Family::Borrowing, Word::Predicate(_, PredicateFamily::Borrowing)) => true,
+            (Family::Freeform, Word::Predicate(_, PredicateFamily::Freeform)) => true,
+            (Family::Particle(name), Word::Particle(family)) => {
+                family.name() == name
+                    || (family.name() == "OTHER" && !PARTICLE_FAMILIES.contains(&&**name))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Family::Root => write!(f, "root"),
+            Family::Borrowing => write!(f, "borrowing"),
+            Family::Freeform => write!(f, "freeform"),
+            Family::Particle(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Place {
+    pub var: GrammarVar,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entry {
+    pub word: String,
+    /// The identifier of the definition in the source dictionary, if it has one.
+    pub id: Option<String>,
+    pub family: Family,
+    pub definition: String,
+    pub gloss: Option<String>,
+    pub places: Vec<Place>,
+}
+
+impl Entry {
+    pub fn arity(&self) -> usize {
+        self.places.len()
+    }
+
+    /// The name of a place: its label in the dictionary, if it has one. Places have no
+    /// conventional names, as what each place holds depends on the root.
+    pub fn role(&self, var: GrammarVar) -> Option<&str> {
+        self.places
+            .iter()
+            .find(|p| p.var == var)
+            .and_then(|p| p.label.as_deref())
+    }
+}
+
+/// The places referenced by a definition, written `[E]`, `[A]`, `[O]` and `[U]` as in the
+/// official dictionary, and later places as `vi` particles spell them, such as `[EHE]`, in place
+/// order.
+pub fn places_of(definition: &str) -> Vec<GrammarVar> {
+    let mut vars: Vec<GrammarVar> = definition
+        .split('[')
+        .skip(1)
+        .filter_map(|s| {
+            let (inside, _) = s.split_once(']')?;
+            parse_place(&inside.to_ascii_lowercase())
+        })
+        .collect();
+    vars.sort_unstable();
+    vars.dedup();
+    vars
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    Json(json::ParseError),
+    Csv {
+        line: usize,
+        message: String,
+    },
+    Format {
+        word: Option<String>,
+        message: String,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Json(e) => write!(f, "{e}"),
+            ImportError::Csv { line, message } => write!(f, "CSV line {line}: {message}"),
+            ImportError::Format {
+                word: Some(word),
+                message,
+            } => write!(f, "entry `{word}`: {message}"),
+            ImportError::Format {
+                word: None,
+                message,
+            } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<json::ParseError> for ImportError {
+    fn from(e: json::ParseError) -> Self {
+        ImportError::Json(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub word: String,
+    pub stated: Family,
+    /// The family the word actually lexes into, or `None` if it is not a single valid word.
+    pub found: Option<String>,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.found {
+            Some(found) => write!(
+                f,
+                "`{}` is listed as {} but lexes as {found}",
+                self.word, self.stated
+            ),
+            None => write!(
+                f,
+                "`{}` is listed as {} but is not a single valid word",
+                self.word, self.stated
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dictionary {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, entry: Entry) -> Option<Entry> {
+        self.entries.insert(entry.word.clone(), entry)
+    }
+
+    pub fn get(&self, word: &str) -> Option<&Entry> {
+        self.entries.get(word)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Imports the official JSON export: either an object mapping each word to its entry, or an
+    /// array of entries each carrying a `word` field. Entries need a `family`; `id`,
+    /// `definition` (or `long`), `gloss` (or `short`) and `places` (an array of place labels)
+    /// are optional.
+    pub fn from_json(s: &str) -> Result<Self, ImportError> {
+        let value = json::parse(s)?;
+        let mut dictionary = Dictionary::new();
+        match &value {
+            Value::Object(fields) => {
+                for (word, entry) in fields {
+                    dictionary.insert(entry_from_json(Some(word), entry)?);
+                }
+            }
+            Value::Array(entries) => {
+                for entry in entries {
+                    dictionary.insert(entry_from_json(None, entry)?);
+                }
+            }
+            _ => {
+                return Err(ImportError::Format {
+                    word: None,
+                    message: "expected an object or an array of entries".to_string(),
+                })
+            }
+        }
+        Ok(dictionary)
+    }
+
+    /// Imports the CSV dump. The first line is a header naming the columns; `word` and `family`
+    /// are required, `id`, `definition`, `gloss` and `places` (labels separated by `;`) are
+    /// optional.
+    pub fn from_csv(s: &str) -> Result<Self, ImportError> {
+        let mut rows = csv_rows(s)?.into_iter();
+        let Some((_, header)) = rows.next() else {
+            return Ok(Dictionary::new());
+        };
+        let column = |name: &str| header.iter().position(|h| h.trim() == name);
+        let missing = |name: &str| ImportError::Csv {
+            line: 1,
+            message: format!("missing `{name}` column"),
+        };
+        let word_col = column("word").ok_or_else(|| missing("word"))?;
+        let family_col = column("family").ok_or_else(|| missing("family"))?;
+        let definition_col = column("definition");
+        let gloss_col = column("gloss");
+        let places_col = column("places");
+        let id_col = column("id");
+
+        let mut dictionary = Dictionary::new();
+        for (line, row) in rows {
+            if row.iter().all(|c| c.trim().is_empty()) {
+                continue;
+            }
+            let cell = |col: usize| row.get(col).map(|c| c.trim()).unwrap_or("");
+            let word = cell(word_col);
+            if word.is_empty() {
+                return Err(ImportError::Csv {
+                    line,
+                    message: "empty word".to_string(),
+                });
+            }
+            let definition = definition_col.map(cell).unwrap_or("").to_string();
+            let labels = places_col
+                .map(cell)
+                .filter(|p| !p.is_empty())
+                .map(|p| p.split(';').map(|l| l.trim().to_string()).collect());
+            dictionary.insert(Entry {
+                word: word.to_string(),
+                id: id_col
+                    .map(cell)
+                    .filter(|i| !i.is_empty())
+                    .map(str::to_string),
+                family: Family::parse(cell(family_col)),
+                places: build_places(&definition, labels),
+                definition,
+                gloss: gloss_col
+                    .map(cell)
+                    .filter(|g| !g.is_empty())
+                    .map(str::to_string),
+            });
+        }
+        Ok(dictionary)
+    }
+
+    /// The pairs of words of the dictionary that are easily confused, as found by
+    /// [`collisions`](crate::collisions::collisions).
+    pub fn collisions(&self) -> Vec<Collision> {
+        collisions(self.iter().map(|entry| &*entry.word))
+    }
+
+    /// Checks that every word lexes as a single word of its stated family.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let lexer = lexer::<Cheap<_>>();
+        self.iter()
+            .filter_map(|entry| {
+                // Sonorant and vowel initial particles need a leading pause.
+                let padded = format!(" {}", entry.word);
+                let found = match lexer.parse(preprocess(&padded)) {
+                    Ok(words) if words.len() == 1 => Some(words[0].clone()),
+                    _ => None,
+                };
+                match found {
+                    Some(w) if entry.family.matches(&w) => None,
+                    found => Some(ValidationIssue {
+                        word: entry.word.clone(),
+                        stated: entry.family.clone(),
+                        found: found.map(|w| match w {
+                            Word::Particle(family) => family.name().to_string(),
+                            Word::Predicate(_, family) => format!("{family:?}").to_lowercase(),
+                        }),
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+fn build_places(definition: &str, labels: Option<Vec<String>>) -> Vec<Place> {
+    match labels {
+        Some(labels) => labels
+            .into_iter()
+            .enumerate()
+            .map(|(i, label)| Place {
+                var: i as GrammarVar,
+                label: (!label.is_empty()).then_some(label),
+            })
+            .collect(),
+        None => places_of(definition)
+            .into_iter()
+            .map(|var| Place { var, label: None })
+            .collect(),
+    }
+}
+
+fn entry_from_json(word: Option<&str>, value: &Value) -> Result<Entry, ImportError> {
+    let word = word
+        .or_else(|| value.get("word").and_then(Value::as_str))
+        .ok_or_else(|| ImportError::Format {
+            word: None,
+            message: "entry without a word".to_string(),
+        })?;
+    let error = |message: &str| ImportError::Format {
+        word: Some(word.to_string()),
+        message: message.to_string(),
+    };
+    if value.as_object().is_none() {
+        return Err(error("expected an object"));
+    }
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| value.get(k).and_then(Value::as_str))
+            .map(str::to_string)
+    };
+    let family = text(&["family"]).ok_or_else(|| error("missing family"))?;
+    let definition = text(&["definition", "long"]).unwrap_or_default();
+    let labels = match value.get("places") {
+        None | Some(Value::Null) => None,
+        Some(Value::Array(labels)) => Some(
+            labels
+                .iter()
+                .map(|l| l.as_str().map(str::to_string).unwrap_or_default())
+                .collect(),
+        ),
+        Some(_) => return Err(error("`places` must be an array")),
+    };
+    Ok(Entry {
+        word: word.to_string(),
+        id: text(&["id"]),
+        family: Family::parse(&family),
+        places: build_places(&definition, labels),
+        definition,
+        gloss: text(&["gloss", "short"]),
+    })
+}
+
+fn csv_rows(s: &str) -> Result<Vec<(usize, Vec<String>)>, ImportError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut in_quotes = false;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (false, '"') if cell.is_empty() => in_quotes = true,
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            (_, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                cell.push(c);
+            }
+        }
+    }
+    if in_quotes {
+        return Err(ImportError::Csv {
+            line: row_line,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+/// What the dictionary says about the word of a leaf.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeafMetadata {
+    pub definition_id: Option<String>,
+    pub family: Family,
+    pub places: Vec<Place>,
+}
+
+/// Metadata of the leaves of a formula whose word is in the dictionary, keyed by leaf id.
+pub type LeafTable = BTreeMap<usize, LeafMetadata>;
+
+/// Builds [`Predicate`]s like [`ExprBackend`], recording the metadata of every leaf.
+struct MetadataBackend<'a> {
+    dictionary: &'a Dictionary,
+    table: LeafTable,
+}
+
+impl SemanticsBackend for MetadataBackend<'_> {
+    type Output = Predicate;
+
+    fn leaf(&mut self, word: &str, id: usize, apply_to: Vec<Var>) -> Predicate {
+        if let Some(entry) = self.dictionary.get(word) {
+            self.table.entry(id).or_insert_with(|| LeafMetadata {
+                definition_id: entry.id.clone(),
+                family: entry.family.clone(),
+                places: entry.places.clone(),
+            });
+        }
+        ExprBackend.leaf(word, id, apply_to)
+    }
+
+    fn conjunction(&mut self, preds: Vec<Predicate>) -> Predicate {
+        ExprBackend.conjunction(preds)
+    }
+
+    fn disjunction(&mut self, preds: Vec<Predicate>) -> Predicate {
+        ExprBackend.disjunction(preds)
+    }
+
+    fn short_negation(&mut self, pred: Predicate) -> Predicate {
+        ExprBackend.short_negation(pred)
+    }
+
+    fn long_negation(&mut self, pred: Predicate) -> Predicate {
+        ExprBackend.long_negation(pred)
+    }
+
+    fn exists(&mut self, vars: Vec<Var>, pred: Predicate) -> Predicate {
+        ExprBackend.exists(vars, pred)
+    }
+
+    fn lambda(&mut self, vars: Vec<Var>, pred: Predicate) -> Predicate {
+        ExprBackend.lambda(vars, pred)
+    }
+
+    fn equivalent(&mut self, var: Var, pred: Predicate) -> Predicate {
+        ExprBackend.equivalent(var, pred)
+    }
+
+    fn abstraction(&mut self, kind: AbstractionKind, var: Var, pred: Predicate) -> Predicate {
+        ExprBackend.abstraction(kind, var, pred)
+    }
+
+    fn name(&mut self, var: Var, name: &str) -> Predicate {
+        ExprBackend.name(var, name)
+    }
+
+    fn substitute(&mut self, pred: Predicate, aliases: &BTreeMap<Var, Var>) -> Predicate {
+        ExprBackend.substitute(pred, aliases)
+    }
+
+    fn cardinality(&mut self, var: Var, count: u64) -> Predicate {
+        ExprBackend.cardinality(var, count)
+    }
+
+    fn quantified(
+        &mut self,
+        quantifier: Quantifier,
+        var: Var,
+        restrictor: Predicate,
+        scope: Predicate,
+    ) -> Predicate {
+        ExprBackend.quantified(quantifier, var, restrictor, scope)
+    }
+}
+
+/// Like [`to_expr`](crate::expr::to_expr), also returning the dictionary metadata of the
+/// leaves.
+pub fn to_expr_with_metadata(
+    tree: PredicateTree,
+    dictionary: &Dictionary,
+) -> (Predicate, Vec<Var>, LeafTable) {
+    let mut backend = MetadataBackend {
+        dictionary,
+        table: LeafTable::new(),
+    };
+    let (pred, vars) = interpret(tree, &mut backend);
+    (pred, vars, backend.table)
+}
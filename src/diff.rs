@@ -0,0 +1,200 @@
+//! How an edit to a sentence changed its structure: the trees in its bindings that were added,
+//! removed, or moved from one place to another, so that tools can show an edit as more than a
+//! change of words.
+
+use crate::{parser::PredicateTree, visit::Position};
+
+/// A tree in a binding: its root, or a tree bound into it by chaining it to a place, after the
+/// whole binding, or by a tag. The whole tree counts as the root of the sentence.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bound {
+    /// The indices in [`children`](PredicateTree::children) of the trees leading to this one
+    /// from the whole tree, the last one being its index in the binding; empty for the whole
+    /// tree.
+    pub path: Vec<usize>,
+    /// Where it sits in the binding, [`Position::Root`] for the whole tree.
+    pub position: Position,
+    pub tree: PredicateTree,
+}
+
+/// A tree found unchanged elsewhere in the new tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: Bound,
+    /// Where it is in the new tree; its `tree` is that of `from`.
+    pub to: Bound,
+}
+
+/// The differences between two predicate trees, as trees in their bindings. A tree added or
+/// removed is reported alone, without the trees inside it. A tree that keeps some of the trees
+/// inside it is not reported itself, only its changes are, so that changes to a binding which
+/// leave the trees in it where they were, such as to its negation, are not reported at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TreeDiff {
+    /// The trees of the new tree found nowhere in the old one, in the order they are
+    /// written.
+    pub added: Vec<Bound>,
+    /// The trees of the old tree found nowhere in the new one, in the order they are
+    /// written.
+    pub removed: Vec<Bound>,
+    /// The trees of the old tree found in the new one at another path or position, in
+    /// the order they are written in the old tree.
+    pub moved: Vec<Move>,
+}
+
+impl TreeDiff {
+    /// Whether the two trees have the same trees in the same places of their bindings.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// A tree in a binding, borrowed from the tree it is in.
+struct Entry<'a> {
+    path: Vec<usize>,
+    position: Position,
+    tree: &'a PredicateTree,
+}
+
+impl Entry<'_> {
+    fn to_bound(&self) -> Bound {
+        Bound {
+            path: self.path.clone(),
+            position: self.position,
+            tree: self.tree.clone(),
+        }
+    }
+
+    /// The path of the binding it is in, or `None` for the whole tree.
+    fn binding(&self) -> Option<&[usize]> {
+        self.path.split_last().map(|(_, binding)| binding)
+    }
+}
+
+/// Where each of the trees nested directly in `tree` sits, in the order of
+/// [`children`](PredicateTree::children), or `None` outside bindings.
+fn positions(tree: &PredicateTree) -> Vec<Option<Position>> {
+    match tree {
+        PredicateTree::Binding {
+            sharers, and, tags, ..
+        } => std::iter::once(Some(Position::Root))
+            .chain(sharers.iter().enumerate().flat_map(|(place, set)| {
+                set.iter().map(move |(chain_with, _)| {
+                    Some(Position::Sharer {
+                        place,
+                        chain_with: *chain_with,
+                    })
+                })
+            }))
+            .chain(and.iter().map(|_| Some(Position::And)))
+            .chain(tags.iter().map(|(tag, _)| {
+                Some(Position::Tag {
+                    place: tag.modified_place() as usize,
+                })
+            }))
+            .collect(),
+        _ => tree.children().iter().map(|_| None).collect(),
+    }
+}
+
+/// The trees in the bindings of `tree`, each before the trees inside it. The trees are kept on a
+/// stack of their own, so that however deep they are nested, this does not recurse.
+fn entries(tree: &PredicateTree) -> Vec<Entry<'_>> {
+    let mut entries = Vec::new();
+    let mut stack = vec![(Vec::new(), Some(Position::Root), tree)];
+    while let Some((path, position, tree)) = stack.pop() {
+        let children = tree.children();
+        let positions = positions(tree);
+        for (i, (child, position)) in children.into_iter().zip(positions).enumerate().rev() {
+            let mut path = path.clone();
+            path.push(i);
+            stack.push((path, position, child));
+        }
+        if let Some(position) = position {
+            entries.push(Entry {
+                path,
+                position,
+                tree,
+            });
+        }
+    }
+    entries
+}
+
+/// Marks `entry` and the entries inside it as settled.
+fn settle(entries: &[Entry], settled: &mut [bool], entry: usize) {
+    let path = &entries[entry].path;
+    for (other, done) in entries.iter().zip(settled.iter_mut()) {
+        if other.path.starts_with(path) {
+            *done = true;
+        }
+    }
+}
+
+/// The unsettled entries with no settled entry inside them, other than those inside another
+/// such entry.
+fn outermost(entries: &[Entry], settled: &[bool]) -> Vec<Bound> {
+    let mut reported: Vec<&[usize]> = Vec::new();
+    let mut bounds = Vec::new();
+    for (entry, done) in entries.iter().zip(settled) {
+        let keeps = entries
+            .iter()
+            .zip(settled)
+            .any(|(inner, done)| *done && inner.path.starts_with(&entry.path));
+        if *done || keeps || reported.iter().any(|path| entry.path.starts_with(path)) {
+            continue;
+        }
+        reported.push(&entry.path);
+        bounds.push(entry.to_bound());
+    }
+    bounds
+}
+
+/// The trees added, removed and moved in the bindings of `old` to give `new`. Equal trees at the
+/// same position of the binding at the same path are unchanged, wherever they are among the
+/// trees at that position; the others are paired with the first equal one left in the new
+/// tree, in the order they are written in the old one.
+pub fn diff(old: &PredicateTree, new: &PredicateTree) -> TreeDiff {
+    let old = entries(old);
+    let new = entries(new);
+    let mut old_settled = vec![false; old.len()];
+    let mut new_settled = vec![false; new.len()];
+
+    for i in 0..old.len() {
+        if old_settled[i] {
+            continue;
+        }
+        let same = (0..new.len()).find(|&j| {
+            !new_settled[j]
+                && new[j].binding() == old[i].binding()
+                && new[j].position == old[i].position
+                && new[j].tree == old[i].tree
+        });
+        if let Some(j) = same {
+            settle(&old, &mut old_settled, i);
+            settle(&new, &mut new_settled, j);
+        }
+    }
+
+    let mut moved = Vec::new();
+    for i in 0..old.len() {
+        if old_settled[i] {
+            continue;
+        }
+        let equal = (0..new.len()).find(|&j| !new_settled[j] && new[j].tree == old[i].tree);
+        if let Some(j) = equal {
+            moved.push(Move {
+                from: old[i].to_bound(),
+                to: new[j].to_bound(),
+            });
+            settle(&old, &mut old_settled, i);
+            settle(&new, &mut new_settled, j);
+        }
+    }
+
+    TreeDiff {
+        added: outermost(&new, &new_settled),
+        removed: outermost(&old, &old_settled),
+        moved,
+    }
+}
@@ -0,0 +1,110 @@
+//! The structure of a whole text: its sentences grouped into paragraphs by `pa` and its
+//! paragraphs into sections by `po`.
+
+use std::{mem, ops::Range};
+
+use chumsky::{error::Cheap, Parser, Stream};
+
+use crate::{
+    lexer::{ParticleFamily, Separator, SpannedWords, Word},
+    parser::{sentence_parser, Sentence},
+};
+
+/// A sentence of a discourse with its span in the text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiscourseSentence {
+    pub sentence: Sentence,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Paragraph {
+    pub sentences: Vec<DiscourseSentence>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Section {
+    pub paragraphs: Vec<Paragraph>,
+}
+
+/// The sentences of a text in order, grouped into paragraphs and sections. Separators with
+/// nothing before them open no empty paragraph or section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Discourse {
+    pub sections: Vec<Section>,
+}
+
+impl Discourse {
+    /// Parses every sentence of a text lexed with its spans, as by
+    /// [`lex_spanned`](crate::lexer::lex_spanned). Sentences that fail to parse do not stop the
+    /// others from being parsed, and the errors of all of them are returned.
+    pub fn parse(words: SpannedWords) -> Result<Self, Vec<Cheap<Word>>> {
+        let mut builder = Builder::default();
+        for (word, span) in words {
+            match word {
+                Word::Particle(ParticleFamily::Separator(separator)) => match separator {
+                    Separator::Sentence => builder.end_sentence(),
+                    Separator::Paragraph => builder.end_paragraph(),
+                    Separator::Section => builder.end_section(),
+                },
+                word => builder.words.push((word, span)),
+            }
+        }
+        builder.end_section();
+        if builder.errors.is_empty() {
+            Ok(builder.discourse)
+        } else {
+            Err(builder.errors)
+        }
+    }
+
+    pub fn paragraphs(&self) -> impl Iterator<Item = &Paragraph> {
+        self.sections.iter().flat_map(|s| &s.paragraphs)
+    }
+
+    pub fn sentences(&self) -> impl Iterator<Item = &DiscourseSentence> {
+        self.paragraphs().flat_map(|p| &p.sentences)
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    discourse: Discourse,
+    section: Section,
+    paragraph: Paragraph,
+    /// The words of the current sentence.
+    words: SpannedWords,
+    errors: Vec<Cheap<Word>>,
+}
+
+impl Builder {
+    fn end_sentence(&mut self) {
+        let (Some((_, first)), Some((_, last))) = (self.words.first(), self.words.last()) else {
+            return;
+        };
+        let span = first.start..last.end;
+        let end = span.end;
+        let words = mem::take(&mut self.words);
+        match sentence_parser().parse(Stream::from_iter(end..end, words.into_iter())) {
+            Ok(sentence) => self
+                .paragraph
+                .sentences
+                .push(DiscourseSentence { sentence, span }),
+            Err(errors) => self.errors.extend(errors),
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        self.end_sentence();
+        if !self.paragraph.sentences.is_empty() {
+            self.section.paragraphs.push(mem::take(&mut self.paragraph));
+        }
+    }
+
+    fn end_section(&mut self) {
+        self.end_paragraph();
+        if !self.section.paragraphs.is_empty() {
+            self.discourse.sections.push(mem::take(&mut self.section));
+        }
+    }
+}
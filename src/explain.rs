@@ -0,0 +1,122 @@
+//! Explanations of why each word of a text belongs to its family: the rule of the morphology that
+//! read it, and the classes of its letters.
+
+use std::{fmt, ops::Range};
+
+use crate::{
+    dfa,
+    lexer::{Word, NON_SONORANT, SONORANT, VOWELS},
+};
+
+/// The rule of the morphology a word was read by, in the order the lexer tries them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MorphRule {
+    /// A freeform word, such as `alis`.
+    Freeform,
+    /// A root starting with a non-sonorant, such as `bure`.
+    NonsonorantRoot,
+    /// A root starting with an initial pair, such as `blan`.
+    InitialPairRoot,
+    /// A particle with a rule of its own, by the name of its family, such as `pe` or a quote.
+    Particle(&'static str),
+    /// Another particle starting with a non-sonorant, such as `ke`.
+    NonsonorantParticle,
+    /// A particle starting with a sonorant or a vowel, such as `mian` or `a`, which must follow a
+    /// pause.
+    SonorantOrVowelParticle,
+}
+
+impl MorphRule {
+    /// Why a word read by the rule belongs to its family.
+    pub fn description(self) -> &'static str {
+        match self {
+            MorphRule::Freeform => {
+                "a freeform word: a whole run of letters between pauses with a vowel, ending in a \
+                 non-sonorant"
+            }
+            MorphRule::NonsonorantRoot => {
+                "a root: a non-sonorant and vowels, then either a final sonorant, or syllables \
+                 opened by `h`, a sonorant or a medial pair, at least one not by `h`, and an \
+                 optional final sonorant"
+            }
+            MorphRule::InitialPairRoot => {
+                "a root: an initial pair and vowels, then syllables opened by `h`, a sonorant or \
+                 a medial pair, and an optional final sonorant"
+            }
+            MorphRule::Particle(_) => "a particle read by the rule of its family",
+            MorphRule::NonsonorantParticle => {
+                "a particle: a non-sonorant and vowels, then syllables opened by `h`"
+            }
+            MorphRule::SonorantOrVowelParticle => {
+                "a particle: a sonorant or a vowel after a pause, then syllables opened by `h` or \
+                 a sonorant, and an optional final sonorant"
+            }
+        }
+    }
+}
+
+impl fmt::Display for MorphRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MorphRule::Freeform => write!(f, "freeform word"),
+            MorphRule::NonsonorantRoot => write!(f, "non-sonorant root"),
+            MorphRule::InitialPairRoot => write!(f, "initial-pair root"),
+            MorphRule::Particle(family) => write!(f, "{family} particle"),
+            MorphRule::NonsonorantParticle => write!(f, "non-sonorant particle"),
+            MorphRule::SonorantOrVowelParticle => write!(f, "sonorant or vowel particle"),
+        }
+    }
+}
+
+/// The classes of the letters of `word`: `C` for a non-sonorant, `S` for a sonorant, `V` for a
+/// vowel and `h` for itself. Other characters, such as the pauses of a quote, are kept.
+pub fn letter_classes(word: &str) -> String {
+    word.chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c if NON_SONORANT.contains(&c) => 'C',
+            c if SONORANT.contains(&c) => 'S',
+            c if VOWELS.contains(&c) => 'V',
+            c => c,
+        })
+        .collect()
+}
+
+/// A word of a text with the rule that read it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Annotation {
+    pub word: Word,
+    /// The span of the word in characters of the text.
+    pub span: Range<usize>,
+    pub rule: MorphRule,
+    /// The classes of the letters of the word, as given by [`letter_classes`].
+    pub classes: String,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}, {}",
+            self.word,
+            self.classes,
+            self.rule,
+            self.rule.description()
+        )
+    }
+}
+
+/// Annotates each word of `s` with the rule that read it, or returns `None` if `s` does not lex.
+/// Each digit of a number is annotated on its own.
+pub fn annotate(s: &str) -> Option<Vec<Annotation>> {
+    Some(
+        dfa::lex_with_rules(s)?
+            .into_iter()
+            .map(|(word, span, rule)| Annotation {
+                classes: letter_classes(&word.text()),
+                word,
+                span,
+                rule,
+            })
+            .collect(),
+    )
+}
@@ -1,6 +1,12 @@
 use std::{collections::BTreeMap, mem};
 
-use crate::{parser::PredicateTree, Exposure, Negation, PredicateChaining};
+use crate::{
+    cancel::{CancellationToken, Cancelled},
+    lexer::{AbstractionKind, Mood, SUBORDINATORS},
+    parser::{PredicateTree, Sentence},
+    semantics::{interpret, interpret_cancellable, SemanticsBackend},
+    Quantifier,
+};
 
 pub type Var = usize;
 
@@ -16,6 +22,9 @@ pub enum Predicate {
     And {
         preds: Vec<Predicate>,
     },
+    Or {
+        preds: Vec<Predicate>,
+    },
     Exists {
         vars: Vec<Var>,
         pred: Box<Predicate>,
@@ -28,11 +37,41 @@ pub enum Predicate {
         vars: Vec<Var>,
         pred: Box<Predicate>,
     },
+    /// `var` is the event, proposition or property described by `pred`, which is a lambda for a
+    /// property.
+    Abstraction {
+        kind: AbstractionKind,
+        var: Var,
+        pred: Box<Predicate>,
+    },
+    /// `var` is the individual called `name`.
+    Name {
+        var: Var,
+        name: String,
+    },
+    /// There are `count` values of `var`.
+    Cardinality {
+        var: Var,
+        count: u64,
+    },
+    /// `scope` holds of as many of the values of `var` satisfying `restrictor` as `quantifier`
+    /// says. An empty conjunction as the scope is left out of the printed form.
+    Quantified {
+        quantifier: Quantifier,
+        var: Var,
+        restrictor: Box<Predicate>,
+        scope: Box<Predicate>,
+    },
+    /// Asks whether the predicate holds.
+    Interrogative(Box<Predicate>),
+    /// Requests that the predicate be made to hold.
+    Imperative(Box<Predicate>),
 }
 impl std::fmt::Display for Predicate {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Predicate::Leaf { word, id, apply_to } => {
+        Notation {
+            pred: self,
+            leaf: &|f, word, id, apply_to| {
                 write!(f, "{word}{id}")?;
                 if apply_to.is_empty() {
                     Ok(())
@@ -49,28 +88,92 @@ impl std::fmt::Display for Predicate {
                     }
                     write!(f, ")")
                 }
-            }
+            },
+            var: &|f, v| write!(f, "{v}"),
+        }
+        .fmt(f)
+    }
+}
+
+/// Writes a leaf given its word, id and arguments.
+pub(crate) type LeafWriter<'a> =
+    dyn Fn(&mut std::fmt::Formatter<'_>, &str, usize, &[Var]) -> std::fmt::Result + 'a;
+
+/// Writes a variable bound by a quantifier or lambda, or equated to a predicate.
+pub(crate) type VarWriter<'a> = dyn Fn(&mut std::fmt::Formatter<'_>, Var) -> std::fmt::Result + 'a;
+
+/// Formats a predicate in the notation of its `Display` implementation, with leaves written by
+/// `leaf` and the other occurrences of variables by `var`.
+pub(crate) struct Notation<'a> {
+    pub pred: &'a Predicate,
+    pub leaf: &'a LeafWriter<'a>,
+    pub var: &'a VarWriter<'a>,
+}
+
+impl Notation<'_> {
+    fn with<'b>(&'b self, pred: &'b Predicate) -> Notation<'b> {
+        Notation {
+            pred,
+            leaf: self.leaf,
+            var: self.var,
+        }
+    }
+}
+
+impl std::fmt::Display for Notation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.pred {
+            Predicate::Leaf { word, id, apply_to } => (self.leaf)(f, word, *id, apply_to),
             Predicate::ShortNot(pred) => {
-                if matches!(**pred, Predicate::And { .. }) {
-                    write!(f, "¬ₛ({pred})")
+                if matches!(**pred, Predicate::And { .. } | Predicate::Or { .. }) {
+                    write!(f, "¬ₛ({})", self.with(pred))
                 } else {
-                    write!(f, "¬ₛ{pred}")
+                    write!(f, "¬ₛ{}", self.with(pred))
                 }
             }
             Predicate::LongNot(pred) => {
-                if matches!(**pred, Predicate::And { .. }) {
-                    write!(f, "¬ₗ({pred})")
+                if matches!(**pred, Predicate::And { .. } | Predicate::Or { .. }) {
+                    write!(f, "¬ₗ({})", self.with(pred))
+                } else {
+                    write!(f, "¬ₗ{}", self.with(pred))
+                }
+            }
+            Predicate::Interrogative(pred) => {
+                if matches!(**pred, Predicate::And { .. } | Predicate::Or { .. }) {
+                    write!(f, "?({})", self.with(pred))
                 } else {
-                    write!(f, "¬ₗ{pred}")
+                    write!(f, "?{}", self.with(pred))
+                }
+            }
+            Predicate::Imperative(pred) => {
+                if matches!(**pred, Predicate::And { .. } | Predicate::Or { .. }) {
+                    write!(f, "!({})", self.with(pred))
+                } else {
+                    write!(f, "!{}", self.with(pred))
                 }
             }
             Predicate::And { preds } => {
+                let mut first = true;
+                for p in preds {
+                    if !first {
+                        write!(f, " ∧ ")?;
+                    }
+                    if matches!(p, Predicate::Or { .. }) {
+                        write!(f, "({})", self.with(p))?;
+                    } else {
+                        write!(f, "{}", self.with(p))?;
+                    }
+                    first = false;
+                }
+                Ok(())
+            }
+            Predicate::Or { preds } => {
                 let mut first = true;
                 for p in preds {
                     if first {
-                        write!(f, "{p}")?;
+                        write!(f, "{}", self.with(p))?;
                     } else {
-                        write!(f, " ∧ {p}")?;
+                        write!(f, " ∨ {}", self.with(p))?;
                     }
                     first = false;
                 }
@@ -78,346 +181,653 @@ impl std::fmt::Display for Predicate {
             }
             Predicate::Exists { vars, pred } => {
                 if vars.is_empty() {
-                    write!(f, "{pred}")
+                    write!(f, "{}", self.with(pred))
                 } else {
                     write!(f, "(∃ ")?;
                     let mut first = true;
                     for v in vars {
-                        if first {
-                            write!(f, "{v}")?;
-                        } else {
-                            write!(f, ",{v}")?;
+                        if !first {
+                            write!(f, ",")?;
                         }
+                        (self.var)(f, *v)?;
                         first = false;
                     }
                     write!(f, ". ")?;
-                    write!(f, "{pred})")
+                    write!(f, "{})", self.with(pred))
                 }
             }
             Predicate::Equivalent { var, pred } => {
-                if matches!(**pred, Predicate::And { .. }) {
-                    write!(f, "({var} = ({pred}))")
+                write!(f, "(")?;
+                (self.var)(f, *var)?;
+                if matches!(**pred, Predicate::And { .. } | Predicate::Or { .. }) {
+                    write!(f, " = ({}))", self.with(pred))
                 } else {
-                    write!(f, "({var} = {pred})")
+                    write!(f, " = {})", self.with(pred))
+                }
+            }
+            Predicate::Abstraction { kind, var, pred } => {
+                write!(f, "(")?;
+                (self.var)(f, *var)?;
+                write!(f, " = {}⌜{}⌝)", kind.text(), self.with(pred))
+            }
+            Predicate::Name { var, name } => {
+                (self.var)(f, *var)?;
+                write!(f, " ≔ ⟨{name}⟩")
+            }
+            Predicate::Cardinality { var, count } => {
+                write!(f, "|")?;
+                (self.var)(f, *var)?;
+                write!(f, "| = {count}")
+            }
+            Predicate::Quantified {
+                quantifier,
+                var,
+                restrictor,
+                scope,
+            } => {
+                match quantifier {
+                    Quantifier::All => write!(f, "(∀ ")?,
+                    Quantifier::Some => write!(f, "(∃ ")?,
+                    Quantifier::Most => write!(f, "(ℳ ")?,
+                    Quantifier::Exactly(n) => write!(f, "(∃!{n} ")?,
+                }
+                (self.var)(f, *var)?;
+                write!(f, ": {}", self.with(restrictor))?;
+                if !matches!(&**scope, Predicate::And { preds } if preds.is_empty()) {
+                    write!(f, ". {}", self.with(scope))?;
                 }
+                write!(f, ")")
             }
             Predicate::Lambda { vars, pred } => {
                 if vars.is_empty() {
-                    write!(f, "{pred}")
+                    write!(f, "{}", self.with(pred))
                 } else {
                     write!(f, "(λ ")?;
                     let mut first = true;
                     for v in vars {
-                        if first {
-                            write!(f, "{v}")?;
-                        } else {
-                            write!(f, ",{v}")?;
+                        if !first {
+                            write!(f, ",")?;
                         }
+                        (self.var)(f, *v)?;
                         first = false;
                     }
                     write!(f, ". ")?;
-                    write!(f, "{pred})")
+                    write!(f, "{})", self.with(pred))
                 }
             }
         }
     }
 }
 
-pub fn to_expr(tree: PredicateTree) -> (Predicate, Vec<Var>) {
-    let mut preds = Vec::new();
-    let mut max_var = 0;
-    let mut max_id = 0;
-    let mut symbol_table = BTreeMap::new();
-    let mut new_vars = Vec::new();
-    to_expr_(
-        tree,
-        PredicateChaining::Equivalence,
-        Vec::new(),
-        &mut new_vars,
-        &mut max_var,
-        &mut max_id,
-        &mut symbol_table,
-        &mut preds,
-    );
-    (
-        if preds.len() == 1 {
-            preds.pop().unwrap()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PredicateParseError {
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl std::fmt::Display for PredicateParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid formula at {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for PredicateParseError {}
+
+/// Parses the notation produced by the `Display` implementation.
+impl std::str::FromStr for Predicate {
+    type Err = PredicateParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = FormulaParser {
+            chars: s.chars().collect(),
+            pos: 0,
+        };
+        let pred = p.disjunction()?;
+        p.whitespace();
+        if p.pos == p.chars.len() {
+            Ok(pred)
         } else {
-            Predicate::And { preds }
-        },
-        new_vars,
-    )
+            Err(p.error("trailing characters"))
+        }
+    }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn to_expr_(
-    tree: PredicateTree,
-    chaining_with: PredicateChaining,
-    mut vars: Vec<Var>,
-    orig_new_vars: &mut Vec<Var>,
-    max_var: &mut Var,
-    max_id: &mut usize,
-    symbol_table: &mut BTreeMap<String, Vec<usize>>,
-    orig_preds: &mut Vec<Predicate>,
-) {
-    match tree {
-        PredicateTree::Leaf { word, negation } => {
-            let p = Predicate::Leaf {
-                word: word.word.clone(),
-                id: *symbol_table
-                    .entry(word.word)
-                    .or_insert_with(|| {
-                        let i = *max_id;
-                        *max_id += 1;
-                        vec![i]
-                    })
-                    .last()
-                    .unwrap(),
-                apply_to: vars,
-            };
-            let p = if negation.short() {
-                Predicate::ShortNot(Box::new(p))
-            } else {
-                p
-            };
-            let p = if negation.long() {
-                Predicate::LongNot(Box::new(p))
-            } else {
-                p
-            };
-            orig_preds.push(p)
+struct FormulaParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl FormulaParser {
+    fn error(&self, message: &'static str) -> PredicateParseError {
+        PredicateParseError {
+            offset: self.pos,
+            message,
         }
-        PredicateTree::Binding {
-            chaining: _,
-            root,
-            negation,
-            exposure,
-            sharers,
-            and,
-        } => {
-            let mut close_over = Vec::new();
-            let chain_place = match &exposure {
-                Exposure::Standard | Exposure::Transparent | Exposure::Explicit(_) => 0,
-                Exposure::Modified(vec) => vec.first().copied().unwrap_or(0),
-            };
-            if matches!(chaining_with, PredicateChaining::Sharing) {
-                let chain_var = vars.first().copied().unwrap_or_else(|| {
-                    let v = *max_var;
-                    close_over.push(v);
-                    *max_var += 1;
-                    v
-                });
-                vars.clear();
-                vars = (0..(sharers.len() as u8))
-                    .map(|i| {
-                        if i == chain_place {
-                            chain_var
-                        } else {
-                            let v = *max_var;
-                            close_over.push(v);
-                            *max_var += 1;
-                            v
-                        }
-                    })
-                    .collect();
-            } else {
-                for _ in 0..(sharers.len().saturating_sub(vars.len())) {
-                    let v = *max_var;
-                    orig_new_vars.push(v);
-                    *max_var += 1;
-                    vars.push(v);
-                }
+    }
+    fn whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+    fn peek(&mut self) -> Option<char> {
+        self.whitespace();
+        self.chars.get(self.pos).copied()
+    }
+    fn eat(&mut self, s: &str) -> bool {
+        self.whitespace();
+        let matches = s
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c));
+        if matches {
+            self.pos += s.chars().count();
+        }
+        matches
+    }
+    fn expect(&mut self, s: &str, message: &'static str) -> Result<(), PredicateParseError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+    fn number(&mut self) -> Result<usize, PredicateParseError> {
+        self.whitespace();
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .map_err(|_| self.error("expected a number"))
+    }
+    fn vars(&mut self) -> Result<Vec<Var>, PredicateParseError> {
+        let mut vars = vec![self.number()?];
+        while self.eat(",") {
+            vars.push(self.number()?);
+        }
+        Ok(vars)
+    }
+    fn disjunction(&mut self) -> Result<Predicate, PredicateParseError> {
+        let mut preds = vec![self.conjunction()?];
+        while self.eat("∨") {
+            preds.push(self.conjunction()?);
+        }
+        Ok(disjunction(preds))
+    }
+    fn conjunction(&mut self) -> Result<Predicate, PredicateParseError> {
+        let mut preds = vec![self.unary()?];
+        while self.eat("∧") {
+            preds.push(self.unary()?);
+        }
+        Ok(conjunction(preds))
+    }
+    fn unary(&mut self) -> Result<Predicate, PredicateParseError> {
+        if self.eat("¬ₛ") {
+            Ok(Predicate::ShortNot(Box::new(self.unary()?)))
+        } else if self.eat("¬ₗ") {
+            Ok(Predicate::LongNot(Box::new(self.unary()?)))
+        } else if self.eat("?") {
+            Ok(Predicate::Interrogative(Box::new(self.unary()?)))
+        } else if self.eat("!") {
+            Ok(Predicate::Imperative(Box::new(self.unary()?)))
+        } else if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let var = self.number()?;
+            self.expect("≔", "expected `≔`")?;
+            self.expect("⟨", "expected `⟨`")?;
+            let start = self.pos;
+            while self.chars.get(self.pos).is_some_and(|c| *c != '⟩') {
+                self.pos += 1;
             }
-            if let Exposure::Explicit(vec) = &exposure {
-                for (i, (word, chain_with)) in vec.iter().enumerate() {
-                    let mut var = *max_var;
-                    *max_var += 1;
-                    if let Some(v) = vars.get_mut(i) {
-                        close_over.push(var);
-                        mem::swap(v, &mut var);
-                    } else {
-                        orig_new_vars.push(var);
+            let name = self.chars[start..self.pos].iter().collect();
+            self.expect("⟩", "expected `⟩`")?;
+            Ok(Predicate::Name { var, name })
+        } else if self.eat("|") {
+            let var = self.number()?;
+            self.expect("|", "expected `|`")?;
+            self.expect("=", "expected `=`")?;
+            let count = self.number()? as u64;
+            Ok(Predicate::Cardinality { var, count })
+        } else if self.eat("(") {
+            let pred = if self.eat("λ") {
+                let vars = self.vars()?;
+                self.expect(".", "expected `.`")?;
+                Predicate::Lambda {
+                    vars,
+                    pred: Box::new(self.disjunction()?),
+                }
+            } else if self.eat("∃!") {
+                let n = self.number()?;
+                self.quantified(Quantifier::Exactly(n as u64))?
+            } else if self.eat("∀") {
+                self.quantified(Quantifier::All)?
+            } else if self.eat("ℳ") {
+                self.quantified(Quantifier::Most)?
+            } else if self.eat("∃") {
+                let vars = self.vars()?;
+                match vars[..] {
+                    [var] if self.eat(":") => self.restricted(Quantifier::Some, var)?,
+                    _ => {
+                        self.expect(".", "expected `.`")?;
+                        Predicate::Exists {
+                            vars,
+                            pred: Box::new(self.disjunction()?),
+                        }
                     }
-                    let var = var;
-
-                    let id = *max_id;
-                    *max_id += 1;
-                    symbol_table.entry(word.clone()).or_default().push(id);
-                    match chain_with {
-                        PredicateChaining::Sharing => orig_preds.push(Predicate::Leaf {
-                            word: word.clone(),
-                            id,
-                            apply_to: vec![var],
-                        }),
-                        PredicateChaining::Equivalence => orig_preds.push(Predicate::Equivalent {
+                }
+            } else if let Some(var) = self.equated() {
+                match SUBORDINATORS
+                    .iter()
+                    .find(|(word, _)| self.eat(&format!("{word}⌜")))
+                {
+                    Some((_, kind)) => {
+                        let pred = self.disjunction()?;
+                        self.expect("⌝", "expected `⌝`")?;
+                        Predicate::Abstraction {
+                            kind: *kind,
                             var,
-                            pred: Box::new(Predicate::Leaf {
-                                word: word.clone(),
-                                id,
-                                apply_to: Vec::new(),
-                            }),
-                        }),
+                            pred: Box::new(pred),
+                        }
                     }
+                    None => Predicate::Equivalent {
+                        var,
+                        pred: Box::new(self.disjunction()?),
+                    },
                 }
-            }
-
-            let closure_needed = !close_over.is_empty();
-            let mut new_new_vars = close_over;
-            let mut new_preds = Vec::new();
-            let (new_vars, preds) = if closure_needed || negation != Negation::None {
-                (&mut new_new_vars, &mut new_preds)
             } else {
-                (&mut *orig_new_vars, &mut *orig_preds)
+                self.disjunction()?
             };
+            self.expect(")", "expected `)`")?;
+            Ok(pred)
+        } else {
+            self.leaf()
+        }
+    }
+    /// The variable at the start of an equivalence, leaving the position alone if there is none
+    /// (as when a group starts with a name).
+    fn equated(&mut self) -> Option<Var> {
+        let start = self.pos;
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            if let Ok(var) = self.number() {
+                if self.eat("=") {
+                    return Some(var);
+                }
+            }
+        }
+        self.pos = start;
+        None
+    }
+    /// The variable, restrictor and scope of a quantifier.
+    fn quantified(&mut self, quantifier: Quantifier) -> Result<Predicate, PredicateParseError> {
+        let var = self.number()?;
+        self.expect(":", "expected `:`")?;
+        self.restricted(quantifier, var)
+    }
+    fn restricted(
+        &mut self,
+        quantifier: Quantifier,
+        var: Var,
+    ) -> Result<Predicate, PredicateParseError> {
+        let restrictor = self.disjunction()?;
+        let scope = if self.eat(".") {
+            self.disjunction()?
+        } else {
+            Predicate::And { preds: Vec::new() }
+        };
+        Ok(Predicate::Quantified {
+            quantifier,
+            var,
+            restrictor: Box::new(restrictor),
+            scope: Box::new(scope),
+        })
+    }
+    fn leaf(&mut self) -> Result<Predicate, PredicateParseError> {
+        self.whitespace();
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_alphabetic() || *c == '\'' || *c == '#')
+        {
+            self.pos += 1;
+        }
+        if self.chars.get(self.pos) == Some(&'«') {
+            while self.chars.get(self.pos).is_some_and(|c| *c != '»') {
+                self.pos += 1;
+            }
+            self.expect("»", "expected `»`")?;
+        }
+        if start == self.pos {
+            return Err(self.error("expected a predicate"));
+        }
+        let word = self.chars[start..self.pos].iter().collect();
+        let id = self.number()?;
+        let apply_to = if self.chars.get(self.pos) == Some(&'(') {
+            self.pos += 1;
+            let vars = self.vars()?;
+            self.expect(")", "expected `)`")?;
+            vars
+        } else {
+            Vec::new()
+        };
+        Ok(Predicate::Leaf { word, id, apply_to })
+    }
+}
 
-            to_expr_(
-                *root,
-                PredicateChaining::Equivalence,
-                vars.clone(),
-                new_vars,
-                max_var,
-                max_id,
-                symbol_table,
-                preds,
-            );
-
-            for (set, var) in sharers.into_iter().zip(vars) {
-                for (chaining, pred_tree) in set {
-                    match chaining {
-                        PredicateChaining::Sharing => to_expr_(
-                            pred_tree,
-                            chaining,
-                            vec![var],
-                            new_vars,
-                            max_var,
-                            max_id,
-                            symbol_table,
-                            preds,
-                        ),
-                        PredicateChaining::Equivalence => {
-                            let mut equiv_preds = Vec::new();
-
-                            if matches!(exposure, Exposure::Transparent) {
-                                to_expr_(
-                                    pred_tree,
-                                    chaining,
-                                    Vec::new(),
-                                    new_vars,
-                                    max_var,
-                                    max_id,
-                                    symbol_table,
-                                    &mut equiv_preds,
-                                );
-                                let p = if equiv_preds.len() == 1 {
-                                    equiv_preds.pop().unwrap()
-                                } else {
-                                    Predicate::And { preds: equiv_preds }
-                                };
-                                preds.push(Predicate::Equivalent {
-                                    var,
-                                    pred: Box::new(p),
-                                });
-                            } else {
-                                let mut new_vars = Vec::new();
-                                to_expr_(
-                                    pred_tree,
-                                    chaining,
-                                    Vec::new(),
-                                    &mut new_vars,
-                                    max_var,
-                                    max_id,
-                                    symbol_table,
-                                    &mut equiv_preds,
-                                );
-                                let p = if equiv_preds.len() == 1 {
-                                    equiv_preds.pop().unwrap()
-                                } else {
-                                    Predicate::And { preds: equiv_preds }
-                                };
-                                preds.push(Predicate::Equivalent {
-                                    var,
-                                    pred: if new_vars.is_empty() {
-                                        Box::new(p)
-                                    } else {
-                                        Box::new(Predicate::Lambda {
-                                            vars: new_vars,
-                                            pred: Box::new(p),
-                                        })
-                                    },
-                                });
-                            }
-                        }
+impl Predicate {
+    /// Rewrites the predicate into canonical form, in which the output is fully determined by
+    /// the structure of the input text:
+    ///
+    /// - nested conjunctions are flattened into their parent, keeping their order;
+    /// - quantifiers and lambdas binding no variables are removed;
+    /// - variables are renumbered from 0 in order of first occurrence in the printed form;
+    /// - leaf ids are renumbered from 0 in order of first occurrence in the printed form.
+    ///
+    /// Two predicates that are equal up to renaming of variables and ids therefore have the same
+    /// canonical form, and printing it gives byte-for-byte identical output.
+    pub fn canonicalize(&mut self) {
+        self.flatten();
+        let mut vars = BTreeMap::new();
+        let mut ids = BTreeMap::new();
+        self.rename(&mut vars, &mut ids);
+    }
+
+    pub fn into_canonical(mut self) -> Self {
+        self.canonicalize();
+        self
+    }
+
+    /// Wraps the predicate in the operator of a sentence of mood `mood`. Assertions, and
+    /// sentences with no illocution, are left as they are.
+    pub fn with_mood(self, mood: Option<Mood>) -> Self {
+        match mood {
+            Some(Mood::Question) => Predicate::Interrogative(Box::new(self)),
+            Some(Mood::Imperative) => Predicate::Imperative(Box::new(self)),
+            Some(Mood::Assertion) | None => self,
+        }
+    }
+
+    fn flatten(&mut self) {
+        match self {
+            Predicate::Leaf { .. } | Predicate::Name { .. } | Predicate::Cardinality { .. } => {}
+            Predicate::ShortNot(pred)
+            | Predicate::LongNot(pred)
+            | Predicate::Interrogative(pred)
+            | Predicate::Imperative(pred) => pred.flatten(),
+            Predicate::Equivalent { pred, .. } | Predicate::Abstraction { pred, .. } => {
+                pred.flatten()
+            }
+            Predicate::And { preds } => {
+                for p in mem::take(preds) {
+                    let mut p = p;
+                    p.flatten();
+                    match p {
+                        Predicate::And { preds: inner } => preds.extend(inner),
+                        p => preds.push(p),
                     }
                 }
+                if preds.len() == 1 {
+                    *self = conjunction(mem::take(preds));
+                }
             }
-
-            let preds = if negation != Negation::None {
-                &mut new_preds
-            } else {
-                &mut *orig_preds
-            };
-            for p in and {
-                let mut new_vars = Vec::new();
-                let mut new_preds = Vec::new();
-                to_expr_(
-                    p,
-                    PredicateChaining::Equivalence,
-                    Vec::new(),
-                    &mut new_vars,
-                    max_var,
-                    max_id,
-                    symbol_table,
-                    &mut new_preds,
-                );
-
-                let p = if new_preds.len() == 1 {
-                    new_preds.pop().unwrap()
-                } else {
-                    Predicate::And { preds: new_preds }
-                };
-                if new_vars.is_empty() {
-                    preds.push(p)
-                } else {
-                    preds.push(Predicate::Exists {
-                        vars: new_vars,
-                        pred: Box::new(p),
-                    })
+            Predicate::Or { preds } => {
+                for p in mem::take(preds) {
+                    let mut p = p;
+                    p.flatten();
+                    match p {
+                        Predicate::Or { preds: inner } => preds.extend(inner),
+                        p => preds.push(p),
+                    }
+                }
+                if preds.len() == 1 {
+                    *self = disjunction(mem::take(preds));
                 }
             }
-
-            if let Exposure::Explicit(vec) = &exposure {
-                for (word, _) in vec.iter() {
-                    symbol_table.get_mut(word).unwrap().pop();
+            Predicate::Quantified {
+                restrictor, scope, ..
+            } => {
+                restrictor.flatten();
+                scope.flatten();
+            }
+            Predicate::Exists { vars, pred } | Predicate::Lambda { vars, pred } => {
+                pred.flatten();
+                if vars.is_empty() {
+                    *self = mem::replace(&mut **pred, Predicate::And { preds: Vec::new() });
                 }
             }
+        }
+    }
 
-            if closure_needed || negation != Negation::None {
-                let p = if new_preds.len() == 1 {
-                    new_preds.pop().unwrap()
-                } else {
-                    Predicate::And { preds: new_preds }
-                };
+    fn substitute(&mut self, aliases: &BTreeMap<Var, Var>) {
+        let var = |v: &mut Var| {
+            if let Some(to) = aliases.get(v) {
+                *v = *to;
+            }
+        };
+        match self {
+            Predicate::Leaf { apply_to, .. } => apply_to.iter_mut().for_each(var),
+            Predicate::ShortNot(pred)
+            | Predicate::LongNot(pred)
+            | Predicate::Interrogative(pred)
+            | Predicate::Imperative(pred) => pred.substitute(aliases),
+            Predicate::And { preds } | Predicate::Or { preds } => {
+                preds.iter_mut().for_each(|p| p.substitute(aliases))
+            }
+            Predicate::Exists { vars: bound, pred } | Predicate::Lambda { vars: bound, pred } => {
+                bound.retain(|v| !aliases.contains_key(v));
+                pred.substitute(aliases);
+            }
+            Predicate::Equivalent { var: v, pred }
+            | Predicate::Abstraction { var: v, pred, .. } => {
+                var(v);
+                pred.substitute(aliases);
+            }
+            Predicate::Name { var: v, .. } | Predicate::Cardinality { var: v, .. } => var(v),
+            Predicate::Quantified {
+                var: v,
+                restrictor,
+                scope,
+                ..
+            } => {
+                var(v);
+                restrictor.substitute(aliases);
+                scope.substitute(aliases);
+            }
+        }
+    }
 
-                let p = if negation.short() {
-                    Predicate::ShortNot(Box::new(p))
-                } else {
-                    p
-                };
-                let p = if closure_needed {
-                    Predicate::Exists {
-                        vars: new_new_vars,
-                        pred: Box::new(p),
-                    }
-                } else {
-                    p
-                };
-                let p = if negation.long() {
-                    Predicate::LongNot(Box::new(p))
-                } else {
-                    p
-                };
-                orig_preds.push(p);
+    fn rename(
+        &mut self,
+        vars: &mut BTreeMap<Var, Var>,
+        ids: &mut BTreeMap<(String, usize), usize>,
+    ) {
+        let mut var = |v: &mut Var| {
+            let next = vars.len();
+            *v = *vars.entry(*v).or_insert(next);
+        };
+        match self {
+            Predicate::Leaf { word, id, apply_to } => {
+                let next = ids.len();
+                *id = *ids.entry((word.clone(), *id)).or_insert(next);
+                apply_to.iter_mut().for_each(var);
+            }
+            Predicate::ShortNot(pred)
+            | Predicate::LongNot(pred)
+            | Predicate::Interrogative(pred)
+            | Predicate::Imperative(pred) => pred.rename(vars, ids),
+            Predicate::And { preds } | Predicate::Or { preds } => {
+                for p in preds {
+                    p.rename(vars, ids);
+                }
+            }
+            Predicate::Exists { vars: bound, pred } | Predicate::Lambda { vars: bound, pred } => {
+                bound.iter_mut().for_each(var);
+                pred.rename(vars, ids);
+            }
+            Predicate::Equivalent { var: v, pred }
+            | Predicate::Abstraction { var: v, pred, .. } => {
+                var(v);
+                pred.rename(vars, ids);
+            }
+            Predicate::Name { var: v, .. } | Predicate::Cardinality { var: v, .. } => var(v),
+            Predicate::Quantified {
+                var: v,
+                restrictor,
+                scope,
+                ..
+            } => {
+                var(v);
+                restrictor.rename(vars, ids);
+                scope.rename(vars, ids);
             }
         }
     }
 }
+
+/// Like [`to_expr`], with the result (including the variables of the outer lambda) in the
+/// canonical form described at [`Predicate::canonicalize`].
+pub fn to_expr_canonical(tree: PredicateTree) -> (Predicate, Vec<Var>) {
+    let (pred, vars) = to_expr(tree);
+    match (Predicate::Lambda {
+        vars,
+        pred: Box::new(pred),
+    })
+    .into_canonical()
+    {
+        Predicate::Lambda { vars, pred } => (*pred, vars),
+        pred => (pred, Vec::new()),
+    }
+}
+
+fn conjunction(mut preds: Vec<Predicate>) -> Predicate {
+    match preds.pop() {
+        Some(p) if preds.is_empty() => p,
+        p => {
+            preds.extend(p);
+            Predicate::And { preds }
+        }
+    }
+}
+
+fn disjunction(mut preds: Vec<Predicate>) -> Predicate {
+    match preds.pop() {
+        Some(p) if preds.is_empty() => p,
+        p => {
+            preds.extend(p);
+            Predicate::Or { preds }
+        }
+    }
+}
+
+/// The backend building the [`Predicate`]s printed by this crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExprBackend;
+
+impl SemanticsBackend for ExprBackend {
+    type Output = Predicate;
+
+    fn leaf(&mut self, word: &str, id: usize, apply_to: Vec<Var>) -> Predicate {
+        Predicate::Leaf {
+            word: word.to_string(),
+            id,
+            apply_to,
+        }
+    }
+
+    fn conjunction(&mut self, preds: Vec<Predicate>) -> Predicate {
+        conjunction(preds)
+    }
+
+    fn disjunction(&mut self, preds: Vec<Predicate>) -> Predicate {
+        disjunction(preds)
+    }
+
+    fn short_negation(&mut self, pred: Predicate) -> Predicate {
+        Predicate::ShortNot(Box::new(pred))
+    }
+
+    fn long_negation(&mut self, pred: Predicate) -> Predicate {
+        Predicate::LongNot(Box::new(pred))
+    }
+
+    fn exists(&mut self, vars: Vec<Var>, pred: Predicate) -> Predicate {
+        Predicate::Exists {
+            vars,
+            pred: Box::new(pred),
+        }
+    }
+
+    fn lambda(&mut self, vars: Vec<Var>, pred: Predicate) -> Predicate {
+        Predicate::Lambda {
+            vars,
+            pred: Box::new(pred),
+        }
+    }
+
+    fn equivalent(&mut self, var: Var, pred: Predicate) -> Predicate {
+        Predicate::Equivalent {
+            var,
+            pred: Box::new(pred),
+        }
+    }
+
+    fn abstraction(&mut self, kind: AbstractionKind, var: Var, pred: Predicate) -> Predicate {
+        Predicate::Abstraction {
+            kind,
+            var,
+            pred: Box::new(pred),
+        }
+    }
+
+    fn name(&mut self, var: Var, name: &str) -> Predicate {
+        Predicate::Name {
+            var,
+            name: name.to_string(),
+        }
+    }
+
+    fn substitute(&mut self, mut pred: Predicate, aliases: &BTreeMap<Var, Var>) -> Predicate {
+        pred.substitute(aliases);
+        pred
+    }
+
+    fn cardinality(&mut self, var: Var, count: u64) -> Predicate {
+        Predicate::Cardinality { var, count }
+    }
+
+    fn quantified(
+        &mut self,
+        quantifier: Quantifier,
+        var: Var,
+        restrictor: Predicate,
+        scope: Predicate,
+    ) -> Predicate {
+        Predicate::Quantified {
+            quantifier,
+            var,
+            restrictor: Box::new(restrictor),
+            scope: Box::new(scope),
+        }
+    }
+}
+
+pub fn to_expr(tree: PredicateTree) -> (Predicate, Vec<Var>) {
+    interpret(tree, &mut ExprBackend)
+}
+
+/// Like [`to_expr`] for a whole sentence, with the predicate wrapped in the operator of its
+/// mood as by [`Predicate::with_mood`].
+pub fn sentence_to_expr(sentence: Sentence) -> (Predicate, Vec<Var>) {
+    let (pred, vars) = to_expr(sentence.tree);
+    (pred.with_mood(sentence.mood), vars)
+}
+
+/// Like [`to_expr`], but stops converting as soon as `cancel` is cancelled.
+pub fn to_expr_cancellable(
+    tree: PredicateTree,
+    cancel: &CancellationToken,
+) -> Result<(Predicate, Vec<Var>), Cancelled> {
+    interpret_cancellable(tree, &mut ExprBackend, cancel)
+}
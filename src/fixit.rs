@@ -0,0 +1,207 @@
+use std::{fmt, ops::Range};
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    lexer::{FiVar, ParticleFamily, Word},
+    parser::{parser, ParseError, PredicateTree},
+};
+
+/// A single-token edit that makes a sentence parse.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Suggestion {
+    /// The tokens to replace, by index. Empty for an insertion.
+    pub span: Range<usize>,
+    /// The text to put in their place. Empty for a deletion.
+    pub replacement: String,
+    /// The characters of the text to replace, for editors to apply the edit: the word replaced,
+    /// the word removed with the pause after it, or an empty range where a word is inserted. For
+    /// words given without their spans, the indices of the tokens.
+    pub chars: Range<usize>,
+    /// The text to put in place of `chars`: the replacement, with a space between an inserted
+    /// word and the word next to it.
+    pub edit: String,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Range { start, end } = self.span;
+        match (start == end, self.replacement.is_empty()) {
+            (true, _) => write!(f, "insert `{}` before token {start}", self.replacement),
+            (false, true) => write!(f, "remove tokens {start}..{end}"),
+            (false, false) => write!(
+                f,
+                "replace tokens {start}..{end} with `{}`",
+                self.replacement
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDiagnostic {
+    pub errors: Vec<Cheap<Word>>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.first() {
+            Some(e) => write!(f, "failed to parse at token {}", e.span().start)?,
+            None => write!(f, "failed to parse")?,
+        }
+        for suggestion in &self.suggestions {
+            write!(f, "\n  help: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseDiagnostic {}
+
+/// Particles that close or separate constructions and are easy to forget.
+const INSERTIONS: [ParticleFamily; 4] = [
+    ParticleFamily::Be,
+    ParticleFamily::Pei,
+    ParticleFamily::Vei,
+    ParticleFamily::Pe,
+];
+
+/// The particle a misused particle was most likely meant to be.
+fn counterpart(word: &Word) -> Option<Word> {
+    match word {
+        Word::Particle(ParticleFamily::Vi { var, chain_with }) => {
+            Some(Word::Particle(ParticleFamily::Fi {
+                var: var.map_or(FiVar::None, FiVar::Var),
+                chain_with: *chain_with,
+            }))
+        }
+        Word::Particle(ParticleFamily::Fi { var, chain_with }) => {
+            let var = match var {
+                FiVar::None => None,
+                FiVar::Var(v) => Some(*v),
+                FiVar::Same | FiVar::Next => return None,
+            };
+            Some(Word::Particle(ParticleFamily::Vi {
+                var,
+                chain_with: *chain_with,
+            }))
+        }
+        Word::Particle(ParticleFamily::Pe) => Some(Word::Particle(ParticleFamily::Pei)),
+        Word::Particle(ParticleFamily::Pei) => Some(Word::Particle(ParticleFamily::Pe)),
+        _ => None,
+    }
+}
+
+/// Parses `words`, and on failure re-tries the parse with the single-token repairs at the first
+/// error (inserting a terminator or separator before the word found there, deleting it, or
+/// swapping it for its counterpart), reporting those that succeed. Only words of the families
+/// [`ParseError::classify`] expects there are inserted or swapped in, so a failure costs a
+/// bounded number of parses.
+pub fn parse_with_suggestions(words: Vec<Word>) -> Result<PredicateTree, ParseDiagnostic> {
+    let spanned: Vec<_> = words
+        .into_iter()
+        .enumerate()
+        .map(|(i, word)| (word, i..i + 1))
+        .collect();
+    parse_spanned_with_suggestions(&spanned)
+}
+
+/// Like [`parse_with_suggestions`], for words with their spans in a text as lexed by
+/// [`lex_spanned`](crate::lexer::lex_spanned), so that suggestions point at the characters of
+/// the text.
+pub fn parse_spanned_with_suggestions(
+    spanned: &[(Word, Range<usize>)],
+) -> Result<PredicateTree, ParseDiagnostic> {
+    let words: Vec<Word> = spanned.iter().map(|(word, _)| word.clone()).collect();
+    let parser = parser::<Cheap<Word>>();
+    let errors = match parser.parse(words.clone()) {
+        Ok(tree) => return Ok(tree),
+        Err(errors) => errors,
+    };
+    let mut suggestions = Vec::new();
+    let mut try_repair = |candidate: Vec<Word>, suggestion: Suggestion| {
+        let success = parser.parse(candidate).is_ok();
+        trace!(Recovery {
+            action: suggestion.to_string(),
+            success,
+        });
+        if success {
+            suggestions.push(suggestion);
+        }
+    };
+
+    let i = errors
+        .first()
+        .map_or(words.len(), |e| e.span().start)
+        .min(words.len());
+    let expected = ParseError::classify(&words, i).expected;
+    let is_expected = |word: &Word| match word {
+        Word::Particle(family) => expected.contains(&family.name()),
+        Word::Predicate(_, family) => expected.contains(&family.name()),
+    };
+
+    // An inserted word goes before the word at `i`, or after the last one.
+    let end = spanned.last().map_or(0, |(_, span)| span.end);
+    let (at, before, after) = match spanned.get(i) {
+        Some((_, span)) => (span.start, "", " "),
+        None if i > 0 => (end, " ", ""),
+        None => (end, "", ""),
+    };
+    for particle in INSERTIONS {
+        let word = Word::Particle(particle);
+        if !is_expected(&word) {
+            continue;
+        }
+        let mut candidate = words.clone();
+        let replacement = word.text();
+        candidate.insert(i, word);
+        try_repair(
+            candidate,
+            Suggestion {
+                span: i..i,
+                chars: at..at,
+                edit: format!("{before}{replacement}{after}"),
+                replacement,
+            },
+        );
+    }
+    if let Some((word, span)) = spanned.get(i) {
+        if let Some(other) = counterpart(word).filter(|other| is_expected(other)) {
+            let mut candidate = words.clone();
+            let replacement = other.text();
+            candidate[i] = other;
+            try_repair(
+                candidate,
+                Suggestion {
+                    span: i..i + 1,
+                    chars: span.clone(),
+                    edit: replacement.clone(),
+                    replacement,
+                },
+            );
+        }
+        // A word is removed with the pause after it, or the last one with the pause before it.
+        let chars = match (spanned.get(i + 1), i.checked_sub(1)) {
+            (Some((_, next)), _) => span.start..next.start,
+            (None, Some(previous)) => spanned[previous].1.end..span.end,
+            (None, None) => span.clone(),
+        };
+        let mut candidate = words.clone();
+        candidate.remove(i);
+        try_repair(
+            candidate,
+            Suggestion {
+                span: i..i + 1,
+                chars,
+                edit: String::new(),
+                replacement: String::new(),
+            },
+        );
+    }
+
+    Err(ParseDiagnostic {
+        errors,
+        suggestions,
+    })
+}
@@ -0,0 +1,264 @@
+//! Rewriting eberban text in a canonical style: words separated by single spaces, each sentence
+//! on a line of its own, terminators written or elided consistently and lines wrapped at a
+//! width. The sentences of the text always parse as they did before.
+
+use std::{fmt, str::FromStr};
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    cst,
+    lexer::{lex_words, to_text, ParticleFamily, PreProcessed, Separator, Word},
+    parser::{sentence_parser, Sentence},
+};
+
+/// Which terminators a formatted sentence writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Terminators {
+    /// Those written in the text.
+    Keep,
+    /// Only those the parse needs, removing the others last first.
+    #[default]
+    Elide,
+    /// Every `pei` and `vei` the grammar allows, including those elided in the text.
+    Write,
+}
+
+impl FromStr for Terminators {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(Terminators::Keep),
+            "elide" => Ok(Terminators::Elide),
+            "write" => Ok(Terminators::Write),
+            _ => Err(format!("unknown terminator style `{s}`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    Lex(Vec<Cheap<PreProcessed>>),
+    Parse(Vec<Cheap<Word>>),
+    /// The formatted text does not parse as the text did, which is a bug of the formatter.
+    Changed,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Lex(errors) => write!(f, "{} lexing error(s)", errors.len()),
+            FormatError::Parse(errors) => write!(f, "{} parsing error(s)", errors.len()),
+            FormatError::Changed => write!(f, "formatting would change the parse"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// How [`FormatOptions::format`] lays out a text. By default, as for [`format`], terminators
+/// are elided wherever the parse allows and lines are not wrapped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FormatOptions {
+    terminators: Terminators,
+    width: Option<usize>,
+}
+
+impl FormatOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn terminators(self, terminators: Terminators) -> Self {
+        Self {
+            terminators,
+            ..self
+        }
+    }
+
+    /// The largest number of characters on a line, unless a single word is longer. Lines are
+    /// broken between words, which a line break separates as well as a space.
+    pub fn width(self, width: usize) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    /// Formats a text of sentences separated by `pu`, `pa` and `po`. A separator ends its
+    /// line, and `pa` and `po` are followed by an empty line.
+    pub fn format(&self, text: &str) -> Result<String, FormatError> {
+        let words = lex_words(text).map_err(FormatError::Lex)?;
+        let before = sentences(&words).map_err(FormatError::Parse)?;
+        let mut lines = Lines {
+            width: self.width,
+            out: String::new(),
+            line: 0,
+        };
+        for (sentence, separator) in split(&words) {
+            if !sentence.is_empty() {
+                for word in self.sentence(sentence)? {
+                    lines.word(&word);
+                }
+            }
+            if let Some(separator) = separator {
+                lines.word(separator);
+                lines.end(!matches!(
+                    separator,
+                    Word::Particle(ParticleFamily::Separator(Separator::Sentence))
+                ));
+            }
+        }
+        let formatted = lines.out.trim_end().to_string();
+        let after = lex_words(&formatted)
+            .ok()
+            .and_then(|words| sentences(&words).ok());
+        if after.as_ref() != Some(&before) {
+            return Err(FormatError::Changed);
+        }
+        Ok(formatted)
+    }
+
+    /// The words of a sentence with its terminators written or elided.
+    fn sentence(&self, words: &[Word]) -> Result<Vec<Word>, FormatError> {
+        let syntax = cst::parse(words).map_err(FormatError::Parse)?;
+        let mut words: Vec<Word> = syntax
+            .root()
+            .tokens()
+            .into_iter()
+            .map(|token| token.word.clone())
+            .collect();
+        match self.terminators {
+            Terminators::Keep => {}
+            Terminators::Write => {
+                // Terminators elided at the same word are listed innermost first.
+                for elided in syntax.root().elided().into_iter().rev() {
+                    words.insert(elided.index, Word::Particle(elided.particle.clone()));
+                }
+            }
+            Terminators::Elide => {
+                // Candidates are checked as text, since removing a terminator can leave words
+                // that lex as one.
+                let parser = sentence_parser::<Cheap<Word>>();
+                let parses = |words: &[Word]| {
+                    lex_words(&to_text(words))
+                        .ok()
+                        .and_then(|words| parser.parse(words).ok())
+                        .as_ref()
+                        == Some(syntax.sentence())
+                };
+                for i in (0..words.len()).rev() {
+                    if !matches!(
+                        words[i],
+                        Word::Particle(ParticleFamily::Pei | ParticleFamily::Vei)
+                    ) {
+                        continue;
+                    }
+                    let mut candidate = words.clone();
+                    candidate.remove(i);
+                    if parses(&candidate) {
+                        words = candidate;
+                    }
+                }
+            }
+        }
+        Ok(words)
+    }
+}
+
+/// Formats a text with the default [`FormatOptions`].
+pub fn format(text: &str) -> Result<String, FormatError> {
+    FormatOptions::default().format(text)
+}
+
+/// The sentences of a text, each with the separator after it, if any.
+fn split(words: &[Word]) -> Vec<(&[Word], Option<&Word>)> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (i, word) in words.iter().enumerate() {
+        if let Word::Particle(ParticleFamily::Separator(_)) = word {
+            sentences.push((&words[start..i], Some(word)));
+            start = i + 1;
+        }
+    }
+    sentences.push((&words[start..], None));
+    sentences
+}
+
+fn sentences(words: &[Word]) -> Result<Vec<Sentence>, Vec<Cheap<Word>>> {
+    let parser = sentence_parser::<Cheap<Word>>();
+    split(words)
+        .into_iter()
+        .filter(|(sentence, _)| !sentence.is_empty())
+        .map(|(sentence, _)| parser.parse(sentence.to_vec()))
+        .collect()
+}
+
+/// Formatted text, with the length of its last line.
+struct Lines {
+    width: Option<usize>,
+    out: String,
+    line: usize,
+}
+
+impl Lines {
+    fn word(&mut self, word: &Word) {
+        let text = word.to_string();
+        let len = text.chars().count();
+        if self.line > 0 {
+            if self.width.is_some_and(|width| self.line + 1 + len > width) {
+                self.out.push('\n');
+                self.line = 0;
+            } else {
+                self.out.push(' ');
+                self.line += 1;
+            }
+        } else if self.out.is_empty() && word.needs_pause() {
+            // Only a word opening the text needs an apostrophe to mark its pause.
+            self.out.push('\'');
+            self.line += 1;
+        }
+        self.out.push_str(&text);
+        self.line += len;
+    }
+
+    /// Ends the line, with an empty line after it if `blank`.
+    fn end(&mut self, blank: bool) {
+        self.out.push('\n');
+        if blank {
+            self.out.push('\n');
+        }
+        self.line = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::texts;
+    use proptest::{prop_assert_eq, prop_oneof, proptest, strategy::Just};
+
+    proptest! {
+        #[test]
+        fn formatting_keeps_the_parse(
+            s in texts(),
+            terminators in prop_oneof![
+                Just(Terminators::Keep),
+                Just(Terminators::Elide),
+                Just(Terminators::Write),
+            ],
+            width in 1..30usize,
+        ) {
+            // `format` fails with `FormatError::Changed` if the parse changes.
+            let options = FormatOptions::new().terminators(terminators).width(width);
+            if lex_words(&s).is_ok_and(|words| sentences(&words).is_ok()) {
+                let formatted = options.format(&s);
+                prop_assert_eq!(formatted.as_ref().err(), None, "{:?}", s);
+                if let Ok(formatted) = formatted {
+                    prop_assert_eq!(options.format(&formatted), Ok(formatted.clone()), "{:?}", s);
+                }
+            }
+        }
+    }
+}
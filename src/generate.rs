@@ -0,0 +1,377 @@
+//! Random valid words: roots, freeform words and particles of every family, for property testing
+//! the lexer and parser. Every word drawn lexes back as itself. With the `proptest` feature,
+//! [`Root`], [`Freeform`] and [`Particle`] implement `proptest::arbitrary::Arbitrary`.
+
+use crate::{
+    dfa,
+    lexer::{
+        place_text, Number, ParticleFamily, PhonologyTables, Word, ARG_VOWELS, CONNECTIVES, DIGITS,
+        ILLOCUTIONS, NON_SONORANT, PARTICLE_FAMILIES, QUANTIFIERS, SENTENCE_SEPARATORS, SONORANT,
+        SUBORDINATORS, VOWELS,
+    },
+    phonotactics::is_valid_root,
+    GrammarVar,
+};
+
+#[cfg(feature = "proptest")]
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    sample::select,
+    strategy::{BoxedStrategy, Strategy},
+};
+
+/// A xorshift generator, so that generated words are reproducible from a seed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    /// A number below `n`, or 0 if `n` is 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n.max(1) as u64) as usize
+    }
+
+    /// An element of `items`, or the default value if it is empty.
+    pub fn pick<T: Copy + Default>(&mut self, items: &[T]) -> T {
+        items
+            .get(self.below(items.len()))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// How many candidates are drawn before giving up on a word.
+const ATTEMPTS: usize = 1000;
+
+/// One or two different vowels.
+fn vowels(rng: &mut Rng) -> String {
+    let first = rng.pick(&VOWELS);
+    let mut vowels = first.to_string();
+    if rng.below(3) == 0 {
+        let others: Vec<char> = VOWELS.iter().copied().filter(|v| *v != first).collect();
+        vowels.push(rng.pick(&others));
+    }
+    vowels
+}
+
+/// Up to `max` syllables, each opened by a letter drawn by `onset` and closed by vowels.
+fn syllables(rng: &mut Rng, max: usize, onset: impl Fn(&mut Rng) -> String) -> String {
+    (0..rng.below(max + 1))
+        .map(|_| {
+            let onset = onset(rng);
+            onset + &vowels(rng)
+        })
+        .collect()
+}
+
+fn pairs(pairs: impl Iterator<Item = (char, char)>) -> Vec<(char, char)> {
+    pairs.collect()
+}
+
+/// A candidate root, following the shapes the lexer reads: a non-sonorant or an initial pair,
+/// vowels, then syllables opened by `h`, a sonorant or a medial pair, and an optional final
+/// sonorant.
+fn root_candidate(rng: &mut Rng) -> String {
+    let tables = PhonologyTables::STANDARD;
+    let medial = pairs(tables.medial_pairs());
+    let mut word = if rng.below(3) == 0 {
+        let (a, b) = rng.pick(&pairs(tables.initial_pairs()));
+        format!("{a}{b}")
+    } else {
+        rng.pick(&NON_SONORANT).to_string()
+    };
+    word += &vowels(rng);
+    word += &syllables(rng, 3, |rng| match rng.below(4) {
+        0 => "h".to_string(),
+        1 => rng.pick(&SONORANT).to_string(),
+        _ => {
+            let (a, b) = rng.pick(&medial);
+            format!("{a}{b}")
+        }
+    });
+    if rng.below(2) == 0 {
+        word.push(rng.pick(&SONORANT));
+    }
+    word
+}
+
+/// A random root that lexes as a single root, or `None` if none of the candidates drawn does.
+pub fn root(rng: &mut Rng) -> Option<String> {
+    (0..ATTEMPTS)
+        .map(|_| root_candidate(rng))
+        .find(|w| is_valid_root(w).is_ok())
+}
+
+/// A random freeform word: one to three syllables, each a consonant or `h` and vowels, then a
+/// final non-sonorant. Every such word lexes as a freeform word.
+pub fn freeform(rng: &mut Rng) -> String {
+    let onsets: Vec<char> = NON_SONORANT
+        .iter()
+        .chain(&SONORANT)
+        .chain(&['h'])
+        .copied()
+        .collect();
+    let mut word: String = (0..1 + rng.below(3))
+        .map(|_| rng.pick(&onsets).to_string() + &vowels(rng))
+        .collect();
+    word.push(rng.pick(&NON_SONORANT));
+    word
+}
+
+/// The letters after the initial of a particle starting with a non-sonorant: vowels, then
+/// syllables opened by `h`.
+fn nonsonorant_tail(rng: &mut Rng) -> String {
+    vowels(rng) + &syllables(rng, 2, |_| "h".to_string())
+}
+
+/// The letters after the first vowel of a particle starting with a sonorant or a vowel:
+/// syllables opened by `h` or a sonorant, and an optional final sonorant.
+fn sonorant_tail(rng: &mut Rng) -> String {
+    let mut tail = syllables(rng, 2, |rng| {
+        if rng.below(2) == 0 {
+            "h".to_string()
+        } else {
+            rng.pick(&SONORANT).to_string()
+        }
+    });
+    if rng.below(3) == 0 {
+        tail.push(rng.pick(&SONORANT));
+    }
+    tail
+}
+
+fn particle_candidate(rng: &mut Rng, family: &str) -> Option<String> {
+    let pick = |rng: &mut Rng, words: &[&str]| rng.pick(words).to_string();
+    let arg_vowel = |rng: &mut Rng| rng.pick(&ARG_VOWELS);
+    let equivalence = |rng: &mut Rng| if rng.below(2) == 0 { "" } else { "i" };
+    // Mostly one of the first four places, sometimes a later one.
+    let place = |rng: &mut Rng| match rng.below(8) {
+        0 => place_text(rng.below(GrammarVar::MAX as usize + 1) as GrammarVar),
+        _ => arg_vowel(rng).to_string(),
+    };
+    Some(match family {
+        "PE" => "pe".to_string(),
+        "PEI" => "pei".to_string(),
+        "VEI" => "vei".to_string(),
+        "BE" => "be".to_string(),
+        "BI" => "bi".to_string(),
+        "VI" => match rng.below(4) {
+            0 => "vi".to_string(),
+            _ => format!("v{}{}", equivalence(rng), place(rng)),
+        },
+        "FI" => match rng.below(4) {
+            0 => pick(rng, &["fi", "feu", "fau", "fei", "fai"]),
+            _ => format!("f{}{}", equivalence(rng), place(rng)),
+        },
+        "KI" => format!("k{}", nonsonorant_tail(rng)),
+        "GI" => format!("g{}", nonsonorant_tail(rng)),
+        "MI" => format!("m{}", vowels(rng)),
+        "ZI" => format!("z{}", nonsonorant_tail(rng)),
+        "SI" => {
+            let vs: String = (0..1 + rng.below(2)).map(|_| arg_vowel(rng)).collect();
+            let place = match rng.below(2) {
+                0 => String::new(),
+                _ => format!("h{}", arg_vowel(rng)),
+            };
+            format!("s{vs}{place}{}", equivalence(rng))
+        }
+        "CE" => format!("ce {}", root(rng)?),
+        "CU" => {
+            let delimiter = root(rng)?;
+            let content = root(rng)?;
+            format!("cu {delimiter} {content} {delimiter}")
+        }
+        "JI" => ParticleFamily::Number(Number {
+            digits: (0..1 + rng.below(3))
+                .map(|_| rng.below(DIGITS.len()) as u8)
+                .collect(),
+        })
+        .text(),
+        "ATTITUDINAL" => rng.pick(&['i', 'e', 'a', 'u']).to_string() + &sonorant_tail(rng),
+        "EXCLAMATIVE" => {
+            format!("{}{}", rng.pick(&SONORANT), rng.pick(&VOWELS)) + &sonorant_tail(rng)
+        }
+        "VOCATIVE" => "o".to_string() + &sonorant_tail(rng),
+        "ILLOCUTION" => rng.pick(&ILLOCUTIONS.map(|(w, _)| w)).to_string(),
+        "BA" => rng.pick(&CONNECTIVES.map(|(w, _)| w)).to_string(),
+        "TA" => {
+            let tag = format!("t{}{}", rng.pick(&['a', 'e', 'o', 'u']), vowels(rng));
+            match rng.below(2) {
+                0 => tag,
+                _ => format!("{tag}h{}", arg_vowel(rng)),
+            }
+        }
+        "DA" => rng.pick(&SUBORDINATORS.map(|(w, _)| w)).to_string(),
+        "DU" => rng.pick(&QUANTIFIERS.map(|(w, _)| w)).to_string(),
+        "PU" => rng.pick(&SENTENCE_SEPARATORS).to_string(),
+        _ => return None,
+    })
+}
+
+/// The single word `text` lexes as after a pause, if it lexes as one.
+fn lex_one(text: &str) -> Option<Word> {
+    let mut words = dfa::lex(&format!(" {text}"))?.into_iter();
+    match (words.next(), words.next()) {
+        (Some((word, _)), None) => Some(word),
+        _ => None,
+    }
+}
+
+/// A random particle of the family named `family`, as returned by
+/// [`ParticleFamily::name`](crate::lexer::ParticleFamily::name), that lexes back as that
+/// particle, or `None` if the lexer has no such family. Quotes and numbers may span several
+/// words.
+pub fn particle(rng: &mut Rng, family: &str) -> Option<String> {
+    if !PARTICLE_FAMILIES.contains(&family) {
+        return None;
+    }
+    (0..ATTEMPTS).find_map(|_| {
+        let text = particle_candidate(rng, family)?;
+        match lex_one(&text)? {
+            Word::Particle(p) if p.name() == family && p.text() == text => Some(text),
+            _ => None,
+        }
+    })
+}
+
+/// A root drawn by [`root`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Root(pub String);
+
+/// A freeform word drawn by [`freeform`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Freeform(pub String);
+
+/// A particle drawn by [`particle`], of a family of
+/// [`PARTICLE_FAMILIES`](crate::lexer::PARTICLE_FAMILIES).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Particle {
+    pub family: &'static str,
+    pub text: String,
+}
+
+/// Words drawn from the seeds proptest generates. Seeds nothing is drawn from are rejected, so
+/// proptest draws another rather than settling for a fixed word.
+#[cfg(feature = "proptest")]
+fn seeded<T: std::fmt::Debug>(
+    rejected: &'static str,
+    draw: impl Fn(&mut Rng) -> Option<T>,
+) -> impl Strategy<Value = T> {
+    any::<u64>().prop_filter_map(rejected, move |seed| draw(&mut Rng::new(seed)))
+}
+
+#[cfg(feature = "proptest")]
+impl Arbitrary for Root {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        seeded("no root drawn", |rng| root(rng).map(Root)).boxed()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Arbitrary for Freeform {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        seeded("no freeform word drawn", |rng| {
+            Some(Freeform(freeform(rng)))
+        })
+        .boxed()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Arbitrary for Particle {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (select(PARTICLE_FAMILIES.to_vec()), any::<u64>())
+            .prop_filter_map("no particle drawn", |(family, seed)| {
+                let text = particle(&mut Rng::new(seed), family)?;
+                Some(Particle { family, text })
+            })
+            .boxed()
+    }
+}
+
+/// A sentence of a few words chained by `vi`, `fe`, `fi`, connectives and tags, with negations,
+/// `si`, groups, abstractions and free modifiers. Most sentences matching it parse.
+#[cfg(test)]
+fn sentence_pattern() -> String {
+    let element = "(mi|dona|mian|alis|ki|kie|pe (mian|dona) (vi|ba) mian( pei)?)";
+    let chained = format!("(zi |bi |sia )?({element}|d[aeo] (mian|dona)( pei)?)");
+    let tagged = format!("t[aeou](h[aeiou])? {chained}( vei)?");
+    format!("{chained}( (vi|fe|ba|bo|fi) {chained}| {tagged}| 'na| a| 'o {element}){{0,4}}")
+}
+
+/// Sentences for property tests of the parsers, most of which parse.
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+pub(crate) fn sentences() -> impl proptest::strategy::Strategy<Value = String> {
+    proptest::string::string_regex(&sentence_pattern()).expect("the pattern is a regex")
+}
+
+/// Texts of up to three [`sentences`] separated by `pu` and `pa`.
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+pub(crate) fn texts() -> impl proptest::strategy::Strategy<Value = String> {
+    let sentence = sentence_pattern();
+    proptest::string::string_regex(&format!("{sentence}( (pu|pa) {sentence}){{0,2}}"))
+        .expect("the pattern is a regex")
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::lexer::{assemble_numbers, lex, to_text, PredicateFamily};
+    use proptest::{prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn drawn_words_lex_back(seed: u64, lengths in (0..4usize, 0..4usize, 0..8usize)) {
+            let rng = &mut Rng::new(seed);
+            let mut lexed = Vec::new();
+            for _ in 0..lengths.0 {
+                let Some(root) = root(rng) else { continue };
+                match lex(&format!(" {root}")).as_deref() {
+                    Ok([word @ Word::Predicate(pw, PredicateFamily::Root)]) if pw.word == root => {
+                        lexed.push(word.clone())
+                    }
+                    other => panic!("root `{root}` lexes as {other:?}"),
+                }
+            }
+            for _ in 0..lengths.1 {
+                let text = freeform(rng);
+                match lex(&format!(" {text}")).as_deref() {
+                    Ok([word @ Word::Predicate(pw, PredicateFamily::Freeform)]) if pw.word == text => {
+                        lexed.push(word.clone())
+                    }
+                    other => panic!("freeform word `{text}` lexes as {other:?}"),
+                }
+            }
+            for _ in 0..lengths.2 {
+                let family = rng.pick(&PARTICLE_FAMILIES);
+                let Some(text) = particle(rng, family) else { continue };
+                match lex(&format!(" {text}")).as_deref() {
+                    Ok([word @ Word::Particle(p)]) if (p.name(), p.text()) == (family, text.clone()) => {
+                        lexed.push(word.clone())
+                    }
+                    other => panic!("particle `{text}` lexes as {other:?}"),
+                }
+            }
+            let text = to_text(&lexed);
+            prop_assert_eq!(lex(&text).ok(), Some(assemble_numbers(lexed)), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use crate::{
+    dictionary::Dictionary,
+    expr::{Notation, Predicate},
+    GrammarVar,
+};
+
+/// Formats a predicate with every argument named after the place it fills, as in
+/// `dona1(lover: 0, beloved: 1)`, by the place labels of `dictionary`. Arguments filling a place
+/// without a label are written by position alone, as in the notation of `Predicate`. With
+/// `gloss`, words with a gloss in the dictionary are written as their gloss.
+pub struct Labelled<'a> {
+    pub pred: &'a Predicate,
+    pub dictionary: Option<&'a Dictionary>,
+    pub gloss: bool,
+}
+
+impl fmt::Display for Labelled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Notation {
+            pred: self.pred,
+            leaf: &|f, word, id, apply_to| {
+                let entry = self.dictionary.and_then(|d| d.get(word));
+                match entry
+                    .and_then(|e| e.gloss.as_deref())
+                    .filter(|_| self.gloss)
+                {
+                    Some(gloss) => write!(f, "{gloss}")?,
+                    None => write!(f, "{word}{id}")?,
+                }
+                if apply_to.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                for (i, v) in apply_to.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    let role = GrammarVar::try_from(i)
+                        .ok()
+                        .zip(entry)
+                        .and_then(|(var, entry)| entry.role(var));
+                    match role {
+                        Some(role) => write!(f, "{role}: {v}")?,
+                        None => write!(f, "{v}")?,
+                    }
+                }
+                write!(f, ")")
+            },
+            var: &|f, v| write!(f, "{v}"),
+        }
+        .fmt(f)
+    }
+}
+
+/// Formats `pred` with the place labels of `dictionary` instead of bare argument positions.
+pub fn with_roles<'a>(pred: &'a Predicate, dictionary: Option<&'a Dictionary>) -> Labelled<'a> {
+    Labelled {
+        pred,
+        dictionary,
+        gloss: false,
+    }
+}
+
+/// Formats `pred` with words replaced by their gloss and arguments named after their places.
+pub fn gloss<'a>(pred: &'a Predicate, dictionary: &'a Dictionary) -> Labelled<'a> {
+    Labelled {
+        pred,
+        dictionary: Some(dictionary),
+        gloss: true,
+    }
+}
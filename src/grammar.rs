@@ -0,0 +1,415 @@
+use crate::{
+    json::Value,
+    lexer::{
+        AbstractionKind, ConnectiveKind, FiVar, Interjection, Mood, Number, ParticleFamily,
+        PredicateWord, QuantifierKind, Quote, Separator, Tag, TagKind, ZiParticle,
+    },
+    ChainingBehavior, Exposure, PredicateChaining,
+};
+
+/// The part a particle family plays in the grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// Opens a group.
+    Opener,
+    /// Closes a group or list; usually elidable.
+    Terminator,
+    /// Introduces a predicate sharing a place of the preceding one.
+    Sharer,
+    /// Changes which places a predicate exposes.
+    Exposure,
+    /// Fills a place with a bound word.
+    Argument,
+    /// Stands for a predicate.
+    Predicate,
+    Negation,
+    /// May appear anywhere, without changing the meaning of the sentence.
+    FreeModifier,
+    /// Separates sentences, which are parsed one at a time.
+    Separator,
+    /// Addresses the sentence to the predicate after it.
+    Vocative,
+    /// Sets the mood of the sentence.
+    Illocution,
+    /// Joins two predicates with a truth function.
+    Connective,
+    /// Quantifies over the place a predicate is chained to.
+    Quantifier,
+    /// Attaches a predicate to a place of the preceding one as its time, place or cause.
+    Tag,
+    /// Lexed, but not handled by the parser.
+    Unsupported,
+}
+
+impl Role {
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Opener => "opener",
+            Role::Terminator => "terminator",
+            Role::Sharer => "sharer",
+            Role::Exposure => "exposure",
+            Role::Argument => "argument",
+            Role::Predicate => "predicate",
+            Role::Negation => "negation",
+            Role::FreeModifier => "free modifier",
+            Role::Separator => "separator",
+            Role::Vocative => "vocative",
+            Role::Illocution => "illocution",
+            Role::Connective => "connective",
+            Role::Quantifier => "quantifier",
+            Role::Tag => "tag",
+            Role::Unsupported => "unsupported",
+        }
+    }
+}
+
+impl Role {
+    /// Whether particles of this role can come after any word, without changing the predicate
+    /// tree of the sentence: interjections, vocatives and illocutions.
+    pub fn is_free(self) -> bool {
+        matches!(self, Role::FreeModifier | Role::Vocative | Role::Illocution)
+    }
+}
+
+pub fn role(family: &ParticleFamily) -> Role {
+    match family {
+        ParticleFamily::Pe | ParticleFamily::Subordinator(_) => Role::Opener,
+        ParticleFamily::Pei | ParticleFamily::Vei | ParticleFamily::Be => Role::Terminator,
+        ParticleFamily::Vi { .. } | ParticleFamily::Fi { .. } => Role::Sharer,
+        ParticleFamily::Si { .. } => Role::Exposure,
+        ParticleFamily::Ki(_) => Role::Argument,
+        ParticleFamily::Gi(_)
+        | ParticleFamily::Mi(_)
+        | ParticleFamily::Quote(_)
+        | ParticleFamily::Number(_) => Role::Predicate,
+        ParticleFamily::Bi | ParticleFamily::Zi(_) => Role::Negation,
+        ParticleFamily::Interjection(_) => Role::FreeModifier,
+        ParticleFamily::Separator(_) => Role::Separator,
+        ParticleFamily::Vocative(_) => Role::Vocative,
+        ParticleFamily::Illocution(_) => Role::Illocution,
+        ParticleFamily::Connective(_) => Role::Connective,
+        ParticleFamily::Quantifier(_) => Role::Quantifier,
+        ParticleFamily::Tag(_) => Role::Tag,
+        ParticleFamily::Other(_) => Role::Unsupported,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FamilyInfo {
+    pub name: &'static str,
+    pub role: Role,
+    /// A particle of the family.
+    pub example: String,
+}
+
+/// A construction the parser recognises, with the families it is built from and the semantics
+/// options that change its interpretation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Construction {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub families: &'static [&'static str],
+    pub options: &'static [&'static str],
+}
+
+/// An option changing how parses are turned into formulas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SemanticsOption {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const SEMANTICS_OPTIONS: [SemanticsOption; 2] = [
+    SemanticsOption {
+        name: "canonical",
+        description: "renumber variables and leaf ids in order of appearance, flatten \
+                      conjunctions and drop empty quantifiers (`to_expr_canonical`)",
+    },
+    SemanticsOption {
+        name: "dictionary",
+        description: "attach dictionary metadata to leaves (`to_expr_with_metadata`)",
+    },
+];
+
+pub const CONSTRUCTIONS: [Construction; 19] = [
+    Construction {
+        name: "predicate",
+        description: "a root, or a `ki`, `gi` or `mi` particle standing for a predicate",
+        families: &["KI", "GI", "MI"],
+        options: &["canonical", "dictionary"],
+    },
+    Construction {
+        name: "quote",
+        description: "a predicate about quoted material: a single word after `ce`, or text \
+                      between two occurrences of a delimiter after `cu`",
+        families: &["CE", "CU"],
+        options: &["dictionary"],
+    },
+    Construction {
+        name: "number",
+        description: "consecutive digit particles, in a predicate or argument position, saying \
+                      how many things fill a place",
+        families: &["JI"],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "name",
+        description: "a freeform word, in a predicate or argument position, naming an \
+                      individual that is never quantified over",
+        families: &[],
+        options: &[],
+    },
+    Construction {
+        name: "group",
+        description: "a predicate tree between `pe` and an elidable `pei`",
+        families: &["PE", "PEI"],
+        options: &[],
+    },
+    Construction {
+        name: "abstraction",
+        description: "a predicate tree after `da`, `de` or `do` and an elidable `pei`, holding \
+                      of the event, proposition or property it describes",
+        families: &["DA", "PEI"],
+        options: &[],
+    },
+    Construction {
+        name: "chaining",
+        description: "a predicate followed by another sharing or equated to its chaining place",
+        families: &[],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "sharers",
+        description: "`vi` and `fi` clauses binding predicates to places, closed by an \
+                      elidable `vei`, the first of them usually a `vi` clause",
+        families: &["VI", "FI", "VEI"],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "arguments",
+        description: "`ki` and `gi` words, names, numbers and quotes bound to the places of a \
+                      sharer, closed by `be`",
+        families: &["KI", "GI", "JI", "CE", "CU", "BE"],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "exposure",
+        description: "a `si` particle changing the places a predicate exposes",
+        families: &["SI"],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "short negation",
+        description: "`zi` negating the following element, among other `zi` family particles \
+                      marking its scope",
+        families: &["ZI"],
+        options: &[],
+    },
+    Construction {
+        name: "long negation",
+        description: "`bi` negating the rest of the predicate tree",
+        families: &["BI"],
+        options: &[],
+    },
+    Construction {
+        name: "interjection",
+        description: "an attitudinal or exclamative particle, anywhere in the sentence, \
+                      attached to the word before it",
+        families: &["ATTITUDINAL", "EXCLAMATIVE"],
+        options: &[],
+    },
+    Construction {
+        name: "vocative",
+        description: "`o` and a predicate or group the sentence is addressed to, after any \
+                      word or at the start of the sentence",
+        families: &["VOCATIVE"],
+        options: &[],
+    },
+    Construction {
+        name: "illocution",
+        description: "`a`, `e` or `u` marking the sentence as an assertion, a question or a \
+                      command, after any word or at the start of the sentence; the first one \
+                      sets the mood",
+        families: &["ILLOCUTION"],
+        options: &[],
+    },
+    Construction {
+        name: "conjunction",
+        description: "a `vi` or `fi` clause with no place, conjoined with the predicate",
+        families: &["VI", "FI"],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "connective",
+        description: "`ba`, `bo`, `bu`, `bai` or `bau` joining two elements, or two predicate \
+                      trees inside a group or at the top of the sentence, by a truth function",
+        families: &["BA"],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "quantifier",
+        description: "`du`, `dai`, `dei`, or `doi` and a number, before a chained predicate \
+                      tree, quantifying over the place it is chained to",
+        families: &["DU", "JI"],
+        options: &["canonical"],
+    },
+    Construction {
+        name: "adjunct",
+        description: "a tag such as `ta` or `tohe` and a predicate tree, among the sharers, \
+                      saying when, where, how or why of a place of the predicate, closed by an \
+                      elidable `vei`",
+        families: &["TA", "VEI"],
+        options: &["canonical"],
+    },
+];
+
+/// A particle of every family, in the order of
+/// [`PARTICLE_FAMILIES`](crate::lexer::PARTICLE_FAMILIES), followed by the particles the parser
+/// does not handle.
+pub(crate) fn representatives() -> [ParticleFamily; 25] {
+    let word = |word: &str| PredicateWord {
+        word: word.to_string(),
+        chaining: ChainingBehavior {
+            var: 0,
+            chain_with: PredicateChaining::Sharing,
+        },
+    };
+    [
+        ParticleFamily::Pe,
+        ParticleFamily::Pei,
+        ParticleFamily::Vi {
+            var: None,
+            chain_with: PredicateChaining::Sharing,
+        },
+        ParticleFamily::Fi {
+            var: FiVar::None,
+            chain_with: PredicateChaining::Sharing,
+        },
+        ParticleFamily::Vei,
+        ParticleFamily::Ki("ke".to_string()),
+        ParticleFamily::Gi(word("ge")),
+        ParticleFamily::Be,
+        ParticleFamily::Mi(word("mi")),
+        ParticleFamily::Si {
+            exposure: Exposure::Transparent,
+            chaining: ChainingBehavior {
+                var: 0,
+                chain_with: PredicateChaining::Equivalence,
+            },
+        },
+        ParticleFamily::Bi,
+        ParticleFamily::Zi(ZiParticle::Negation),
+        ParticleFamily::Quote(Quote::Word("dona".to_string())),
+        ParticleFamily::Quote(Quote::Foreign {
+            delimiter: "zoi".to_string(),
+            content: "hello".to_string(),
+        }),
+        ParticleFamily::Number(Number { digits: vec![4, 2] }),
+        ParticleFamily::Interjection(Interjection::new("ahe".to_string())),
+        ParticleFamily::Interjection(Interjection::new("na".to_string())),
+        ParticleFamily::Vocative("o".to_string()),
+        ParticleFamily::Illocution(Mood::Question),
+        ParticleFamily::Connective(ConnectiveKind::And),
+        ParticleFamily::Tag(Tag {
+            tag: "ta".to_string(),
+            kind: TagKind::Tense,
+            place: None,
+        }),
+        ParticleFamily::Subordinator(AbstractionKind::Event),
+        ParticleFamily::Quantifier(QuantifierKind::All),
+        ParticleFamily::Separator(Separator::Sentence),
+        ParticleFamily::Other("tce".to_string()),
+    ]
+}
+
+/// A description of the implemented grammar, derived from the lexer and parser so that it stays
+/// in step with them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grammar {
+    pub families: Vec<FamilyInfo>,
+    pub constructions: Vec<Construction>,
+    pub options: Vec<SemanticsOption>,
+}
+
+pub fn grammar() -> Grammar {
+    let families = representatives()
+        .iter()
+        .map(|p| FamilyInfo {
+            name: p.name(),
+            role: role(p),
+            example: p.text(),
+        })
+        .collect();
+    Grammar {
+        families,
+        constructions: CONSTRUCTIONS.to_vec(),
+        options: SEMANTICS_OPTIONS.to_vec(),
+    }
+}
+
+impl Grammar {
+    pub fn family(&self, name: &str) -> Option<&FamilyInfo> {
+        self.families.iter().find(|f| f.name == name)
+    }
+
+    /// The constructions a family takes part in.
+    pub fn constructions_of<'a>(
+        &'a self,
+        family: &'a str,
+    ) -> impl Iterator<Item = &'a Construction> + 'a {
+        self.constructions
+            .iter()
+            .filter(move |c| c.families.contains(&family))
+    }
+
+    pub fn to_json(&self) -> Value {
+        let strings = |s: &[&str]| Value::Array(s.iter().map(|s| Value::from(*s)).collect());
+        Value::Object(vec![
+            (
+                "families".into(),
+                Value::Array(
+                    self.families
+                        .iter()
+                        .map(|f| {
+                            Value::Object(vec![
+                                ("name".into(), f.name.into()),
+                                ("role".into(), f.role.name().into()),
+                                ("example".into(), f.example.as_str().into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "constructions".into(),
+                Value::Array(
+                    self.constructions
+                        .iter()
+                        .map(|c| {
+                            Value::Object(vec![
+                                ("name".into(), c.name.into()),
+                                ("description".into(), c.description.into()),
+                                ("families".into(), strings(c.families)),
+                                ("options".into(), strings(c.options)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "options".into(),
+                Value::Array(
+                    self.options
+                        .iter()
+                        .map(|o| {
+                            Value::Object(vec![
+                                ("name".into(), o.name.into()),
+                                ("description".into(), o.description.into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
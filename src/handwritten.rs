@@ -0,0 +1,630 @@
+//! A hand-written backtracking recursive-descent parser accepting exactly the language of
+//! [`parser`](crate::parser::parser) and building the same trees, without the overhead of
+//! parser combinators. It also serves as an oracle for differential testing of the two.
+
+use std::collections::VecDeque;
+
+use chumsky::{error::Cheap, Error};
+
+use crate::{
+    cst::argument,
+    lexer::{
+        ConnectiveKind, FiVar, Mood, ParticleFamily, PredicateFamily, PredicateWord,
+        QuantifierKind, Tag, Word, ZiParticle,
+    },
+    parser::{
+        bind, connect, element, keep_free, leaf, number, quantify, starts_tree, Clause, Modifier,
+        PredicateTree, Sentence, Sharer, SpannedTrees, DEFAULT_MAX_DEPTH,
+    },
+    Argument, ChainingBehavior, Negation, PredicateChaining, Quantifier,
+};
+
+struct Parser<'a> {
+    words: &'a [Word],
+    /// The free modifiers kept by the leaf, number or name at each position.
+    owned: Vec<Vec<Modifier>>,
+    /// The furthest position at which a token was rejected.
+    furthest: usize,
+    /// The number of predicate trees being read, nested in each other.
+    depth: usize,
+    /// The first position at which a predicate tree was nested too deep.
+    exceeded: Option<usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(words: &'a [Word], owned: Vec<Vec<Modifier>>) -> Self {
+        Parser {
+            words,
+            owned,
+            furthest: 0,
+            depth: 0,
+            exceeded: None,
+        }
+    }
+
+    /// The token at `pos` if `f` accepts it.
+    fn token<T>(&mut self, pos: usize, f: impl FnOnce(&'a Word) -> Option<T>) -> Option<T> {
+        let out = self.words.get(pos).and_then(f);
+        if out.is_none() {
+            self.furthest = self.furthest.max(pos);
+        }
+        out
+    }
+
+    fn particle(&mut self, pos: usize, particle: &ParticleFamily) -> Option<usize> {
+        self.token(pos, |w| match w {
+            Word::Particle(p) if p == particle => Some(pos + 1),
+            _ => None,
+        })
+    }
+
+    fn count(&mut self, mut pos: usize, particle: &ParticleFamily) -> (usize, usize) {
+        let start = pos;
+        while let Some(next) = self.particle(pos, particle) {
+            pos = next;
+        }
+        (pos - start, pos)
+    }
+
+    fn predicate(&mut self, pos: usize) -> Option<PredicateWord> {
+        self.token(pos, |w| match w {
+            Word::Predicate(pw, _) => Some(pw.clone()),
+            Word::Particle(ParticleFamily::Ki(word)) => Some(PredicateWord {
+                word: word.clone(),
+                chaining: ChainingBehavior {
+                    var: 0,
+                    chain_with: PredicateChaining::Sharing,
+                },
+            }),
+            Word::Particle(ParticleFamily::Gi(pw) | ParticleFamily::Mi(pw)) => Some(pw.clone()),
+            Word::Particle(ParticleFamily::Quote(quote)) => Some(quote.predicate()),
+            _ => None,
+        })
+    }
+
+    /// A name, a predicate word or a number, with the free modifiers it keeps.
+    fn leaf(&mut self, pos: usize) -> Option<(PredicateTree, usize)> {
+        let tree = self.atom(pos)?;
+        let free = self.owned.get(pos).cloned().unwrap_or_default();
+        Some((keep_free(tree, free), pos + 1))
+    }
+
+    fn atom(&mut self, pos: usize) -> Option<PredicateTree> {
+        if let Some(word) = self.token(pos, |w| match w {
+            Word::Predicate(pw, PredicateFamily::Freeform) => Some(pw.clone()),
+            _ => None,
+        }) {
+            return Some(PredicateTree::Name {
+                word,
+                negation: Negation::None,
+                free: Vec::new(),
+            });
+        }
+        if let Some(word) = self.predicate(pos) {
+            return Some(PredicateTree::Leaf {
+                word,
+                negation: Negation::None,
+                free: Vec::new(),
+            });
+        }
+        self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Number(n)) => Some(number(n)),
+            _ => None,
+        })
+    }
+
+    fn connective(&mut self, pos: usize) -> Option<(ConnectiveKind, usize)> {
+        self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Connective(kind)) => Some((*kind, pos + 1)),
+            _ => None,
+        })
+    }
+
+    /// Operands read by `operand` joined by connectives, each read one tree deeper than the one
+    /// before it, as the trees before it are nested in the connective.
+    fn joined(
+        &mut self,
+        pos: usize,
+        operand: fn(&mut Self, usize) -> Option<(PredicateTree, usize)>,
+    ) -> Option<(PredicateTree, usize)> {
+        let depth = self.depth;
+        let (mut p, mut pos) = operand(self, pos)?;
+        while let Some((kind, next)) = self.connective(pos) {
+            if !self.enter(next) {
+                break;
+            }
+            let Some((right, next)) = operand(self, next) else {
+                break;
+            };
+            p = connect(p, (kind, right));
+            pos = next;
+        }
+        self.depth = depth;
+        Some((p, pos))
+    }
+
+    /// Elements joined by connectives.
+    fn element(&mut self, pos: usize) -> Option<(PredicateTree, usize)> {
+        self.joined(pos, Self::single_element)
+    }
+
+    /// Predicate trees joined by connectives, as inside a group or at the top of a sentence.
+    fn connected(&mut self, pos: usize) -> Option<(PredicateTree, usize)> {
+        self.joined(pos, |this, pos| this.predicate_tree(pos, true))
+    }
+
+    fn single_element(&mut self, pos: usize) -> Option<(PredicateTree, usize)> {
+        let (mut zi, mut pos) = (0, pos);
+        while let Some(negation) = self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Zi(zi)) => Some(*zi == ZiParticle::Negation),
+            _ => None,
+        }) {
+            zi += usize::from(negation);
+            pos += 1;
+        }
+        let si = self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Si { exposure, chaining }) => {
+                Some((exposure.clone(), *chaining))
+            }
+            _ => None,
+        });
+        let pos = if si.is_some() { pos + 1 } else { pos };
+        let (p, pos) = if let Some(leaf) = self.leaf(pos) {
+            leaf
+        } else if let Some(kind) = self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Subordinator(kind)) => Some(*kind),
+            _ => None,
+        }) {
+            let (tree, pos) = self.connected(pos + 1)?;
+            let pos = self.particle(pos, &ParticleFamily::Pei).unwrap_or(pos);
+            let abstraction = PredicateTree::Abstraction {
+                kind,
+                negation: Negation::None,
+                tree: Box::new(tree),
+            };
+            (abstraction, pos)
+        } else {
+            let pos = self.particle(pos, &ParticleFamily::Pe)?;
+            let (p, pos) = self.connected(pos)?;
+            let pos = self.particle(pos, &ParticleFamily::Pei).unwrap_or(pos);
+            (p, pos)
+        };
+        Some((element(zi, si, p), pos))
+    }
+
+    fn argument_list(
+        &mut self,
+        mut pos: usize,
+    ) -> Option<(Vec<(Argument, PredicateChaining)>, usize)> {
+        let mut args = Vec::new();
+        while let Some(arg) = self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Ki(word)) => {
+                Some((Argument::Word(word.clone()), PredicateChaining::Sharing))
+            }
+            Word::Particle(ParticleFamily::Gi(pw)) => Some((
+                Argument::Word(pw.word.clone()),
+                PredicateChaining::Equivalence,
+            )),
+            Word::Particle(ParticleFamily::Number(number)) => number
+                .value()
+                .map(|n| (Argument::Number(n), PredicateChaining::Sharing)),
+            Word::Predicate(pw, PredicateFamily::Freeform) => {
+                Some((Argument::Name(pw.word.clone()), PredicateChaining::Sharing))
+            }
+            Word::Particle(ParticleFamily::Quote(quote)) => {
+                Some((Argument::Quote(quote.clone()), PredicateChaining::Sharing))
+            }
+            _ => None,
+        }) {
+            args.push(arg);
+            pos += 1;
+        }
+        let pos = self.particle(pos, &ParticleFamily::Be)?;
+        Some((args, pos))
+    }
+
+    /// A `vi` or `fi` (as selected by `binding`), its arguments and its predicate tree.
+    fn sharer(
+        &mut self,
+        pos: usize,
+        binding: impl FnOnce(&Word) -> Option<(FiVar, PredicateChaining)>,
+    ) -> Option<((Sharer, PredicateTree), usize)> {
+        let binding = self.token(pos, binding)?;
+        let pos = pos + 1;
+        let (args, pos) = match self.argument_list(pos) {
+            Some((args, pos)) => (Some(args), pos),
+            None => (None, pos),
+        };
+        let (p, pos) = self.shared(pos)?;
+        Some((((binding, args), p), pos))
+    }
+
+    /// A predicate tree chained to a place by a clause. If quantified, it scopes over the rest
+    /// of the binding, so it counts as one tree deeper until the binding is read.
+    fn shared(&mut self, pos: usize) -> Option<(PredicateTree, usize)> {
+        let Some((q, pos)) = self.quantifier(pos) else {
+            return self.predicate_tree(pos, false);
+        };
+        if !self.enter(pos) {
+            return None;
+        }
+        let Some((p, pos)) = self.predicate_tree(pos, false) else {
+            self.depth -= 1;
+            return None;
+        };
+        Some((quantify(Some(q), p), pos))
+    }
+
+    fn quantifier(&mut self, pos: usize) -> Option<(Quantifier, usize)> {
+        let kind = self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Quantifier(kind)) => Some(*kind),
+            _ => None,
+        })?;
+        let pos = pos + 1;
+        match kind {
+            QuantifierKind::All => Some((Quantifier::All, pos)),
+            QuantifierKind::Some => Some((Quantifier::Some, pos)),
+            QuantifierKind::Most => Some((Quantifier::Most, pos)),
+            QuantifierKind::Exactly => {
+                let n = self.token(pos, |w| match w {
+                    Word::Particle(ParticleFamily::Number(number)) => number.value(),
+                    _ => None,
+                })?;
+                Some((Quantifier::Exactly(n), pos + 1))
+            }
+        }
+    }
+
+    /// A predicate tree chained to a place, with an optional quantifier.
+    fn chained(&mut self, pos: usize, leading: bool) -> Option<(PredicateTree, usize)> {
+        let (q, pos) = match self.quantifier(pos) {
+            Some((q, pos)) => (Some(q), pos),
+            None => (None, pos),
+        };
+        let (p, pos) = self.predicate_tree(pos, leading)?;
+        Some((quantify(q, p), pos))
+    }
+
+    /// A `vi` clause, or a `fi` clause if `leading`, and the `fi` clauses after it.
+    fn sharer_group(
+        &mut self,
+        pos: usize,
+        leading: bool,
+    ) -> Option<(Vec<(Sharer, PredicateTree)>, usize)> {
+        let (first, mut pos) = self.sharer(pos, |w| match w {
+            Word::Particle(ParticleFamily::Vi { var, chain_with }) => {
+                Some((var.map_or(FiVar::None, FiVar::Var), *chain_with))
+            }
+            Word::Particle(ParticleFamily::Fi { var, chain_with }) if leading => {
+                Some((var.clone(), *chain_with))
+            }
+            _ => None,
+        })?;
+        let mut group = vec![first];
+        while let Some((sharer, next)) = self.sharer(pos, |w| match w {
+            Word::Particle(ParticleFamily::Fi { var, chain_with }) => {
+                Some((var.clone(), *chain_with))
+            }
+            _ => None,
+        }) {
+            group.push(sharer);
+            pos = next;
+        }
+        let pos = self.particle(pos, &ParticleFamily::Vei).unwrap_or(pos);
+        Some((group, pos))
+    }
+
+    /// A tag and the tree it attaches, closed by an optional `vei`.
+    fn adjunct(&mut self, pos: usize) -> Option<(Tag, PredicateTree, usize)> {
+        let tag = self.token(pos, |w| match w {
+            Word::Particle(ParticleFamily::Tag(tag)) => Some(tag.clone()),
+            _ => None,
+        })?;
+        let (p, pos) = self.chained(pos + 1, false)?;
+        let pos = self.particle(pos, &ParticleFamily::Vei).unwrap_or(pos);
+        Some((tag, p, pos))
+    }
+
+    /// The leaf or group a vocative addresses, and whether it is a group closed by `pei`.
+    fn addressed(&mut self, pos: usize) -> Option<(PredicateTree, usize, bool)> {
+        if let Some((leaf, pos)) = self.leaf(pos) {
+            return Some((leaf, pos, false));
+        }
+        let pos = self.particle(pos, &ParticleFamily::Pe)?;
+        let (p, pos) = self.connected(pos)?;
+        match self.particle(pos, &ParticleFamily::Pei) {
+            Some(pos) => Some((p, pos, true)),
+            None => Some((p, pos, false)),
+        }
+    }
+
+    /// A predicate tree, whose chains can start with `fi` if `leading`. The trees chained by a
+    /// clause cannot, so that a `fi` after them continues the clause.
+    fn predicate_tree(&mut self, pos: usize, leading: bool) -> Option<(PredicateTree, usize)> {
+        if !self.enter(pos) {
+            return None;
+        }
+        let tree = self.nested_tree(pos, leading);
+        self.depth -= 1;
+        tree
+    }
+
+    /// Enters one more predicate tree at `pos`, unless the deepest allowed are already being
+    /// read. Only a tree that could start at `pos` counts as nested too deep.
+    fn enter(&mut self, pos: usize) -> bool {
+        if self.depth >= DEFAULT_MAX_DEPTH {
+            if self.words.get(pos).is_some_and(starts_tree) {
+                self.exceeded.get_or_insert(pos);
+            }
+            return false;
+        }
+        self.depth += 1;
+        true
+    }
+
+    /// A [`predicate_tree`](Self::predicate_tree) one level deeper.
+    fn nested_tree(&mut self, pos: usize, leading: bool) -> Option<(PredicateTree, usize)> {
+        let depth = self.depth;
+        let (bi, pos) = self.count(pos, &ParticleFamily::Bi);
+        let (l, mut pos) = self.element(pos)?;
+        let mut clauses = Vec::new();
+        loop {
+            if let Some((group, next)) = self.sharer_group(pos, leading) {
+                clauses.push(Clause::Sharers(group));
+                pos = next;
+            } else if let Some((tag, tree, next)) = self.adjunct(pos) {
+                clauses.push(Clause::Adjunct(tag, tree));
+                pos = next;
+            } else {
+                break;
+            }
+        }
+        let (r, pos) = match self.chained(pos, leading) {
+            Some((r, pos)) => (Some(r), pos),
+            None => (None, pos),
+        };
+        self.depth = depth;
+        Some((bind(bi, l, clauses, r), pos))
+    }
+}
+
+/// Parses a whole sentence, keeping free modifiers on the leaf, number or name they follow as
+/// [`parser`](crate::parser::parser) does.
+/// On failure, reports the first predicate tree nested more than
+/// [`DEFAULT_MAX_DEPTH`] deep, or else the furthest token that could not be accepted.
+pub fn parse(words: &[Word]) -> Result<PredicateTree, Vec<Cheap<Word>>> {
+    parse_sentence(words).map(|sentence| sentence.tree)
+}
+
+/// Like [`parse`], keeping the addressees of the sentence.
+pub fn parse_sentence(words: &[Word]) -> Result<Sentence, Vec<Cheap<Word>>> {
+    let Stripped {
+        indices,
+        kept,
+        owned,
+        addressees,
+        mood,
+        vocatives,
+    } = Stripped::strip(words);
+    let mut parser = Parser::new(&kept, owned);
+    let parsed = parser.connected(0);
+    match (parsed, parser.exceeded) {
+        (Some((tree, pos)), None) if pos == kept.len() => Ok(Sentence {
+            addressees,
+            tree,
+            mood,
+        }),
+        (parsed, exceeded) => {
+            let at = exceeded.unwrap_or_else(|| {
+                parsed.map_or(parser.furthest, |(_, pos)| parser.furthest.max(pos))
+            });
+            let at = indices.get(at).copied().unwrap_or(words.len());
+            let at = rejected(&vocatives, at);
+            Err(vec![Cheap::expected_input_found(
+                at..at + 1,
+                None,
+                words.get(at).cloned(),
+            )])
+        }
+    }
+}
+
+/// Where the parser rejects the word at `at`: where what a vocative there could address stops,
+/// if it addresses nothing, and otherwise the furthest word rejected in reading what a vocative
+/// before it addresses, if that is further.
+fn rejected(vocatives: &[(usize, usize)], at: usize) -> usize {
+    vocatives
+        .iter()
+        .filter(|(vocative, _)| *vocative <= at)
+        .fold(at, |at, (_, furthest)| at.max(*furthest))
+}
+
+/// A sentence without its free modifiers: interjections, illocutions and vocatives with the
+/// leaf or group they address.
+struct Stripped {
+    /// The indices in the sentence of the words left.
+    indices: Vec<usize>,
+    kept: Vec<Word>,
+    /// The free modifiers kept by the leaf, number or name at each position of `kept`.
+    owned: Vec<Vec<Modifier>>,
+    /// The leaves and groups addressed, in order.
+    addressees: Vec<PredicateTree>,
+    /// The mood set by the first illocution.
+    mood: Option<Mood>,
+    /// The index of each vocative, with that of the furthest word rejected in reading what it
+    /// addresses.
+    vocatives: Vec<(usize, usize)>,
+}
+
+impl Stripped {
+    /// Takes the free modifiers out of `words`. The leaf or group a vocative addresses can hold
+    /// free modifiers of its own, so the words are read from the end, each vocative taking what
+    /// it addresses from the words after it once their own modifiers are out. A vocative
+    /// addressing nothing is left for the parser to reject.
+    fn strip(words: &[Word]) -> Self {
+        // The words left, with their index and the free modifiers after them.
+        let mut kept: VecDeque<(usize, Word, Vec<Modifier>)> = VecDeque::new();
+        // The free modifiers after the last word read, last first.
+        let mut pending = Vec::new();
+        let mut addressees = Vec::new();
+        let mut mood = None;
+        let mut vocatives = Vec::new();
+        for (i, word) in words.iter().enumerate().rev() {
+            match word {
+                Word::Particle(ParticleFamily::Interjection(interjection)) => {
+                    pending.push(Modifier::Interjection(interjection.clone()));
+                    continue;
+                }
+                Word::Particle(ParticleFamily::Illocution(m)) => {
+                    mood = Some(*m);
+                    pending.push(Modifier::Illocution(*m));
+                    continue;
+                }
+                Word::Particle(ParticleFamily::Vocative(vocative)) => {
+                    let after: Vec<Word> = kept.iter().map(|(_, w, _)| w.clone()).collect();
+                    let mut parser = Parser::new(&after, Vec::new());
+                    let addressed = parser.addressed(0);
+                    let furthest = kept.get(parser.furthest).map_or(words.len(), |(i, ..)| *i);
+                    vocatives.push((i, rejected(&vocatives, furthest)));
+                    if let Some((_, len, closed)) = addressed {
+                        let mut runs: Vec<_> = kept.drain(..len).map(|(.., run)| run).collect();
+                        // Those after the `pei` of a group addressed are not the group's.
+                        let after_pei = if closed { runs.pop() } else { None };
+                        let owned = own(&after[..len], runs, Vec::new());
+                        let addressee = Parser::new(&after[..len], owned).addressed(0);
+                        let Some((addressee, ..)) = addressee else {
+                            continue;
+                        };
+                        addressees.push(addressee.clone());
+                        let vocative = Modifier::Vocative {
+                            word: vocative.clone(),
+                            free: pending.drain(..).rev().collect(),
+                            addressee,
+                        };
+                        pending.extend(after_pei.into_iter().flatten().rev());
+                        pending.push(vocative);
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            kept.push_front((i, word.clone(), pending.drain(..).rev().collect()));
+        }
+        addressees.reverse();
+        let mut indices = Vec::new();
+        let mut runs = Vec::new();
+        let kept: Vec<Word> = kept
+            .into_iter()
+            .map(|(i, word, run)| {
+                indices.push(i);
+                runs.push(run);
+                word
+            })
+            .collect();
+        let owned = own(&kept, runs, pending.into_iter().rev().collect());
+        Stripped {
+            indices,
+            kept,
+            owned,
+            addressees,
+            mood,
+            vocatives,
+        }
+    }
+}
+
+/// The free modifiers kept by each leaf, number or name of `words`, `runs` being those after
+/// each word and `leading` those before the first. Each run is kept by the last leaf, number or
+/// name at or before it, and those before the first by the first.
+fn own(words: &[Word], runs: Vec<Vec<Modifier>>, leading: Vec<Modifier>) -> Vec<Vec<Modifier>> {
+    let atoms = atoms(words);
+    let mut owned = vec![Vec::new(); words.len()];
+    let mut owner = atoms.iter().position(|atom| *atom);
+    if let Some(first) = owner {
+        owned[first] = leading;
+    }
+    for (k, run) in runs.into_iter().enumerate() {
+        if atoms[k] {
+            owner = Some(k);
+        }
+        if let Some(owner) = owner {
+            owned[owner].extend(run);
+        }
+    }
+    owned
+}
+
+/// Whether each word is read as a leaf, number or name, rather than as an argument of a `vi` or
+/// `fi` or as the number of a quantifier.
+fn atoms(words: &[Word]) -> Vec<bool> {
+    let mut atoms: Vec<bool> = words.iter().map(|word| leaf(word).is_some()).collect();
+    for (k, word) in words.iter().enumerate() {
+        match word {
+            Word::Particle(ParticleFamily::Vi { .. } | ParticleFamily::Fi { .. }) => {
+                let args = words[k + 1..]
+                    .iter()
+                    .take_while(|word| argument(word).is_some())
+                    .count();
+                if words.get(k + 1 + args) == Some(&Word::Particle(ParticleFamily::Be)) {
+                    atoms[k + 1..k + 1 + args].fill(false);
+                }
+            }
+            Word::Particle(ParticleFamily::Quantifier(QuantifierKind::Exactly)) => {
+                if let Some(atom) = atoms.get_mut(k + 1) {
+                    *atom = false;
+                }
+            }
+            _ => {}
+        }
+    }
+    atoms
+}
+
+/// Parses a text of sentences separated by sentence-separator particles, as
+/// [`text_parser`](crate::parser::text_parser) does, with the span of every sentence in token
+/// indices.
+pub fn parse_text(words: &[Word]) -> Result<SpannedTrees, Vec<Cheap<Word>>> {
+    let mut trees = Vec::new();
+    let mut start = 0;
+    for sentence in words.split(|w| matches!(w, Word::Particle(ParticleFamily::Separator(_)))) {
+        if !sentence.is_empty() {
+            let tree = parse(sentence).map_err(|errors| {
+                errors
+                    .into_iter()
+                    .map(|e| {
+                        let at = e.span().start + start;
+                        Cheap::expected_input_found(at..at + 1, None, words.get(at).cloned())
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            trees.push((tree, start..start + sentence.len()));
+        }
+        start += sentence.len() + 1;
+    }
+    Ok(trees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate::sentences, lexer::lex, parser::ParserBackend};
+    use proptest::{prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn parses_as_the_combinators(s in "(mi|dona|mian|alis|vi|va|fi|fe|vei|be|pe|pei|zi|bi|ba|bo|da|de|du|ta|su|pa|kie|gie|sia|si|ki|gi|a|'o|'na| )*") {
+            if let Ok(words) = lex(&s) {
+                let combinator = ParserBackend::Combinator.parse_sentence(words.clone()).ok();
+                prop_assert_eq!(parse_sentence(&words).ok(), combinator, "{:?}", s);
+            }
+        }
+
+        #[test]
+        fn parses_tags_as_the_combinators(s in sentences()) {
+            if let Ok(words) = lex(&s) {
+                let combinator = ParserBackend::Combinator.parse_sentence(words.clone()).ok();
+                prop_assert_eq!(parse_sentence(&words).ok(), combinator, "{:?}", s);
+            }
+        }
+    }
+}
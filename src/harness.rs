@@ -0,0 +1,141 @@
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{compat::to_compat, lexer::lex_words, parser::parser};
+
+/// A reference implementation run as a subprocess: the sentence is written to its standard input
+/// and its standard output is read as the serialized parse. A non-zero exit status means the
+/// reference rejected the sentence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceParser {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl ReferenceParser {
+    pub fn new(program: impl Into<String>) -> Self {
+        ReferenceParser {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn run(&self, sentence: &str) -> io::Result<Option<String>> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(sentence.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        Ok(output
+            .status
+            .success()
+            .then(|| normalize(&String::from_utf8_lossy(&output.stdout))))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub sentence: String,
+    pub minimized: String,
+    pub ours: Option<String>,
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HarnessReport {
+    pub checked: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+/// Removes insignificant whitespace (outside of string literals) so that pretty-printed and
+/// compact serializations compare equal.
+pub fn normalize(output: &str) -> String {
+    let mut normalized = String::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in output.chars() {
+        if in_string {
+            normalized.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+            normalized.push(c);
+        } else if !c.is_whitespace() {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+/// The compatibility serialization of `sentence` by this crate, or `None` if it is rejected.
+pub fn ours(sentence: &str) -> Option<String> {
+    let words = lex_words(sentence).ok()?;
+    let tree = parser::<Cheap<_>>().parse(words).ok()?;
+    Some(normalize(&to_compat(&tree).to_string()))
+}
+
+fn diverges(reference: &ReferenceParser, sentence: &str) -> io::Result<bool> {
+    Ok(ours(sentence) != reference.run(sentence)?)
+}
+
+/// Shrinks a diverging sentence by repeatedly dropping single words for as long as the two
+/// implementations still disagree.
+pub fn minimize(reference: &ReferenceParser, sentence: &str) -> io::Result<String> {
+    let mut words: Vec<&str> = sentence.split_whitespace().collect();
+    let mut i = 0;
+    while i < words.len() && words.len() > 1 {
+        let mut candidate = words.clone();
+        candidate.remove(i);
+        if diverges(reference, &candidate.join(" "))? {
+            words = candidate;
+        } else {
+            i += 1;
+        }
+    }
+    Ok(words.join(" "))
+}
+
+pub fn run_corpus<'a>(
+    reference: &ReferenceParser,
+    corpus: impl IntoIterator<Item = &'a str>,
+) -> io::Result<HarnessReport> {
+    let mut report = HarnessReport::default();
+    for sentence in corpus {
+        let sentence = sentence.trim();
+        if sentence.is_empty() || sentence.starts_with('#') {
+            continue;
+        }
+        report.checked += 1;
+        let ours = ours(sentence);
+        let theirs = reference.run(sentence)?;
+        if ours != theirs {
+            report.divergences.push(Divergence {
+                sentence: sentence.to_string(),
+                minimized: minimize(reference, sentence)?,
+                ours,
+                reference: theirs,
+            });
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,139 @@
+//! Classes for highlighting the words of a text, for editors and renderers: each word is given
+//! a class from what it does in the parse of its sentence, or from its family alone in sentences
+//! that do not parse.
+
+use std::ops::Range;
+
+use crate::{
+    cst::{self, Child, Node, NodeKind},
+    grammar::{role, Role},
+    lexer::{ParticleFamily, PredicateFamily, Word, ZiParticle},
+};
+
+/// How a word is highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HighlightClass {
+    Root,
+    Borrowing,
+    /// A freeform word naming an individual as a predicate.
+    Freeform,
+    /// A particle with no class of its own, by the name of its family.
+    Particle(&'static str),
+    /// `pei`, `vei` or `be`.
+    Terminator,
+    /// `bi`, or `zi` negating an element.
+    Negation,
+    /// A word bound to a place in an argument list, or a `ki` particle outside a parse.
+    Argument,
+}
+
+impl HighlightClass {
+    /// The name of the class, fit for a CSS class or an editor's token type.
+    pub fn name(self) -> &'static str {
+        match self {
+            HighlightClass::Root => "root",
+            HighlightClass::Borrowing => "borrowing",
+            HighlightClass::Freeform => "freeform",
+            HighlightClass::Particle(_) => "particle",
+            HighlightClass::Terminator => "terminator",
+            HighlightClass::Negation => "negation",
+            HighlightClass::Argument => "argument",
+        }
+    }
+}
+
+/// A word of the text and its class.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Highlight {
+    pub span: Range<usize>,
+    pub class: HighlightClass,
+}
+
+/// The class of `word` from its family alone.
+pub fn classify(word: &Word) -> HighlightClass {
+    match word {
+        Word::Predicate(_, PredicateFamily::Root) => HighlightClass::Root,
+        Word::Predicate(_, PredicateFamily::Borrowing) => HighlightClass::Borrowing,
+        Word::Predicate(_, PredicateFamily::Freeform) => HighlightClass::Freeform,
+        Word::Particle(family) => match role(family) {
+            Role::Terminator => HighlightClass::Terminator,
+            Role::Argument => HighlightClass::Argument,
+            Role::Negation => match family {
+                ParticleFamily::Zi(ZiParticle::Scope(_)) => HighlightClass::Particle(family.name()),
+                _ => HighlightClass::Negation,
+            },
+            _ => HighlightClass::Particle(family.name()),
+        },
+    }
+}
+
+/// The class of every word of a text lexed by [`lex_spanned`](crate::lexer::lex_spanned), in
+/// order. Each sentence is parsed as [`cst::parse_spanned`] does, so that a `ki` or `gi`
+/// particle standing for a predicate is told apart from one bound to a place; the words of a
+/// sentence that does not parse are classed by [`classify`].
+pub fn highlight(words: &[(Word, Range<usize>)]) -> Vec<Highlight> {
+    let mut highlights = Vec::with_capacity(words.len());
+    let mut start = 0;
+    for (i, (word, span)) in words.iter().enumerate() {
+        if let Word::Particle(ParticleFamily::Separator(_)) = word {
+            sentence(&words[start..i], &mut highlights);
+            highlights.push(Highlight {
+                span: span.clone(),
+                class: classify(word),
+            });
+            start = i + 1;
+        }
+    }
+    sentence(&words[start..], &mut highlights);
+    highlights
+}
+
+/// Pushes the classes of the words of a sentence.
+fn sentence(words: &[(Word, Range<usize>)], highlights: &mut Vec<Highlight>) {
+    let len = words.last().map_or(0, |(_, span)| span.end);
+    match cst::parse_spanned(words, len) {
+        Ok(tree) => tokens(tree.root(), highlights),
+        Err(_) => highlights.extend(words.iter().map(|(word, span)| Highlight {
+            span: span.clone(),
+            class: classify(word),
+        })),
+    }
+}
+
+/// Pushes the classes of the words under `node`. The words of an argument list other than its
+/// terminator and free modifiers are arguments, and `ki` particles elsewhere stand for
+/// predicates.
+fn tokens(node: &Node, highlights: &mut Vec<Highlight>) {
+    // The children still to visit, in reverse order, with whether they are in an argument list.
+    let mut stack: Vec<(&Child, bool)> = node.children().iter().rev().map(|c| (c, false)).collect();
+    while let Some((child, arguments)) = stack.pop() {
+        match child {
+            Child::Node(node) => {
+                let arguments = match node.kind() {
+                    NodeKind::Arguments => true,
+                    NodeKind::Addressee => false,
+                    _ => arguments,
+                };
+                stack.extend(node.children().iter().rev().map(|c| (c, arguments)));
+            }
+            Child::Token(token) => {
+                let class = match &token.word {
+                    Word::Particle(family) if arguments && role(family).is_free() => {
+                        classify(&token.word)
+                    }
+                    Word::Particle(ParticleFamily::Be) => HighlightClass::Terminator,
+                    _ if arguments => HighlightClass::Argument,
+                    Word::Particle(family @ ParticleFamily::Ki(_)) => {
+                        HighlightClass::Particle(family.name())
+                    }
+                    word => classify(word),
+                };
+                highlights.push(Highlight {
+                    span: token.span.clone(),
+                    class,
+                });
+            }
+            Child::Elided(_) | Child::Trivia(_) => {}
+        }
+    }
+}
@@ -0,0 +1,489 @@
+use std::{collections::BTreeMap, ops::Range, sync::Arc};
+
+use chumsky::{error::Cheap, BoxedParser, Parser, Stream};
+
+use crate::{
+    chunked::Chunks,
+    expr::{to_expr, Predicate, Var},
+    lexer::{
+        assemble_spanned_numbers, lex_spanned, lexer, LexError, ParticleFamily, PreProcessed,
+        SpannedWords, Word,
+    },
+    parser::{parser, sentence_parser, PredicateTree, Sentence},
+};
+
+pub type Revision = u64;
+
+pub type PreProcessedText = Vec<(PreProcessed, Range<usize>)>;
+pub type LexResult = Result<Vec<Word>, Vec<Cheap<PreProcessed>>>;
+pub type ParseResult = Option<Result<PredicateTree, Vec<Cheap<Word>>>>;
+pub type ExprResult = Option<(Predicate, Vec<Var>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DocumentId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Query {
+    PreProcess,
+    Lex,
+    Parse,
+    ToExpr,
+}
+
+#[derive(Debug, Clone)]
+struct Memo<T> {
+    value: Arc<T>,
+    verified_at: Revision,
+    changed_at: Revision,
+}
+
+#[derive(Debug, Clone)]
+struct Document {
+    text: Arc<str>,
+    text_changed_at: Revision,
+    preprocessed: Option<Memo<PreProcessedText>>,
+    tokens: Option<Memo<LexResult>>,
+    tree: Option<Memo<ParseResult>>,
+    expr: Option<Memo<ExprResult>>,
+}
+
+/// A memoized query database: the source text of each document is an input, and preprocessing,
+/// lexing, parsing and conversion to an expression are derived queries memoized per document. Setting an input bumps the revision; derived queries are only re-executed when
+/// one of their dependencies changed since they were last verified, and a re-executed query whose
+/// result is unchanged does not invalidate the queries depending on it.
+pub struct Database {
+    revision: Revision,
+    documents: BTreeMap<DocumentId, Document>,
+    executed: Vec<(DocumentId, Query)>,
+    lexer: BoxedParser<'static, PreProcessed, Vec<Word>, Cheap<PreProcessed>>,
+    parser: BoxedParser<'static, Word, PredicateTree, Cheap<Word>>,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Database {
+            revision: 0,
+            documents: BTreeMap::new(),
+            executed: Vec::new(),
+            lexer: lexer().boxed(),
+            parser: parser().boxed(),
+        }
+    }
+
+    pub fn revision(&self) -> Revision {
+        self.revision
+    }
+
+    pub fn documents(&self) -> impl Iterator<Item = DocumentId> + '_ {
+        self.documents.keys().copied()
+    }
+
+    pub fn set_source_text(&mut self, id: DocumentId, text: &str) {
+        if let Some(doc) = self.documents.get(&id) {
+            if *doc.text == *text {
+                return;
+            }
+        }
+        self.revision += 1;
+        let revision = self.revision;
+        let doc = self.documents.entry(id).or_insert_with(|| Document {
+            text: Arc::from(""),
+            text_changed_at: revision,
+            preprocessed: None,
+            tokens: None,
+            tree: None,
+            expr: None,
+        });
+        doc.text = Arc::from(text);
+        doc.text_changed_at = revision;
+    }
+
+    pub fn remove_document(&mut self, id: DocumentId) {
+        if self.documents.remove(&id).is_some() {
+            self.revision += 1;
+        }
+    }
+
+    pub fn source_text(&self, id: DocumentId) -> Option<Arc<str>> {
+        self.documents.get(&id).map(|doc| doc.text.clone())
+    }
+
+    /// The queries executed (rather than answered from the memo tables) since the last call.
+    pub fn take_executed(&mut self) -> Vec<(DocumentId, Query)> {
+        std::mem::take(&mut self.executed)
+    }
+
+    pub fn preprocessed(&mut self, id: DocumentId) -> Option<Arc<PreProcessedText>> {
+        self.preprocessed_memo(id).map(|(value, _)| value)
+    }
+
+    pub fn tokens(&mut self, id: DocumentId) -> Option<Arc<LexResult>> {
+        self.tokens_memo(id).map(|(value, _)| value)
+    }
+
+    pub fn tree(&mut self, id: DocumentId) -> Option<Arc<ParseResult>> {
+        self.tree_memo(id).map(|(value, _)| value)
+    }
+
+    pub fn expr(&mut self, id: DocumentId) -> Option<Arc<ExprResult>> {
+        self.expr_memo(id).map(|(value, _)| value)
+    }
+
+    fn preprocessed_memo(&mut self, id: DocumentId) -> Option<(Arc<PreProcessedText>, Revision)> {
+        let revision = self.revision;
+        let doc = self.documents.get_mut(&id)?;
+        let dep_changed_at = doc.text_changed_at;
+        let text = doc.text.clone();
+        let executed = &mut self.executed;
+        Some(verify(
+            &mut doc.preprocessed,
+            revision,
+            dep_changed_at,
+            || {
+                executed.push((id, Query::PreProcess));
+                crate::lexer::preprocess(&text).fetch_tokens().collect()
+            },
+        ))
+    }
+
+    fn tokens_memo(&mut self, id: DocumentId) -> Option<(Arc<LexResult>, Revision)> {
+        let (preprocessed, dep_changed_at) = self.preprocessed_memo(id)?;
+        let revision = self.revision;
+        let doc = self.documents.get_mut(&id)?;
+        let len = doc.text.chars().count();
+        let lexer = &self.lexer;
+        let executed = &mut self.executed;
+        Some(verify(&mut doc.tokens, revision, dep_changed_at, || {
+            executed.push((id, Query::Lex));
+            lexer.parse(Stream::from_iter(len..len, preprocessed.iter().cloned()))
+        }))
+    }
+
+    fn tree_memo(&mut self, id: DocumentId) -> Option<(Arc<ParseResult>, Revision)> {
+        let (tokens, dep_changed_at) = self.tokens_memo(id)?;
+        let revision = self.revision;
+        let doc = self.documents.get_mut(&id)?;
+        let parser = &self.parser;
+        let executed = &mut self.executed;
+        Some(verify(&mut doc.tree, revision, dep_changed_at, || {
+            executed.push((id, Query::Parse));
+            tokens
+                .as_ref()
+                .as_ref()
+                .ok()
+                .map(|tokens| parser.parse(tokens.clone()))
+        }))
+    }
+
+    fn expr_memo(&mut self, id: DocumentId) -> Option<(Arc<ExprResult>, Revision)> {
+        let (tree, dep_changed_at) = self.tree_memo(id)?;
+        let revision = self.revision;
+        let doc = self.documents.get_mut(&id)?;
+        let executed = &mut self.executed;
+        Some(verify(&mut doc.expr, revision, dep_changed_at, || {
+            executed.push((id, Query::ToExpr));
+            match tree.as_ref() {
+                Some(Ok(tree)) => Some(to_expr(tree.clone())),
+                _ => None,
+            }
+        }))
+    }
+}
+
+/// Brings a memo up to date with the current revision, re-executing `compute` only if the
+/// dependency changed after the memo was last verified. Returns the value and the revision at
+/// which it last changed.
+fn verify<T: PartialEq>(
+    memo: &mut Option<Memo<T>>,
+    revision: Revision,
+    dep_changed_at: Revision,
+    compute: impl FnOnce() -> T,
+) -> (Arc<T>, Revision) {
+    if let Some(m) = memo {
+        if m.verified_at == revision || dep_changed_at <= m.verified_at {
+            m.verified_at = revision;
+            return (m.value.clone(), m.changed_at);
+        }
+    }
+    let value = compute();
+    let m = match memo.take() {
+        Some(m) if *m.value == value => Memo {
+            verified_at: revision,
+            ..m
+        },
+        _ => Memo {
+            value: Arc::new(value),
+            verified_at: revision,
+            changed_at: revision,
+        },
+    };
+    let result = (m.value.clone(), m.changed_at);
+    *memo = Some(m);
+    result
+}
+
+/// A change to a text: the characters in `range` are replaced by `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// `text` with the edit applied. A range past the end of the text is clamped to it.
+    pub fn apply(&self, text: &str) -> String {
+        let (start, end) = self.clamped(text.chars().count());
+        let mut chars = text.chars();
+        let mut edited: String = chars.by_ref().take(start).collect();
+        edited.push_str(&self.replacement);
+        edited.extend(chars.skip(end - start));
+        edited
+    }
+
+    fn clamped(&self, len: usize) -> (usize, usize) {
+        let end = self.range.end.min(len);
+        (self.range.start.min(end), end)
+    }
+}
+
+/// The offsets in characters at which `text` may be split into independently lexed parts, as
+/// by [`Chunks`], in increasing order and ending with the length of the text.
+fn boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    Chunks::new(text, 1)
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.chars().count()))
+}
+
+/// Whether an old word spans across `at`, so that the text cannot be split there without
+/// changing it.
+fn straddled(words: &[(Word, Range<usize>)], at: usize) -> bool {
+    let i = words.partition_point(|(_, span)| span.start < at);
+    i > 0 && words.get(i - 1).is_some_and(|(_, span)| at < span.end)
+}
+
+/// The words of `old_text` with `edit` applied, given the words `old_words` of `old_text` as
+/// returned by [`lex_spanned`]. Only the text between the points around the edit where the text
+/// may be split is lexed again; the words before and after it are kept, those after it moved
+/// by the change in length. The result is the same as lexing the edited text as a whole, with
+/// the spans of errors in its characters.
+pub fn relex(
+    old_text: &str,
+    old_words: &[(Word, Range<usize>)],
+    edit: &TextEdit,
+) -> Result<SpannedWords, Vec<LexError>> {
+    let new_text = edit.apply(old_text);
+    let (start, end) = edit.clamped(old_text.chars().count());
+    let new_end = start + edit.replacement.chars().count();
+
+    // The texts are the same before the edit, and so are the points where they may be split.
+    let before = boundaries(&new_text)
+        .take_while(|b| *b < start)
+        .filter(|b| !straddled(old_words, *b))
+        .last()
+        .unwrap_or(0);
+
+    // The first point after the edit where both texts may be split, in the old text.
+    let mut old_boundaries = boundaries(old_text).skip_while(|b| *b < end).peekable();
+    let after = boundaries(&new_text)
+        .skip_while(|b| *b < new_end)
+        .map(|b| b - new_end + end)
+        .find(|b| {
+            while old_boundaries.next_if(|o| o < b).is_some() {}
+            old_boundaries.peek() == Some(b) && !straddled(old_words, *b)
+        })
+        .unwrap_or(old_text.chars().count());
+    let new_after = after - end + new_end;
+
+    let region: String = new_text
+        .chars()
+        .skip(before)
+        .take(new_after - before)
+        .collect();
+    let lexed = lex_spanned(&region).map_err(|errors| {
+        errors
+            .iter()
+            .map(|e| {
+                let error = LexError::classify(&region, e.span());
+                LexError {
+                    span: error.span.start + before..error.span.end + before,
+                    ..error
+                }
+            })
+            .collect::<Vec<_>>()
+    })?;
+
+    let words = old_words
+        .iter()
+        .filter(|(_, span)| span.end <= before)
+        .cloned()
+        .chain(
+            lexed
+                .into_iter()
+                .map(|(word, span)| (word, span.start + before..span.end + before)),
+        )
+        .chain(
+            old_words
+                .iter()
+                .filter(|(_, span)| span.start >= after)
+                .map(|(word, span)| {
+                    (
+                        word.clone(),
+                        span.start - end + new_end..span.end - end + new_end,
+                    )
+                }),
+        )
+        .collect();
+    // A number may continue across either end of the region.
+    Ok(assemble_spanned_numbers(words))
+}
+
+/// A sentence of a [`ParsedText`] and its parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedSentence {
+    /// The words of the sentence, with their spans in characters of the text.
+    pub words: SpannedWords,
+    pub sentence: Arc<Result<Sentence, Vec<Cheap<Word>>>>,
+}
+
+impl ParsedSentence {
+    /// The characters of the text the sentence covers.
+    pub fn span(&self) -> Range<usize> {
+        match (self.words.first(), self.words.last()) {
+            (Some((_, first)), Some((_, last))) => first.start..last.end,
+            _ => 0..0,
+        }
+    }
+
+    fn same_words(&self, words: &[(Word, Range<usize>)]) -> bool {
+        self.words.len() == words.len()
+            && self.words.iter().zip(words).all(|((a, _), (b, _))| a == b)
+    }
+}
+
+/// The words of a text and the parse of each of its sentences, separated by `pu`, `pa` and `po`,
+/// kept so that after an edit only the sentences whose words changed are parsed again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedText {
+    words: SpannedWords,
+    sentences: Vec<ParsedSentence>,
+}
+
+impl ParsedText {
+    pub fn parse(text: &str) -> Result<Self, Vec<LexError>> {
+        let words = lex_spanned(text).map_err(|errors| {
+            errors
+                .iter()
+                .map(|e| LexError::classify(text, e.span()))
+                .collect::<Vec<_>>()
+        })?;
+        Ok(Self::from_words(words, &[]).0)
+    }
+
+    pub fn words(&self) -> &[(Word, Range<usize>)] {
+        &self.words
+    }
+
+    pub fn sentences(&self) -> &[ParsedSentence] {
+        &self.sentences
+    }
+
+    /// The text parsed from `text` with `edit` applied, `text` being the text `self` was parsed
+    /// from, and the indices of the sentences that were parsed again. The words are lexed again
+    /// as by [`relex`], and the sentences whose words are unchanged at the start and the end of
+    /// the text keep their parse. The result is the same as parsing the edited text as a whole.
+    pub fn edit(&self, text: &str, edit: &TextEdit) -> Result<(Self, Vec<usize>), Vec<LexError>> {
+        let words = relex(text, &self.words, edit)?;
+        Ok(Self::from_words(words, &self.sentences))
+    }
+
+    /// Splits `words` into sentences, reusing the parse of those of `old` in the same order at
+    /// the start and the end.
+    fn from_words(words: SpannedWords, old: &[ParsedSentence]) -> (Self, Vec<usize>) {
+        let split: Vec<&[(Word, Range<usize>)]> = words
+            .split(|(word, _)| matches!(word, Word::Particle(ParticleFamily::Separator(_))))
+            .filter(|sentence| !sentence.is_empty())
+            .collect();
+        let prefix = split
+            .iter()
+            .zip(old)
+            .take_while(|(new, old)| old.same_words(new))
+            .count();
+        let suffix = split[prefix..]
+            .iter()
+            .rev()
+            .zip(old[prefix..].iter().rev())
+            .take_while(|(new, old)| old.same_words(new))
+            .count();
+        let parser = sentence_parser::<Cheap<Word>>();
+        let mut reparsed = Vec::new();
+        let sentences = split
+            .iter()
+            .enumerate()
+            .map(|(i, words)| {
+                let reused = if i < prefix {
+                    old.get(i)
+                } else if i >= split.len() - suffix {
+                    old.get(old.len() + i - split.len())
+                } else {
+                    None
+                };
+                let sentence = match reused {
+                    Some(old) => old.sentence.clone(),
+                    None => {
+                        reparsed.push(i);
+                        let words: Vec<Word> = words.iter().map(|(word, _)| word.clone()).collect();
+                        Arc::new(parser.parse(words))
+                    }
+                };
+                ParsedSentence {
+                    words: words.to_vec(),
+                    sentence,
+                }
+            })
+            .collect();
+        (Self { words, sentences }, reparsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::texts;
+    use proptest::{prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn relexes_as_lex(
+            text in "( (ce )?(vi|fe|mo|dona|mian|pe|pei|be|zi|pu|pa|re|'a|'e))*",
+            replacement in "(ce|cu|dona|mian|pu|re|[a-z0-9]| |')*",
+            start in 0..40usize,
+            len in 0..8usize,
+        ) {
+            if let Ok(words) = lex_spanned(&text) {
+                let edit = TextEdit { range: start..start + len, replacement };
+                let relexed = relex(&text, &words, &edit).ok();
+                prop_assert_eq!(relexed, lex_spanned(&edit.apply(&text)).ok(), "{:?} {:?}", text, edit);
+            }
+        }
+
+        #[test]
+        fn reparses_as_parse(
+            text in texts(),
+            replacement in "( (pu|pa|pe|pei|vi|dona|mian|'na))*",
+            start in 0..60usize,
+            len in 0..8usize,
+        ) {
+            if let Ok(parsed) = ParsedText::parse(&text) {
+                let edit = TextEdit { range: start..start + len, replacement };
+                let reparsed = parsed.edit(&text, &edit).ok().map(|(parsed, _)| parsed);
+                prop_assert_eq!(reparsed, ParsedText::parse(&edit.apply(&text)).ok(), "{:?} {:?}", text, edit);
+            }
+        }
+    }
+}
@@ -1,130 +1,239 @@
-use std::{iter, ops::Range};
+use std::{fmt, iter, mem, ops::Range, str::Chars};
 
-use crate::{ChainingBehavior, Exposure, GrammarVar, PredicateChaining};
+use crate::{
+    cancel::{checkpoint, CancellationToken},
+    ChainingBehavior, Exposure, GrammarVar, PredicateChaining,
+};
 use chumsky::{
-    prelude::{choice, end, filter, just},
+    error::Cheap,
+    prelude::{choice, empty, end, filter, just, take_until},
     Error, Parser, Stream,
 };
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 
 pub const VOWELS: [char; 5] = ['i', 'e', 'a', 'o', 'u'];
 pub const NON_SONORANT: [char; 13] = [
     'm', 'p', 'b', 'f', 'v', 't', 'd', 's', 'z', 'c', 'j', 'k', 'g',
 ];
 pub const SONORANT: [char; 3] = ['n', 'r', 'l'];
-pub const INITIAL_PAIRS: [(char, char); 69] = [
-    ('b', 'z'),
-    ('b', 'j'),
-    ('b', 'r'),
-    ('b', 'l'),
-    ('d', 'z'),
-    ('d', 'j'),
-    ('d', 'r'),
-    ('g', 'z'),
-    ('g', 'j'),
-    ('g', 'n'),
-    ('g', 'r'),
-    ('v', 'l'),
-    ('v', 'z'),
-    ('v', 'j'),
-    ('v', 'n'),
-    ('v', 'r'),
-    ('v', 'l'),
-    ('z', 'b'),
-    ('z', 'd'),
-    ('z', 'g'),
-    ('z', 'v'),
-    ('z', 'm'),
-    ('z', 'n'),
-    ('z', 'r'),
-    ('z', 'l'),
-    ('j', 'b'),
-    ('j', 'd'),
-    ('j', 'g'),
-    ('j', 'v'),
-    ('j', 'm'),
-    ('j', 'n'),
-    ('j', 'r'),
-    ('j', 'l'),
-    ('c', 'f'),
-    ('c', 'k'),
-    ('c', 't'),
-    ('c', 'p'),
-    ('c', 'm'),
-    ('c', 'n'),
-    ('c', 'r'),
-    ('c', 'l'),
-    ('s', 'f'),
-    ('s', 'k'),
-    ('s', 't'),
-    ('s', 'p'),
-    ('s', 'm'),
-    ('s', 'n'),
-    ('s', 'r'),
-    ('s', 'l'),
-    ('f', 'c'),
-    ('f', 's'),
-    ('f', 'n'),
-    ('f', 'r'),
-    ('f', 'l'),
-    ('k', 'c'),
-    ('k', 's'),
-    ('k', 'n'),
-    ('k', 'r'),
-    ('k', 'l'),
-    ('t', 'c'),
-    ('t', 's'),
-    ('t', 'r'),
-    ('p', 'c'),
-    ('p', 's'),
-    ('p', 'r'),
-    ('p', 'l'),
-    ('m', 'n'),
-    ('m', 'r'),
-    ('m', 'l'),
-];
-pub const MEDIAL_PAIRS: [(char, char); 36] = [
-    ('b', 'd'),
-    ('b', 'g'),
-    ('b', 'v'),
-    ('b', 'm'),
-    ('d', 'b'),
-    ('d', 'g'),
-    ('d', 'v'),
-    ('d', 'm'),
-    ('g', 'b'),
-    ('g', 'd'),
-    ('g', 'v'),
-    ('g', 'm'),
-    ('v', 'b'),
-    ('v', 'd'),
-    ('v', 'g'),
-    ('v', 'm'),
-    ('f', 'k'),
-    ('f', 't'),
-    ('f', 'p'),
-    ('f', 'm'),
-    ('k', 'f'),
-    ('k', 't'),
-    ('k', 'p'),
-    ('k', 'm'),
-    ('t', 'f'),
-    ('t', 'k'),
-    ('t', 'p'),
-    ('t', 'm'),
-    ('p', 'f'),
-    ('p', 'k'),
-    ('p', 't'),
-    ('p', 'm'),
-    ('n', 'r'),
-    ('n', 'l'),
-    ('r', 'n'),
-    ('l', 'n'),
-];
+pub const ARG_VOWELS: [char; 4] = ['e', 'a', 'o', 'u'];
+/// The consonant pairs a word may start with, and those it may have between syllables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PhonologyTables {
+    initial: [[bool; 26]; 26],
+    medial: [[bool; 26]; 26],
+}
+
+/// The offset of a lower case ASCII letter from `a`.
+const fn letter_index(c: char) -> Option<usize> {
+    if c.is_ascii_lowercase() {
+        Some(c as usize - 'a' as usize)
+    } else {
+        None
+    }
+}
+
+const fn pair_table(pairs: &[(char, char)]) -> [[bool; 26]; 26] {
+    let mut table = [[false; 26]; 26];
+    let mut i = 0;
+    while i < pairs.len() {
+        if let (Some(a), Some(b)) = (letter_index(pairs[i].0), letter_index(pairs[i].1)) {
+            table[a][b] = true;
+        }
+        i += 1;
+    }
+    table
+}
+
+impl PhonologyTables {
+    /// The tables of the standard phonology.
+    pub const STANDARD: PhonologyTables = PhonologyTables::new(
+        &[
+            ('b', 'z'),
+            ('b', 'j'),
+            ('b', 'r'),
+            ('b', 'l'),
+            ('d', 'z'),
+            ('d', 'j'),
+            ('d', 'r'),
+            ('g', 'z'),
+            ('g', 'j'),
+            ('g', 'n'),
+            ('g', 'r'),
+            ('v', 'l'),
+            ('v', 'z'),
+            ('v', 'j'),
+            ('v', 'n'),
+            ('v', 'r'),
+            ('z', 'b'),
+            ('z', 'd'),
+            ('z', 'g'),
+            ('z', 'v'),
+            ('z', 'm'),
+            ('z', 'n'),
+            ('z', 'r'),
+            ('z', 'l'),
+            ('j', 'b'),
+            ('j', 'd'),
+            ('j', 'g'),
+            ('j', 'v'),
+            ('j', 'm'),
+            ('j', 'n'),
+            ('j', 'r'),
+            ('j', 'l'),
+            ('c', 'f'),
+            ('c', 'k'),
+            ('c', 't'),
+            ('c', 'p'),
+            ('c', 'm'),
+            ('c', 'n'),
+            ('c', 'r'),
+            ('c', 'l'),
+            ('s', 'f'),
+            ('s', 'k'),
+            ('s', 't'),
+            ('s', 'p'),
+            ('s', 'm'),
+            ('s', 'n'),
+            ('s', 'r'),
+            ('s', 'l'),
+            ('f', 'c'),
+            ('f', 's'),
+            ('f', 'n'),
+            ('f', 'r'),
+            ('f', 'l'),
+            ('k', 'c'),
+            ('k', 's'),
+            ('k', 'n'),
+            ('k', 'r'),
+            ('k', 'l'),
+            ('t', 'c'),
+            ('t', 's'),
+            ('t', 'r'),
+            ('p', 'c'),
+            ('p', 's'),
+            ('p', 'r'),
+            ('p', 'l'),
+            ('m', 'n'),
+            ('m', 'r'),
+            ('m', 'l'),
+        ],
+        &[
+            ('b', 'd'),
+            ('b', 'g'),
+            ('b', 'v'),
+            ('b', 'm'),
+            ('d', 'b'),
+            ('d', 'g'),
+            ('d', 'v'),
+            ('d', 'm'),
+            ('g', 'b'),
+            ('g', 'd'),
+            ('g', 'v'),
+            ('g', 'm'),
+            ('v', 'b'),
+            ('v', 'd'),
+            ('v', 'g'),
+            ('v', 'm'),
+            ('f', 'k'),
+            ('f', 't'),
+            ('f', 'p'),
+            ('f', 'm'),
+            ('k', 'f'),
+            ('k', 't'),
+            ('k', 'p'),
+            ('k', 'm'),
+            ('t', 'f'),
+            ('t', 'k'),
+            ('t', 'p'),
+            ('t', 'm'),
+            ('p', 'f'),
+            ('p', 'k'),
+            ('p', 't'),
+            ('p', 'm'),
+            ('n', 'r'),
+            ('n', 'l'),
+            ('r', 'n'),
+            ('l', 'n'),
+        ],
+    );
+
+    /// Tables allowing the given pairs. Pairs of anything but lower case ASCII letters are
+    /// ignored.
+    pub const fn new(initial: &[(char, char)], medial: &[(char, char)]) -> Self {
+        PhonologyTables {
+            initial: pair_table(initial),
+            medial: pair_table(medial),
+        }
+    }
+
+    pub fn is_initial_pair(&self, a: char, b: char) -> bool {
+        Self::get(&self.initial, a, b)
+    }
+
+    pub fn is_medial_pair(&self, a: char, b: char) -> bool {
+        Self::get(&self.medial, a, b)
+    }
+
+    /// Allows or forbids `a` followed by `b` at the start of a word.
+    pub fn set_initial_pair(&mut self, a: char, b: char, allowed: bool) {
+        Self::set(&mut self.initial, a, b, allowed);
+    }
+
+    /// Allows or forbids `a` followed by `b` between syllables.
+    pub fn set_medial_pair(&mut self, a: char, b: char, allowed: bool) {
+        Self::set(&mut self.medial, a, b, allowed);
+    }
+
+    /// The initial pairs, in alphabetical order.
+    pub fn initial_pairs(&self) -> impl Iterator<Item = (char, char)> + '_ {
+        Self::pairs(&self.initial)
+    }
+
+    /// The medial pairs, in alphabetical order.
+    pub fn medial_pairs(&self) -> impl Iterator<Item = (char, char)> + '_ {
+        Self::pairs(&self.medial)
+    }
+
+    fn get(table: &[[bool; 26]; 26], a: char, b: char) -> bool {
+        match (letter_index(a), letter_index(b)) {
+            (Some(a), Some(b)) => table[a][b],
+            _ => false,
+        }
+    }
+
+    fn set(table: &mut [[bool; 26]; 26], a: char, b: char, allowed: bool) {
+        if let (Some(a), Some(b)) = (letter_index(a), letter_index(b)) {
+            table[a][b] = allowed;
+        }
+    }
+
+    fn pairs(table: &[[bool; 26]; 26]) -> impl Iterator<Item = (char, char)> + '_ {
+        let letter = |i: usize| char::from(b'a' + i as u8);
+        table.iter().enumerate().flat_map(move |(a, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, allowed)| **allowed)
+                .map(move |(b, _)| (letter(a), letter(b)))
+        })
+    }
+}
+
+impl Default for PhonologyTables {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PreProcessed(char);
 
+impl PreProcessed {
+    pub fn char(self) -> char {
+        self.0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Word {
     Particle(ParticleFamily),
@@ -144,6 +253,16 @@ pub enum PredicateFamily {
     Freeform,
 }
 
+impl PredicateFamily {
+    pub fn name(self) -> &'static str {
+        match self {
+            PredicateFamily::Root => "ROOT",
+            PredicateFamily::Borrowing => "BORROWING",
+            PredicateFamily::Freeform => "FREEFORM",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ParticleFamily {
     Pe,
@@ -166,10 +285,850 @@ pub enum ParticleFamily {
         chaining: ChainingBehavior,
     },
     Bi,
-    Zi(String),
+    Zi(ZiParticle),
+    Quote(Quote),
+    Number(Number),
+    Interjection(Interjection),
+    /// A particle starting with `o`, addressing the predicate after it.
+    Vocative(String),
+    Illocution(Mood),
+    Connective(ConnectiveKind),
+    Tag(Tag),
+    Subordinator(AbstractionKind),
+    Quantifier(QuantifierKind),
+    Separator(Separator),
+    /// A particle of a series the grammar does not define yet.
     Other(String),
 }
 
+/// The digit particles, by value.
+pub const DIGITS: [&str; 10] = [
+    "ja", "je", "ji", "jo", "ju", "jai", "jei", "joi", "jui", "jau",
+];
+
+/// A numeral: one or more digit particles, most significant first. The lexer reads each digit
+/// particle as a single-digit number, then [`assemble_numbers`] joins consecutive ones.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Number {
+    pub digits: Vec<u8>,
+}
+
+impl Number {
+    /// The integer value, or `None` if it does not fit in a `u64`.
+    pub fn value(&self) -> Option<u64> {
+        self.digits
+            .iter()
+            .try_fold(0u64, |n, d| n.checked_mul(10)?.checked_add(u64::from(*d)))
+    }
+
+    /// The name of the leaf a number stands for in a formula.
+    pub fn leaf_name(&self) -> String {
+        let digits: String = self.digits.iter().map(|d| char::from(b'0' + d)).collect();
+        format!("#«{digits}»")
+    }
+
+    /// The predicate a number stands for, which chains by sharing its first place.
+    pub fn predicate(&self) -> PredicateWord {
+        PredicateWord {
+            word: self.leaf_name(),
+            chaining: ChainingBehavior {
+                var: 0,
+                chain_with: PredicateChaining::Sharing,
+            },
+        }
+    }
+}
+
+/// Joins consecutive numbers into one.
+pub fn assemble_numbers(words: Vec<Word>) -> Vec<Word> {
+    assemble_spanned_numbers(words.into_iter().map(|word| (word, 0..0)).collect())
+        .into_iter()
+        .map(|(word, _)| word)
+        .collect()
+}
+
+/// Like [`assemble_numbers`], the span of each number covering its digits.
+pub fn assemble_spanned_numbers(words: Vec<(Word, Range<usize>)>) -> Vec<(Word, Range<usize>)> {
+    let mut assembled: Vec<(Word, Range<usize>)> = Vec::with_capacity(words.len());
+    for (word, span) in words {
+        if let (
+            Some((Word::Particle(ParticleFamily::Number(number)), number_span)),
+            Word::Particle(ParticleFamily::Number(next)),
+        ) = (assembled.last_mut(), &word)
+        {
+            number.digits.extend(&next.digits);
+            number_span.end = span.end;
+        } else {
+            assembled.push((word, span));
+        }
+    }
+    assembled
+}
+
+/// Quoted material, captured as preprocessed without being split into words.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Quote {
+    /// `ce` followed by a single word, which need not be a valid eberban word.
+    Word(String),
+    /// `cu` followed by a delimiter word, then any text up to the next occurrence of the
+    /// delimiter as a word of its own.
+    Foreign { delimiter: String, content: String },
+}
+
+impl Quote {
+    pub fn content(&self) -> &str {
+        match self {
+            Quote::Word(content) | Quote::Foreign { content, .. } => content,
+        }
+    }
+
+    /// The name of the leaf a quote stands for in a formula.
+    pub fn leaf_name(&self) -> String {
+        format!("«{}»", self.content())
+    }
+
+    /// The predicate a quote stands for, which chains by sharing its first place.
+    pub fn predicate(&self) -> PredicateWord {
+        PredicateWord {
+            word: self.leaf_name(),
+            chaining: ChainingBehavior {
+                var: 0,
+                chain_with: PredicateChaining::Sharing,
+            },
+        }
+    }
+}
+
+/// What an interjection expresses, by the letter it starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InterjectionClass {
+    /// Starts with a vowel: an attitude or emotion of the speaker.
+    Attitudinal,
+    /// Starts with a sonorant: an exclamation, such as a greeting or a call for attention.
+    Exclamative,
+}
+
+/// A particle starting with a sonorant or a vowel that is neither a pronoun nor a vocative.
+/// Interjections are free modifiers: they may appear anywhere in a sentence, and do not change its meaning.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interjection {
+    pub word: String,
+    pub class: InterjectionClass,
+}
+
+impl Interjection {
+    pub fn new(word: String) -> Self {
+        let class = match word.chars().next() {
+            Some(c) if VOWELS.contains(&c) => InterjectionClass::Attitudinal,
+            _ => InterjectionClass::Exclamative,
+        };
+        Interjection { word, class }
+    }
+}
+
+/// A particle of the `zi` family: `z` followed by vowels, applying to the element after it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ZiParticle {
+    /// `zi`, negating the element.
+    Negation,
+    /// Any other member, marking the scope of the element without changing its meaning.
+    Scope(String),
+}
+
+impl ZiParticle {
+    pub fn new(word: String) -> Self {
+        if word == "zi" {
+            ZiParticle::Negation
+        } else {
+            ZiParticle::Scope(word)
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            ZiParticle::Negation => "zi",
+            ZiParticle::Scope(word) => word,
+        }
+    }
+}
+
+/// The truth function of a connective particle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConnectiveKind {
+    And,
+    Or,
+    Xor,
+    /// The first operand implies the second.
+    Implication,
+    /// Both operands are true, or neither is.
+    Equivalence,
+}
+
+/// The connective particles of the `b` series, which joins two predicates; `be` and `bi` are of
+/// families of their own.
+pub const CONNECTIVES: [(&str, ConnectiveKind); 5] = [
+    ("ba", ConnectiveKind::And),
+    ("bo", ConnectiveKind::Or),
+    ("bu", ConnectiveKind::Xor),
+    ("bai", ConnectiveKind::Implication),
+    ("bau", ConnectiveKind::Equivalence),
+];
+
+impl ConnectiveKind {
+    pub fn parse(word: &str) -> Option<Self> {
+        CONNECTIVES
+            .iter()
+            .find(|(w, _)| *w == word)
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn text(self) -> &'static str {
+        CONNECTIVES
+            .iter()
+            .find(|(_, kind)| *kind == self)
+            .map_or("", |(word, _)| word)
+    }
+}
+
+/// What a tag says about the predicate it modifies, by the first vowel after the `t`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagKind {
+    /// `ta…`: when.
+    Tense,
+    /// `te…`: how an event unfolds in time.
+    Aspect,
+    /// `to…`: where.
+    Location,
+    /// `tu…`: why.
+    Causation,
+}
+
+impl TagKind {
+    fn from_vowel(v: char) -> Option<Self> {
+        match v {
+            'a' => Some(TagKind::Tense),
+            'e' => Some(TagKind::Aspect),
+            'o' => Some(TagKind::Location),
+            'u' => Some(TagKind::Causation),
+            _ => None,
+        }
+    }
+}
+
+/// A particle of the `t` series, attaching adverbial information to a place of a predicate: `t`
+/// and the vowels naming the tag, optionally followed by `h` and the argument vowel of the place.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag {
+    /// The tag without its place, such as `ta` or `tei`.
+    pub tag: String,
+    pub kind: TagKind,
+    /// The place the tag modifies, if it is given; tags modify the first place otherwise.
+    pub place: Option<GrammarVar>,
+}
+
+impl Tag {
+    pub fn parse(word: &str) -> Option<Self> {
+        let (tag, place) = match word.split_once('h') {
+            Some((tag, place)) => (tag, Some(place)),
+            None => (word, None),
+        };
+        let kind = TagKind::from_vowel(tag.strip_prefix('t')?.chars().next()?)?;
+        let place = match place.map(|p| p.chars().collect::<Vec<_>>()).as_deref() {
+            None => None,
+            Some(&[v]) => Some(ARG_VOWELS.iter().position(|a| *a == v)? as GrammarVar),
+            Some(_) => return None,
+        };
+        Some(Tag {
+            tag: tag.to_string(),
+            kind,
+            place,
+        })
+    }
+
+    /// The place the tag modifies.
+    pub fn modified_place(&self) -> GrammarVar {
+        self.place.unwrap_or(0)
+    }
+
+    pub fn text(&self) -> String {
+        match self.place {
+            Some(var) => format!("{}h{}", self.tag, arg_vowel(var)),
+            None => self.tag.clone(),
+        }
+    }
+}
+
+/// What a subordinate clause stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AbstractionKind {
+    /// The event described by the clause.
+    Event,
+    /// The proposition that the clause holds.
+    Proposition,
+    /// The property the clause ascribes to its first place.
+    Property,
+}
+
+/// The particles of the `d` series opening a subordinate clause.
+pub const SUBORDINATORS: [(&str, AbstractionKind); 3] = [
+    ("da", AbstractionKind::Event),
+    ("de", AbstractionKind::Proposition),
+    ("do", AbstractionKind::Property),
+];
+
+impl AbstractionKind {
+    pub fn parse(word: &str) -> Option<Self> {
+        SUBORDINATORS
+            .iter()
+            .find(|(w, _)| *w == word)
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn text(self) -> &'static str {
+        SUBORDINATORS
+            .iter()
+            .find(|(_, kind)| *kind == self)
+            .map_or("", |(word, _)| word)
+    }
+}
+
+/// How many of the things filling a place a quantifier particle speaks of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QuantifierKind {
+    All,
+    Some,
+    Most,
+    /// As many as the number after the particle.
+    Exactly,
+}
+
+/// The quantifier particles, which share the `d` series with the subordinators.
+pub const QUANTIFIERS: [(&str, QuantifierKind); 4] = [
+    ("du", QuantifierKind::All),
+    ("dai", QuantifierKind::Some),
+    ("dei", QuantifierKind::Most),
+    ("doi", QuantifierKind::Exactly),
+];
+
+impl QuantifierKind {
+    pub fn parse(word: &str) -> Option<Self> {
+        QUANTIFIERS
+            .iter()
+            .find(|(w, _)| *w == word)
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn text(self) -> &'static str {
+        QUANTIFIERS
+            .iter()
+            .find(|(_, kind)| *kind == self)
+            .map_or("", |(word, _)| word)
+    }
+}
+
+/// Particles separating sentences: `pu` starts a new sentence, `pa` a new paragraph and `po` a
+/// new section. They are lexed as [`Separator`] particles.
+pub const SENTENCE_SEPARATORS: [&str; 3] = ["pu", "pa", "po"];
+
+/// What a particle of [`SENTENCE_SEPARATORS`] starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Separator {
+    Sentence,
+    Paragraph,
+    Section,
+}
+
+impl Separator {
+    pub fn parse(word: &str) -> Option<Self> {
+        match word {
+            "pu" => Some(Separator::Sentence),
+            "pa" => Some(Separator::Paragraph),
+            "po" => Some(Separator::Section),
+            _ => None,
+        }
+    }
+
+    pub fn text(self) -> &'static str {
+        match self {
+            Separator::Sentence => "pu",
+            Separator::Paragraph => "pa",
+            Separator::Section => "po",
+        }
+    }
+}
+
+/// The mood of a sentence, set by an illocution particle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mood {
+    /// The sentence is claimed to be true.
+    Assertion,
+    /// The sentence is asked about.
+    Question,
+    /// The sentence is requested to be made true.
+    Imperative,
+}
+
+/// The single-vowel particles marking the mood of a sentence.
+pub const ILLOCUTIONS: [(&str, Mood); 3] = [
+    ("a", Mood::Assertion),
+    ("e", Mood::Question),
+    ("u", Mood::Imperative),
+];
+
+impl Mood {
+    pub fn parse(word: &str) -> Option<Self> {
+        ILLOCUTIONS
+            .iter()
+            .find(|(w, _)| *w == word)
+            .map(|(_, mood)| *mood)
+    }
+
+    pub fn text(self) -> &'static str {
+        ILLOCUTIONS
+            .iter()
+            .find(|(_, mood)| *mood == self)
+            .map_or("", |(word, _)| word)
+    }
+}
+
+/// A pronoun of the `mi` family: `m` followed by vowels. Pronouns whose last two vowels are `a`
+/// and a rounded vowel, such as `mao` and `mua`, are equated to their second place when chained;
+/// others share their first.
+pub fn pronoun(word: String) -> PredicateWord {
+    PredicateWord {
+        chaining: chaining_of(&word, ChainingFamily::Mi),
+        word,
+    }
+}
+
+/// The families of words whose chaining behavior follows from their shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChainingFamily {
+    Root,
+    Mi,
+    Gi,
+    Freeform,
+}
+
+/// The chaining behavior of `word`, a word of `family` with repeated letters merged.
+///
+/// - A root ending in `i`, or of three letters starting with an initial pair such as `bla`, is
+///   equated to its second place; another root ending in a vowel shares its second place, and
+///   one ending in a sonorant such as `ban` shares its first.
+/// - A pronoun whose last two vowels are `a` and a rounded vowel, such as `mao` and `mua`, is
+///   equated to its second place; others share their first.
+/// - A `gi` particle starting with `gi` shares its first place; another ending in `i` is equated
+///   to its second, and others share their second.
+/// - A freeform word shares its first place, whatever its shape.
+pub fn chaining_of(word: &str, family: ChainingFamily) -> ChainingBehavior {
+    let chaining = |var, chain_with| ChainingBehavior { var, chain_with };
+    let last = word.chars().last();
+    match family {
+        ChainingFamily::Root => {
+            let initial_pair = word.chars().nth(1).is_some_and(|c| !VOWELS.contains(&c));
+            if last == Some('i') || (initial_pair && word.chars().count() == 3) {
+                chaining(1, PredicateChaining::Equivalence)
+            } else if last.is_some_and(|c| VOWELS.contains(&c)) {
+                chaining(1, PredicateChaining::Sharing)
+            } else {
+                chaining(0, PredicateChaining::Sharing)
+            }
+        }
+        ChainingFamily::Mi => {
+            let vowels: Vec<char> = word.chars().filter(|c| VOWELS.contains(c)).collect();
+            match vowels.as_slice() {
+                [.., 'a', 'o' | 'u'] | [.., 'o' | 'u', 'a'] => {
+                    chaining(1, PredicateChaining::Equivalence)
+                }
+                _ => chaining(0, PredicateChaining::Sharing),
+            }
+        }
+        ChainingFamily::Gi => {
+            if word.starts_with("gi") {
+                chaining(0, PredicateChaining::Sharing)
+            } else if last == Some('i') {
+                chaining(1, PredicateChaining::Equivalence)
+            } else {
+                chaining(1, PredicateChaining::Sharing)
+            }
+        }
+        ChainingFamily::Freeform => chaining(0, PredicateChaining::Sharing),
+    }
+}
+
+/// Whether a run of letters between pauses is a freeform word such as `alis`: it has a vowel and
+/// ends in a non-sonorant, which roots and particles never do.
+pub fn is_freeform(word: &str) -> bool {
+    word.chars().any(|c| VOWELS.contains(&c)) && word.ends_with(|c| NON_SONORANT.contains(&c))
+}
+
+/// The family of a particle starting with a sonorant or a vowel other than a pronoun.
+pub fn sonorant_or_vowel_family(word: String) -> ParticleFamily {
+    if let Some(mood) = Mood::parse(&word) {
+        ParticleFamily::Illocution(mood)
+    } else if word.starts_with('o') {
+        ParticleFamily::Vocative(word)
+    } else {
+        ParticleFamily::Interjection(Interjection::new(word))
+    }
+}
+
+/// The family of a particle starting with a non-sonorant, other than those with rules of their
+/// own, by its initial.
+pub fn nonsonorant_family(word: String) -> ParticleFamily {
+    let Some(initial) = word.chars().next() else {
+        return ParticleFamily::Other(word);
+    };
+    match initial {
+        'j' if DIGITS.contains(&&*word) => ParticleFamily::Number(Number {
+            digits: DIGITS
+                .iter()
+                .position(|d| *d == word)
+                .map(|d| d as u8)
+                .into_iter()
+                .collect(),
+        }),
+        'k' => ParticleFamily::Ki(word),
+        'g' => ParticleFamily::Gi(PredicateWord {
+            chaining: chaining_of(&word, ChainingFamily::Gi),
+            word,
+        }),
+        'z' => ParticleFamily::Zi(ZiParticle::new(word)),
+        'b' => match ConnectiveKind::parse(&word) {
+            Some(kind) => ParticleFamily::Connective(kind),
+            None => ParticleFamily::Other(word),
+        },
+        't' => match Tag::parse(&word) {
+            Some(tag) => ParticleFamily::Tag(tag),
+            None => ParticleFamily::Other(word),
+        },
+        'd' => match (AbstractionKind::parse(&word), QuantifierKind::parse(&word)) {
+            (Some(kind), _) => ParticleFamily::Subordinator(kind),
+            (None, Some(kind)) => ParticleFamily::Quantifier(kind),
+            (None, None) => ParticleFamily::Other(word),
+        },
+        'p' => match Separator::parse(&word) {
+            Some(separator) => ParticleFamily::Separator(separator),
+            None => ParticleFamily::Other(word),
+        },
+        _ => ParticleFamily::Other(word),
+    }
+}
+
+/// The names of the particle families the lexer distinguishes, as returned by
+/// [`ParticleFamily::name`]. Particles of any other family are lexed as `Other`.
+pub const PARTICLE_FAMILIES: [&str; 24] = [
+    "PE",
+    "PEI",
+    "VI",
+    "FI",
+    "VEI",
+    "KI",
+    "GI",
+    "BE",
+    "MI",
+    "SI",
+    "BI",
+    "ZI",
+    "CE",
+    "CU",
+    "JI",
+    "ATTITUDINAL",
+    "EXCLAMATIVE",
+    "VOCATIVE",
+    "ILLOCUTION",
+    "BA",
+    "TA",
+    "DA",
+    "DU",
+    "PU",
+];
+
+impl ParticleFamily {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParticleFamily::Pe => "PE",
+            ParticleFamily::Pei => "PEI",
+            ParticleFamily::Vi { .. } => "VI",
+            ParticleFamily::Fi { .. } => "FI",
+            ParticleFamily::Vei => "VEI",
+            ParticleFamily::Ki(_) => "KI",
+            ParticleFamily::Gi(_) => "GI",
+            ParticleFamily::Be => "BE",
+            ParticleFamily::Mi(_) => "MI",
+            ParticleFamily::Si { .. } => "SI",
+            ParticleFamily::Bi => "BI",
+            ParticleFamily::Zi(_) => "ZI",
+            ParticleFamily::Quote(Quote::Word(_)) => "CE",
+            ParticleFamily::Quote(Quote::Foreign { .. }) => "CU",
+            ParticleFamily::Number(_) => "JI",
+            ParticleFamily::Interjection(Interjection {
+                class: InterjectionClass::Attitudinal,
+                ..
+            }) => "ATTITUDINAL",
+            ParticleFamily::Interjection(Interjection {
+                class: InterjectionClass::Exclamative,
+                ..
+            }) => "EXCLAMATIVE",
+            ParticleFamily::Vocative(_) => "VOCATIVE",
+            ParticleFamily::Illocution(_) => "ILLOCUTION",
+            ParticleFamily::Connective(_) => "BA",
+            ParticleFamily::Tag(_) => "TA",
+            ParticleFamily::Subordinator(_) => "DA",
+            ParticleFamily::Quantifier(_) => "DU",
+            ParticleFamily::Separator(_) => "PU",
+            ParticleFamily::Other(_) => "OTHER",
+        }
+    }
+
+    /// The spelling of the particle, as the lexer would read it back.
+    pub fn text(&self) -> String {
+        let equivalence = |chain_with: &PredicateChaining| match chain_with {
+            PredicateChaining::Sharing => "",
+            PredicateChaining::Equivalence => "i",
+        };
+        match self {
+            ParticleFamily::Pe => "pe".to_string(),
+            ParticleFamily::Pei => "pei".to_string(),
+            ParticleFamily::Vi {
+                var: Some(var),
+                chain_with,
+            } => format!("v{}{}", equivalence(chain_with), place_text(*var)),
+            ParticleFamily::Vi { var: None, .. } => "vi".to_string(),
+            ParticleFamily::Fi { var, chain_with } => match var {
+                FiVar::None => "fi".to_string(),
+                FiVar::Var(var) => format!("f{}{}", equivalence(chain_with), place_text(*var)),
+                FiVar::Same => format!(
+                    "fe{}",
+                    if chain_with == &PredicateChaining::Sharing {
+                        'u'
+                    } else {
+                        'i'
+                    }
+                ),
+                FiVar::Next => format!(
+                    "fa{}",
+                    if chain_with == &PredicateChaining::Sharing {
+                        'u'
+                    } else {
+                        'i'
+                    }
+                ),
+            },
+            ParticleFamily::Vei => "vei".to_string(),
+            ParticleFamily::Be => "be".to_string(),
+            ParticleFamily::Bi => "bi".to_string(),
+            ParticleFamily::Gi(PredicateWord { word, .. })
+            | ParticleFamily::Mi(PredicateWord { word, .. })
+            | ParticleFamily::Ki(word)
+            | ParticleFamily::Interjection(Interjection { word, .. })
+            | ParticleFamily::Vocative(word)
+            | ParticleFamily::Other(word) => word.clone(),
+            ParticleFamily::Zi(zi) => zi.text().to_string(),
+            ParticleFamily::Illocution(mood) => mood.text().to_string(),
+            ParticleFamily::Connective(kind) => kind.text().to_string(),
+            ParticleFamily::Tag(tag) => tag.text(),
+            ParticleFamily::Subordinator(kind) => kind.text().to_string(),
+            ParticleFamily::Quantifier(kind) => kind.text().to_string(),
+            ParticleFamily::Separator(separator) => separator.text().to_string(),
+            ParticleFamily::Si { exposure, chaining } => si_text(exposure, *chaining),
+            ParticleFamily::Quote(Quote::Word(word)) => format!("ce {word}"),
+            ParticleFamily::Quote(Quote::Foreign { delimiter, content }) => {
+                format!("cu {delimiter} {content} {delimiter}")
+            }
+            ParticleFamily::Number(Number { digits }) => digits
+                .iter()
+                .filter_map(|d| DIGITS.get(usize::from(*d)))
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+impl Word {
+    pub fn text(&self) -> String {
+        match self {
+            Word::Particle(p) => p.text(),
+            Word::Predicate(PredicateWord { word, .. }, _) => word.clone(),
+        }
+    }
+
+    /// Whether the word must follow a pause, even at the start of the text, as particles
+    /// starting with a sonorant or a vowel do.
+    pub fn needs_pause(&self) -> bool {
+        self.text()
+            .starts_with(|c| VOWELS.contains(&c) || SONORANT.contains(&c))
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+/// Words written back as text that lexes as the same words: separated by spaces, and after an
+/// apostrophe if the first one [needs a pause](Word::needs_pause). A `ce` that opens no quote is
+/// joined to the word after it, as a pause after it would open one. After a `cu` that opens no
+/// quote, words are joined to the `cu`, to the delimiter the lexer would read after it, or to the
+/// later words spelled like it, until none of them closes the quote. Words are only joined where
+/// they lex as themselves once joined. Consecutive numbers lex back as a single number, as the
+/// lexer never produces them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Text<'a>(pub &'a [Word]);
+
+impl Text<'_> {
+    /// Whether each word is written joined to the one before it.
+    fn joined(&self) -> Vec<bool> {
+        let words = self.0;
+        let mut joined = vec![false; words.len()];
+        // Joins word `k` to the one before it, if the words joined lex as themselves.
+        let join = |joined: &mut Vec<bool>, k: usize| {
+            let start = (0..k).rev().find(|j| !joined[*j]).unwrap_or(0);
+            let end = (k + 1..words.len())
+                .find(|j| !joined[*j])
+                .unwrap_or(words.len());
+            let run = &words[start..end];
+            let pause = if run[0].needs_pause() { "'" } else { "" };
+            let text: String = run.iter().map(Word::text).collect();
+            let joins = lex(&format!("{pause}{text}")).is_ok_and(|lexed| lexed == run);
+            joined[k] |= joins;
+            joins
+        };
+        for i in 1..words.len() {
+            match &words[i - 1] {
+                Word::Particle(ParticleFamily::Other(text)) if text == "ce" => {
+                    join(&mut joined, i);
+                }
+                Word::Particle(ParticleFamily::Other(text)) if text == "cu" => loop {
+                    if joined[i] {
+                        break;
+                    }
+                    // The text from `i` split at pauses, each part with the index of the word
+                    // after it if it ends one.
+                    let mut runs = Vec::new();
+                    let mut run = String::new();
+                    for (j, word) in words.iter().enumerate().skip(i) {
+                        let text = word.text();
+                        let mut parts = text.split(' ').peekable();
+                        while let Some(part) = parts.next() {
+                            run += part;
+                            if parts.peek().is_some() {
+                                runs.push((mem::take(&mut run), None));
+                            }
+                        }
+                        if !joined.get(j + 1).copied().unwrap_or(false) {
+                            runs.push((mem::take(&mut run), Some(j + 1)));
+                        }
+                    }
+                    let Some((_, closing_end)) =
+                        runs[1..].iter().find(|(text, _)| *text == runs[0].0)
+                    else {
+                        break;
+                    };
+                    let joined_any = [Some(i), runs[0].1, *closing_end]
+                        .into_iter()
+                        .flatten()
+                        .filter(|k| *k < words.len())
+                        .any(|k| join(&mut joined, k));
+                    if !joined_any {
+                        break;
+                    }
+                },
+                _ => {}
+            }
+        }
+        joined
+    }
+}
+
+impl fmt::Display for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((i, word), joined) in self.0.iter().enumerate().zip(self.joined()) {
+            if i > 0 && !joined {
+                write!(f, " ")?;
+            } else if i == 0 && word.needs_pause() {
+                write!(f, "'")?;
+            }
+            write!(f, "{word}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The text of `words`, as written by [`Text`].
+pub fn to_text(words: &[Word]) -> String {
+    Text(words).to_string()
+}
+
+pub fn arg_vowel(var: GrammarVar) -> char {
+    ARG_VOWELS.get(var as usize).copied().unwrap_or('e')
+}
+
+/// The spelling of a place in a `vi` or `fi` particle. The first four places are the vowels of
+/// [`ARG_VOWELS`]; later places join several of them with `h`, counting on from the last place
+/// spelled with one vowel fewer, so that `ehe` is the fifth place, `eha` the sixth and `ahe`
+/// the ninth.
+pub fn place_text(var: GrammarVar) -> String {
+    let base = ARG_VOWELS.len();
+    let mut n = var as usize;
+    let mut vowels = vec![ARG_VOWELS[n % base]];
+    n /= base;
+    while n > 0 {
+        n -= 1;
+        vowels.push(ARG_VOWELS[n % base]);
+        n /= base;
+    }
+    let vowels: Vec<String> = vowels.iter().rev().map(char::to_string).collect();
+    vowels.join("h")
+}
+
+/// The place spelled `text` as [`place_text`] spells it, if it is one.
+pub fn parse_place(text: &str) -> Option<GrammarVar> {
+    let mut var: Option<GrammarVar> = None;
+    for (i, part) in text.split('h').enumerate() {
+        let mut chars = part.chars();
+        let (Some(v), None) = (chars.next(), chars.next()) else {
+            return None;
+        };
+        let digit = ARG_VOWELS.iter().position(|a| *a == v)? as GrammarVar;
+        var = Some(match var {
+            None if i == 0 => digit,
+            Some(var) => var
+                .checked_add(1)?
+                .checked_mul(ARG_VOWELS.len() as GrammarVar)?
+                .checked_add(digit)?,
+            None => return None,
+        });
+    }
+    var
+}
+
+/// The spelling of the `si` particle exposing `exposure` and chaining with `chaining`. Only
+/// transparent and modified exposures are spelled with a `si` particle; others have no spelling.
+pub fn si_text(exposure: &Exposure, chaining: ChainingBehavior) -> String {
+    match exposure {
+        Exposure::Standard | Exposure::Explicit(_) => String::new(),
+        Exposure::Transparent => format!("si{}", arg_vowel(chaining.var)),
+        Exposure::Modified(vars) => {
+            let mut word: String = iter::once('s')
+                .chain(vars.iter().map(|v| arg_vowel(*v)))
+                .collect();
+            if vars.is_empty() {
+                word.push('i');
+            }
+            if vars.last() != Some(&chaining.var) {
+                word.push('h');
+                word.push(arg_vowel(chaining.var));
+            }
+            if chaining.chain_with == PredicateChaining::Equivalence {
+                word.push('i');
+            }
+            word
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FiVar {
     None,
@@ -178,26 +1137,794 @@ pub enum FiVar {
     Next,
 }
 
+/// What [`PreprocessOptions::preprocess`] does to a text before it is lexed. By default, as in
+/// [`preprocess`], ASCII case is folded and repeated letters are merged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PreprocessOptions {
+    dedup: bool,
+    fold_case: bool,
+    pauses: Vec<char>,
+    spellings: Vec<(Vec<char>, char)>,
+    join_hyphenated: bool,
+    digits: bool,
+}
+
+impl Default for PreprocessOptions {
+    fn default() -> Self {
+        PreprocessOptions {
+            dedup: true,
+            fold_case: true,
+            pauses: Vec::new(),
+            spellings: Vec::new(),
+            join_hyphenated: false,
+            digits: false,
+        }
+    }
+}
+
+impl PreprocessOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether runs of the same character are merged into one, spanning the whole run.
+    pub fn dedup(self, dedup: bool) -> Self {
+        Self { dedup, ..self }
+    }
+
+    /// Whether ASCII letters are folded to lower case. Upper case letters do not lex.
+    pub fn fold_case(self, fold_case: bool) -> Self {
+        Self { fold_case, ..self }
+    }
+
+    /// Characters read as the pause mark `'`, in addition to whitespace and `'` itself.
+    pub fn pauses(self, pauses: impl IntoIterator<Item = char>) -> Self {
+        Self {
+            pauses: pauses.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Alternative spellings of letters, such as the letters or digraphs of an older or informal
+    /// orthography, each read as the canonical letter it is paired with and spanning all of its
+    /// characters. Where several spellings match, the longest is read. Spellings are matched
+    /// after case folding, and are merged with repeated letters like the letter they stand for.
+    pub fn spellings<S: AsRef<str>>(self, spellings: impl IntoIterator<Item = (S, char)>) -> Self {
+        Self {
+            spellings: spellings
+                .into_iter()
+                .map(|(from, to)| (from.as_ref().chars().collect::<Vec<_>>(), to))
+                .filter(|(from, _)| !from.is_empty())
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Whether a word split across lines with a hyphen is read as a single word: a hyphen right
+    /// after a letter and ending its line is skipped along with the line break and the
+    /// indentation of the next line, so that the span of the word covers both fragments. See
+    /// [`fragments`] for the parts of such a span that are the word's letters.
+    pub fn join_hyphenated(self, join_hyphenated: bool) -> Self {
+        Self {
+            join_hyphenated,
+            ..self
+        }
+    }
+
+    /// Whether the ASCII digits `0` to `9` are read as the letters of the number particles of
+    /// [`DIGITS`], each letter spanning the digit, so that `mi duna 3` lexes like `mi duna jo` and
+    /// `12` like the number `jeji`.
+    pub fn digits(self, digits: bool) -> Self {
+        Self { digits, ..self }
+    }
+
+    /// The character at the start of `chars`, as a count of input characters and the character
+    /// they are read as.
+    fn read(&self, chars: &[char]) -> Option<(usize, Either<char, Chars<'static>>)> {
+        let fold = |c: char| {
+            if self.fold_case {
+                c.to_ascii_lowercase()
+            } else {
+                c
+            }
+        };
+        let spelling = self
+            .spellings
+            .iter()
+            .filter(|(from, _)| {
+                from.len() <= chars.len() && from.iter().zip(chars).all(|(a, b)| *a == fold(*b))
+            })
+            .max_by_key(|(from, _)| from.len());
+        if let Some((from, to)) = spelling {
+            return Some((from.len(), Either::Left(*to)));
+        }
+        let c = *chars.first()?;
+        if let Some(digit) = c.to_digit(10).filter(|_| self.digits && c.is_ascii_digit()) {
+            return DIGITS
+                .get(digit as usize)
+                .map(|word| (1, Either::Right(word.chars())));
+        }
+        Some(if self.pauses.contains(&c) {
+            (1, Either::Left('\''))
+        } else {
+            (1, Either::Left(fold(c)))
+        })
+    }
+
+    pub fn preprocess<'a>(
+        &self,
+        s: &'a str,
+    ) -> Stream<
+        'a,
+        PreProcessed,
+        Range<usize>,
+        impl Iterator<Item = (PreProcessed, Range<usize>)> + 'a,
+    > {
+        let chars: Vec<char> = s.chars().collect();
+        let len = chars.len();
+        let options = self.clone();
+        let dedup = self.dedup;
+        let mut i = 0;
+        Stream::from_iter(
+            len..len,
+            iter::from_fn(move || {
+                if options.join_hyphenated && i > 0 && !is_pause(chars[i - 1]) {
+                    i += continuation(&chars[i..]);
+                }
+                let (n, read) = options.read(&chars[i..])?;
+                i += n;
+                Some((read, i - n..i))
+            })
+            .flat_map(|(read, span)| {
+                read.map_left(iter::once)
+                    .into_iter()
+                    .map(move |c| (c, span.clone()))
+            })
+            .coalesce(move |(a, r), (b, q)| {
+                if dedup && a == b {
+                    Ok((a, r.start..q.end))
+                } else {
+                    Err(((a, r), (b, q)))
+                }
+            })
+            .map(|(c, r)| (PreProcessed(c), r)),
+        )
+    }
+}
+
+/// The length of the line continuation at the start of `chars`: a hyphen ending its line, the line
+/// break, and the indentation of the next line. 0 if there is none.
+fn continuation(chars: &[char]) -> usize {
+    let indentation = |chars: &[char]| chars.iter().take_while(|c| matches!(c, ' ' | '\t')).count();
+    if chars.first() != Some(&'-') {
+        return 0;
+    }
+    let mut n = 1 + indentation(&chars[1..]);
+    match chars.get(n..) {
+        Some(['\r', '\n', ..]) => n += 2,
+        Some(['\n', ..]) => n += 1,
+        _ => return 0,
+    }
+    n + indentation(&chars[n..])
+}
+
+/// The parts of `span`, in characters of `s`, outside the line continuations skipped by
+/// [`PreprocessOptions::join_hyphenated`]: the fragments of a word split across lines.
+pub fn fragments(s: &str, span: Range<usize>) -> Vec<Range<usize>> {
+    let chars: Vec<char> = s.chars().collect();
+    let end = span.end.min(chars.len());
+    let mut fragments = Vec::new();
+    let mut start = span.start.min(end);
+    let mut i = start;
+    while i < end {
+        let n = if i > 0 && !is_pause(chars[i - 1]) {
+            continuation(&chars[i..end])
+        } else {
+            0
+        };
+        if n > 0 {
+            if start < i {
+                fragments.push(start..i);
+            }
+            i += n;
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    if start < end {
+        fragments.push(start..end);
+    }
+    fragments
+}
+
+/// Folds ASCII case and merges repeated letters. Text typed with typographic apostrophes,
+/// full-width letters or diacritics should go through [`normalize`](crate::normalize::normalize)
+/// first; [`PreprocessOptions`] changes what is folded and merged, and reads alternative
+/// spellings of letters.
 pub fn preprocess(
     s: &str,
-) -> Stream<PreProcessed, Range<usize>, impl Iterator<Item = (PreProcessed, Range<usize>)> + '_> {
-    let len = s.chars().count();
-    Stream::from_iter(
-        len..len,
-        s.chars()
+) -> Stream<'_, PreProcessed, Range<usize>, impl Iterator<Item = (PreProcessed, Range<usize>)> + '_>
+{
+    PreprocessOptions::default().preprocess(s)
+}
+
+/// Why a word failed to lex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LexErrorReason {
+    /// A character that is neither a letter nor a pause.
+    InvalidCharacter(char),
+    /// A word starts with a consonant pair that is not an initial pair of the standard
+    /// [`PhonologyTables`].
+    InvalidInitialPair(char, char),
+    /// A consonant pair inside a word that is not a medial pair of the standard
+    /// [`PhonologyTables`].
+    InvalidMedialPair(char, char),
+    /// A consonant that cannot attach to the letters around it, such as the third consonant of a
+    /// cluster or a consonant ending the text.
+    StrayConsonant(char),
+    /// A word made only of consonants.
+    MissingVowel,
+    /// The text ends in the middle of a word.
+    UnexpectedEnd,
+    /// The letters do not form a word for another reason.
+    Malformed,
+    /// The text has more characters than the limit of [`LexLimits::max_len`].
+    TextTooLong(usize),
+    /// A run of characters without a pause is longer than the limit of
+    /// [`LexLimits::max_word_len`].
+    WordTooLong(usize),
+}
+
+impl fmt::Display for LexErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexErrorReason::InvalidCharacter(c) => write!(f, "invalid character `{c}`"),
+            LexErrorReason::InvalidInitialPair(a, b) => write!(f, "invalid initial pair `{a}{b}`"),
+            LexErrorReason::InvalidMedialPair(a, b) => write!(f, "invalid medial pair `{a}{b}`"),
+            LexErrorReason::StrayConsonant(c) => write!(f, "stray consonant `{c}`"),
+            LexErrorReason::MissingVowel => write!(f, "word without a vowel"),
+            LexErrorReason::UnexpectedEnd => write!(f, "unexpected end of text"),
+            LexErrorReason::Malformed => write!(f, "malformed word"),
+            LexErrorReason::TextTooLong(limit) => {
+                write!(f, "text longer than {limit} characters")
+            }
+            LexErrorReason::WordTooLong(limit) => {
+                write!(f, "word longer than {limit} characters")
+            }
+        }
+    }
+}
+
+/// A word that failed to lex, with its span in the characters of the text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LexError {
+    pub span: Range<usize>,
+    pub reason: LexErrorReason,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.reason, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for LexError {}
+
+/// What makes text that lexes hard to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LexWarningReason {
+    /// A word follows the one before it without a pause.
+    MissingPause,
+    /// A run of the same letter, which is read as a single one.
+    RepeatedLetter(char),
+    /// Three vowels or more in a row, whose syllables are easily misread; an `h` can separate
+    /// them.
+    VowelRun,
+}
+
+impl fmt::Display for LexWarningReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexWarningReason::MissingPause => write!(f, "missing pause before word"),
+            LexWarningReason::RepeatedLetter(c) => write!(f, "repeated `{c}` read as one"),
+            LexWarningReason::VowelRun => write!(f, "run of vowels"),
+        }
+    }
+}
+
+/// Valid text that is easily misread, with its span in the characters of the text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LexWarning {
+    pub span: Range<usize>,
+    pub reason: LexWarningReason,
+}
+
+impl fmt::Display for LexWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.reason, self.span.start, self.span.end
+        )
+    }
+}
+
+fn is_consonant(c: char) -> bool {
+    NON_SONORANT.contains(&c) || SONORANT.contains(&c) || c == 'h'
+}
+
+fn is_pause(c: char) -> bool {
+    c.is_whitespace() || c == '\''
+}
+
+impl LexError {
+    /// Explains an error of the lexer at `span` in `text` from the letters around it.
+    pub fn classify(text: &str, span: Range<usize>) -> Self {
+        // The letters as preprocessed, with their spans.
+        let letters: Vec<(char, Range<usize>)> = text
+            .chars()
             .map(|c| c.to_ascii_lowercase())
             .dedup_with_count()
             .scan(0, |i, (n, c)| {
                 let start = *i;
                 *i += n;
-                let end = *i;
-                Some((c, start..end))
+                Some((c, start..*i))
             })
-            .map(|(c, r)| (PreProcessed(c), r)),
-    )
+            .collect();
+        let new = |span: Range<usize>, reason| LexError { span, reason };
+        let Some(at) = letters.iter().position(|(_, r)| r.start >= span.start) else {
+            let last_word = letters
+                .iter()
+                .rev()
+                .take_while(|(c, _)| !is_pause(*c))
+                .collect::<Vec<_>>();
+            return match (last_word.first(), last_word.last()) {
+                (Some((_, end)), Some((_, start)))
+                    if !last_word.iter().any(|(c, _)| VOWELS.contains(c)) =>
+                {
+                    new(start.start..end.end, LexErrorReason::MissingVowel)
+                }
+                (Some((c, r)), _) if is_consonant(*c) => {
+                    new(r.clone(), LexErrorReason::StrayConsonant(*c))
+                }
+                _ => new(span, LexErrorReason::UnexpectedEnd),
+            };
+        };
+        let letter = |i: Option<usize>| i.and_then(|i| letters.get(i)).map(|(c, _)| *c);
+        let (c, prev, before_prev) = (
+            letter(Some(at)).unwrap_or(' '),
+            letter(at.checked_sub(1)),
+            letter(at.checked_sub(2)),
+        );
+        let until = |end: usize| {
+            let start = letters
+                .get(at.saturating_sub(1))
+                .map_or(span.start, |(_, r)| r.start);
+            start..letters.get(end).map_or(span.end, |(_, r)| r.end)
+        };
+        if !is_consonant(c) && !VOWELS.contains(&c) && !is_pause(c) {
+            return new(span, LexErrorReason::InvalidCharacter(c));
+        }
+        match prev {
+            Some(p) if is_consonant(c) && is_consonant(p) => {
+                if before_prev.is_some_and(is_consonant) {
+                    new(span, LexErrorReason::StrayConsonant(c))
+                } else if before_prev.is_none_or(is_pause) {
+                    new(until(at), LexErrorReason::InvalidInitialPair(p, c))
+                } else {
+                    new(until(at), LexErrorReason::InvalidMedialPair(p, c))
+                }
+            }
+            _ if is_consonant(c) && prev.is_none_or(is_pause) => {
+                let word = letters[at..]
+                    .iter()
+                    .take_while(|(c, _)| !is_pause(*c))
+                    .count();
+                if letters[at..at + word]
+                    .iter()
+                    .any(|(c, _)| VOWELS.contains(c))
+                {
+                    new(span, LexErrorReason::Malformed)
+                } else {
+                    let end = letters[at + word - 1].1.end;
+                    new(span.start..end, LexErrorReason::MissingVowel)
+                }
+            }
+            _ => new(span, LexErrorReason::Malformed),
+        }
+    }
+}
+
+/// Words with their spans in the characters of the text, as returned by [`spanned_lexer`].
+pub type SpannedWords = Vec<(Word, Range<usize>)>;
+
+/// Lexes `s` like [`spanned_lexer`] with the table-driven lexer in [`crate::dfa`], running the
+/// combinators only to report the errors of text that does not lex.
+pub fn lex_spanned(s: &str) -> Result<SpannedWords, Vec<Cheap<PreProcessed>>> {
+    match crate::dfa::lex(s) {
+        Some(words) => Ok(words),
+        None => spanned_lexer().parse(preprocess(s)),
+    }
+}
+
+/// A token of [`lex_with_pauses`] or [`Islands::lex`]: a word, a run of pause characters, or
+/// text that is not eberban.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Token {
+    Word(Word),
+    /// Whitespace and pause marks. `explicit` if there is a `'` among them.
+    Pause {
+        explicit: bool,
+    },
+    /// The text between the delimiters of an island, which is not lexed.
+    Island(String),
+}
+
+/// Tokens with their spans in the characters of the text, as returned by [`lex_with_pauses`].
+pub type SpannedTokens = Vec<(Token, Range<usize>)>;
+
+/// Lexes `s` like [`lex_spanned`], with a pause token for the pause characters between words and
+/// around them, so that the tokens cover the whole text. The pauses inside a quote are part of
+/// the quote.
+pub fn lex_with_pauses(s: &str) -> Result<SpannedTokens, Vec<Cheap<PreProcessed>>> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut end = 0;
+    let pause = |tokens: &mut SpannedTokens, span: Range<usize>| {
+        if !span.is_empty() {
+            let explicit = chars.get(span.clone()).is_some_and(|p| p.contains(&'\''));
+            tokens.push((Token::Pause { explicit }, span));
+        }
+    };
+    for (word, span) in lex_spanned(s)? {
+        pause(&mut tokens, end..span.start);
+        end = span.end;
+        tokens.push((Token::Word(word), span));
+    }
+    pause(&mut tokens, end..chars.len());
+    Ok(tokens)
+}
+
+/// Delimiters marking islands of text that is not eberban, such as quoted English or code, for
+/// [`Islands::lex`] to skip. There are none by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Islands {
+    delimiters: Vec<(Vec<char>, Vec<char>)>,
+}
+
+impl Islands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pairs of an opening and a closing delimiter. Where several opening delimiters match, the
+    /// longest is used; empty opening delimiters are ignored.
+    pub fn delimiters<S: AsRef<str>>(self, delimiters: impl IntoIterator<Item = (S, S)>) -> Self {
+        let chars = |s: S| s.as_ref().chars().collect::<Vec<_>>();
+        Self {
+            delimiters: delimiters
+                .into_iter()
+                .map(|(open, close)| (chars(open), chars(close)))
+                .filter(|(open, _)| !open.is_empty())
+                .collect(),
+        }
+    }
+
+    /// The island starting at `at` in `chars`: its span, delimiters included, and its text. An
+    /// island without a closing delimiter runs to the end of the text.
+    fn island_at(&self, chars: &[char], at: usize) -> Option<(Range<usize>, String)> {
+        let rest = chars.get(at..)?;
+        let (open, close) = self
+            .delimiters
+            .iter()
+            .filter(|(open, _)| rest.starts_with(open))
+            .max_by_key(|(open, _)| open.len())?;
+        let start = at + open.len();
+        let (end, after) = (start..chars.len())
+            .find(|i| !close.is_empty() && chars[*i..].starts_with(close))
+            .map_or((chars.len(), chars.len()), |end| (end, end + close.len()));
+        Some((at..after, chars[start..end].iter().collect()))
+    }
+
+    /// Lexes `s` like [`lex_spanned`], skipping its islands and emitting each as a single
+    /// [`Token::Island`], explaining why the text between them fails to lex. Each stretch of text
+    /// between islands is lexed on its own.
+    pub fn lex(&self, s: &str) -> Result<SpannedTokens, Vec<LexError>> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        let mut lex_text = |tokens: &mut SpannedTokens, span: Range<usize>| {
+            let text: String = chars[span.clone()].iter().collect();
+            match lex_spanned(&text) {
+                Ok(words) => tokens.extend(words.into_iter().map(|(word, r)| {
+                    (Token::Word(word), span.start + r.start..span.start + r.end)
+                })),
+                Err(lex_errors) => errors.extend(lex_errors.iter().map(|e| {
+                    let error = LexError::classify(&text, e.span());
+                    LexError {
+                        span: span.start + error.span.start..span.start + error.span.end,
+                        ..error
+                    }
+                })),
+            }
+        };
+        let (mut start, mut i) = (0, 0);
+        while i < chars.len() {
+            let Some((span, text)) = self.island_at(&chars, i) else {
+                i += 1;
+                continue;
+            };
+            lex_text(&mut tokens, start..i);
+            i = span.end;
+            start = i;
+            tokens.push((Token::Island(text), span));
+        }
+        lex_text(&mut tokens, start..chars.len());
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Lexes `s` like [`lexer`] with the table-driven lexer in [`crate::dfa`].
+pub fn lex_words(s: &str) -> Result<Vec<Word>, Vec<Cheap<PreProcessed>>> {
+    lex_spanned(s).map(|words| words.into_iter().map(|(word, _)| word).collect())
+}
+
+/// Lexes `s`, explaining why it fails to lex.
+pub fn lex(s: &str) -> Result<Vec<Word>, Vec<LexError>> {
+    lex_words(s).map_err(|errors| {
+        errors
+            .iter()
+            .map(|e| LexError::classify(s, e.span()))
+            .collect()
+    })
+}
+
+/// The warnings about `s`, lexed as `words` by [`lex_spanned`], in the order of their spans.
+/// Foreign quotes are not checked, as their content is not eberban.
+pub fn lex_warnings(s: &str, words: &[(Word, Range<usize>)]) -> Vec<LexWarning> {
+    let chars: Vec<char> = s.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut warnings = Vec::new();
+    let mut previous_end = None;
+    for (word, span) in words {
+        if previous_end == Some(span.start) {
+            warnings.push(LexWarning {
+                span: span.start..span.start + 1,
+                reason: LexWarningReason::MissingPause,
+            });
+        }
+        previous_end = Some(span.end);
+        if let Word::Particle(ParticleFamily::Quote(Quote::Foreign { .. })) = word {
+            continue;
+        }
+        let letters = chars.get(span.clone()).unwrap_or_default();
+        let mut start = 0;
+        for run in letters.chunk_by(|a, b| a == b) {
+            if let [c, _, ..] = run {
+                if !is_pause(*c) {
+                    warnings.push(LexWarning {
+                        span: span.start + start..span.start + start + run.len(),
+                        reason: LexWarningReason::RepeatedLetter(*c),
+                    });
+                }
+            }
+            start += run.len();
+        }
+        let mut start = 0;
+        for run in letters.chunk_by(|a, b| VOWELS.contains(a) == VOWELS.contains(b)) {
+            let vowels = run.iter().dedup().count();
+            if run.first().is_some_and(|c| VOWELS.contains(c)) && vowels >= 3 {
+                warnings.push(LexWarning {
+                    span: span.start + start..span.start + start + run.len(),
+                    reason: LexWarningReason::VowelRun,
+                });
+            }
+            start += run.len();
+        }
+    }
+    warnings.sort_by_key(|w| (w.span.start, w.span.end));
+    warnings
+}
+
+/// Lexes `s` like [`lex_spanned`], explaining why it fails to lex, and also returning the
+/// warnings of [`lex_warnings`] about it.
+pub fn lex_with_warnings(s: &str) -> Result<(SpannedWords, Vec<LexWarning>), Vec<LexError>> {
+    let words = lex_spanned(s).map_err(|errors| {
+        errors
+            .iter()
+            .map(|e| LexError::classify(s, e.span()))
+            .collect::<Vec<_>>()
+    })?;
+    let warnings = lex_warnings(s, &words);
+    Ok((words, warnings))
+}
+
+/// Limits on the text accepted by [`LexLimits::lex`], so that untrusted input is rejected before
+/// it is preprocessed. There are no limits by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LexLimits {
+    max_len: Option<usize>,
+    max_word_len: Option<usize>,
+}
+
+impl LexLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The largest number of characters in the text.
+    pub fn max_len(self, max_len: usize) -> Self {
+        Self {
+            max_len: Some(max_len),
+            ..self
+        }
+    }
+
+    /// The largest number of characters between two pauses. This includes the words of quotes,
+    /// and the text of foreign quotes between the pauses in it.
+    pub fn max_word_len(self, max_word_len: usize) -> Self {
+        Self {
+            max_word_len: Some(max_word_len),
+            ..self
+        }
+    }
+
+    /// Checks `s` against the limits in a single pass over its characters, without allocating.
+    /// The span of a text that is too long covers the characters over the limit, and that of a
+    /// word too long covers the word.
+    pub fn check(&self, s: &str) -> Result<(), LexError> {
+        let mut word_start = None;
+        for (i, c) in s.chars().enumerate() {
+            if let Some(max) = self.max_len.filter(|max| i >= *max) {
+                return Err(LexError {
+                    span: i..i + s.chars().skip(i).count(),
+                    reason: LexErrorReason::TextTooLong(max),
+                });
+            }
+            if is_pause(c) {
+                word_start = None;
+                continue;
+            }
+            let start = *word_start.get_or_insert(i);
+            if let Some(max) = self.max_word_len.filter(|max| i - start >= *max) {
+                let end = i + s.chars().skip(i).take_while(|c| !is_pause(*c)).count();
+                return Err(LexError {
+                    span: start..end,
+                    reason: LexErrorReason::WordTooLong(max),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks `s` against the limits, then lexes it like [`lex_spanned`], explaining why it fails
+    /// to lex.
+    pub fn lex(&self, s: &str) -> Result<SpannedWords, Vec<LexError>> {
+        self.check(s).map_err(|error| vec![error])?;
+        lex_spanned(s).map_err(|errors| {
+            errors
+                .iter()
+                .map(|e| LexError::classify(s, e.span()))
+                .collect()
+        })
+    }
+}
+
+/// Lexes `s` without stopping at the first malformed word: the text from the last pause before
+/// an error to the next pause after it is skipped, and lexing resumes there. Returns the words of
+/// every part that lexed, and an error for every part that was skipped.
+pub fn lex_recovering(s: &str) -> (Vec<Word>, Vec<LexError>) {
+    let (mut words, mut errors) = (Vec::new(), Vec::new());
+    recover(s, 0, &mut words, &mut errors);
+    // A number may straddle a skipped part.
+    (assemble_numbers(words), errors)
+}
+
+fn recover(s: &str, offset: usize, words: &mut Vec<Word>, errors: &mut Vec<LexError>) {
+    let error = match lex_words(s) {
+        Ok(lexed) => return words.extend(lexed),
+        Err(lex_errors) => match lex_errors.iter().min_by_key(|e| e.span().start) {
+            Some(e) => LexError::classify(s, e.span()),
+            None => LexError::classify(s, 0..0),
+        },
+    };
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let at = error.span.start.min(chars.len());
+    // The pauses around the error, as indices of characters. Lexing never resumes at the start
+    // of the text, so that every call lexes less of it.
+    let start = chars[..at].iter().rposition(|(_, c)| is_pause(*c));
+    let end = (at.max(1)..chars.len()).find(|i| is_pause(chars[*i].1));
+    let byte =
+        |i: Option<usize>, default| i.and_then(|i| chars.get(i)).map_or(default, |(b, _)| *b);
+    let prefix = &s[..byte(start, 0)];
+    if prefix.len() < s.len() {
+        recover(prefix, offset, words, errors);
+    }
+    errors.push(LexError {
+        span: error.span.start + offset..error.span.end + offset,
+        reason: error.reason,
+    });
+    if let Some(end) = end {
+        recover(&s[byte(Some(end), s.len())..], offset + end, words, errors);
+    }
+}
+
+pub fn lexer<E: Error<PreProcessed, Span = Range<usize>>>(
+) -> impl Parser<PreProcessed, Vec<Word>, Error = E> {
+    cancellable_lexer(CancellationToken::new())
+}
+
+/// Like [`lexer`], but fails before every word once `cancel` is cancelled.
+pub fn cancellable_lexer<E: Error<PreProcessed, Span = Range<usize>>>(
+    cancel: CancellationToken,
+) -> impl Parser<PreProcessed, Vec<Word>, Error = E> {
+    cancellable_spanned_lexer(cancel).map(|words| words.into_iter().map(|(word, _)| word).collect())
+}
+
+/// The spans of the words of `s` in its characters, found without building the words.
+/// Consecutive digits are separate words.
+pub fn word_boundaries(s: &str) -> Result<Vec<Range<usize>>, Vec<LexError>> {
+    match crate::dfa::boundaries(s) {
+        Some(boundaries) => Ok(boundaries),
+        None => lex(s).map(|_| Vec::new()),
+    }
+}
+
+/// Splits `s` into the text of its words: `"pedonapei"` gives `["pe", "dona", "pei"]`.
+pub fn split_words(s: &str) -> Result<Vec<&str>, Vec<LexError>> {
+    let bytes: Vec<usize> = s
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(iter::once(s.len()))
+        .collect();
+    let byte = |char: usize| bytes.get(char).copied().unwrap_or(s.len());
+    Ok(word_boundaries(s)?
+        .into_iter()
+        .map(|span| s.get(byte(span.start)..byte(span.end)).unwrap_or_default())
+        .collect())
+}
+
+/// Like [`lexer`], also returning the span of each word in the characters of the text given to
+/// [`preprocess`]. Spans do not include the pauses before words.
+pub fn spanned_lexer<E: Error<PreProcessed, Span = Range<usize>>>(
+) -> impl Parser<PreProcessed, Vec<(Word, Range<usize>)>, Error = E> {
+    cancellable_spanned_lexer(CancellationToken::new())
+}
+
+/// Like [`spanned_lexer`], but fails before every word once `cancel` is cancelled.
+pub fn cancellable_spanned_lexer<E: Error<PreProcessed, Span = Range<usize>>>(
+    cancel: CancellationToken,
+) -> impl Parser<PreProcessed, Vec<(Word, Range<usize>)>, Error = E> {
+    configured_lexer(cancel, PhonologyTables::STANDARD)
+}
+
+/// Like [`lexer`], but allowing the consonant pairs of `tables` instead of the standard ones.
+pub fn lexer_with_tables<E: Error<PreProcessed, Span = Range<usize>>>(
+    tables: PhonologyTables,
+) -> impl Parser<PreProcessed, Vec<Word>, Error = E> {
+    spanned_lexer_with_tables(tables).map(|words| words.into_iter().map(|(word, _)| word).collect())
+}
+
+/// Like [`spanned_lexer`], but allowing the consonant pairs of `tables` instead of the standard
+/// ones.
+pub fn spanned_lexer_with_tables<E: Error<PreProcessed, Span = Range<usize>>>(
+    tables: PhonologyTables,
+) -> impl Parser<PreProcessed, Vec<(Word, Range<usize>)>, Error = E> {
+    configured_lexer(CancellationToken::new(), tables)
 }
 
-pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, Error = E> {
+fn configured_lexer<E: Error<PreProcessed, Span = Range<usize>>>(
+    cancel: CancellationToken,
+    tables: PhonologyTables,
+) -> impl Parser<PreProcessed, Vec<(Word, Range<usize>)>, Error = E> {
     let pause = filter::<_, _, E>(|PreProcessed(ref c)| c.is_whitespace() || *c == '\'').repeated();
     let letter = |c: char| just(PreProcessed(c));
 
@@ -205,8 +1932,15 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
     let non_sonorant = choice::<_, E>(NON_SONORANT.map(letter));
     let sonorant = choice::<_, E>(SONORANT.map(letter));
 
-    let initial_pair = choice::<_, E>(INITIAL_PAIRS.map(|(a, b)| letter(a).then(letter(b))));
-    let medial_pair = choice::<_, E>(MEDIAL_PAIRS.map(|(a, b)| letter(a).then(letter(b))));
+    let pairs = |pairs: &mut dyn Iterator<Item = (char, char)>| {
+        choice::<_, E>(
+            pairs
+                .map(|(a, b)| letter(a).then(letter(b)))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let initial_pair = pairs(&mut tables.initial_pairs());
+    let medial_pair = pairs(&mut tables.medial_pairs());
 
     let nonsonorant_particle = pause.ignore_then(
         non_sonorant
@@ -223,30 +1957,7 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
                     .map(|(vowel, vhowels)| vowel.into_iter().chain(vhowels.into_iter().flatten())),
             )
             .map(|(c, vh)| {
-                let word: String = iter::once(c).chain(vh).map(|PreProcessed(c)| c).collect();
-                match c.0 {
-                    'k' => ParticleFamily::Ki(word),
-                    'g' => ParticleFamily::Gi(PredicateWord {
-                        chaining: if word.starts_with("gi") {
-                            ChainingBehavior {
-                                var: 0,
-                                chain_with: PredicateChaining::Sharing,
-                            }
-                        } else if word.ends_with('i') {
-                            ChainingBehavior {
-                                var: 1,
-                                chain_with: PredicateChaining::Equivalence,
-                            }
-                        } else {
-                            ChainingBehavior {
-                                var: 1,
-                                chain_with: PredicateChaining::Sharing,
-                            }
-                        },
-                        word,
-                    }),
-                    _ => ParticleFamily::Other(word),
-                }
+                nonsonorant_family(iter::once(c).chain(vh).map(|PreProcessed(c)| c).collect())
             }),
     );
     let sonorant_or_vowel_particle = pause.at_least(1).ignore_then(
@@ -262,7 +1973,7 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
         )
         .then(sonorant.or_not())
         .map(|(((a, b), c), d)| {
-            ParticleFamily::Other(
+            sonorant_or_vowel_family(
                 a.into_iter()
                     .chain(iter::once(b))
                     .chain(c.into_iter().flatten())
@@ -274,40 +1985,25 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
     );
 
     let mi = pause
-        .ignore_then(choice(
-            [
-                "mai", "mao", "mui", "mue", "mua", "mio", "mie", "moe", "ma", "mi", "mo", "me",
-            ]
-            .map(|s| just(s.chars().map(PreProcessed).collect::<Vec<_>>())),
-        ))
-        .map(|w| {
-            let word: String = w.into_iter().map(|PreProcessed(c)| c).collect();
-            ParticleFamily::Mi(PredicateWord {
-                chaining: match &*word {
-                    "mao" => ChainingBehavior {
-                        var: 1,
-                        chain_with: PredicateChaining::Equivalence,
-                    },
-                    "mua" => ChainingBehavior {
-                        var: 1,
-                        chain_with: PredicateChaining::Equivalence,
-                    },
-                    _ => ChainingBehavior {
-                        var: 0,
-                        chain_with: PredicateChaining::Sharing,
-                    },
-                },
-                word,
-            })
-        });
+        .ignore_then(letter('m').chain(vowel.repeated().at_least(1)))
+        .map(|w| ParticleFamily::Mi(pronoun(w.into_iter().map(|PreProcessed(c)| c).collect())));
 
-    let arg_vowel = choice(['e', 'a', 'o', 'u'].map(letter)).map(|PreProcessed(v)| match v {
-        'e' => 0,
-        'a' => 1,
-        'o' => 2,
-        'u' => 3,
-        _ => unreachable!(),
-    });
+    let arg_vowel =
+        choice(ARG_VOWELS.map(|v| {
+            letter(v).to(ARG_VOWELS.iter().position(|a| *a == v).unwrap_or(0) as GrammarVar)
+        }));
+    // A place as `place_text` spells it, failing past the last place a `GrammarVar` holds.
+    let place = arg_vowel
+        .then(letter('h').ignore_then(arg_vowel).repeated())
+        .try_map(|(first, rest), span| {
+            rest.into_iter()
+                .try_fold(first, |var: GrammarVar, digit| {
+                    var.checked_add(1)?
+                        .checked_mul(ARG_VOWELS.len() as GrammarVar)?
+                        .checked_add(digit)
+                })
+                .ok_or_else(|| E::expected_input_found(span, None, None))
+        });
     let si = pause.ignore_then(
         letter('s').ignore_then(choice((
             letter('i')
@@ -332,11 +2028,7 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
             ))
             .map(|((vs, b), i)| ParticleFamily::Si {
                 chaining: ChainingBehavior {
-                    var: if let Some(i) = b {
-                        i
-                    } else {
-                        *vs.last().unwrap()
-                    },
+                    var: b.or(vs.last().copied()).unwrap_or(0),
                     chain_with: if i.is_some() {
                         PredicateChaining::Equivalence
                     } else {
@@ -350,7 +2042,7 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
 
     let vi = pause.ignore_then(choice((
         letter('v')
-            .ignore_then(letter('i').ignored().or_not().then(arg_vowel))
+            .ignore_then(letter('i').ignored().or_not().then(place))
             .map(|(i, var_i)| ParticleFamily::Vi {
                 var: Some(var_i),
                 chain_with: if i.is_some() {
@@ -382,7 +2074,7 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
             chain_with: PredicateChaining::Equivalence,
         }),
         letter('f')
-            .ignore_then(letter('i').ignored().or_not().then(arg_vowel))
+            .ignore_then(letter('i').ignored().or_not().then(place))
             .map(|(i, var_i)| ParticleFamily::Fi {
                 var: FiVar::Var(var_i),
                 chain_with: if i.is_some() {
@@ -413,13 +2105,38 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
     let bi = pause
         .then(just(['b', 'i'].map(PreProcessed)))
         .to(ParticleFamily::Bi);
-    let zi = pause
-        .ignore_then(choice(
-            ["zi"].map(|s| just(s.chars().map(PreProcessed).collect::<Vec<_>>())),
-        ))
-        .map(|w| ParticleFamily::Zi(w.into_iter().map(|PreProcessed(c)| c).collect()));
+    let is_pause = |PreProcessed(c): &PreProcessed| c.is_whitespace() || *c == '\'';
+    let text = |cs: Vec<PreProcessed>| cs.into_iter().map(|PreProcessed(c)| c).collect::<String>();
+    let quoted_word = filter::<_, _, E>(move |c| !is_pause(c))
+        .repeated()
+        .at_least(1)
+        .map(text);
+    let ce = pause
+        .then(just(['c', 'e'].map(PreProcessed)))
+        .then(pause.at_least(1))
+        .ignore_then(quoted_word)
+        .map(|word| ParticleFamily::Quote(Quote::Word(word)));
+    let cu = pause
+        .then(just(['c', 'u'].map(PreProcessed)))
+        .then(pause.at_least(1))
+        .ignore_then(quoted_word)
+        .then_with(move |delimiter: String| {
+            let closing = pause
+                .at_least(1)
+                .then(just(
+                    delimiter.chars().map(PreProcessed).collect::<Vec<_>>(),
+                ))
+                .then(choice((filter(is_pause).ignored(), end())).rewind());
+            take_until(closing).map(move |(content, _)| {
+                let content = text(content.into_iter().skip_while(is_pause).collect());
+                ParticleFamily::Quote(Quote::Foreign {
+                    delimiter: delimiter.clone(),
+                    content,
+                })
+            })
+        });
 
-    let specific_particle = choice((pei, pe, be, vei, vi, fi, mi, si, bi, zi));
+    let specific_particle = choice((ce, cu, pei, pe, be, vei, vi, fi, mi, si, bi));
 
     let particle = choice((
         specific_particle,
@@ -429,7 +2146,7 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
     .map(Word::Particle);
 
     let root_mix = choice((
-        medial_pair.map(|(a, b)| (a, Some(b))),
+        medial_pair.clone().map(|(a, b)| (a, Some(b))),
         choice((letter('h'), sonorant)).map(|c| (c, None)),
     ))
     .then(vowel.repeated().at_least(1))
@@ -444,13 +2161,14 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
         .chain(vowel.repeated().at_least(1))
         .then(choice((
             choice((
-                required_string,
+                required_string.clone(),
                 root_mix
+                    .clone()
                     .repeated()
                     .chain::<Vec<PreProcessed>, _, _>(required_string)
                     .flatten(),
             ))
-            .chain::<Vec<PreProcessed>, _, _>(root_mix.repeated())
+            .chain::<Vec<PreProcessed>, _, _>(root_mix.clone().repeated())
             .flatten()
             .then(sonorant.or_not()),
             sonorant.map(|s| (Vec::new(), Some(s))),
@@ -463,23 +2181,7 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
                 .collect()
         })
         .map(|w: String| {
-            let last = w.chars().last().unwrap();
-            let chaining = if last == 'i' {
-                ChainingBehavior {
-                    var: 1,
-                    chain_with: PredicateChaining::Equivalence,
-                }
-            } else if VOWELS.contains(&last) {
-                ChainingBehavior {
-                    var: 1,
-                    chain_with: PredicateChaining::Sharing,
-                }
-            } else {
-                ChainingBehavior {
-                    var: 0,
-                    chain_with: PredicateChaining::Sharing,
-                }
-            };
+            let chaining = chaining_of(&w, ChainingFamily::Root);
             (w, chaining)
         });
     let initial_pair_root = initial_pair
@@ -494,32 +2196,79 @@ pub fn lexer<E: Error<PreProcessed>>() -> impl Parser<PreProcessed, Vec<Word>, E
                 .collect()
         })
         .map(|w: String| {
-            let last = w.chars().last().unwrap();
-            let chaining = if last == 'i' || w.len() == 3 {
-                ChainingBehavior {
-                    var: 1,
-                    chain_with: PredicateChaining::Equivalence,
-                }
-            } else if VOWELS.contains(&last) {
-                ChainingBehavior {
-                    var: 1,
-                    chain_with: PredicateChaining::Sharing,
-                }
-            } else {
-                ChainingBehavior {
-                    var: 0,
-                    chain_with: PredicateChaining::Sharing,
-                }
-            };
+            let chaining = chaining_of(&w, ChainingFamily::Root);
             (w, chaining)
         });
     let root = pause.ignore_then(
         choice((nonsonorant_root, initial_pair_root)).map(|(w, c)| (w, c, PredicateFamily::Root)),
     );
-    let predicate = choice([root])
+    // A whole run of letters ending in a non-sonorant, which no other word ends in. It is tried
+    // before any other word, so a run such as `alis` is not read as words up to its last letter.
+    let freeform = pause.ignore_then(
+        filter(|PreProcessed(c): &PreProcessed| {
+            VOWELS.contains(c) || NON_SONORANT.contains(c) || SONORANT.contains(c) || *c == 'h'
+        })
+        .repeated()
+        .at_least(1)
+        .then_ignore(
+            filter(|PreProcessed(c): &PreProcessed| c.is_whitespace() || *c == '\'')
+                .ignored()
+                .or(end())
+                .rewind(),
+        )
+        .try_map(|letters, span| {
+            let w: String = letters.into_iter().map(|PreProcessed(c)| c).collect();
+            is_freeform(&w)
+                .then(|| {
+                    let chaining = chaining_of(&w, ChainingFamily::Freeform);
+                    (w, chaining, PredicateFamily::Freeform)
+                })
+                .ok_or_else(|| E::expected_input_found(span, None, None))
+        }),
+    );
+    let predicate = choice((freeform, root))
         .map(|(word, chaining, family)| Word::Predicate(PredicateWord { word, chaining }, family));
 
-    let word = choice((predicate, particle));
+    // The offset of the first letter after the pause, without consuming anything.
+    let start = pause
+        .ignore_then(empty().map_with_span(|(), span: Range<usize>| span.start))
+        .rewind();
+    let word = checkpoint(cancel).ignore_then(traced!(
+        "word",
+        start.then(choice((predicate, particle))).map_with_span(
+            |(start, word): (usize, Word), span: Range<usize>| {
+                trace!(Token { text: word.text() });
+                (word, start..span.end)
+            }
+        )
+    ));
+
+    word.repeated()
+        .then_ignore(pause.then(end()))
+        .map(assemble_spanned_numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn lexed_text_lexes_back(s in "(ce|cu|vi|fe|ge|ie|mo|bure|dona|mian|alis|pe|pei|be|ba|zi|ni|a|e| |')*") {
+            if let Ok(words) = lex(&s) {
+                let text = to_text(&words);
+                prop_assert_eq!(lex(&text), Ok(words), "{:?} written as {:?}", s, text);
+            }
+        }
 
-    word.repeated().then_ignore(pause.then(end()))
+        #[test]
+        fn recovering_lexes_as_lex(s in "(ce|cu|vi|fe|ge|ie|mo|bure|dona|mian|alis|pe|pei|be|ba|zi|ni|pa|re|a|e|[a-z]| |')*") {
+            let (words, errors) = lex_recovering(&s);
+            match lex(&s) {
+                Ok(lexed) => prop_assert_eq!((words, errors), (lexed, Vec::new()), "{:?}", s),
+                Err(_) => prop_assert!(!errors.is_empty(), "{:?}", s),
+            }
+        }
+    }
 }
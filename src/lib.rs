@@ -1,6 +1,128 @@
+// Public entry points must report malformed input as errors rather than panicking; the fuzz
+// target in `fuzz/` exercises the lexer → parser → expr pipeline to keep it that way.
+#![deny(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::unreachable,
+    clippy::todo,
+    clippy::unimplemented
+)]
+
+/// Emits a [`trace::Event`] when the `tracing` feature is enabled.
+macro_rules! trace {
+    ($($event:tt)*) => {
+        #[cfg(feature = "tracing")]
+        crate::trace::emit(|| crate::trace::Event::$($event)*);
+    };
+}
+
+/// Wraps a grammar rule with [`trace::traced`] when the `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+macro_rules! traced {
+    ($rule:expr, $p:expr) => {
+        crate::trace::traced($rule, $p)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! traced {
+    ($rule:expr, $p:expr) => {
+        $p
+    };
+}
+
+pub mod ambiguity;
+pub mod attachment;
+pub mod build;
+pub mod cache;
+pub mod cancel;
+pub mod chunked;
+pub mod coinage;
+pub mod collisions;
+pub mod compat;
+pub mod conformance;
+pub mod cst;
+pub mod decompose;
+pub mod dfa;
+pub mod diagnostics;
+pub mod dictionary;
+pub mod diff;
+pub mod discourse;
+pub mod explain;
 pub mod expr;
+pub mod fixit;
+pub mod format;
+pub mod generate;
+pub mod gloss;
+pub mod grammar;
+#[cfg(feature = "handwritten-parser")]
+pub mod handwritten;
+#[cfg(feature = "reference-harness")]
+pub mod harness;
+pub mod highlight;
+pub mod incremental;
+pub mod json;
 pub mod lexer;
+pub mod normalize;
 pub mod parser;
+pub mod phonotactics;
+pub mod provenance;
+pub mod render;
+pub mod roundtrip;
+pub mod sanitize;
+pub mod segment;
+pub mod semantics;
+pub mod spelling;
+pub mod stats;
+pub mod stream;
+pub mod testcorpus;
+#[cfg(feature = "tracing")]
+pub mod trace;
+pub mod transliterate;
+pub mod unparse;
+pub mod visit;
+
+pub use lexer::LexError;
+pub use parser::ParseError;
+
+use std::fmt;
+
+/// Why [`parse`] or [`parse_to_expr`] failed: the text does not lex, or its words do not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EberbanError {
+    Lex(Vec<LexError>),
+    Parse(Vec<ParseError>),
+}
+
+impl fmt::Display for EberbanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = match self {
+            EberbanError::Lex(errors) => errors.iter().map(ToString::to_string).collect(),
+            EberbanError::Parse(errors) => errors.iter().map(ToString::to_string).collect(),
+        };
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for EberbanError {}
+
+/// Lexes and parses a sentence, with its addressees and mood.
+pub fn parse_sentence(text: &str) -> Result<parser::Sentence, EberbanError> {
+    let words = lexer::lex(text).map_err(EberbanError::Lex)?;
+    parser::parse(words).map_err(EberbanError::Parse)
+}
+
+/// Lexes and parses a sentence into its predicate tree, leaving out its addressees and mood.
+pub fn parse(text: &str) -> Result<parser::PredicateTree, EberbanError> {
+    parse_sentence(text).map(|sentence| sentence.tree)
+}
+
+/// Lexes and parses a sentence and interprets it with [`expr::sentence_to_expr`], so that the
+/// operator of its mood wraps the predicate.
+pub fn parse_to_expr(text: &str) -> Result<(expr::Predicate, Vec<expr::Var>), EberbanError> {
+    parse_sentence(text).map(expr::sentence_to_expr)
+}
 
 pub type GrammarVar = u8;
 
@@ -21,7 +143,20 @@ pub enum Exposure {
     Standard,
     Transparent,
     Modified(Vec<GrammarVar>),
-    Explicit(Vec<(String, PredicateChaining)>),
+    Explicit(Vec<(Argument, PredicateChaining)>),
+}
+
+/// A particle of an argument list, naming the value of a place.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Argument {
+    /// A `ki` or `gi` particle, which stands for the value in the rest of the predicate.
+    Word(String),
+    /// A number, saying how many things fill the place.
+    Number(u64),
+    /// A freeform word naming the one thing filling the place.
+    Name(String),
+    /// Quoted material, whose predicate shares the place as it would if chained to it.
+    Quote(lexer::Quote),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -56,3 +191,12 @@ impl std::ops::BitXor for Negation {
         Self::new(short, long)
     }
 }
+
+/// How many of the things filling a place a chained predicate is said to hold of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Quantifier {
+    All,
+    Some,
+    Most,
+    Exactly(u64),
+}
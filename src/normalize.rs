@@ -0,0 +1,530 @@
+use std::fmt;
+
+/// What [`normalize`] does with letters that carry diacritics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Strictness {
+    /// Diacritics are stripped, leaving the base letter.
+    #[default]
+    Strip,
+    /// Letters with diacritics are kept, composed, so that lexing fails on them.
+    Flag,
+}
+
+/// A change made or a problem found by [`normalize`], with the offset (in characters) of the
+/// affected character in the original text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NormalizeAction {
+    /// A typographic apostrophe or prime was replaced by the pause mark `'`.
+    ApostropheToPause { offset: usize, character: char },
+    /// A full-width letter or digit was replaced by its ASCII form.
+    FullWidthFolded { offset: usize, character: char },
+    /// A letter lost its diacritics. `character` is the letter as composed, or the first
+    /// combining mark if none composed with the letter.
+    DiacriticStripped { offset: usize, character: char },
+    /// A letter with diacritics was kept, with [`Strictness::Flag`].
+    DiacriticFlagged { offset: usize, character: char },
+}
+
+impl fmt::Display for NormalizeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NormalizeAction::ApostropheToPause { offset, character } => {
+                write!(f, "{offset}: replaced `{character}` with `'`")
+            }
+            NormalizeAction::FullWidthFolded { offset, character } => {
+                write!(f, "{offset}: replaced full-width `{character}`")
+            }
+            NormalizeAction::DiacriticStripped { offset, character } => {
+                write!(f, "{offset}: stripped the diacritics of `{character}`")
+            }
+            NormalizeAction::DiacriticFlagged { offset, character } => {
+                write!(f, "{offset}: `{character}` has diacritics")
+            }
+        }
+    }
+}
+
+fn is_apostrophe(c: char) -> bool {
+    matches!(
+        c,
+        '`' | '\u{00B4}' | '\u{02B9}' | '\u{02BB}'
+            ..='\u{02BD}'
+                | '\u{02C8}'
+                | '\u{2018}'
+                | '\u{2019}'
+                | '\u{201B}'
+                | '\u{2032}'
+                | '\u{FF07}'
+    )
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}')
+}
+
+/// The ASCII form of a full-width letter or digit.
+fn full_width(c: char) -> Option<char> {
+    match c {
+        '\u{FF10}'..='\u{FF19}' | '\u{FF21}'..='\u{FF3A}' | '\u{FF41}'..='\u{FF5A}' => {
+            char::from_u32(u32::from(c) - 0xFEE0)
+        }
+        _ => None,
+    }
+}
+
+/// The precomposed form of `base` followed by the combining `mark`, if there is one.
+fn compose(base: char, mark: char) -> Option<char> {
+    COMPOSITIONS
+        .iter()
+        .find(|(_, b, m)| *b == base && *m == mark)
+        .map(|(c, _, _)| *c)
+}
+
+/// `c` without any of its diacritics.
+fn strip(mut c: char) -> char {
+    while let Some((_, base, _)) = COMPOSITIONS.iter().find(|(composed, _, _)| *composed == c) {
+        c = *base;
+    }
+    c
+}
+
+/// Brings text typed with typographic or non-Latin conventions to the alphabet
+/// [`preprocess`](crate::lexer::preprocess) reads, which only folds ASCII case. Typographic
+/// apostrophes become pauses and full-width letters and digits become ASCII. Combining marks
+/// are composed with the letter before them where Unicode has a precomposed form, as NFC does,
+/// and diacritics are then handled according to `strictness`. Every change is reported, in
+/// order, along with every letter flagged.
+///
+/// Stripped combining marks are removed, so offsets into the result may differ from offsets
+/// into `s`; offsets of actions are always into `s`.
+pub fn normalize(s: &str, strictness: Strictness) -> (String, Vec<NormalizeAction>) {
+    let mut out = String::with_capacity(s.len());
+    let mut actions = Vec::new();
+    let mut chars = s.chars().enumerate().peekable();
+    while let Some((offset, character)) = chars.next() {
+        if is_apostrophe(character) {
+            actions.push(NormalizeAction::ApostropheToPause { offset, character });
+            out.push('\'');
+            continue;
+        }
+        let mut letter = match full_width(character) {
+            Some(c) => {
+                actions.push(NormalizeAction::FullWidthFolded { offset, character });
+                c
+            }
+            None => character,
+        };
+        // Marks that do not compose with the letter, with their offsets.
+        let mut marks = Vec::new();
+        if !is_combining_mark(letter) {
+            while let Some((mark_offset, mark)) = chars.next_if(|(_, c)| is_combining_mark(*c)) {
+                match compose(letter, mark) {
+                    Some(composed) if marks.is_empty() => letter = composed,
+                    _ => marks.push((mark_offset, mark)),
+                }
+            }
+        }
+        let base = if is_combining_mark(letter) {
+            None
+        } else {
+            Some(strip(letter))
+        };
+        if base == Some(letter) && marks.is_empty() {
+            out.push(letter);
+            continue;
+        }
+        let (offset, character) = match marks.first() {
+            Some(&first) if base == Some(letter) => first,
+            _ => (offset, letter),
+        };
+        match strictness {
+            Strictness::Strip => {
+                actions.push(NormalizeAction::DiacriticStripped { offset, character });
+                out.extend(base);
+            }
+            Strictness::Flag => {
+                actions.push(NormalizeAction::DiacriticFlagged { offset, character });
+                out.push(letter);
+                out.extend(marks.iter().map(|(_, mark)| mark));
+            }
+        }
+    }
+    (out, actions)
+}
+
+/// The canonical compositions of a letter of the alphabet, in either case, and a combining
+/// mark, from the Latin-1 Supplement, Latin Extended-A and Latin Extended Additional blocks. The
+/// first letter of a composition may itself be composed.
+const COMPOSITIONS: [(char, char, char); 371] = [
+    ('\u{00C0}', 'A', '\u{0300}'),
+    ('\u{00C1}', 'A', '\u{0301}'),
+    ('\u{00C2}', 'A', '\u{0302}'),
+    ('\u{00C3}', 'A', '\u{0303}'),
+    ('\u{00C4}', 'A', '\u{0308}'),
+    ('\u{00C5}', 'A', '\u{030A}'),
+    ('\u{00C7}', 'C', '\u{0327}'),
+    ('\u{00C8}', 'E', '\u{0300}'),
+    ('\u{00C9}', 'E', '\u{0301}'),
+    ('\u{00CA}', 'E', '\u{0302}'),
+    ('\u{00CB}', 'E', '\u{0308}'),
+    ('\u{00CC}', 'I', '\u{0300}'),
+    ('\u{00CD}', 'I', '\u{0301}'),
+    ('\u{00CE}', 'I', '\u{0302}'),
+    ('\u{00CF}', 'I', '\u{0308}'),
+    ('\u{00D1}', 'N', '\u{0303}'),
+    ('\u{00D2}', 'O', '\u{0300}'),
+    ('\u{00D3}', 'O', '\u{0301}'),
+    ('\u{00D4}', 'O', '\u{0302}'),
+    ('\u{00D5}', 'O', '\u{0303}'),
+    ('\u{00D6}', 'O', '\u{0308}'),
+    ('\u{00D9}', 'U', '\u{0300}'),
+    ('\u{00DA}', 'U', '\u{0301}'),
+    ('\u{00DB}', 'U', '\u{0302}'),
+    ('\u{00DC}', 'U', '\u{0308}'),
+    ('\u{00E0}', 'a', '\u{0300}'),
+    ('\u{00E1}', 'a', '\u{0301}'),
+    ('\u{00E2}', 'a', '\u{0302}'),
+    ('\u{00E3}', 'a', '\u{0303}'),
+    ('\u{00E4}', 'a', '\u{0308}'),
+    ('\u{00E5}', 'a', '\u{030A}'),
+    ('\u{00E7}', 'c', '\u{0327}'),
+    ('\u{00E8}', 'e', '\u{0300}'),
+    ('\u{00E9}', 'e', '\u{0301}'),
+    ('\u{00EA}', 'e', '\u{0302}'),
+    ('\u{00EB}', 'e', '\u{0308}'),
+    ('\u{00EC}', 'i', '\u{0300}'),
+    ('\u{00ED}', 'i', '\u{0301}'),
+    ('\u{00EE}', 'i', '\u{0302}'),
+    ('\u{00EF}', 'i', '\u{0308}'),
+    ('\u{00F1}', 'n', '\u{0303}'),
+    ('\u{00F2}', 'o', '\u{0300}'),
+    ('\u{00F3}', 'o', '\u{0301}'),
+    ('\u{00F4}', 'o', '\u{0302}'),
+    ('\u{00F5}', 'o', '\u{0303}'),
+    ('\u{00F6}', 'o', '\u{0308}'),
+    ('\u{00F9}', 'u', '\u{0300}'),
+    ('\u{00FA}', 'u', '\u{0301}'),
+    ('\u{00FB}', 'u', '\u{0302}'),
+    ('\u{00FC}', 'u', '\u{0308}'),
+    ('\u{0100}', 'A', '\u{0304}'),
+    ('\u{0101}', 'a', '\u{0304}'),
+    ('\u{0102}', 'A', '\u{0306}'),
+    ('\u{0103}', 'a', '\u{0306}'),
+    ('\u{0104}', 'A', '\u{0328}'),
+    ('\u{0105}', 'a', '\u{0328}'),
+    ('\u{0106}', 'C', '\u{0301}'),
+    ('\u{0107}', 'c', '\u{0301}'),
+    ('\u{0108}', 'C', '\u{0302}'),
+    ('\u{0109}', 'c', '\u{0302}'),
+    ('\u{010A}', 'C', '\u{0307}'),
+    ('\u{010B}', 'c', '\u{0307}'),
+    ('\u{010C}', 'C', '\u{030C}'),
+    ('\u{010D}', 'c', '\u{030C}'),
+    ('\u{010E}', 'D', '\u{030C}'),
+    ('\u{010F}', 'd', '\u{030C}'),
+    ('\u{0112}', 'E', '\u{0304}'),
+    ('\u{0113}', 'e', '\u{0304}'),
+    ('\u{0114}', 'E', '\u{0306}'),
+    ('\u{0115}', 'e', '\u{0306}'),
+    ('\u{0116}', 'E', '\u{0307}'),
+    ('\u{0117}', 'e', '\u{0307}'),
+    ('\u{0118}', 'E', '\u{0328}'),
+    ('\u{0119}', 'e', '\u{0328}'),
+    ('\u{011A}', 'E', '\u{030C}'),
+    ('\u{011B}', 'e', '\u{030C}'),
+    ('\u{011C}', 'G', '\u{0302}'),
+    ('\u{011D}', 'g', '\u{0302}'),
+    ('\u{011E}', 'G', '\u{0306}'),
+    ('\u{011F}', 'g', '\u{0306}'),
+    ('\u{0120}', 'G', '\u{0307}'),
+    ('\u{0121}', 'g', '\u{0307}'),
+    ('\u{0122}', 'G', '\u{0327}'),
+    ('\u{0123}', 'g', '\u{0327}'),
+    ('\u{0124}', 'H', '\u{0302}'),
+    ('\u{0125}', 'h', '\u{0302}'),
+    ('\u{0128}', 'I', '\u{0303}'),
+    ('\u{0129}', 'i', '\u{0303}'),
+    ('\u{012A}', 'I', '\u{0304}'),
+    ('\u{012B}', 'i', '\u{0304}'),
+    ('\u{012C}', 'I', '\u{0306}'),
+    ('\u{012D}', 'i', '\u{0306}'),
+    ('\u{012E}', 'I', '\u{0328}'),
+    ('\u{012F}', 'i', '\u{0328}'),
+    ('\u{0130}', 'I', '\u{0307}'),
+    ('\u{0134}', 'J', '\u{0302}'),
+    ('\u{0135}', 'j', '\u{0302}'),
+    ('\u{0136}', 'K', '\u{0327}'),
+    ('\u{0137}', 'k', '\u{0327}'),
+    ('\u{0139}', 'L', '\u{0301}'),
+    ('\u{013A}', 'l', '\u{0301}'),
+    ('\u{013B}', 'L', '\u{0327}'),
+    ('\u{013C}', 'l', '\u{0327}'),
+    ('\u{013D}', 'L', '\u{030C}'),
+    ('\u{013E}', 'l', '\u{030C}'),
+    ('\u{0143}', 'N', '\u{0301}'),
+    ('\u{0144}', 'n', '\u{0301}'),
+    ('\u{0145}', 'N', '\u{0327}'),
+    ('\u{0146}', 'n', '\u{0327}'),
+    ('\u{0147}', 'N', '\u{030C}'),
+    ('\u{0148}', 'n', '\u{030C}'),
+    ('\u{014C}', 'O', '\u{0304}'),
+    ('\u{014D}', 'o', '\u{0304}'),
+    ('\u{014E}', 'O', '\u{0306}'),
+    ('\u{014F}', 'o', '\u{0306}'),
+    ('\u{0150}', 'O', '\u{030B}'),
+    ('\u{0151}', 'o', '\u{030B}'),
+    ('\u{0154}', 'R', '\u{0301}'),
+    ('\u{0155}', 'r', '\u{0301}'),
+    ('\u{0156}', 'R', '\u{0327}'),
+    ('\u{0157}', 'r', '\u{0327}'),
+    ('\u{0158}', 'R', '\u{030C}'),
+    ('\u{0159}', 'r', '\u{030C}'),
+    ('\u{015A}', 'S', '\u{0301}'),
+    ('\u{015B}', 's', '\u{0301}'),
+    ('\u{015C}', 'S', '\u{0302}'),
+    ('\u{015D}', 's', '\u{0302}'),
+    ('\u{015E}', 'S', '\u{0327}'),
+    ('\u{015F}', 's', '\u{0327}'),
+    ('\u{0160}', 'S', '\u{030C}'),
+    ('\u{0161}', 's', '\u{030C}'),
+    ('\u{0162}', 'T', '\u{0327}'),
+    ('\u{0163}', 't', '\u{0327}'),
+    ('\u{0164}', 'T', '\u{030C}'),
+    ('\u{0165}', 't', '\u{030C}'),
+    ('\u{0168}', 'U', '\u{0303}'),
+    ('\u{0169}', 'u', '\u{0303}'),
+    ('\u{016A}', 'U', '\u{0304}'),
+    ('\u{016B}', 'u', '\u{0304}'),
+    ('\u{016C}', 'U', '\u{0306}'),
+    ('\u{016D}', 'u', '\u{0306}'),
+    ('\u{016E}', 'U', '\u{030A}'),
+    ('\u{016F}', 'u', '\u{030A}'),
+    ('\u{0170}', 'U', '\u{030B}'),
+    ('\u{0171}', 'u', '\u{030B}'),
+    ('\u{0172}', 'U', '\u{0328}'),
+    ('\u{0173}', 'u', '\u{0328}'),
+    ('\u{0179}', 'Z', '\u{0301}'),
+    ('\u{017A}', 'z', '\u{0301}'),
+    ('\u{017B}', 'Z', '\u{0307}'),
+    ('\u{017C}', 'z', '\u{0307}'),
+    ('\u{017D}', 'Z', '\u{030C}'),
+    ('\u{017E}', 'z', '\u{030C}'),
+    ('\u{1E00}', 'A', '\u{0325}'),
+    ('\u{1E01}', 'a', '\u{0325}'),
+    ('\u{1E02}', 'B', '\u{0307}'),
+    ('\u{1E03}', 'b', '\u{0307}'),
+    ('\u{1E04}', 'B', '\u{0323}'),
+    ('\u{1E05}', 'b', '\u{0323}'),
+    ('\u{1E06}', 'B', '\u{0331}'),
+    ('\u{1E07}', 'b', '\u{0331}'),
+    ('\u{1E08}', '\u{00C7}', '\u{0301}'),
+    ('\u{1E09}', '\u{00E7}', '\u{0301}'),
+    ('\u{1E0A}', 'D', '\u{0307}'),
+    ('\u{1E0B}', 'd', '\u{0307}'),
+    ('\u{1E0C}', 'D', '\u{0323}'),
+    ('\u{1E0D}', 'd', '\u{0323}'),
+    ('\u{1E0E}', 'D', '\u{0331}'),
+    ('\u{1E0F}', 'd', '\u{0331}'),
+    ('\u{1E10}', 'D', '\u{0327}'),
+    ('\u{1E11}', 'd', '\u{0327}'),
+    ('\u{1E12}', 'D', '\u{032D}'),
+    ('\u{1E13}', 'd', '\u{032D}'),
+    ('\u{1E14}', '\u{0112}', '\u{0300}'),
+    ('\u{1E15}', '\u{0113}', '\u{0300}'),
+    ('\u{1E16}', '\u{0112}', '\u{0301}'),
+    ('\u{1E17}', '\u{0113}', '\u{0301}'),
+    ('\u{1E18}', 'E', '\u{032D}'),
+    ('\u{1E19}', 'e', '\u{032D}'),
+    ('\u{1E1A}', 'E', '\u{0330}'),
+    ('\u{1E1B}', 'e', '\u{0330}'),
+    ('\u{1E1C}', '\u{0228}', '\u{0306}'),
+    ('\u{1E1D}', '\u{0229}', '\u{0306}'),
+    ('\u{1E1E}', 'F', '\u{0307}'),
+    ('\u{1E1F}', 'f', '\u{0307}'),
+    ('\u{1E20}', 'G', '\u{0304}'),
+    ('\u{1E21}', 'g', '\u{0304}'),
+    ('\u{1E22}', 'H', '\u{0307}'),
+    ('\u{1E23}', 'h', '\u{0307}'),
+    ('\u{1E24}', 'H', '\u{0323}'),
+    ('\u{1E25}', 'h', '\u{0323}'),
+    ('\u{1E26}', 'H', '\u{0308}'),
+    ('\u{1E27}', 'h', '\u{0308}'),
+    ('\u{1E28}', 'H', '\u{0327}'),
+    ('\u{1E29}', 'h', '\u{0327}'),
+    ('\u{1E2A}', 'H', '\u{032E}'),
+    ('\u{1E2B}', 'h', '\u{032E}'),
+    ('\u{1E2C}', 'I', '\u{0330}'),
+    ('\u{1E2D}', 'i', '\u{0330}'),
+    ('\u{1E2E}', '\u{00CF}', '\u{0301}'),
+    ('\u{1E2F}', '\u{00EF}', '\u{0301}'),
+    ('\u{1E30}', 'K', '\u{0301}'),
+    ('\u{1E31}', 'k', '\u{0301}'),
+    ('\u{1E32}', 'K', '\u{0323}'),
+    ('\u{1E33}', 'k', '\u{0323}'),
+    ('\u{1E34}', 'K', '\u{0331}'),
+    ('\u{1E35}', 'k', '\u{0331}'),
+    ('\u{1E36}', 'L', '\u{0323}'),
+    ('\u{1E37}', 'l', '\u{0323}'),
+    ('\u{1E38}', '\u{1E36}', '\u{0304}'),
+    ('\u{1E39}', '\u{1E37}', '\u{0304}'),
+    ('\u{1E3A}', 'L', '\u{0331}'),
+    ('\u{1E3B}', 'l', '\u{0331}'),
+    ('\u{1E3C}', 'L', '\u{032D}'),
+    ('\u{1E3D}', 'l', '\u{032D}'),
+    ('\u{1E3E}', 'M', '\u{0301}'),
+    ('\u{1E3F}', 'm', '\u{0301}'),
+    ('\u{1E40}', 'M', '\u{0307}'),
+    ('\u{1E41}', 'm', '\u{0307}'),
+    ('\u{1E42}', 'M', '\u{0323}'),
+    ('\u{1E43}', 'm', '\u{0323}'),
+    ('\u{1E44}', 'N', '\u{0307}'),
+    ('\u{1E45}', 'n', '\u{0307}'),
+    ('\u{1E46}', 'N', '\u{0323}'),
+    ('\u{1E47}', 'n', '\u{0323}'),
+    ('\u{1E48}', 'N', '\u{0331}'),
+    ('\u{1E49}', 'n', '\u{0331}'),
+    ('\u{1E4A}', 'N', '\u{032D}'),
+    ('\u{1E4B}', 'n', '\u{032D}'),
+    ('\u{1E4C}', '\u{00D5}', '\u{0301}'),
+    ('\u{1E4D}', '\u{00F5}', '\u{0301}'),
+    ('\u{1E4E}', '\u{00D5}', '\u{0308}'),
+    ('\u{1E4F}', '\u{00F5}', '\u{0308}'),
+    ('\u{1E50}', '\u{014C}', '\u{0300}'),
+    ('\u{1E51}', '\u{014D}', '\u{0300}'),
+    ('\u{1E52}', '\u{014C}', '\u{0301}'),
+    ('\u{1E53}', '\u{014D}', '\u{0301}'),
+    ('\u{1E54}', 'P', '\u{0301}'),
+    ('\u{1E55}', 'p', '\u{0301}'),
+    ('\u{1E56}', 'P', '\u{0307}'),
+    ('\u{1E57}', 'p', '\u{0307}'),
+    ('\u{1E58}', 'R', '\u{0307}'),
+    ('\u{1E59}', 'r', '\u{0307}'),
+    ('\u{1E5A}', 'R', '\u{0323}'),
+    ('\u{1E5B}', 'r', '\u{0323}'),
+    ('\u{1E5C}', '\u{1E5A}', '\u{0304}'),
+    ('\u{1E5D}', '\u{1E5B}', '\u{0304}'),
+    ('\u{1E5E}', 'R', '\u{0331}'),
+    ('\u{1E5F}', 'r', '\u{0331}'),
+    ('\u{1E60}', 'S', '\u{0307}'),
+    ('\u{1E61}', 's', '\u{0307}'),
+    ('\u{1E62}', 'S', '\u{0323}'),
+    ('\u{1E63}', 's', '\u{0323}'),
+    ('\u{1E64}', '\u{015A}', '\u{0307}'),
+    ('\u{1E65}', '\u{015B}', '\u{0307}'),
+    ('\u{1E66}', '\u{0160}', '\u{0307}'),
+    ('\u{1E67}', '\u{0161}', '\u{0307}'),
+    ('\u{1E68}', '\u{1E62}', '\u{0307}'),
+    ('\u{1E69}', '\u{1E63}', '\u{0307}'),
+    ('\u{1E6A}', 'T', '\u{0307}'),
+    ('\u{1E6B}', 't', '\u{0307}'),
+    ('\u{1E6C}', 'T', '\u{0323}'),
+    ('\u{1E6D}', 't', '\u{0323}'),
+    ('\u{1E6E}', 'T', '\u{0331}'),
+    ('\u{1E6F}', 't', '\u{0331}'),
+    ('\u{1E70}', 'T', '\u{032D}'),
+    ('\u{1E71}', 't', '\u{032D}'),
+    ('\u{1E72}', 'U', '\u{0324}'),
+    ('\u{1E73}', 'u', '\u{0324}'),
+    ('\u{1E74}', 'U', '\u{0330}'),
+    ('\u{1E75}', 'u', '\u{0330}'),
+    ('\u{1E76}', 'U', '\u{032D}'),
+    ('\u{1E77}', 'u', '\u{032D}'),
+    ('\u{1E78}', '\u{0168}', '\u{0301}'),
+    ('\u{1E79}', '\u{0169}', '\u{0301}'),
+    ('\u{1E7A}', '\u{016A}', '\u{0308}'),
+    ('\u{1E7B}', '\u{016B}', '\u{0308}'),
+    ('\u{1E7C}', 'V', '\u{0303}'),
+    ('\u{1E7D}', 'v', '\u{0303}'),
+    ('\u{1E7E}', 'V', '\u{0323}'),
+    ('\u{1E7F}', 'v', '\u{0323}'),
+    ('\u{1E90}', 'Z', '\u{0302}'),
+    ('\u{1E91}', 'z', '\u{0302}'),
+    ('\u{1E92}', 'Z', '\u{0323}'),
+    ('\u{1E93}', 'z', '\u{0323}'),
+    ('\u{1E94}', 'Z', '\u{0331}'),
+    ('\u{1E95}', 'z', '\u{0331}'),
+    ('\u{1E96}', 'h', '\u{0331}'),
+    ('\u{1E97}', 't', '\u{0308}'),
+    ('\u{1EA0}', 'A', '\u{0323}'),
+    ('\u{1EA1}', 'a', '\u{0323}'),
+    ('\u{1EA2}', 'A', '\u{0309}'),
+    ('\u{1EA3}', 'a', '\u{0309}'),
+    ('\u{1EA4}', '\u{00C2}', '\u{0301}'),
+    ('\u{1EA5}', '\u{00E2}', '\u{0301}'),
+    ('\u{1EA6}', '\u{00C2}', '\u{0300}'),
+    ('\u{1EA7}', '\u{00E2}', '\u{0300}'),
+    ('\u{1EA8}', '\u{00C2}', '\u{0309}'),
+    ('\u{1EA9}', '\u{00E2}', '\u{0309}'),
+    ('\u{1EAA}', '\u{00C2}', '\u{0303}'),
+    ('\u{1EAB}', '\u{00E2}', '\u{0303}'),
+    ('\u{1EAC}', '\u{1EA0}', '\u{0302}'),
+    ('\u{1EAD}', '\u{1EA1}', '\u{0302}'),
+    ('\u{1EAE}', '\u{0102}', '\u{0301}'),
+    ('\u{1EAF}', '\u{0103}', '\u{0301}'),
+    ('\u{1EB0}', '\u{0102}', '\u{0300}'),
+    ('\u{1EB1}', '\u{0103}', '\u{0300}'),
+    ('\u{1EB2}', '\u{0102}', '\u{0309}'),
+    ('\u{1EB3}', '\u{0103}', '\u{0309}'),
+    ('\u{1EB4}', '\u{0102}', '\u{0303}'),
+    ('\u{1EB5}', '\u{0103}', '\u{0303}'),
+    ('\u{1EB6}', '\u{1EA0}', '\u{0306}'),
+    ('\u{1EB7}', '\u{1EA1}', '\u{0306}'),
+    ('\u{1EB8}', 'E', '\u{0323}'),
+    ('\u{1EB9}', 'e', '\u{0323}'),
+    ('\u{1EBA}', 'E', '\u{0309}'),
+    ('\u{1EBB}', 'e', '\u{0309}'),
+    ('\u{1EBC}', 'E', '\u{0303}'),
+    ('\u{1EBD}', 'e', '\u{0303}'),
+    ('\u{1EBE}', '\u{00CA}', '\u{0301}'),
+    ('\u{1EBF}', '\u{00EA}', '\u{0301}'),
+    ('\u{1EC0}', '\u{00CA}', '\u{0300}'),
+    ('\u{1EC1}', '\u{00EA}', '\u{0300}'),
+    ('\u{1EC2}', '\u{00CA}', '\u{0309}'),
+    ('\u{1EC3}', '\u{00EA}', '\u{0309}'),
+    ('\u{1EC4}', '\u{00CA}', '\u{0303}'),
+    ('\u{1EC5}', '\u{00EA}', '\u{0303}'),
+    ('\u{1EC6}', '\u{1EB8}', '\u{0302}'),
+    ('\u{1EC7}', '\u{1EB9}', '\u{0302}'),
+    ('\u{1EC8}', 'I', '\u{0309}'),
+    ('\u{1EC9}', 'i', '\u{0309}'),
+    ('\u{1ECA}', 'I', '\u{0323}'),
+    ('\u{1ECB}', 'i', '\u{0323}'),
+    ('\u{1ECC}', 'O', '\u{0323}'),
+    ('\u{1ECD}', 'o', '\u{0323}'),
+    ('\u{1ECE}', 'O', '\u{0309}'),
+    ('\u{1ECF}', 'o', '\u{0309}'),
+    ('\u{1ED0}', '\u{00D4}', '\u{0301}'),
+    ('\u{1ED1}', '\u{00F4}', '\u{0301}'),
+    ('\u{1ED2}', '\u{00D4}', '\u{0300}'),
+    ('\u{1ED3}', '\u{00F4}', '\u{0300}'),
+    ('\u{1ED4}', '\u{00D4}', '\u{0309}'),
+    ('\u{1ED5}', '\u{00F4}', '\u{0309}'),
+    ('\u{1ED6}', '\u{00D4}', '\u{0303}'),
+    ('\u{1ED7}', '\u{00F4}', '\u{0303}'),
+    ('\u{1ED8}', '\u{1ECC}', '\u{0302}'),
+    ('\u{1ED9}', '\u{1ECD}', '\u{0302}'),
+    ('\u{1EDA}', '\u{01A0}', '\u{0301}'),
+    ('\u{1EDB}', '\u{01A1}', '\u{0301}'),
+    ('\u{1EDC}', '\u{01A0}', '\u{0300}'),
+    ('\u{1EDD}', '\u{01A1}', '\u{0300}'),
+    ('\u{1EDE}', '\u{01A0}', '\u{0309}'),
+    ('\u{1EDF}', '\u{01A1}', '\u{0309}'),
+    ('\u{1EE0}', '\u{01A0}', '\u{0303}'),
+    ('\u{1EE1}', '\u{01A1}', '\u{0303}'),
+    ('\u{1EE2}', '\u{01A0}', '\u{0323}'),
+    ('\u{1EE3}', '\u{01A1}', '\u{0323}'),
+    ('\u{1EE4}', 'U', '\u{0323}'),
+    ('\u{1EE5}', 'u', '\u{0323}'),
+    ('\u{1EE6}', 'U', '\u{0309}'),
+    ('\u{1EE7}', 'u', '\u{0309}'),
+    ('\u{1EE8}', '\u{01AF}', '\u{0301}'),
+    ('\u{1EE9}', '\u{01B0}', '\u{0301}'),
+    ('\u{1EEA}', '\u{01AF}', '\u{0300}'),
+    ('\u{1EEB}', '\u{01B0}', '\u{0300}'),
+    ('\u{1EEC}', '\u{01AF}', '\u{0309}'),
+    ('\u{1EED}', '\u{01B0}', '\u{0309}'),
+    ('\u{1EEE}', '\u{01AF}', '\u{0303}'),
+    ('\u{1EEF}', '\u{01B0}', '\u{0303}'),
+    ('\u{1EF0}', '\u{01AF}', '\u{0323}'),
+    ('\u{1EF1}', '\u{01B0}', '\u{0323}'),
+];
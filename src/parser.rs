@@ -1,13 +1,21 @@
-use std::collections::BTreeSet;
+use std::{cell::Cell, fmt, ops::Range, rc::Rc, str::FromStr};
 
 use chumsky::{
-    prelude::{choice, end, filter, just, recursive},
-    Error, Parser,
+    error::Cheap,
+    prelude::{choice, empty, end, filter, recursive, BoxedParser},
+    select, Error, Parser, Stream,
 };
 
 use crate::{
-    lexer::{FiVar, ParticleFamily, PredicateWord, Word},
-    ChainingBehavior, Exposure, Negation, PredicateChaining,
+    cancel::{checkpoint, CancellationToken},
+    cst::{argument, count, derive_sentence, Child, Elided, Node, NodeKind, Token},
+    grammar::{representatives, role},
+    lexer::{
+        AbstractionKind, ConnectiveKind, FiVar, Interjection, Mood, Number, ParticleFamily,
+        PredicateFamily, PredicateWord, QuantifierKind, Tag, Word,
+    },
+    visit::Order,
+    Argument, ChainingBehavior, Exposure, GrammarVar, Negation, PredicateChaining, Quantifier,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -15,48 +23,267 @@ pub enum PredicateTree {
     Leaf {
         word: PredicateWord,
         negation: Negation,
+        /// The free modifiers after the word, and after the particles up to the next leaf,
+        /// number or name, in order. Those at the start of a sentence are on its first leaf,
+        /// number or name.
+        free: Vec<Modifier>,
     },
     Binding {
         chaining: ChainingBehavior,
         root: Box<PredicateTree>,
         negation: Negation,
         exposure: Exposure,
-        sharers: Vec<BTreeSet<(PredicateChaining, PredicateTree)>>,
-        and: BTreeSet<PredicateTree>,
+        /// The trees chained to each place, in the order they are written. A tree chained twice
+        /// to the same place is kept twice.
+        sharers: Vec<Vec<(PredicateChaining, PredicateTree)>>,
+        /// The trees chained after the whole binding, in the order they are written.
+        and: Vec<PredicateTree>,
+        /// The trees attached to places of the root by a tag, saying when, where, how or why
+        /// it holds, in the order they are written.
+        tags: Vec<(Tag, PredicateTree)>,
+    },
+    /// Two predicate trees joined by a connective particle, filling the same places.
+    Connective {
+        kind: ConnectiveKind,
+        negation: Negation,
+        left: Box<PredicateTree>,
+        right: Box<PredicateTree>,
+    },
+    /// A number standing for a predicate, which holds of as many things as it says.
+    Number {
+        value: u64,
+        negation: Negation,
+        /// The free modifiers after the number, as after the word of a [`Leaf`](Self::Leaf).
+        free: Vec<Modifier>,
+    },
+    /// A freeform word naming an individual, which holds only of that individual.
+    Name {
+        word: PredicateWord,
+        negation: Negation,
+        /// The free modifiers after the name, as after the word of a [`Leaf`](Self::Leaf).
+        free: Vec<Modifier>,
+    },
+    /// A subordinate clause, holding of the event, proposition or property it describes.
+    Abstraction {
+        kind: AbstractionKind,
+        negation: Negation,
+        tree: Box<PredicateTree>,
+    },
+    /// A chained predicate tree quantifying over the place it is chained to.
+    Quantified {
+        quantifier: Quantifier,
+        tree: Box<PredicateTree>,
+    },
+}
+
+/// A free modifier, kept on the leaf, number or name it follows so that renderers can write it
+/// back. Free modifiers do not change the meaning of a sentence.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Modifier {
+    Interjection(Interjection),
+    Illocution(Mood),
+    /// A vocative, the free modifiers between it and what it addresses, and the leaf or group it
+    /// addresses, whose own free modifiers it keeps.
+    Vocative {
+        word: String,
+        free: Vec<Modifier>,
+        addressee: PredicateTree,
     },
 }
 
 impl PredicateTree {
     pub fn chaining_behavior(&self) -> ChainingBehavior {
-        match self {
-            PredicateTree::Leaf {
-                word: PredicateWord { chaining, .. },
-                ..
-            } => *chaining,
-            PredicateTree::Binding { chaining, .. } => *chaining,
+        let mut tree = self;
+        loop {
+            match tree {
+                PredicateTree::Leaf {
+                    word: PredicateWord { chaining, .. },
+                    ..
+                } => return *chaining,
+                PredicateTree::Binding { chaining, .. } => return *chaining,
+                PredicateTree::Number { .. }
+                | PredicateTree::Name { .. }
+                | PredicateTree::Abstraction { .. } => {
+                    return ChainingBehavior {
+                        var: 0,
+                        chain_with: PredicateChaining::Sharing,
+                    }
+                }
+                PredicateTree::Connective { left, .. } => tree = left,
+                PredicateTree::Quantified { tree: inner, .. } => tree = inner,
+            }
         }
     }
+    /// Applies `f` to the tree under the quantifiers of this one, keeping the quantifiers, so
+    /// that the trees under many of them are reached without recursing.
+    fn map_quantified(self, f: impl FnOnce(Self) -> Self) -> Self {
+        let mut quantifiers = Vec::new();
+        let mut tree = self;
+        while let PredicateTree::Quantified {
+            quantifier,
+            tree: inner,
+        } = tree
+        {
+            quantifiers.push(quantifier);
+            tree = *inner;
+        }
+        quantifiers
+            .into_iter()
+            .rev()
+            .fold(f(tree), |tree, quantifier| PredicateTree::Quantified {
+                quantifier,
+                tree: Box::new(tree),
+            })
+    }
     pub fn to_binding(self) -> Self {
-        match self {
-            b @ PredicateTree::Binding { .. } => b,
-            l @ PredicateTree::Leaf { negation, .. } => PredicateTree::Binding {
+        self.map_quantified(|tree| match tree {
+            b @ (PredicateTree::Binding { .. } | PredicateTree::Quantified { .. }) => b,
+            l @ (PredicateTree::Leaf { negation, .. }
+            | PredicateTree::Number { negation, .. }
+            | PredicateTree::Name { negation, .. }
+            | PredicateTree::Abstraction { negation, .. }
+            | PredicateTree::Connective { negation, .. }) => PredicateTree::Binding {
                 chaining: l.chaining_behavior(),
                 root: Box::new(l),
                 negation,
                 exposure: Exposure::Standard,
                 sharers: Vec::new(),
-                and: BTreeSet::new(),
+                and: Vec::new(),
+                tags: Vec::new(),
+            },
+        })
+    }
+    pub fn with_exposure(self, exposure: Exposure, chaining: ChainingBehavior) -> Self {
+        self.map_quantified(|tree| match tree {
+            PredicateTree::Binding {
+                root,
+                negation,
+                sharers,
+                and,
+                tags,
+                ..
+            } => PredicateTree::Binding {
+                chaining,
+                root,
+                negation,
+                exposure,
+                sharers,
+                and,
+                tags,
             },
+            q @ PredicateTree::Quantified { .. } => q,
+            l @ (PredicateTree::Leaf { negation, .. }
+            | PredicateTree::Number { negation, .. }
+            | PredicateTree::Name { negation, .. }
+            | PredicateTree::Abstraction { negation, .. }
+            | PredicateTree::Connective { negation, .. }) => PredicateTree::Binding {
+                chaining,
+                root: Box::new(l),
+                negation,
+                exposure,
+                sharers: Vec::new(),
+                and: Vec::new(),
+                tags: Vec::new(),
+            },
+        })
+    }
+    /// Rewrites the negations of this tree and the trees nested in it into a normal form. An even
+    /// number of `zi` or of `bi` already cancels out as the tree is read. On top of that, a leaf
+    /// or name, or a connective of them, keeps only its short negation: a long one is hoisted to
+    /// a binding of the tree with nothing bound, which scopes over the same predicates. Long
+    /// negations are thus left on bindings, and on numbers and abstractions, which open a
+    /// variable of their own where nothing fills their place that a binding would close under
+    /// the negation. The meaning of the tree is unchanged.
+    pub fn normalize_negation(&mut self) {
+        self.visit_mut(Order::Post, &mut |tree| {
+            if !tree.opens_no_variable() {
+                return;
+            }
+            let long = match tree {
+                PredicateTree::Leaf { negation, .. }
+                | PredicateTree::Name { negation, .. }
+                | PredicateTree::Connective { negation, .. } => {
+                    let long = negation.long();
+                    *negation = Negation::new(negation.short(), false);
+                    long
+                }
+                _ => false,
+            };
+            if long {
+                let root = std::mem::replace(
+                    tree,
+                    PredicateTree::Number {
+                        value: 0,
+                        negation: Negation::None,
+                        free: Vec::new(),
+                    },
+                );
+                *tree = PredicateTree::Binding {
+                    chaining: root.chaining_behavior(),
+                    root: Box::new(root),
+                    negation: Negation::Long,
+                    exposure: Exposure::Standard,
+                    sharers: Vec::new(),
+                    and: Vec::new(),
+                    tags: Vec::new(),
+                };
+            }
+        });
+    }
+
+    /// Whether this tree is a leaf or name, or a connective of them.
+    fn opens_no_variable(&self) -> bool {
+        let mut trees = vec![self];
+        while let Some(tree) = trees.pop() {
+            match tree {
+                PredicateTree::Leaf { .. } | PredicateTree::Name { .. } => {}
+                PredicateTree::Connective { left, right, .. } => trees.extend([&**left, &**right]),
+                _ => return false,
+            }
         }
+        true
     }
+
     pub fn negate(self, orig_negation: Negation) -> Self {
         if orig_negation == Negation::None {
             self
         } else {
-            match self {
-                PredicateTree::Leaf { word, negation } => PredicateTree::Leaf {
+            self.map_quantified(|tree| match tree {
+                PredicateTree::Leaf {
+                    word,
+                    negation,
+                    free,
+                } => PredicateTree::Leaf {
                     word,
                     negation: orig_negation ^ negation,
+                    free,
+                },
+                PredicateTree::Number {
+                    value,
+                    negation,
+                    free,
+                } => PredicateTree::Number {
+                    value,
+                    negation: orig_negation ^ negation,
+                    free,
+                },
+                PredicateTree::Name {
+                    word,
+                    negation,
+                    free,
+                } => PredicateTree::Name {
+                    word,
+                    negation: orig_negation ^ negation,
+                    free,
+                },
+                PredicateTree::Abstraction {
+                    kind,
+                    negation,
+                    tree,
+                } => PredicateTree::Abstraction {
+                    kind,
+                    negation: orig_negation ^ negation,
+                    tree,
                 },
                 PredicateTree::Binding {
                     chaining,
@@ -65,6 +292,7 @@ impl PredicateTree {
                     exposure,
                     sharers,
                     and,
+                    tags,
                 } => PredicateTree::Binding {
                     chaining,
                     root,
@@ -72,227 +300,1255 @@ impl PredicateTree {
                     exposure,
                     sharers,
                     and,
+                    tags,
                 },
+                PredicateTree::Connective {
+                    kind,
+                    negation,
+                    left,
+                    right,
+                } => PredicateTree::Connective {
+                    kind,
+                    negation: orig_negation ^ negation,
+                    left,
+                    right,
+                },
+                q @ PredicateTree::Quantified { .. } => q,
+            })
+        }
+    }
+}
+
+/// Predicate trees with the spans of their sentences.
+pub type SpannedTrees = Vec<(PredicateTree, Range<usize>)>;
+
+/// The parser implementations that can be selected at runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ParserBackend {
+    /// The parser built from combinators by [`parser`].
+    #[default]
+    Combinator,
+    /// The hand-written recursive-descent parser in [`crate::handwritten`].
+    #[cfg(feature = "handwritten-parser")]
+    Handwritten,
+}
+
+impl ParserBackend {
+    pub fn parse(self, words: Vec<Word>) -> Result<PredicateTree, Vec<Cheap<Word>>> {
+        match self {
+            ParserBackend::Combinator => parser().parse(words),
+            #[cfg(feature = "handwritten-parser")]
+            ParserBackend::Handwritten => crate::handwritten::parse(&words),
+        }
+    }
+
+    pub fn parse_sentence(self, words: Vec<Word>) -> Result<Sentence, Vec<Cheap<Word>>> {
+        match self {
+            ParserBackend::Combinator => sentence_parser().parse(words),
+            #[cfg(feature = "handwritten-parser")]
+            ParserBackend::Handwritten => crate::handwritten::parse_sentence(&words),
+        }
+    }
+
+    /// Parses words with their spans, as returned by
+    /// [`spanned_lexer`](crate::lexer::spanned_lexer), so that errors point at the text rather
+    /// than at token indices.
+    pub fn parse_spanned(
+        self,
+        words: Vec<(Word, Range<usize>)>,
+    ) -> Result<PredicateTree, Vec<Cheap<Word>>> {
+        let end = words.last().map_or(0, |(_, span)| span.end);
+        match self {
+            ParserBackend::Combinator => {
+                parser().parse(Stream::from_iter(end..end, words.into_iter()))
+            }
+            #[cfg(feature = "handwritten-parser")]
+            ParserBackend::Handwritten => {
+                let (words, spans): (Vec<_>, Vec<_>) = words.into_iter().unzip();
+                crate::handwritten::parse(&words).map_err(|errors| {
+                    errors
+                        .into_iter()
+                        .map(|e| {
+                            let span = spans.get(e.span().start).cloned().unwrap_or(end..end);
+                            Cheap::expected_input_found(span, None, None)
+                        })
+                        .collect()
+                })
+            }
+        }
+    }
+
+    /// Parses a text of several sentences, as [`text_parser`] does, from words with their spans.
+    /// Each predicate tree is returned with the span of its sentence in the text.
+    pub fn parse_text(
+        self,
+        words: Vec<(Word, Range<usize>)>,
+    ) -> Result<SpannedTrees, Vec<Cheap<Word>>> {
+        let end = words.last().map_or(0, |(_, span)| span.end);
+        match self {
+            ParserBackend::Combinator => {
+                text_parser().parse(Stream::from_iter(end..end, words.into_iter()))
+            }
+            #[cfg(feature = "handwritten-parser")]
+            ParserBackend::Handwritten => {
+                let (words, spans): (Vec<_>, Vec<_>) = words.into_iter().unzip();
+                let at = |i: usize| spans.get(i).cloned().unwrap_or(end..end);
+                match crate::handwritten::parse_text(&words) {
+                    Ok(trees) => Ok(trees
+                        .into_iter()
+                        .map(|(tree, span)| (tree, at(span.start).start..at(span.end - 1).end))
+                        .collect()),
+                    Err(errors) => Err(errors
+                        .into_iter()
+                        .map(|e| Cheap::expected_input_found(at(e.span().start), None, None))
+                        .collect()),
+                }
             }
         }
     }
 }
 
-pub fn parser<E: Error<Word> + 'static>() -> impl Parser<Word, PredicateTree, Error = E> {
-    let predicate = filter(|w: &Word| {
-        matches!(
-            w,
-            Word::Predicate { .. }
-                | Word::Particle(
-                    ParticleFamily::Ki(_) | ParticleFamily::Gi(_) | ParticleFamily::Mi(_)
-                )
-        )
+impl FromStr for ParserBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "combinator" => Ok(ParserBackend::Combinator),
+            #[cfg(feature = "handwritten-parser")]
+            "handwritten" => Ok(ParserBackend::Handwritten),
+            _ => Err(format!("unknown or disabled parser backend `{s}`")),
+        }
+    }
+}
+
+/// A sentence that failed to parse: the word at which it did, and what could have come there
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// The span of the offending word among the words of the sentence, or the empty span at its
+    /// end.
+    pub span: Range<usize>,
+    /// The offending word, or `None` at the end of the sentence.
+    pub found: Option<Word>,
+    /// The families of the words that could have come instead, by name: predicate families
+    /// first, then particle families in the order of [`grammar`](crate::grammar::grammar).
+    /// Free modifiers, which can come anywhere, are left out (see
+    /// [`Role::is_free`](crate::grammar::Role::is_free)).
+    pub expected: Vec<&'static str>,
+    /// Whether the sentence could have ended instead.
+    pub end_expected: bool,
+    /// Whether the word starts a predicate tree nested more than [`DEFAULT_MAX_DEPTH`] deep, in
+    /// which case nothing is expected instead.
+    pub too_deep: bool,
+}
+
+impl ParseError {
+    /// Finds what could have come at word `at` of `words` by trying a word of every family
+    /// there.
+    pub fn classify(words: &[Word], at: usize) -> Self {
+        let at = at.min(words.len());
+        let prefix = &words[..at];
+        let accepts = |word: Word| {
+            let mut words = prefix.to_vec();
+            words.push(word);
+            match sentence_parser::<Cheap<Word>>().parse(words) {
+                Ok(_) => true,
+                Err(errors) => errors.iter().any(|e| e.span().start > at),
+            }
+        };
+        let predicate = |family: PredicateFamily| {
+            let word = PredicateWord {
+                word: "dona".to_string(),
+                chaining: ChainingBehavior {
+                    var: 0,
+                    chain_with: PredicateChaining::Sharing,
+                },
+            };
+            (family.name(), Word::Predicate(word, family))
+        };
+        let mut expected = Vec::new();
+        let candidates = [
+            predicate(PredicateFamily::Root),
+            predicate(PredicateFamily::Borrowing),
+            predicate(PredicateFamily::Freeform),
+        ]
+        .into_iter()
+        .chain(
+            representatives()
+                .into_iter()
+                .filter(|p| !role(p).is_free())
+                .map(|p| (p.name(), Word::Particle(p))),
+        );
+        for (name, word) in candidates {
+            if !expected.contains(&name) && accepts(word) {
+                expected.push(name);
+            }
+        }
+        ParseError {
+            span: if at < words.len() { at..at + 1 } else { at..at },
+            found: words.get(at).cloned(),
+            expected,
+            end_expected: sentence_parser::<Cheap<Word>>()
+                .parse(prefix.to_vec())
+                .is_ok(),
+            too_deep: false,
+        }
+    }
+
+    /// The error at word `at` of `words`, where a predicate tree starts nested too deep.
+    fn too_deep(words: &[Word], at: usize) -> Self {
+        let at = at.min(words.len());
+        ParseError {
+            span: if at < words.len() { at..at + 1 } else { at..at },
+            found: words.get(at).cloned(),
+            expected: Vec::new(),
+            end_expected: false,
+            too_deep: true,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.found {
+            Some(word) => write!(f, "unexpected `{word}`")?,
+            None => write!(f, "unexpected end of sentence")?,
+        }
+        write!(f, " at {}..{}", self.span.start, self.span.end)?;
+        if self.too_deep {
+            return write!(
+                f,
+                ", nested more than {DEFAULT_MAX_DEPTH} predicate trees deep"
+            );
+        }
+        let end = self.end_expected.then_some("end of sentence");
+        let expected: Vec<&str> = self.expected.iter().copied().chain(end).collect();
+        if let Some((last, rest)) = expected.split_last() {
+            write!(f, ", expected ")?;
+            if !rest.is_empty() {
+                write!(f, "{} or ", rest.join(", "))?;
+            }
+            write!(f, "{last}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the words of a sentence with [`sentence_parser`], reporting each error as a
+/// [`ParseError`].
+pub fn parse(words: Vec<Word>) -> Result<Sentence, Vec<ParseError>> {
+    let depth = Depth::new(DEFAULT_MAX_DEPTH);
+    sentence_in::<Cheap<Word>>(CancellationToken::new(), ParserOptions::default(), &depth)
+        .then_ignore(end())
+        .parse(words.clone())
+        .map_err(|errors| match depth.exceeded() {
+            Some(span) => vec![ParseError::too_deep(&words, span.start)],
+            None => errors
+                .iter()
+                .map(|e| ParseError::classify(&words, e.span().start))
+                .collect(),
+        })
+}
+
+/// A parsed sentence: its predicate tree, the predicates it is addressed to and its mood.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sentence {
+    /// The predicates or groups after each vocative, in order, wherever the vocatives are.
+    pub addressees: Vec<PredicateTree>,
+    pub tree: PredicateTree,
+    /// The mood set by the first illocution particle, or `None` if the sentence has none.
+    pub mood: Option<Mood>,
+}
+
+/// The constructions of the grammar a parser reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// Every construction of [`CONSTRUCTIONS`](crate::grammar::CONSTRUCTIONS).
+    #[default]
+    Full,
+    /// The predicate trees of sentences alone, without interjections, vocatives or
+    /// illocutions.
+    Core,
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Dialect::Full),
+            "core" => Ok(Dialect::Core),
+            _ => Err(format!("unknown dialect `{s}`")),
+        }
+    }
+}
+
+/// The release of the grammar a parser reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GrammarVersion {
+    /// The grammar of [`CONSTRUCTIONS`](crate::grammar::CONSTRUCTIONS).
+    #[default]
+    Current,
+    /// The grammar of the release before, for texts written against it: it has no quantifier
+    /// or tag particles, and closes `vi` and `fi` clauses with `pei` as well as `vei`.
+    Previous,
+}
+
+impl FromStr for GrammarVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "current" => Ok(GrammarVersion::Current),
+            "previous" => Ok(GrammarVersion::Previous),
+            _ => Err(format!("unknown grammar version `{s}`")),
+        }
+    }
+}
+
+/// The number of predicate trees the parsers read nested in each other unless
+/// [`ParserOptions::max_depth`] says otherwise, and the most it can allow: far more than any
+/// sentence written by hand needs, and few enough that walking the trees they produce
+/// recursively, to interpret, unparse or print them, stays within the 2 MiB stack of a spawned
+/// thread.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// How the parsers of [`ParserOptions::parser`] and its siblings read sentences. By default, as
+/// for [`parser`], terminators can be elided wherever the grammar allows, predicate trees can be
+/// nested up to [`DEFAULT_MAX_DEPTH`] deep, chains can start with a `fi` clause, the
+/// [`Dialect::Full`] grammar of the [`GrammarVersion::Current`] release is read and negations
+/// are kept where they are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParserOptions {
+    strict_terminators: bool,
+    strict_chains: bool,
+    max_depth: usize,
+    dialect: Dialect,
+    version: GrammarVersion,
+    normalize_negation: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            strict_terminators: false,
+            strict_chains: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            dialect: Dialect::default(),
+            version: GrammarVersion::default(),
+            normalize_negation: false,
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether every group and abstraction must be closed by `pei`, and every `vi` or `fi`
+    /// clause by `vei`, even at the end of the sentence.
+    pub fn strict_terminators(self, strict_terminators: bool) -> Self {
+        Self {
+            strict_terminators,
+            ..self
+        }
+    }
+
+    /// Whether every chain must start with a `vi` clause, as the strict reading of the grammar
+    /// has it, rather than with a `vi` or `fi` clause.
+    pub fn strict_chains(self, strict_chains: bool) -> Self {
+        Self {
+            strict_chains,
+            ..self
+        }
+    }
+
+    /// The largest number of predicate trees nested in each other, counting the sentence, every
+    /// group, abstraction and clause, every predicate chained directly to the one before it,
+    /// every operand after a connective and every quantified tree a clause chains to a place,
+    /// until the end of its binding. Deeper sentences fail to parse where the first tree too
+    /// deep starts, without the parser recursing any further, rather than being read in some
+    /// shallower way. Depths above [`DEFAULT_MAX_DEPTH`] are read as [`DEFAULT_MAX_DEPTH`].
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: max_depth.min(DEFAULT_MAX_DEPTH),
+            ..self
+        }
+    }
+
+    pub fn dialect(self, dialect: Dialect) -> Self {
+        Self { dialect, ..self }
+    }
+
+    pub fn version(self, version: GrammarVersion) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Whether the negations of the trees read are rewritten into the normal form of
+    /// [`PredicateTree::normalize_negation`], so that trees with the same meaning compare equal
+    /// however their negations were written.
+    pub fn normalize_negation(self, normalize_negation: bool) -> Self {
+        Self {
+            normalize_negation,
+            ..self
+        }
+    }
+
+    pub fn parser<E: Error<Word, Span = Range<usize>> + 'static>(
+        self,
+    ) -> impl Parser<Word, PredicateTree, Error = E> {
+        self.cancellable_parser(CancellationToken::new())
+    }
+
+    /// Like [`parser`](Self::parser), but fails at every nested predicate tree once `cancel` is
+    /// cancelled.
+    pub fn cancellable_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+        self,
+        cancel: CancellationToken,
+    ) -> impl Parser<Word, PredicateTree, Error = E> {
+        self.cancellable_sentence_parser(cancel)
+            .map(|sentence| sentence.tree)
+    }
+
+    /// Like [`parser`](Self::parser), keeping the addressees of the sentence.
+    pub fn sentence_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+        self,
+    ) -> impl Parser<Word, Sentence, Error = E> {
+        self.cancellable_sentence_parser(CancellationToken::new())
+    }
+
+    /// Like [`sentence_parser`](Self::sentence_parser), but fails at every nested predicate tree
+    /// once `cancel` is cancelled.
+    pub fn cancellable_sentence_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+        self,
+        cancel: CancellationToken,
+    ) -> impl Parser<Word, Sentence, Error = E> {
+        sentence(cancel, self).then_ignore(end())
+    }
+
+    /// Like [`text_parser`], reading each sentence as these options say.
+    pub fn text_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+        self,
+    ) -> impl Parser<Word, SpannedTrees, Error = E> {
+        self.cancellable_text_parser(CancellationToken::new())
+    }
+
+    /// Like [`text_parser`](Self::text_parser), but fails at every nested predicate tree once
+    /// `cancel` is cancelled.
+    pub fn cancellable_text_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+        self,
+        cancel: CancellationToken,
+    ) -> impl Parser<Word, SpannedTrees, Error = E> {
+        let separator = select! {
+            Word::Particle(ParticleFamily::Separator(_)) => (),
+        };
+        separator
+            .repeated()
+            .ignore_then(
+                sentence(cancel, self)
+                    .map_with_span(|sentence, span| (sentence.tree, span))
+                    .separated_by(separator.repeated().at_least(1))
+                    .allow_trailing(),
+            )
+            .then_ignore(end())
+    }
+}
+
+/// The parser of a sentence with the default [`ParserOptions`].
+pub fn parser<E: Error<Word, Span = Range<usize>> + 'static>(
+) -> impl Parser<Word, PredicateTree, Error = E> {
+    cancellable_parser(CancellationToken::new())
+}
+
+/// Like [`parser`], but fails at every nested predicate tree once `cancel` is cancelled.
+pub fn cancellable_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+    cancel: CancellationToken,
+) -> impl Parser<Word, PredicateTree, Error = E> {
+    ParserOptions::default().cancellable_parser(cancel)
+}
+
+/// Like [`parser`], keeping the addressees of the sentence.
+pub fn sentence_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+) -> impl Parser<Word, Sentence, Error = E> {
+    cancellable_sentence_parser(CancellationToken::new())
+}
+
+/// Like [`sentence_parser`], but fails at every nested predicate tree once `cancel` is
+/// cancelled.
+pub fn cancellable_sentence_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+    cancel: CancellationToken,
+) -> impl Parser<Word, Sentence, Error = E> {
+    ParserOptions::default().cancellable_sentence_parser(cancel)
+}
+
+/// Parses a text of sentences separated by sentence-separator particles, returning the
+/// predicate tree of every sentence with its span. Separators before the first sentence, after
+/// the last one and repeated between two sentences are allowed.
+pub fn text_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+) -> impl Parser<Word, SpannedTrees, Error = E> {
+    cancellable_text_parser(CancellationToken::new())
+}
+
+/// Like [`text_parser`], but fails at every nested predicate tree once `cancel` is cancelled.
+pub fn cancellable_text_parser<E: Error<Word, Span = Range<usize>> + 'static>(
+    cancel: CancellationToken,
+) -> impl Parser<Word, SpannedTrees, Error = E> {
+    ParserOptions::default().cancellable_text_parser(cancel)
+}
+
+/// A single sentence, not necessarily followed by the end of the input.
+fn sentence<E: Error<Word, Span = Range<usize>> + 'static>(
+    cancel: CancellationToken,
+    options: ParserOptions,
+) -> impl Parser<Word, Sentence, Error = E> + Clone {
+    sentence_in(cancel, options, &Depth::new(options.max_depth))
+}
+
+/// Like [`sentence`], keeping track of the nesting of its trees in `depth`.
+fn sentence_in<E: Error<Word, Span = Range<usize>> + 'static>(
+    cancel: CancellationToken,
+    options: ParserOptions,
+    depth: &Depth<E::Span>,
+) -> impl Parser<Word, Sentence, Error = E> + Clone {
+    syntax_in(cancel, options, depth).try_map(move |node, span| {
+        let mut sentence =
+            derive_sentence(&node).ok_or_else(|| E::expected_input_found(span, None, None))?;
+        if options.normalize_negation {
+            for tree in std::iter::once(&mut sentence.tree).chain(&mut sentence.addressees) {
+                tree.normalize_negation();
+            }
+        }
+        Ok(sentence)
     })
-    .map(|w| match w {
-        Word::Predicate(pw, _) => pw,
-        Word::Particle(ParticleFamily::Ki(word)) => PredicateWord {
-            word,
-            chaining: ChainingBehavior {
-                var: 0,
-                chain_with: PredicateChaining::Sharing,
-            },
-        },
-        Word::Particle(ParticleFamily::Gi(pw) | ParticleFamily::Mi(pw)) => pw,
-        _ => unreachable!(),
+}
+
+/// The syntax tree of a single sentence, from which [`sentence_in`] derives the sentence, its
+/// words spanned as in the input.
+pub(crate) fn syntax_in<E: Error<Word, Span = Range<usize>> + 'static>(
+    cancel: CancellationToken,
+    options: ParserOptions,
+    depth: &Depth<E::Span>,
+) -> impl Parser<Word, Node, Error = E> + Clone {
+    // Free modifiers can follow every word, and a vocative among them can address a group of
+    // predicate trees, so the trees and the modifiers are read by parsers referring to each
+    // other; `recursive` keeps the reference back weak.
+    let connected = recursive(|connected| {
+        let free = free(options, connected.boxed()).boxed();
+        connected_in(cancel, options, depth, free)
     });
-    let predicate_tree =
-        recursive(|predicate_tree| {
-            let leaf = predicate.map(|word| PredicateTree::Leaf {
+    let free = free(options, connected.clone().boxed());
+    depth
+        .start()
+        .ignore_then(free)
+        .chain(connected)
+        .then_ignore(depth.end())
+        .map(|children| Node::new(NodeKind::Sentence, children))
+}
+
+/// A word `accept` takes, as a token of the syntax tree, with the free modifiers after it.
+fn word<E, P>(
+    accept: impl Fn(&Word) -> bool + Clone + 'static,
+    free: P,
+) -> impl Parser<Word, Vec<Child>, Error = E> + Clone
+where
+    E: Error<Word, Span = Range<usize>> + 'static,
+    P: Parser<Word, Vec<Child>, Error = E> + Clone,
+{
+    filter(accept)
+        .map_with_span(|word, span: Range<usize>| {
+            Child::Token(Token {
                 word,
-                negation: Negation::None,
-            });
-            let pe_pei = predicate_tree.clone().delimited_by(
-                just(Word::Particle(ParticleFamily::Pe)),
-                just(Word::Particle(ParticleFamily::Pei)).or_not(),
-            );
+                index: span.start,
+                span,
+            })
+        })
+        .chain(free)
+}
 
-            let zi = just(Word::Particle(ParticleFamily::Zi("zi".to_string())))
-                .ignored()
-                .repeated()
-                .map(|x| x.len());
-            let si = filter(|w: &Word| matches!(w, Word::Particle(ParticleFamily::Si { .. }))).map(
-                |s| match s {
-                    Word::Particle(ParticleFamily::Si { exposure, chaining }) => {
-                        (exposure, chaining)
-                    }
-                    _ => unreachable!(),
-                },
-            );
-            let element = zi
-                .then(si.or_not())
-                .then(choice((leaf, pe_pei.clone())))
-                .map(|((z, s), p)| {
-                    let p = match s {
-                        None => p,
-                        Some((exposure, chaining)) => {
-                            let mut e = p.to_binding();
-                            match &mut e {
-                                PredicateTree::Binding {
-                                    chaining: old_chaining,
-                                    exposure: old_exposure,
-                                    ..
-                                } => {
-                                    *old_chaining = chaining;
-                                    *old_exposure = exposure;
-                                }
-                                _ => unreachable!(),
-                            }
-                            e
-                        }
-                    };
-                    p.negate(if z % 2 == 0 {
-                        Negation::None
-                    } else {
-                        Negation::Short
-                    })
-                });
-
-            let vi = filter(|w: &Word| matches!(w, Word::Particle(ParticleFamily::Vi { .. }))).map(
-                |w| match w {
-                    Word::Particle(f) => f,
-                    _ => unreachable!(),
+/// The node of the syntax tree of kind `kind` over the children read.
+fn node(kind: NodeKind) -> impl Fn(Vec<Child>) -> Child + Clone {
+    move |children| Child::Node(Node::new(kind, children))
+}
+
+/// A terminator, `particle` or `also`, which is optional unless terminators are strict. When it
+/// is left out, `particle` is elided where it would have been.
+fn terminator<E, P>(
+    options: ParserOptions,
+    particle: ParticleFamily,
+    also: Option<ParticleFamily>,
+    free: P,
+) -> impl Parser<Word, Vec<Child>, Error = E> + Clone
+where
+    E: Error<Word, Span = Range<usize>> + 'static,
+    P: Parser<Word, Vec<Child>, Error = E> + Clone,
+{
+    let accepted = particle.clone();
+    word(
+        move |word| matches!(word, Word::Particle(p) if *p == accepted || also.as_ref() == Some(p)),
+        free,
+    )
+    .repeated()
+    .at_least(options.strict_terminators as usize)
+    .at_most(1)
+    .flatten()
+    .map_with_span(move |children, span: Range<usize>| {
+        if !children.is_empty() {
+            return children;
+        }
+        vec![Child::Elided(Elided {
+            particle: particle.clone(),
+            index: span.start,
+            at: span.start,
+        })]
+    })
+}
+
+/// Connected predicate trees between `pe` and `pei`, with `closed` read after `pei`.
+fn group<E, P, Q>(
+    options: ParserOptions,
+    connected: impl Parser<Word, Child, Error = E> + Clone,
+    free: P,
+    closed: Q,
+) -> impl Parser<Word, Child, Error = E> + Clone
+where
+    E: Error<Word, Span = Range<usize>> + 'static,
+    P: Parser<Word, Vec<Child>, Error = E> + Clone,
+    Q: Parser<Word, Vec<Child>, Error = E> + Clone,
+{
+    word(|word| *word == Word::Particle(ParticleFamily::Pe), free)
+        .chain(connected)
+        .chain(terminator(options, ParticleFamily::Pei, None, closed))
+        .map(node(NodeKind::Group))
+}
+
+/// Interjections, illocutions and vocatives with the leaf or group they address, read after
+/// every word and at the start of a sentence.
+fn free<E: Error<Word, Span = Range<usize>> + 'static>(
+    options: ParserOptions,
+    connected: BoxedParser<'static, Word, Child, E>,
+) -> impl Parser<Word, Vec<Child>, Error = E> + Clone {
+    let full = options.dialect == Dialect::Full;
+    recursive(move |free| {
+        let interjection = word(
+            move |word| full && matches!(word, Word::Particle(ParticleFamily::Interjection(_))),
+            free.clone(),
+        );
+        let illocution = word(
+            move |word| full && matches!(word, Word::Particle(ParticleFamily::Illocution(_))),
+            free.clone(),
+        );
+        let vocative = word(
+            move |word| full && matches!(word, Word::Particle(ParticleFamily::Vocative(_))),
+            free.clone(),
+        );
+        let addressed = word(|word| leaf(word).is_some(), free.clone());
+        // The free modifiers after the `pei` of a group addressed are read as those of the word
+        // the vocative follows, as they would be after a leaf addressed in a group.
+        let group =
+            group(options, connected, free, empty().to(Vec::new())).map(|group| vec![group]);
+        let vocative = traced!(
+            "addressee",
+            vocative
+                .chain(addressed.or(group))
+                .map(node(NodeKind::Addressee))
+                .map(|addressee| vec![addressee])
+        );
+        choice((interjection, illocution, vocative))
+            .repeated()
+            .flatten()
+    })
+}
+
+/// The predicate trees of a sentence joined by connectives, with `free` read after every word.
+fn connected_in<E: Error<Word, Span = Range<usize>> + 'static>(
+    cancel: CancellationToken,
+    options: ParserOptions,
+    depth: &Depth<E::Span>,
+    free: BoxedParser<'static, Word, Vec<Child>, E>,
+) -> impl Parser<Word, Child, Error = E> + Clone {
+    let current = options.version == GrammarVersion::Current;
+    let atom = word(|word| leaf(word).is_some(), free.clone());
+    // Connectives join elements, and chains inside groups and at the top of the sentence.
+    let ba = word(
+        |word| matches!(word, Word::Particle(ParticleFamily::Connective(_))),
+        free.clone(),
+    );
+    // Each operand after a connective is read one tree deeper than the one before it, as the
+    // trees before it are nested in the connective.
+    let joined = |kind: NodeKind, operand: BoxedParser<'static, Word, Child, E>| {
+        operand
+            .clone()
+            .then(ba.clone().then(depth.nested()).then(operand).repeated())
+            .map(move |(first, rest)| {
+                let mut children = vec![first];
+                for ((ba, _nesting), operand) in rest {
+                    children.extend(ba);
+                    children.push(operand);
+                }
+                node(kind)(children)
+            })
+    };
+    // Each level of the grammar reads predicate trees with the trees nested in them read by
+    // `predicate_tree`, or by `clause_tree` for those chained by a clause. The chains of a tree
+    // can start with `fi` if `leading`; those of a tree chained by a clause cannot, so that a
+    // `fi` after it continues the clause.
+    let level = |predicate_tree: BoxedParser<'static, Word, Child, E>,
+                 clause_tree: BoxedParser<'static, Word, Child, E>,
+                 leading: bool| {
+        let connected = traced!(
+            "connective",
+            joined(NodeKind::Connected, predicate_tree.clone())
+        );
+        let pe_pei = group(options, connected.clone(), free.clone(), free.clone());
+        let da = word(
+            |word| matches!(word, Word::Particle(ParticleFamily::Subordinator(_))),
+            free.clone(),
+        );
+        let abstraction = traced!(
+            "abstraction",
+            da.chain(connected)
+                .chain(terminator(options, ParticleFamily::Pei, None, free.clone()))
+                .map(node(NodeKind::Abstraction))
+        );
+
+        let zi = word(
+            |word| matches!(word, Word::Particle(ParticleFamily::Zi(_))),
+            free.clone(),
+        )
+        .repeated()
+        .flatten();
+        let si = word(
+            |word| matches!(word, Word::Particle(ParticleFamily::Si { .. })),
+            free.clone(),
+        );
+        let element = traced!(
+            "element",
+            zi.chain::<Child, _, _>(si.or_not())
+                .chain::<Child, _, _>(choice((
+                    atom.clone(),
+                    pe_pei.map(|group| vec![group]),
+                    abstraction.map(|abstraction| vec![abstraction]),
+                )))
+                .map(node(NodeKind::Element))
+        );
+        let element = joined(NodeKind::Elements, element.boxed());
+
+        let vi = word(
+            |word| matches!(word, Word::Particle(ParticleFamily::Vi { .. })),
+            free.clone(),
+        );
+        let fi = word(
+            |word| matches!(word, Word::Particle(ParticleFamily::Fi { .. })),
+            free.clone(),
+        );
+        // A chain can start with `fi` unless chains are strict.
+        let fi_first = leading && !options.strict_chains;
+        let leading_fi = word(
+            move |word| fi_first && matches!(word, Word::Particle(ParticleFamily::Fi { .. })),
+            free.clone(),
+        );
+        // The previous release closes clauses with `pei` too.
+        let vei = terminator(
+            options,
+            ParticleFamily::Vei,
+            (!current).then_some(ParticleFamily::Pei),
+            free.clone(),
+        );
+
+        let count = word(|word| count(word).is_some(), free.clone());
+        let argument = word(|word| argument(word).is_some(), free.clone());
+        let be = word(
+            |word| *word == Word::Particle(ParticleFamily::Be),
+            free.clone(),
+        );
+        // Quantifiers and tags are not in the previous release.
+        let quantifier = choice((
+            word(
+                move |word| {
+                    current
+                        && *word
+                            == Word::Particle(ParticleFamily::Quantifier(QuantifierKind::Exactly))
                 },
-            );
-            let fi = filter(|w: &Word| matches!(w, Word::Particle(ParticleFamily::Fi { .. }))).map(
-                |w| match w {
-                    Word::Particle(f) => f,
-                    _ => unreachable!(),
+                free.clone(),
+            )
+            .chain(count),
+            word(
+                move |word| {
+                    current
+                        && matches!(
+                            word,
+                            Word::Particle(ParticleFamily::Quantifier(kind))
+                                if *kind != QuantifierKind::Exactly
+                        )
                 },
-            );
-            let vei = just(Word::Particle(ParticleFamily::Vei));
-
-            let argument = choice((
-                filter(|w: &Word| matches!(w, Word::Particle(ParticleFamily::Ki(_)))),
-                filter(|w: &Word| matches!(w, Word::Particle(ParticleFamily::Gi { .. }))),
-            ))
-            .map(|w| match w {
-                Word::Particle(ParticleFamily::Ki(word)) => (word, PredicateChaining::Sharing),
-                Word::Particle(ParticleFamily::Gi(pw)) => (pw.word, PredicateChaining::Equivalence),
-                _ => unreachable!(),
-            });
-            let be = just(Word::Particle(ParticleFamily::Be));
-            let argument_list = argument.repeated().then_ignore(be);
-
-            let bi = just(Word::Particle(ParticleFamily::Bi))
-                .ignored()
+                free.clone(),
+            ),
+        ))
+        .map(node(NodeKind::Quantifier));
+        let chained = |tree: BoxedParser<'static, Word, Child, E>| {
+            traced!(
+                "quantifier",
+                quantifier
+                    .clone()
+                    .or_not()
+                    .chain(tree)
+                    .map(node(NodeKind::Chained))
+            )
+        };
+        let clause_chained = chained(clause_tree.clone());
+        // A quantified tree chained to a place scopes over the rest of the binding, so it counts
+        // as one tree deeper until the binding is read.
+        let sharer_chained = traced!(
+            "quantifier",
+            quantifier
+                .clone()
+                .then(depth.nested())
+                .or_not()
+                .then(clause_tree.clone())
+                .map(|(q, p)| match q {
+                    Some((q, nesting)) => (node(NodeKind::Chained)(vec![q, p]), Some(nesting)),
+                    None => (node(NodeKind::Chained)(vec![p]), None),
+                })
+        );
+        let chained = chained(if leading { predicate_tree } else { clause_tree });
+        let argument_list = traced!(
+            "arguments",
+            argument
                 .repeated()
-                .map(|x| x.len());
+                .flatten()
+                .chain(be)
+                .map(node(NodeKind::Arguments))
+        );
+        let sharer = |binding: BoxedParser<'static, Word, Vec<Child>, E>| {
+            binding
+                .chain(argument_list.clone().or_not())
+                .then(sharer_chained.clone())
+                .map(|(mut children, (chained, nesting))| {
+                    children.push(chained);
+                    (node(NodeKind::Sharer)(children), nesting)
+                })
+        };
+
+        let bi = word(
+            |word| *word == Word::Particle(ParticleFamily::Bi),
+            free.clone(),
+        )
+        .repeated()
+        .flatten();
 
-            let binding = bi
+        let sharers = traced!(
+            "sharers",
+            sharer(vi.or(leading_fi).boxed())
+                .then(sharer(fi.boxed()).repeated())
+                .then(vei.clone())
+                .map(|((first, rest), vei)| {
+                    let (mut children, nestings): (Vec<_>, Vec<_>) =
+                        std::iter::once(first).chain(rest).unzip();
+                    children.extend(vei);
+                    (node(NodeKind::Sharers)(children), nestings)
+                })
+        );
+        let ta = word(
+            move |word| current && matches!(word, Word::Particle(ParticleFamily::Tag(_))),
+            free.clone(),
+        );
+        let adjunct = traced!(
+            "adjunct",
+            ta.chain(clause_chained)
+                .chain(vei)
+                .map(node(NodeKind::Adjunct))
+        );
+        let clause = sharers.or(adjunct.map(|adjunct| (adjunct, Vec::new())));
+
+        traced!(
+            "predicate_tree",
+            checkpoint(cancel.clone())
+                .ignore_then(depth.nested())
+                .then(bi)
                 .then(element)
-                .then(
-                    vi.then(argument_list.clone().or_not())
-                        .then(predicate_tree.clone())
-                        .chain(
-                            fi.then(argument_list.or_not())
-                                .then(predicate_tree.clone())
-                                .repeated(),
-                        )
-                        .then_ignore(vei.or_not())
-                        .repeated(),
-                )
-                .then(predicate_tree.or_not())
-                .map(|(((bi, l), b), r)| {
-                    let negation = if bi % 2 == 0 {
-                        Negation::None
+                .then(clause.repeated())
+                .then(chained.or_not())
+                .map(|((((_nesting, mut children), l), clauses), r)| {
+                    let (clauses, _nestings): (Vec<_>, Vec<_>) = clauses.into_iter().unzip();
+                    children.push(l);
+                    children.extend(clauses);
+                    children.extend(r);
+                    node(NodeKind::PredicateTree)(children)
+                })
+        )
+        .boxed()
+    };
+    // `recursive` refers back to the parser being defined weakly, so nesting one in the other
+    // keeps the two from owning each other, and the parser is freed once dropped.
+    let predicate_tree = recursive(|predicate_tree| {
+        let clause_tree = recursive(|clause_tree| {
+            level(predicate_tree.clone().boxed(), clause_tree.boxed(), false)
+        });
+        level(predicate_tree.boxed(), clause_tree.boxed(), true)
+    })
+    .boxed();
+
+    traced!("connective", joined(NodeKind::Connected, predicate_tree))
+}
+
+/// The nesting of the predicate trees a sentence parser is reading. Once a tree would be nested
+/// deeper than allowed, the whole sentence fails there, rather than backtracking to a shallower
+/// reading of it that means something else.
+#[derive(Clone)]
+pub(crate) struct Depth<S> {
+    max_depth: usize,
+    /// The number of trees being read, nested in each other.
+    current: Rc<Cell<usize>>,
+    /// Where a tree was first nested too deep in the last sentence read.
+    exceeded: Rc<Cell<Option<S>>>,
+}
+
+impl<S: Clone + 'static> Depth<S> {
+    pub(crate) fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            current: Rc::new(Cell::new(0)),
+            exceeded: Rc::new(Cell::new(None)),
+        }
+    }
+
+    /// Where a tree was first nested too deep in the last sentence read, if it was.
+    pub(crate) fn exceeded(&self) -> Option<S> {
+        let exceeded = self.exceeded.take();
+        self.exceeded.set(exceeded.clone());
+        exceeded
+    }
+
+    /// A parser that consumes nothing and starts a sentence.
+    fn start<E: Error<Word, Span = S>>(&self) -> impl Parser<Word, (), Error = E> + Clone {
+        let exceeded = self.exceeded.clone();
+        empty().map(move |()| exceeded.set(None))
+    }
+
+    /// A parser that consumes nothing and enters one more predicate tree, failing instead if
+    /// the deepest allowed are already being read. Only a tree that could start at the next
+    /// word counts as nested too deep.
+    fn nested<E: Error<Word, Span = S>>(&self) -> impl Parser<Word, Nesting, Error = E> + Clone {
+        let depth = self.clone();
+        filter(starts_tree)
+            .rewind()
+            .or_not()
+            .try_map(move |next, span: S| {
+                let current = depth.current.get();
+                if current >= depth.max_depth {
+                    if next.is_some() {
+                        let first = depth.exceeded.take();
+                        depth
+                            .exceeded
+                            .set(Some(first.unwrap_or_else(|| span.clone())));
+                    }
+                    return Err(E::expected_input_found(span, None, None));
+                }
+                depth.current.set(current + 1);
+                Ok(Nesting(depth.current.clone()))
+            })
+    }
+
+    /// A parser that consumes nothing and ends a sentence, failing where a tree was nested too
+    /// deep in it.
+    fn end<E: Error<Word, Span = S>>(&self) -> impl Parser<Word, (), Error = E> + Clone {
+        let depth = self.clone();
+        empty().try_map(move |(), _| match depth.exceeded() {
+            Some(span) => Err(E::expected_input_found(span, None, None)),
+            None => Ok(()),
+        })
+    }
+}
+
+/// The tree a word stands for in the position of a leaf.
+pub(crate) fn leaf(word: &Word) -> Option<PredicateTree> {
+    let leaf = |word| PredicateTree::Leaf {
+        word,
+        negation: Negation::None,
+        free: Vec::new(),
+    };
+    match word {
+        Word::Predicate(pw, PredicateFamily::Freeform) => Some(PredicateTree::Name {
+            word: pw.clone(),
+            negation: Negation::None,
+            free: Vec::new(),
+        }),
+        Word::Predicate(pw, _) => Some(leaf(pw.clone())),
+        Word::Particle(ParticleFamily::Ki(word)) => Some(leaf(PredicateWord {
+            word: word.clone(),
+            chaining: ChainingBehavior {
+                var: 0,
+                chain_with: PredicateChaining::Sharing,
+            },
+        })),
+        Word::Particle(ParticleFamily::Gi(pw) | ParticleFamily::Mi(pw)) => Some(leaf(pw.clone())),
+        Word::Particle(ParticleFamily::Quote(quote)) => Some(leaf(quote.predicate())),
+        Word::Particle(ParticleFamily::Number(n)) => Some(number(n)),
+        _ => None,
+    }
+}
+
+/// The leaf, number or name `tree`, with `free` after its word.
+pub(crate) fn keep_free(tree: PredicateTree, free: Vec<Modifier>) -> PredicateTree {
+    match tree {
+        PredicateTree::Leaf { word, negation, .. } => PredicateTree::Leaf {
+            word,
+            negation,
+            free,
+        },
+        PredicateTree::Number {
+            value, negation, ..
+        } => PredicateTree::Number {
+            value,
+            negation,
+            free,
+        },
+        PredicateTree::Name { word, negation, .. } => PredicateTree::Name {
+            word,
+            negation,
+            free,
+        },
+        tree => tree,
+    }
+}
+
+/// Whether a predicate tree can start with `word`.
+pub(crate) fn starts_tree(word: &Word) -> bool {
+    matches!(
+        word,
+        Word::Predicate(..)
+            | Word::Particle(
+                ParticleFamily::Bi
+                    | ParticleFamily::Zi(_)
+                    | ParticleFamily::Si { .. }
+                    | ParticleFamily::Pe
+                    | ParticleFamily::Subordinator(_)
+                    | ParticleFamily::Ki(_)
+                    | ParticleFamily::Gi(_)
+                    | ParticleFamily::Mi(_)
+                    | ParticleFamily::Quote(_)
+                    | ParticleFamily::Number(_)
+            )
+    )
+}
+
+/// A predicate tree being read, counted until it is dropped, whether the tree is read or the
+/// parser backtracks out of it.
+struct Nesting(Rc<Cell<usize>>);
+
+impl Drop for Nesting {
+    fn drop(&mut self) {
+        self.0.set(self.0.get().saturating_sub(1));
+    }
+}
+
+/// An interjection with the word it modifies.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FreeModifier {
+    pub interjection: Interjection,
+    /// The index of the interjection among the words of the sentence.
+    pub index: usize,
+    /// The index of the word it modifies: the last word before it that is not an interjection,
+    /// or `None` if it opens the sentence, in which case it modifies the whole sentence.
+    pub attached_to: Option<usize>,
+}
+
+/// The interjections of a sentence, in order.
+pub fn free_modifiers(words: &[Word]) -> Vec<FreeModifier> {
+    let mut modifiers = Vec::new();
+    let mut attached_to = None;
+    for (index, word) in words.iter().enumerate() {
+        match word {
+            Word::Particle(ParticleFamily::Interjection(interjection)) => {
+                modifiers.push(FreeModifier {
+                    interjection: interjection.clone(),
+                    index,
+                    attached_to,
+                })
+            }
+            _ => attached_to = Some(index),
+        }
+    }
+    modifiers
+}
+
+/// A `vi` or `fi` introducing a sharer, with its optional explicit argument list.
+pub(crate) type Sharer = (
+    (FiVar, PredicateChaining),
+    Option<Vec<(Argument, PredicateChaining)>>,
+);
+
+/// A clause bound to the first element of a predicate tree: a `vi` or `fi` clause with the `fi`
+/// clauses after it, or a tag with the tree it attaches.
+pub(crate) enum Clause {
+    Sharers(Vec<(Sharer, PredicateTree)>),
+    Adjunct(Tag, PredicateTree),
+}
+
+/// The tree a number stands for: a node carrying its value, or a leaf named after its digits if
+/// it does not fit in a `u64`.
+pub(crate) fn number(number: &Number) -> PredicateTree {
+    match number.value() {
+        Some(value) => PredicateTree::Number {
+            value,
+            negation: Negation::None,
+            free: Vec::new(),
+        },
+        None => PredicateTree::Leaf {
+            word: number.predicate(),
+            negation: Negation::None,
+            free: Vec::new(),
+        },
+    }
+}
+
+/// Builds an element from the number of `zi` negating it, its optional `si` and the predicate.
+pub(crate) fn element(
+    zi: usize,
+    si: Option<(Exposure, ChainingBehavior)>,
+    p: PredicateTree,
+) -> PredicateTree {
+    let p = match si {
+        None => p,
+        Some((exposure, chaining)) => p.with_exposure(exposure, chaining),
+    };
+    p.negate(if zi.is_multiple_of(2) {
+        Negation::None
+    } else {
+        Negation::Short
+    })
+}
+
+/// Builds a predicate tree from the number of `bi` before it, its first element, the clauses
+/// bound to it and the predicate tree chained after it.
+pub(crate) fn bind(
+    bi: usize,
+    l: PredicateTree,
+    clauses: Vec<Clause>,
+    r: Option<PredicateTree>,
+) -> PredicateTree {
+    let negation = if bi.is_multiple_of(2) {
+        Negation::None
+    } else {
+        Negation::Long
+    };
+    let no_binding = clauses.is_empty() && r.is_none();
+    if no_binding {
+        l.negate(negation)
+    } else {
+        let (chaining, root, exposure, mut sharers, mut and, mut tags) = match l {
+            PredicateTree::Binding {
+                chaining,
+                root,
+                negation: _,
+                exposure,
+                sharers,
+                and,
+                tags,
+            } => (chaining, root, exposure, sharers, and, tags),
+            l @ (PredicateTree::Leaf { .. }
+            | PredicateTree::Number { .. }
+            | PredicateTree::Name { .. }
+            | PredicateTree::Abstraction { .. }
+            | PredicateTree::Connective { .. }
+            | PredicateTree::Quantified { .. }) => (
+                l.chaining_behavior(),
+                Box::new(l),
+                Exposure::Standard,
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+            ),
+        };
+
+        let mut b = Vec::new();
+        for clause in clauses {
+            match clause {
+                Clause::Sharers(group) => b.push(group),
+                Clause::Adjunct(tag, p) => tags.push((tag, p)),
+            }
+        }
+        let direct = r.is_some();
+        let children = r
+            .into_iter()
+            .map(|r| {
+                let binding = (FiVar::Var(chaining.var), chaining.chain_with);
+                (binding, r)
+            })
+            .chain(b.into_iter().flat_map(|b| {
+                b.into_iter().map(|((binding, args), p)| {
+                    let p = if let Some(args) = args {
+                        let chaining = p.chaining_behavior();
+                        p.with_exposure(Exposure::Explicit(args), chaining)
                     } else {
-                        Negation::Long
+                        p
                     };
-                    let no_binding = b.is_empty() && r.is_none();
-                    if no_binding {
-                        l.negate(negation)
-                    } else {
-                        let (chaining, root, exposure, mut sharers, mut and) = match l.to_binding()
-                        {
-                            PredicateTree::Binding {
-                                chaining,
-                                root,
-                                negation: _,
-                                exposure,
-                                sharers,
-                                and,
-                            } => (chaining, root, exposure, sharers, and),
-                            _ => unreachable!(),
-                        };
-
-                        let children = r
-                            .into_iter()
-                            .map(|r| {
-                                let binding = (FiVar::Var(chaining.var), chaining.chain_with);
-                                (binding, r)
-                            })
-                            .chain(b.into_iter().flat_map(|b| {
-                                b.into_iter().map(|((pf, args), p)| {
-                                    let binding = match pf {
-                                        ParticleFamily::Vi { var, chain_with } => {
-                                            let var = match var {
-                                                Some(var) => FiVar::Var(var),
-                                                None => FiVar::None,
-                                            };
-                                            (var, chain_with)
-                                        }
-                                        ParticleFamily::Fi { var, chain_with } => (var, chain_with),
-                                        _ => unreachable!(),
-                                    };
-                                    let p = if let Some(args) = args {
-                                        match p.to_binding() {
-                                            PredicateTree::Binding {
-                                                chaining,
-                                                root,
-                                                negation,
-                                                sharers,
-                                                and,
-                                                ..
-                                            } => PredicateTree::Binding {
-                                                exposure: Exposure::Explicit(args),
-                                                chaining,
-                                                root,
-                                                negation,
-                                                sharers,
-                                                and,
-                                            },
-                                            _ => unreachable!(),
-                                        }
-                                    } else {
-                                        p
-                                    };
-                                    (binding, p)
-                                })
-                            }));
-                        let mut v = 0;
-                        for ((var, chain_with), p) in children {
-                            v = match var {
-                                FiVar::Same | FiVar::None => v,
-                                FiVar::Next => v + 1,
-                                FiVar::Var(v) => v,
-                            };
-                            if let FiVar::None = var {
-                                and.insert(p);
-                            } else {
-                                while sharers.len() <= v as usize {
-                                    sharers.push(BTreeSet::new());
-                                }
-                                sharers[v as usize].insert((chain_with, p));
-                            }
-                        }
-
-                        PredicateTree::Binding {
-                            chaining,
-                            root,
-                            negation,
-                            exposure,
-                            sharers,
-                            and,
-                        }
+                    (binding, p)
+                })
+            }));
+        let mut v: GrammarVar = 0;
+        let mut children: Vec<_> = children
+            .map(|((var, chain_with), p)| {
+                v = match var {
+                    FiVar::Same | FiVar::None => v,
+                    FiVar::Next => v.saturating_add(1),
+                    FiVar::Var(v) => v,
+                };
+                let place = (var != FiVar::None).then_some((v, chain_with));
+                (place, p)
+            })
+            .collect();
+        // The tree chained directly is numbered first but written after the clauses.
+        if direct {
+            children.rotate_left(1);
+        }
+        for (place, p) in children {
+            match place {
+                Some((v, chain_with)) => {
+                    while sharers.len() <= v as usize {
+                        sharers.push(Vec::new());
                     }
-                });
+                    sharers[v as usize].push((chain_with, p));
+                }
+                None => and.push(p),
+            }
+        }
 
-            binding
-        });
-    predicate_tree.then_ignore(end())
+        PredicateTree::Binding {
+            chaining,
+            root,
+            negation,
+            exposure,
+            sharers,
+            and,
+            tags,
+        }
+    }
+}
+
+/// Joins two predicate trees with the connective between them.
+pub(crate) fn connect(
+    left: PredicateTree,
+    (kind, right): (ConnectiveKind, PredicateTree),
+) -> PredicateTree {
+    PredicateTree::Connective {
+        kind,
+        negation: Negation::None,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+/// Quantifies a chained predicate tree if a quantifier comes before it.
+pub(crate) fn quantify(quantifier: Option<Quantifier>, tree: PredicateTree) -> PredicateTree {
+    match quantifier {
+        Some(quantifier) => PredicateTree::Quantified {
+            quantifier,
+            tree: Box::new(tree),
+        },
+        None => tree,
+    }
 }
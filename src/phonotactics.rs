@@ -0,0 +1,369 @@
+//! Checks that words are spelled as words of their family, explaining the first constraint they
+//! break. Letter-level constraints are checked first, so that the explanation points at the
+//! offending letters; a word that passes them is then lexed, which is the final authority.
+
+use std::fmt;
+
+use crate::{
+    dfa,
+    lexer::{PhonologyTables, PredicateFamily, Word, NON_SONORANT, SONORANT, VOWELS},
+};
+
+/// A constraint broken by a word. Indices are of characters in the word.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Violation {
+    Empty,
+    /// A character that is not a letter of the alphabet.
+    InvalidCharacter {
+        index: usize,
+        character: char,
+    },
+    /// The same letter twice in a row, which preprocessing would merge.
+    RepeatedLetter {
+        index: usize,
+        letter: char,
+    },
+    /// A word that must start with a consonant starts with a vowel or `h`.
+    InitialVowel {
+        index: usize,
+        letter: char,
+    },
+    InvalidInitialPair {
+        index: usize,
+        pair: (char, char),
+    },
+    InvalidMedialPair {
+        index: usize,
+        pair: (char, char),
+    },
+    /// Three consonants in a row, or two in a word that allows none.
+    ConsonantCluster {
+        index: usize,
+    },
+    /// A sonorant where the family does not allow one: at the start of a root, or at the end of
+    /// a particle that starts with a non-sonorant.
+    SonorantPlacement {
+        index: usize,
+        letter: char,
+    },
+    /// A non-sonorant consonant at the end of the word.
+    FinalConsonant {
+        index: usize,
+        letter: char,
+    },
+    /// An `h` that does not separate two vowels.
+    Hiatus {
+        index: usize,
+    },
+    MissingVowel,
+    /// The word breaks no letter-level constraint, but lexes as something else.
+    LexesAs(String),
+    /// The word breaks no letter-level constraint, but does not lex.
+    Unlexable,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Empty => write!(f, "empty word"),
+            Violation::InvalidCharacter { index, character } => {
+                write!(f, "{index}: `{character}` is not a letter of the alphabet")
+            }
+            Violation::RepeatedLetter { index, letter } => {
+                write!(f, "{index}: `{letter}` is repeated")
+            }
+            Violation::InitialVowel { index, letter } => {
+                write!(f, "{index}: starts with `{letter}` instead of a consonant")
+            }
+            Violation::InvalidInitialPair {
+                index,
+                pair: (a, b),
+            } => {
+                write!(f, "{index}: `{a}{b}` is not a valid initial pair")
+            }
+            Violation::InvalidMedialPair {
+                index,
+                pair: (a, b),
+            } => {
+                write!(f, "{index}: `{a}{b}` is not a valid medial pair")
+            }
+            Violation::ConsonantCluster { index } => {
+                write!(f, "{index}: too many consonants in a row")
+            }
+            Violation::SonorantPlacement { index, letter } => {
+                write!(f, "{index}: sonorant `{letter}` is not allowed here")
+            }
+            Violation::FinalConsonant { index, letter } => {
+                write!(f, "{index}: ends with non-sonorant `{letter}`")
+            }
+            Violation::Hiatus { index } => write!(f, "{index}: `h` must be between vowels"),
+            Violation::MissingVowel => write!(f, "no vowel"),
+            Violation::LexesAs(words) => write!(f, "lexes as {words}"),
+            Violation::Unlexable => write!(f, "does not lex"),
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+fn is_consonant(c: char) -> bool {
+    NON_SONORANT.contains(&c) || SONORANT.contains(&c)
+}
+
+/// The lower case letters of `word`, checking the constraints shared by every family.
+fn letters(word: &str) -> Result<Vec<char>, Violation> {
+    let letters: Vec<char> = word.chars().map(|c| c.to_ascii_lowercase()).collect();
+    if letters.is_empty() {
+        return Err(Violation::Empty);
+    }
+    for (index, &c) in letters.iter().enumerate() {
+        if !is_consonant(c) && !VOWELS.contains(&c) && c != 'h' {
+            return Err(Violation::InvalidCharacter {
+                index,
+                character: word.chars().nth(index).unwrap_or(c),
+            });
+        }
+        if index > 0 && letters.get(index - 1) == Some(&c) {
+            return Err(Violation::RepeatedLetter { index, letter: c });
+        }
+    }
+    for (index, &c) in letters.iter().enumerate() {
+        let vowel_at = |i: Option<usize>| {
+            i.and_then(|i| letters.get(i))
+                .is_some_and(|c| VOWELS.contains(c))
+        };
+        if c == 'h' && !(vowel_at(index.checked_sub(1)) && vowel_at(Some(index + 1))) {
+            return Err(Violation::Hiatus { index });
+        }
+    }
+    if !letters.iter().any(|c| VOWELS.contains(c)) {
+        return Err(Violation::MissingVowel);
+    }
+    Ok(letters)
+}
+
+/// Checks the consonant clusters of a predicate word: an initial pair at the start, medial
+/// pairs elsewhere, and never three consonants in a row.
+fn clusters(letters: &[char]) -> Result<(), Violation> {
+    for (index, window) in letters.windows(2).enumerate() {
+        let (a, b) = (window[0], window[1]);
+        if !is_consonant(a) || !is_consonant(b) {
+            continue;
+        }
+        if letters.get(index + 2).is_some_and(|c| is_consonant(*c)) {
+            return Err(Violation::ConsonantCluster { index });
+        }
+        if index == 0 && !PhonologyTables::STANDARD.is_initial_pair(a, b) {
+            return Err(Violation::InvalidInitialPair {
+                index,
+                pair: (a, b),
+            });
+        }
+        if index > 0 && !PhonologyTables::STANDARD.is_medial_pair(a, b) {
+            return Err(Violation::InvalidMedialPair {
+                index,
+                pair: (a, b),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Describes the words `word` lexes as, for [`Violation::LexesAs`].
+fn lexes_as(words: &[Word]) -> Violation {
+    let describe = |word: &Word| match word {
+        Word::Particle(p) => format!("{} particle `{}`", p.name(), p.text()),
+        Word::Predicate(p, _) => format!("root `{}`", p.word),
+    };
+    Violation::LexesAs(words.iter().map(describe).collect::<Vec<_>>().join(", "))
+}
+
+/// Lexes `letters` after a pause, as they would be read in a sentence.
+fn lex(letters: &[char]) -> Option<Vec<Word>> {
+    let text: String = std::iter::once(' ')
+        .chain(letters.iter().copied())
+        .collect();
+    dfa::lex(&text).map(|words| words.into_iter().map(|(word, _)| word).collect())
+}
+
+/// Checks that `word` is a root: a non-sonorant and vowels followed by more letters, or an
+/// initial pair and vowels, with medial pairs between syllables and only a sonorant at the end.
+pub fn is_valid_root(word: &str) -> Result<(), Violation> {
+    let letters = letters(word)?;
+    match letters.first() {
+        Some(&c) if VOWELS.contains(&c) || c == 'h' => {
+            return Err(Violation::InitialVowel {
+                index: 0,
+                letter: c,
+            })
+        }
+        Some(&c) if SONORANT.contains(&c) => {
+            return Err(Violation::SonorantPlacement {
+                index: 0,
+                letter: c,
+            })
+        }
+        _ => {}
+    }
+    clusters(&letters)?;
+    if let Some(&c) = letters.last().filter(|c| NON_SONORANT.contains(c)) {
+        return Err(Violation::FinalConsonant {
+            index: letters.len() - 1,
+            letter: c,
+        });
+    }
+    match lex(&letters).as_deref() {
+        Some([Word::Predicate(_, PredicateFamily::Root)]) => Ok(()),
+        Some(words) => Err(lexes_as(words)),
+        None => Err(Violation::Unlexable),
+    }
+}
+
+/// Checks that `word` is a particle: a single consonant between syllables, and a final sonorant
+/// only after an initial sonorant or vowel.
+pub fn is_valid_particle(word: &str) -> Result<(), Violation> {
+    let letters = letters(word)?;
+    if let Some(index) = letters
+        .windows(2)
+        .position(|w| is_consonant(w[0]) && is_consonant(w[1]))
+    {
+        return Err(Violation::ConsonantCluster { index });
+    }
+    let starts_with_non_sonorant = letters.first().is_some_and(|c| NON_SONORANT.contains(c));
+    match letters.last() {
+        Some(&c) if NON_SONORANT.contains(&c) => {
+            return Err(Violation::FinalConsonant {
+                index: letters.len() - 1,
+                letter: c,
+            })
+        }
+        Some(&c) if SONORANT.contains(&c) && starts_with_non_sonorant => {
+            return Err(Violation::SonorantPlacement {
+                index: letters.len() - 1,
+                letter: c,
+            })
+        }
+        _ => {}
+    }
+    match lex(&letters).as_deref() {
+        Some([Word::Particle(_)]) => Ok(()),
+        Some(words) => Err(lexes_as(words)),
+        None => Err(Violation::Unlexable),
+    }
+}
+
+/// Checks the constraints a borrowing shares with roots: the alphabet, hiatus, an initial pair
+/// at the start, medial pairs elsewhere and a vowel. The lexer does not read borrowings yet, so
+/// a word passing these checks is not lexed.
+pub fn is_valid_borrowing(word: &str) -> Result<(), Violation> {
+    let letters = letters(word)?;
+    if let Some(&c) = letters.first().filter(|c| VOWELS.contains(c) || **c == 'h') {
+        return Err(Violation::InitialVowel {
+            index: 0,
+            letter: c,
+        });
+    }
+    clusters(&letters)
+}
+
+/// A syllable of a word: the consonants before its vowels, the vowels, and the consonants
+/// closing it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Syllable {
+    pub onset: String,
+    pub nucleus: String,
+    pub coda: String,
+    /// Whether the syllable carries the predicted stress of the word.
+    pub stressed: bool,
+}
+
+impl Syllable {
+    pub fn text(&self) -> String {
+        format!("{}{}{}", self.onset, self.nucleus, self.coda)
+    }
+}
+
+impl fmt::Display for Syllable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.stressed {
+            write!(f, "{}", self.text().to_uppercase())
+        } else {
+            write!(f, "{}", self.text())
+        }
+    }
+}
+
+/// Splits `word` into syllables, one for each run of vowels as the lexer reads them, so that
+/// `h` always opens a syllable.
+///
+/// Consonants before the first vowels and after the last ones belong to the first and last
+/// syllable. Between two runs of vowels, a single consonant opens the next syllable; of two
+/// consonants, a medial pair is split between the syllables, and an initial pair that is not a
+/// medial pair opens the next syllable. The stress is predicted on the next to last syllable, or
+/// on the only one.
+///
+/// The letters are lowered but not otherwise checked: the syllables of a word that is not valid
+/// follow the same rules, and always spell the word back.
+pub fn syllabify(word: &str) -> Vec<Syllable> {
+    // The runs of vowels, the consonants before each run, and those after the last.
+    let mut nuclei: Vec<String> = Vec::new();
+    let mut clusters: Vec<Vec<char>> = Vec::new();
+    let mut coda = Vec::new();
+    for c in word.chars().map(|c| c.to_ascii_lowercase()) {
+        if !VOWELS.contains(&c) {
+            coda.push(c);
+        } else if let Some(nucleus) = nuclei.last_mut().filter(|_| coda.is_empty()) {
+            nucleus.push(c);
+        } else {
+            clusters.push(std::mem::take(&mut coda));
+            nuclei.push(c.to_string());
+        }
+    }
+    if nuclei.is_empty() {
+        return if coda.is_empty() {
+            Vec::new()
+        } else {
+            vec![Syllable {
+                onset: String::new(),
+                nucleus: String::new(),
+                coda: coda.into_iter().collect(),
+                stressed: true,
+            }]
+        };
+    }
+
+    // Split each cluster between the coda of the syllable before it and the onset of the one
+    // after it.
+    let tables = PhonologyTables::STANDARD;
+    let mut codas = Vec::new();
+    let mut onsets = Vec::new();
+    for (i, cluster) in clusters.into_iter().enumerate() {
+        let at = match cluster.as_slice() {
+            _ if i == 0 => 0,
+            [.., a, b] if !tables.is_medial_pair(*a, *b) && tables.is_initial_pair(*a, *b) => {
+                cluster.len() - 2
+            }
+            _ => cluster.len().saturating_sub(1),
+        };
+        let (before, after) = cluster.split_at(at);
+        if i > 0 {
+            codas.push(before.iter().collect::<String>());
+        }
+        onsets.push(after.iter().collect::<String>());
+    }
+    codas.push(coda.into_iter().collect());
+
+    let stressed = nuclei.len().saturating_sub(2);
+    nuclei
+        .into_iter()
+        .zip(onsets)
+        .zip(codas)
+        .enumerate()
+        .map(|(i, ((nucleus, onset), coda))| Syllable {
+            onset,
+            nucleus,
+            coda,
+            stressed: i == stressed,
+        })
+        .collect()
+}
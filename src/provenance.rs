@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    expr::{to_expr, ExprBackend, Predicate, Var},
+    lexer::{ParticleFamily, Quote, Word},
+    parser::parser,
+    semantics::interpret_with_aliases,
+};
+
+/// Separates the text of a leaf from the index of the word it was read from, in the words
+/// [`Provenance::of_words`] interprets again.
+const TAG: char = '\u{1f}';
+
+/// A mention of a variable by a leaf of a formula.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Occurrence {
+    pub word: String,
+    pub id: usize,
+    /// The place of the leaf the variable fills, or `None` if the variable is equated to the
+    /// leaf itself.
+    pub place: Option<usize>,
+}
+
+/// Which words of a formula mention each of its variables, in formula order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    vars: BTreeMap<Var, Vec<Occurrence>>,
+    /// The variables said to have a number of values, with that number, in formula order.
+    counts: Vec<(Var, u64)>,
+    /// The variable each word of the sentence describes, by the index of the word.
+    words: BTreeMap<usize, Var>,
+}
+
+impl Provenance {
+    pub fn of(pred: &Predicate) -> Self {
+        let mut provenance = Provenance::default();
+        provenance.visit(pred);
+        provenance
+    }
+
+    /// Like [`of`](Self::of), also finding the variable each of `words`, the sentence `pred` was
+    /// read from, describes. Occurrences of the same word are told apart: the sentence is
+    /// interpreted again with the index of each word written into the leaf it is read as, and
+    /// the variables of that interpretation are matched with those of `pred` leaf by leaf. A
+    /// sentence whose leaves do not match those of `pred` gets no variable for its words.
+    pub fn of_words(words: &[Word], pred: &Predicate) -> Self {
+        let mut provenance = Provenance::of(pred);
+        let parser = parser::<Cheap<Word>>();
+        let tagged: Vec<Word> = words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| tagged(word, i))
+            .collect();
+        let (Ok(tree), Ok(tagged)) = (parser.parse(words.to_vec()), parser.parse(tagged)) else {
+            return provenance;
+        };
+        let ((read, _), aliases) = interpret_with_aliases(tree, &mut ExprBackend);
+        let (tagged, _) = to_expr(tagged);
+
+        // The variables of `read`, which is `pred` before it was printed, as they are in `pred`.
+        let (mut read_mentions, mut pred_mentions) = (Vec::new(), Vec::new());
+        mentions(&read, &mut read_mentions);
+        mentions(pred, &mut pred_mentions);
+        if read_mentions.len() != pred_mentions.len() {
+            return provenance;
+        }
+        let mut renamed = BTreeMap::new();
+        for ((_, _, from), (_, _, to)) in read_mentions.into_iter().zip(pred_mentions) {
+            if *renamed.entry(from).or_insert(to) != to {
+                return provenance;
+            }
+        }
+
+        // The later occurrences of a bound `ki` word are not bound in `tagged`, each being a
+        // word of its own, but their variables are the same as in `read` before aliasing.
+        let mut tagged_mentions = Vec::new();
+        mentions(&tagged, &mut tagged_mentions);
+        for (word, place, var) in tagged_mentions {
+            let Some(index) = word.and_then(token) else {
+                continue;
+            };
+            let var = aliases.get(&var).copied().unwrap_or(var);
+            if let (None | Some(0), Some(var)) = (place, renamed.get(&var)) {
+                provenance.words.entry(index).or_insert(*var);
+            }
+        }
+        // A number describes what it counts, or else the first variable its value describes.
+        for (index, word) in words.iter().enumerate() {
+            let Word::Particle(ParticleFamily::Number(number)) = word else {
+                continue;
+            };
+            let name = number.leaf_name();
+            let var = number
+                .value()
+                .and_then(|n| provenance.counted(n))
+                .or_else(|| {
+                    provenance.vars.iter().find_map(|(v, occurrences)| {
+                        occurrences
+                            .iter()
+                            .any(|o| o.word == name && matches!(o.place, None | Some(0)))
+                            .then_some(*v)
+                    })
+                });
+            if let Some(var) = var {
+                provenance.words.insert(index, var);
+            }
+        }
+        provenance
+    }
+
+    fn visit(&mut self, pred: &Predicate) {
+        match pred {
+            Predicate::Leaf { word, id, apply_to } => {
+                for (place, v) in apply_to.iter().enumerate() {
+                    self.vars.entry(*v).or_default().push(Occurrence {
+                        word: word.clone(),
+                        id: *id,
+                        place: Some(place),
+                    });
+                }
+            }
+            Predicate::Equivalent { var, pred } => {
+                if let Predicate::Leaf { word, id, .. } = &**pred {
+                    self.vars.entry(*var).or_default().push(Occurrence {
+                        word: word.clone(),
+                        id: *id,
+                        place: None,
+                    });
+                }
+                self.visit(pred);
+            }
+            Predicate::ShortNot(pred)
+            | Predicate::LongNot(pred)
+            | Predicate::Abstraction { pred, .. }
+            | Predicate::Interrogative(pred)
+            | Predicate::Imperative(pred)
+            | Predicate::Exists { pred, .. }
+            | Predicate::Lambda { pred, .. } => self.visit(pred),
+            Predicate::And { preds } | Predicate::Or { preds } => {
+                preds.iter().for_each(|p| self.visit(p))
+            }
+            Predicate::Name { .. } => {}
+            Predicate::Cardinality { var, count } => self.counts.push((*var, *count)),
+            Predicate::Quantified {
+                restrictor, scope, ..
+            } => {
+                self.visit(restrictor);
+                self.visit(scope);
+            }
+        }
+    }
+
+    pub fn vars(&self) -> impl Iterator<Item = Var> + '_ {
+        self.vars.keys().copied()
+    }
+
+    pub fn occurrences(&self, var: Var) -> &[Occurrence] {
+        self.vars.get(&var).map_or(&[], Vec::as_slice)
+    }
+
+    /// The first variable said to have `count` values.
+    pub fn counted(&self, count: u64) -> Option<Var> {
+        self.counts
+            .iter()
+            .find_map(|(v, c)| (*c == count).then_some(*v))
+    }
+
+    /// The variable the word at `index` in the sentence describes: the one filling its first
+    /// place, or the one it is equated to. Only known for provenance built by
+    /// [`of_words`](Self::of_words).
+    pub fn described_by(&self, index: usize) -> Option<Var> {
+        self.words.get(&index).copied()
+    }
+}
+
+/// `word` with `index` written into the text of the leaf it is read as. Numbers, whose leaves
+/// are named by their value, are left as they are.
+fn tagged(word: &Word, index: usize) -> Word {
+    let tag = |text: &mut String| *text = format!("{text}{TAG}{index}");
+    let mut word = word.clone();
+    match &mut word {
+        Word::Predicate(pw, _)
+        | Word::Particle(ParticleFamily::Gi(pw) | ParticleFamily::Mi(pw)) => tag(&mut pw.word),
+        Word::Particle(ParticleFamily::Ki(text))
+        | Word::Particle(ParticleFamily::Quote(
+            Quote::Word(text) | Quote::Foreign { content: text, .. },
+        )) => tag(text),
+        _ => {}
+    }
+    word
+}
+
+/// The index of the word a leaf of a tagged sentence was read from.
+fn token(text: &str) -> Option<usize> {
+    let (_, index) = text.split_once(TAG)?;
+    let digits: String = index.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+/// The variables the leaves, names and counts of `pred` mention, in formula order, with the
+/// word mentioning them and its place, `None` for a variable equated to a leaf.
+fn mentions<'a>(pred: &'a Predicate, out: &mut Vec<(Option<&'a str>, Option<usize>, Var)>) {
+    match pred {
+        Predicate::Leaf { word, apply_to, .. } => out.extend(
+            apply_to
+                .iter()
+                .enumerate()
+                .map(|(place, v)| (Some(word.as_str()), Some(place), *v)),
+        ),
+        Predicate::Equivalent { var, pred } => {
+            let word = match &**pred {
+                Predicate::Leaf { word, .. } => Some(word.as_str()),
+                _ => None,
+            };
+            out.push((word, None, *var));
+            mentions(pred, out);
+        }
+        Predicate::Name { var, name } => out.push((Some(name.as_str()), Some(0), *var)),
+        Predicate::Cardinality { var, .. } => out.push((None, Some(0), *var)),
+        Predicate::ShortNot(pred)
+        | Predicate::LongNot(pred)
+        | Predicate::Abstraction { pred, .. }
+        | Predicate::Interrogative(pred)
+        | Predicate::Imperative(pred)
+        | Predicate::Exists { pred, .. }
+        | Predicate::Lambda { pred, .. } => mentions(pred, out),
+        Predicate::And { preds } | Predicate::Or { preds } => {
+            preds.iter().for_each(|p| mentions(p, out))
+        }
+        Predicate::Quantified {
+            restrictor, scope, ..
+        } => {
+            mentions(restrictor, out);
+            mentions(scope, out);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use crate::{
+    expr::{Notation, Predicate, Var},
+    lexer::Word,
+    provenance::Provenance,
+};
+
+/// ANSI escape codes for the colors given to variables, in order.
+pub const PALETTE: [&str; 6] = [
+    "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m",
+];
+const RESET: &str = "\x1b[0m";
+
+pub fn color(var: Var) -> &'static str {
+    PALETTE[var % PALETTE.len()]
+}
+
+fn paint(f: &mut fmt::Formatter<'_>, var: Option<Var>, text: &dyn fmt::Display) -> fmt::Result {
+    match var {
+        Some(var) => write!(f, "{}{text}{RESET}", color(var)),
+        None => write!(f, "{text}"),
+    }
+}
+
+/// A sentence and its formula with every variable in its own color, and each word of the
+/// sentence in the color of the variable it describes (see [`Provenance::described_by`]).
+pub struct Colored<'a> {
+    pub words: &'a [Word],
+    pub pred: &'a Predicate,
+}
+
+impl Colored<'_> {
+    pub fn source(&self) -> String {
+        let provenance = Provenance::of_words(self.words, self.pred);
+        self.words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let text = word.text();
+                match provenance.described_by(i) {
+                    Some(var) => format!("{}{text}{RESET}", color(var)),
+                    None => text,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn formula(&self) -> String {
+        FormulaColors(self.pred).to_string()
+    }
+}
+
+struct FormulaColors<'a>(&'a Predicate);
+
+impl fmt::Display for FormulaColors<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Notation {
+            pred: self.0,
+            leaf: &|f, word, id, apply_to| {
+                paint(f, apply_to.first().copied(), &format_args!("{word}{id}"))?;
+                if apply_to.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                for (i, v) in apply_to.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    paint(f, Some(*v), v)?;
+                }
+                write!(f, ")")
+            },
+            var: &|f, v| paint(f, Some(v), &v),
+        }
+        .fmt(f)
+    }
+}
+
+impl fmt::Display for Colored<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.source())?;
+        write!(f, "{}", self.formula())
+    }
+}
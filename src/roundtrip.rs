@@ -0,0 +1,287 @@
+use std::{fmt, ops::Range};
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    format::{format, FormatError},
+    lexer::{lex_words, to_text, ParticleFamily, Word},
+    parser::parser,
+};
+
+/// A piece of information present in the input that does not survive processing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Loss {
+    /// Upper-case letters, folded to lower case by preprocessing.
+    CaseFolded { span: Range<usize> },
+    /// A run of the same letter, collapsed into one by preprocessing.
+    RepeatedLetter { span: Range<usize>, letter: char },
+    /// Pause characters other than a single space or line break between words, which the token
+    /// stream does not keep.
+    Pause { span: Range<usize> },
+    /// The token stream does not spell the same letters as the input.
+    Respelled { expected: String, found: String },
+    /// A word of the text that the text written back from the tree of its sentence leaves out,
+    /// such as an elidable terminator.
+    Unwritten { index: usize, word: String },
+    /// A word that the text written back from the tree of a sentence has before the word of the
+    /// text at `index`, or at its end.
+    Written { index: usize, word: String },
+    /// The text written back from the tree of a sentence does not parse to that tree.
+    Changed { sentence: usize, text: String },
+    /// The formatter fails on the text, or would change its parse.
+    Format(FormatError),
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Loss::CaseFolded { span } => {
+                write!(f, "{}..{}: case folded to lower case", span.start, span.end)
+            }
+            Loss::RepeatedLetter { span, letter } => write!(
+                f,
+                "{}..{}: repeated `{letter}` collapsed",
+                span.start, span.end
+            ),
+            Loss::Pause { span } => write!(f, "{}..{}: pause dropped", span.start, span.end),
+            Loss::Respelled { expected, found } => {
+                write!(f, "tokens spell `{found}` instead of `{expected}`")
+            }
+            Loss::Unwritten { index, word } => {
+                write!(f, "token {index}: `{word}` not written back from the tree")
+            }
+            Loss::Written { index, word } => {
+                write!(f, "token {index}: `{word}` written back before it")
+            }
+            Loss::Changed { sentence, text } => {
+                write!(
+                    f,
+                    "sentence {sentence}: `{text}` does not parse back to its tree"
+                )
+            }
+            Loss::Format(error) => write!(f, "formatting failed: {error}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTripReport {
+    pub input: String,
+    pub lexed: bool,
+    pub parsed: bool,
+    pub losses: Vec<Loss>,
+}
+
+impl RoundTripReport {
+    /// Nothing at all was lost: the text is written back word for word from its trees.
+    pub fn is_lossless(&self) -> bool {
+        self.lexed && self.parsed && self.losses.is_empty()
+    }
+
+    /// Processing was lossless up to the normalization performed by `preprocess` (case,
+    /// repeated letters and pauses) and the words that the trees do not record: the words are
+    /// spelled as in the text, and the text written back from each tree, as well as the
+    /// formatted text, parse as the text did.
+    pub fn is_faithful(&self) -> bool {
+        self.lexed
+            && self.parsed
+            && !self.losses.iter().any(|l| {
+                matches!(
+                    l,
+                    Loss::Respelled { .. } | Loss::Changed { .. } | Loss::Format(_)
+                )
+            })
+    }
+}
+
+impl fmt::Display for RoundTripReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "round trip of `{}`:", self.input)?;
+        if !self.lexed {
+            writeln!(f, "  failed to lex")?;
+        } else if !self.parsed {
+            writeln!(f, "  failed to parse")?;
+        }
+        for loss in &self.losses {
+            writeln!(f, "  {loss}")?;
+        }
+        Ok(())
+    }
+}
+
+fn is_pause(c: char) -> bool {
+    c.is_whitespace() || c == '\''
+}
+
+/// Runs `s` through the pipeline and back and reports every place where information was lost.
+/// Each sentence is parsed, written back from its tree with
+/// [`to_words`](crate::parser::PredicateTree::to_words) and parsed again, and the whole text is
+/// [`format`]ted, which parses it again as well.
+pub fn round_trip(s: &str) -> RoundTripReport {
+    let mut losses = Vec::new();
+    let mut letters = String::new();
+    let len = s.chars().count();
+    let mut offset = 0;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let start = offset;
+        let mut count = 1;
+        if is_pause(c) {
+            while chars.next_if(|c| is_pause(*c)).is_some() {
+                count += 1;
+            }
+            offset += count;
+            // A single space or line break between words only separates them.
+            if !(count == 1 && matches!(c, ' ' | '\n') && start > 0 && offset < len) {
+                losses.push(Loss::Pause {
+                    span: start..offset,
+                });
+            }
+            continue;
+        }
+        let lower = c.to_ascii_lowercase();
+        let mut upper = c.is_ascii_uppercase();
+        while let Some(next) = chars.next_if(|next| next.to_ascii_lowercase() == lower) {
+            upper |= next.is_ascii_uppercase();
+            count += 1;
+        }
+        offset += count;
+        let span = start..offset;
+        if upper {
+            losses.push(Loss::CaseFolded { span: span.clone() });
+        }
+        if count > 1 {
+            losses.push(Loss::RepeatedLetter {
+                span,
+                letter: lower,
+            });
+        }
+        letters.push(lower);
+    }
+
+    let Ok(words) = lex_words(s) else {
+        return RoundTripReport {
+            input: s.to_string(),
+            lexed: false,
+            parsed: false,
+            losses,
+        };
+    };
+    let spelled: String = words
+        .iter()
+        .flat_map(|word| {
+            word.text()
+                .chars()
+                .filter(|c| !is_pause(*c))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if spelled != letters {
+        losses.push(Loss::Respelled {
+            expected: letters,
+            found: spelled,
+        });
+    }
+
+    let parser = parser::<Cheap<Word>>();
+    let mut parsed = true;
+    let mut start = 0;
+    let mut sentence = 0;
+    for end in 0..=words.len() {
+        if end < words.len() && !matches!(words[end], Word::Particle(ParticleFamily::Separator(_)))
+        {
+            continue;
+        }
+        let text = &words[start..end];
+        start = end + 1;
+        if text.is_empty() {
+            continue;
+        }
+        let Ok(tree) = parser.parse(text.to_vec()) else {
+            parsed = false;
+            break;
+        };
+        let written = tree.to_words();
+        let reparsed = lex_words(&to_text(&written))
+            .ok()
+            .and_then(|words| parser.parse(words).ok());
+        if reparsed.as_ref() != Some(&tree) {
+            losses.push(Loss::Changed {
+                sentence,
+                text: to_text(&written),
+            });
+        }
+        losses.extend(differences(text, &written, end - text.len()));
+        sentence += 1;
+    }
+    if parsed {
+        if let Err(error) = format(s) {
+            losses.push(Loss::Format(error));
+        }
+    }
+    RoundTripReport {
+        input: s.to_string(),
+        lexed: true,
+        parsed,
+        losses,
+    }
+}
+
+/// The words of `text`, whose first word is at `offset` in the text, left out of `written`, and
+/// the words of `written` not in `text`, pairing the longest common run of words of both.
+fn differences(text: &[Word], written: &[Word], offset: usize) -> Vec<Loss> {
+    let text: Vec<String> = text.iter().map(Word::text).collect();
+    let written: Vec<String> = written.iter().map(Word::text).collect();
+    // `common[i][j]` is the length of the longest common subsequence of `text[i..]` and
+    // `written[j..]`.
+    let mut common = vec![vec![0; written.len() + 1]; text.len() + 1];
+    for i in (0..text.len()).rev() {
+        for j in (0..written.len()).rev() {
+            common[i][j] = if text[i] == written[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut losses = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < text.len() || j < written.len() {
+        if i < text.len() && j < written.len() && text[i] == written[j] {
+            i += 1;
+            j += 1;
+        } else if j < written.len() && (i == text.len() || common[i][j + 1] >= common[i + 1][j]) {
+            losses.push(Loss::Written {
+                index: offset + i,
+                word: written[j].clone(),
+            });
+            j += 1;
+        } else {
+            losses.push(Loss::Unwritten {
+                index: offset + i,
+                word: text[i].clone(),
+            });
+            i += 1;
+        }
+    }
+    losses
+}
+
+/// Panics with the round-trip report if `s` is not processed faithfully (see
+/// [`RoundTripReport::is_faithful`]). Intended for the test suites of downstream corpora.
+#[allow(clippy::panic)]
+pub fn assert_round_trip(s: &str) {
+    let report = round_trip(s);
+    if !report.is_faithful() {
+        panic!("{report}");
+    }
+}
+
+/// Like [`assert_round_trip`], but also rejects the normalizations performed by preprocessing.
+#[allow(clippy::panic)]
+pub fn assert_lossless_round_trip(s: &str) {
+    let report = round_trip(s);
+    if !report.is_lossless() {
+        panic!("{report}");
+    }
+}
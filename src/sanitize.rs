@@ -0,0 +1,90 @@
+use std::fmt;
+
+/// A change made by [`sanitize`], with the offset (in characters) of the affected character in
+/// the original text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SanitizeAction {
+    /// An invisible character, such as a zero-width space or joiner, was removed.
+    ZeroWidthRemoved { offset: usize, character: char },
+    /// A punctuation mark was replaced by a pause.
+    PunctuationToPause { offset: usize, character: char },
+    /// An emoji or pictographic symbol was removed.
+    EmojiRemoved { offset: usize, character: char },
+}
+
+impl fmt::Display for SanitizeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanitizeAction::ZeroWidthRemoved { offset, character } => write!(
+                f,
+                "{offset}: removed invisible U+{:04X}",
+                u32::from(*character)
+            ),
+            SanitizeAction::PunctuationToPause { offset, character } => {
+                write!(f, "{offset}: replaced `{character}` with a pause")
+            }
+            SanitizeAction::EmojiRemoved { offset, character } => {
+                write!(f, "{offset}: removed `{character}`")
+            }
+        }
+    }
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{180E}' | '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}' | '\u{FEFF}'
+    )
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{2190}'..='\u{21FF}'
+            | '\u{2300}'..='\u{23FF}'
+            | '\u{2460}'..='\u{24FF}'
+            | '\u{25A0}'..='\u{27BF}'
+            | '\u{2900}'..='\u{297F}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{1F000}'..='\u{1FAFF}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+fn is_punctuation(c: char) -> bool {
+    (c.is_ascii_punctuation() && c != '\'')
+        || matches!(
+            c,
+            '\u{00A1}'..='\u{00BF}'
+                | '\u{00D7}'
+                | '\u{00F7}'
+                | '\u{2010}'..='\u{2027}'
+                | '\u{2030}'..='\u{205E}'
+                | '\u{3000}'..='\u{303F}'
+                | '\u{FF01}'..='\u{FF0F}'
+                | '\u{FF1A}'..='\u{FF20}'
+        )
+}
+
+/// Cleans up user-generated text before it is fed to [`preprocess`](crate::lexer::preprocess):
+/// invisible characters and emoji are dropped and punctuation becomes a pause. Every change is
+/// reported, in order.
+pub fn sanitize(s: &str) -> (String, Vec<SanitizeAction>) {
+    let mut out = String::with_capacity(s.len());
+    let mut actions = Vec::new();
+    for (offset, character) in s.chars().enumerate() {
+        if is_zero_width(character) {
+            actions.push(SanitizeAction::ZeroWidthRemoved { offset, character });
+        } else if is_emoji(character) {
+            actions.push(SanitizeAction::EmojiRemoved { offset, character });
+        } else if is_punctuation(character) {
+            actions.push(SanitizeAction::PunctuationToPause { offset, character });
+            out.push(' ');
+        } else {
+            out.push(character);
+        }
+    }
+    (out, actions)
+}
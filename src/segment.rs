@@ -0,0 +1,67 @@
+use chumsky::{error::Cheap, Error, Parser};
+
+use crate::lexer::{lexer, preprocess, ParticleFamily, PreProcessed, Word};
+
+fn is_separator(word: &Word) -> bool {
+    matches!(word, Word::Particle(ParticleFamily::Separator(_)))
+}
+
+/// Splits a token stream into sentences as it is read, at the particles in
+/// [`SENTENCE_SEPARATORS`](crate::lexer::SENTENCE_SEPARATORS). The separators themselves are
+/// dropped, as are empty sentences.
+pub struct Segments<I> {
+    words: I,
+}
+
+impl<I: Iterator<Item = Word>> Iterator for Segments<I> {
+    type Item = Vec<Word>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut sentence = Vec::new();
+        for word in self.words.by_ref() {
+            if !is_separator(&word) {
+                sentence.push(word);
+            } else if !sentence.is_empty() {
+                return Some(sentence);
+            }
+        }
+        (!sentence.is_empty()).then_some(sentence)
+    }
+}
+
+pub fn segments<I: IntoIterator<Item = Word>>(words: I) -> Segments<I::IntoIter> {
+    Segments {
+        words: words.into_iter(),
+    }
+}
+
+/// Splits a token stream into sentences that can each be given to
+/// [`parser`](crate::parser::parser).
+pub fn segment(words: Vec<Word>) -> Vec<Vec<Word>> {
+    segments(words).collect()
+}
+
+/// Lexes and segments a text. Blank lines also end a sentence, since a separator is often
+/// omitted at the end of a paragraph.
+pub fn segment_text(s: &str) -> Result<Vec<Vec<Word>>, Vec<Cheap<PreProcessed>>> {
+    let lexer = lexer::<Cheap<_>>();
+    let mut sentences = Vec::new();
+    let mut offset = 0;
+    for paragraph in s.split("\n\n") {
+        let words = lexer.parse(preprocess(paragraph)).map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|e| {
+                    Cheap::expected_input_found(
+                        e.span().start + offset..e.span().end + offset,
+                        None,
+                        None,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })?;
+        sentences.extend(segments(words));
+        offset += paragraph.chars().count() + 2;
+    }
+    Ok(sentences)
+}
@@ -0,0 +1,847 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
+};
+
+use crate::{
+    cancel::{CancellationToken, Cancelled},
+    expr::Var,
+    lexer::{AbstractionKind, ConnectiveKind, Tag},
+    parser::PredicateTree,
+    Argument, Exposure, Negation, PredicateChaining, Quantifier,
+};
+
+/// A meaning representation that predicate trees can be interpreted into. The traversal of the
+/// tree, the allocation of variables and the resolution of words to leaf ids are done by
+/// [`interpret`], which calls back into the backend to build each construction.
+pub trait SemanticsBackend {
+    type Output: Clone;
+
+    /// A predicate word, whose places are filled by `apply_to` in order. Occurrences of the same
+    /// word share an `id`, unless they refer to a word bound by an explicit exposure.
+    fn leaf(&mut self, word: &str, id: usize, apply_to: Vec<Var>) -> Self::Output;
+
+    /// All of `preds` hold.
+    fn conjunction(&mut self, preds: Vec<Self::Output>) -> Self::Output;
+
+    /// At least one of `preds` holds.
+    fn disjunction(&mut self, preds: Vec<Self::Output>) -> Self::Output;
+
+    /// Negation scoping over a single predicate, as with `zi`.
+    fn short_negation(&mut self, pred: Self::Output) -> Self::Output;
+
+    /// Negation scoping over a whole chain, as with `bi`.
+    fn long_negation(&mut self, pred: Self::Output) -> Self::Output;
+
+    /// `vars` are bound existentially in `pred`.
+    fn exists(&mut self, vars: Vec<Var>, pred: Self::Output) -> Self::Output;
+
+    /// `pred` is abstracted over `vars`.
+    fn lambda(&mut self, vars: Vec<Var>, pred: Self::Output) -> Self::Output;
+
+    /// `var` stands for the predicate `pred`.
+    fn equivalent(&mut self, var: Var, pred: Self::Output) -> Self::Output;
+
+    /// `var` is the event, proposition or property described by `pred`. For a property, `pred`
+    /// is a lambda over the place the property is ascribed to.
+    fn abstraction(&mut self, kind: AbstractionKind, var: Var, pred: Self::Output) -> Self::Output;
+
+    /// `var` is the individual called `name`, a constant rather than a bound variable.
+    fn name(&mut self, var: Var, name: &str) -> Self::Output;
+
+    /// Replaces each variable of `pred` by the one `aliases` maps it to, dropping the aliased
+    /// variables from the binders that introduced them.
+    fn substitute(&mut self, pred: Self::Output, aliases: &BTreeMap<Var, Var>) -> Self::Output;
+
+    /// There are `count` values of `var`.
+    fn cardinality(&mut self, var: Var, count: u64) -> Self::Output;
+
+    /// `scope` holds of as many of the values of `var` satisfying `restrictor` as `quantifier`
+    /// says.
+    fn quantified(
+        &mut self,
+        quantifier: Quantifier,
+        var: Var,
+        restrictor: Self::Output,
+        scope: Self::Output,
+    ) -> Self::Output;
+
+    /// A word bound by an explicit exposure, naming `var` either by sharing or by equivalence.
+    fn exposure(
+        &mut self,
+        word: &str,
+        id: usize,
+        var: Var,
+        chain_with: PredicateChaining,
+    ) -> Self::Output {
+        match chain_with {
+            PredicateChaining::Sharing => self.leaf(word, id, vec![var]),
+            PredicateChaining::Equivalence => {
+                let leaf = self.leaf(word, id, Vec::new());
+                self.equivalent(var, leaf)
+            }
+        }
+    }
+}
+
+struct Interpreter<'a, B> {
+    backend: &'a mut B,
+    max_var: Var,
+    max_id: usize,
+    symbol_table: BTreeMap<String, Vec<usize>>,
+    /// The variables standing for named individuals, which are never bound.
+    constants: BTreeSet<Var>,
+    /// The variables `ki` words are bound to by explicit exposures, innermost last.
+    ki_vars: BTreeMap<String, Vec<Var>>,
+    /// Variables of later occurrences of a bound `ki` word, mapped to the variable it is bound to.
+    aliases: BTreeMap<Var, Var>,
+    cancel: Option<&'a CancellationToken>,
+}
+
+/// Interprets `tree` with `backend`, returning its meaning and the variables left free in it.
+pub fn interpret<B: SemanticsBackend>(
+    tree: PredicateTree,
+    backend: &mut B,
+) -> (B::Output, Vec<Var>) {
+    let (out, _) = run(tree, backend, None);
+    out
+}
+
+/// Like [`interpret`], also returning the variables of the later occurrences of bound `ki` words,
+/// which are left out of the meaning, mapped to the variable the word is bound to.
+pub(crate) fn interpret_with_aliases<B: SemanticsBackend>(
+    tree: PredicateTree,
+    backend: &mut B,
+) -> ((B::Output, Vec<Var>), BTreeMap<Var, Var>) {
+    run(tree, backend, None)
+}
+
+/// Like [`interpret`], but stops as soon as `cancel` is cancelled.
+pub fn interpret_cancellable<B: SemanticsBackend>(
+    tree: PredicateTree,
+    backend: &mut B,
+    cancel: &CancellationToken,
+) -> Result<(B::Output, Vec<Var>), Cancelled> {
+    let (out, _) = run(tree, backend, Some(cancel));
+    cancel.check()?;
+    Ok(out)
+}
+
+fn run<B: SemanticsBackend>(
+    tree: PredicateTree,
+    backend: &mut B,
+    cancel: Option<&CancellationToken>,
+) -> ((B::Output, Vec<Var>), BTreeMap<Var, Var>) {
+    let mut interpreter = Interpreter {
+        backend,
+        max_var: 0,
+        max_id: 0,
+        symbol_table: BTreeMap::new(),
+        constants: BTreeSet::new(),
+        ki_vars: BTreeMap::new(),
+        aliases: BTreeMap::new(),
+        cancel,
+    };
+    let mut preds = Vec::new();
+    let mut new_vars = Vec::new();
+    interpreter.visit(
+        tree,
+        PredicateChaining::Equivalence,
+        Vec::new(),
+        &mut new_vars,
+        &mut preds,
+    );
+    new_vars.retain(|v| !interpreter.constants.contains(v));
+    let pred = interpreter.backend.conjunction(preds);
+    if interpreter.aliases.is_empty() {
+        ((pred, new_vars), interpreter.aliases)
+    } else {
+        new_vars.retain(|v| !interpreter.aliases.contains_key(v));
+        let pred = interpreter.backend.substitute(pred, &interpreter.aliases);
+        ((pred, new_vars), interpreter.aliases)
+    }
+}
+
+impl<B: SemanticsBackend> Interpreter<'_, B> {
+    fn fresh_var(&mut self) -> Var {
+        let v = self.max_var;
+        trace!(VarAllocated { var: v });
+        self.max_var += 1;
+        v
+    }
+
+    /// The id of the innermost occurrence of `word`, or a new id if it has none.
+    fn leaf_id(&mut self, word: &str) -> usize {
+        match self.symbol_table.get(word).and_then(|ids| ids.last()) {
+            Some(id) => *id,
+            None => {
+                let id = self.max_id;
+                self.max_id += 1;
+                self.symbol_table.insert(word.to_string(), vec![id]);
+                id
+            }
+        }
+    }
+
+    /// Links a bound `ki` word filling a single place to the variable it is bound to, returning
+    /// whether it did.
+    fn resolve_ki(&mut self, word: &str, negation: Negation, vars: &[Var]) -> bool {
+        let (Negation::None, [var], Some(bound)) = (
+            negation,
+            vars,
+            self.ki_vars.get(word).and_then(|vars| vars.last()),
+        ) else {
+            return false;
+        };
+        if var == bound || self.aliases.contains_key(var) {
+            return false;
+        }
+        self.aliases.insert(*var, *bound);
+        true
+    }
+
+    fn visit(
+        &mut self,
+        tree: PredicateTree,
+        chaining_with: PredicateChaining,
+        vars: Vec<Var>,
+        orig_new_vars: &mut Vec<Var>,
+        orig_preds: &mut Vec<B::Output>,
+    ) {
+        if self.cancel.is_some_and(CancellationToken::is_cancelled) {
+            return;
+        }
+        trace!(Enter { rule: "to_expr" });
+        // Each kind of tree is visited in a function of its own, which takes it apart, so that
+        // the frames every tree nested in another adds to the stack stay small.
+        match tree {
+            PredicateTree::Binding { .. } => {
+                self.binding(tree, chaining_with, vars, orig_new_vars, orig_preds)
+            }
+            PredicateTree::Connective { .. } => {
+                self.connected(tree, chaining_with, vars, orig_new_vars, orig_preds)
+            }
+            PredicateTree::Abstraction { .. } => {
+                self.abstraction(tree, &vars, orig_new_vars, orig_preds)
+            }
+            PredicateTree::Quantified { .. } => {
+                self.quantified(tree, &vars, orig_new_vars, orig_preds)
+            }
+            PredicateTree::Leaf { .. }
+            | PredicateTree::Name { .. }
+            | PredicateTree::Number { .. } => self.atom(tree, vars, orig_new_vars, orig_preds),
+        }
+        trace!(Exit { rule: "to_expr" });
+    }
+
+    /// Visits a leaf, name or number filling `vars`.
+    fn atom(
+        &mut self,
+        tree: PredicateTree,
+        vars: Vec<Var>,
+        orig_new_vars: &mut Vec<Var>,
+        orig_preds: &mut Vec<B::Output>,
+    ) {
+        let (p, negation) = match tree {
+            PredicateTree::Leaf { word, negation, .. } => {
+                if self.resolve_ki(&word.word, negation, &vars) {
+                    return;
+                }
+                let id = self.leaf_id(&word.word);
+                (self.backend.leaf(&word.word, id, vars), negation)
+            }
+            PredicateTree::Name { word, negation, .. } => {
+                let var = match vars.first() {
+                    Some(v) => *v,
+                    None => self.fresh_var(),
+                };
+                self.constants.insert(var);
+                (self.backend.name(var, &word.word), negation)
+            }
+            PredicateTree::Number {
+                value, negation, ..
+            } => {
+                let var = match vars.first() {
+                    Some(v) => *v,
+                    None => {
+                        let v = self.fresh_var();
+                        orig_new_vars.push(v);
+                        v
+                    }
+                };
+                (self.backend.cardinality(var, value), negation)
+            }
+            _ => return,
+        };
+        let p = self.negated(negation, p);
+        orig_preds.push(p)
+    }
+
+    /// Negates `p` as `negation` says.
+    fn negated(&mut self, negation: Negation, p: B::Output) -> B::Output {
+        let p = if negation.short() {
+            self.backend.short_negation(p)
+        } else {
+            p
+        };
+        if negation.long() {
+            self.backend.long_negation(p)
+        } else {
+            p
+        }
+    }
+
+    /// Visits a connective, both operands of which fill `vars`.
+    fn connected(
+        &mut self,
+        tree: PredicateTree,
+        chaining_with: PredicateChaining,
+        mut vars: Vec<Var>,
+        orig_new_vars: &mut Vec<Var>,
+        orig_preds: &mut Vec<B::Output>,
+    ) {
+        let PredicateTree::Connective {
+            kind,
+            negation,
+            left,
+            right,
+        } = tree
+        else {
+            return;
+        };
+        let mut left_vars = Vec::new();
+        let mut left_preds = Vec::new();
+        self.visit(
+            *left,
+            chaining_with,
+            vars.clone(),
+            &mut left_vars,
+            &mut left_preds,
+        );
+        // The right operand fills the same places, including those the left one opened.
+        vars.extend(left_vars.iter().copied());
+        let mut right_vars = Vec::new();
+        let mut right_preds = Vec::new();
+        self.visit(
+            *right,
+            chaining_with,
+            vars,
+            &mut right_vars,
+            &mut right_preds,
+        );
+        orig_new_vars.extend(left_vars);
+        orig_new_vars.extend(right_vars);
+
+        let l = self.backend.conjunction(left_preds);
+        let r = self.backend.conjunction(right_preds);
+        let p = self.connective(kind, l, r);
+        let p = self.negated(negation, p);
+        orig_preds.push(p)
+    }
+
+    /// Visits an abstraction over the first of `vars`.
+    fn abstraction(
+        &mut self,
+        tree: PredicateTree,
+        vars: &[Var],
+        orig_new_vars: &mut Vec<Var>,
+        orig_preds: &mut Vec<B::Output>,
+    ) {
+        let PredicateTree::Abstraction {
+            kind,
+            negation,
+            tree,
+        } = tree
+        else {
+            return;
+        };
+        let var = match vars.first() {
+            Some(v) => *v,
+            None => {
+                let v = self.fresh_var();
+                orig_new_vars.push(v);
+                v
+            }
+        };
+        // The clause is closed off: its own places are bound inside it, except the one a
+        // property is ascribed to, which is abstracted over.
+        let place = match kind {
+            AbstractionKind::Property => vec![self.fresh_var()],
+            AbstractionKind::Event | AbstractionKind::Proposition => Vec::new(),
+        };
+        let mut new_vars = Vec::new();
+        let mut new_preds = Vec::new();
+        self.visit(
+            *tree,
+            PredicateChaining::Equivalence,
+            place.clone(),
+            &mut new_vars,
+            &mut new_preds,
+        );
+        new_vars.retain(|v| !self.constants.contains(v));
+        let p = self.backend.conjunction(new_preds);
+        let p = if new_vars.is_empty() {
+            p
+        } else {
+            self.backend.exists(new_vars, p)
+        };
+        let p = if place.is_empty() {
+            p
+        } else {
+            self.backend.lambda(place, p)
+        };
+        let p = self.backend.abstraction(kind, var, p);
+        let p = self.negated(negation, p);
+        orig_preds.push(p)
+    }
+
+    /// Visits a quantified tree outside of a sharer. The quantifier ranges over the first of
+    /// `vars`, with nothing in its scope.
+    fn quantified(
+        &mut self,
+        tree: PredicateTree,
+        vars: &[Var],
+        orig_new_vars: &mut Vec<Var>,
+        orig_preds: &mut Vec<B::Output>,
+    ) {
+        let PredicateTree::Quantified { quantifier, tree } = tree else {
+            return;
+        };
+        let var = match vars.first() {
+            Some(v) => *v,
+            None => self.fresh_var(),
+        };
+        let mut restrictor = Vec::new();
+        self.visit(
+            *tree,
+            PredicateChaining::Sharing,
+            vec![var],
+            orig_new_vars,
+            &mut restrictor,
+        );
+        let r = self.backend.conjunction(restrictor);
+        let scope = self.backend.conjunction(Vec::new());
+        let p = self.backend.quantified(quantifier, var, r, scope);
+        orig_preds.push(p)
+    }
+
+    /// Visits a binding. The steps of a binding are done in functions of their own, for the
+    /// same reason as in [`visit`](Self::visit).
+    fn binding(
+        &mut self,
+        tree: PredicateTree,
+        chaining_with: PredicateChaining,
+        vars: Vec<Var>,
+        orig_new_vars: &mut Vec<Var>,
+        orig_preds: &mut Vec<B::Output>,
+    ) {
+        let PredicateTree::Binding {
+            chaining: _,
+            root,
+            negation,
+            exposure,
+            sharers,
+            and,
+            tags,
+        } = tree
+        else {
+            return;
+        };
+        // Tags can modify places that nothing is chained to.
+        let places = tags
+            .iter()
+            .map(|(tag, _)| tag.modified_place() as usize + 1)
+            .fold(sharers.len(), usize::max);
+        let (vars, close_over) = self.places(
+            &exposure,
+            places,
+            chaining_with,
+            vars,
+            orig_new_vars,
+            orig_preds,
+        );
+
+        let closure_needed = !close_over.is_empty();
+        // Quantified sharers scope over the rest of the binding.
+        let quantified = sharers.iter().flatten().any(|(chaining, p)| {
+            *chaining == PredicateChaining::Sharing && matches!(p, PredicateTree::Quantified { .. })
+        });
+        let mut new_new_vars = close_over;
+        let mut new_preds = Vec::new();
+        let new_vars = if closure_needed || negation != Negation::None {
+            &mut new_new_vars
+        } else {
+            &mut *orig_new_vars
+        };
+        let preds = if closure_needed || negation != Negation::None || quantified {
+            &mut new_preds
+        } else {
+            &mut *orig_preds
+        };
+
+        self.visit(
+            *root,
+            PredicateChaining::Equivalence,
+            vars.clone(),
+            new_vars,
+            preds,
+        );
+
+        let transparent = matches!(exposure, Exposure::Transparent);
+        let quantifiers = self.sharers(sharers, &vars, transparent, new_vars, preds);
+        self.adjuncts(tags, &vars, preds);
+
+        let preds = if negation != Negation::None {
+            &mut new_preds
+        } else {
+            &mut *orig_preds
+        };
+        self.chained(and, preds);
+
+        self.release(&exposure);
+
+        if closure_needed || negation != Negation::None || quantified {
+            let p = self.close(
+                negation,
+                quantifiers,
+                new_new_vars,
+                new_preds,
+                orig_new_vars,
+            );
+            orig_preds.push(p);
+        }
+    }
+
+    /// The variables filling the places of a binding with `places` places and the variables it
+    /// closes over, pushing those it opens to `new_vars` and the predicates of its explicit
+    /// exposure to `preds`.
+    fn places(
+        &mut self,
+        exposure: &Exposure,
+        places: usize,
+        chaining_with: PredicateChaining,
+        mut vars: Vec<Var>,
+        new_vars: &mut Vec<Var>,
+        preds: &mut Vec<B::Output>,
+    ) -> (Vec<Var>, Vec<Var>) {
+        let mut close_over = Vec::new();
+        let chain_place = match exposure {
+            Exposure::Standard | Exposure::Transparent | Exposure::Explicit(_) => 0,
+            Exposure::Modified(vec) => vec.first().copied().unwrap_or(0),
+        };
+        if matches!(chaining_with, PredicateChaining::Sharing) {
+            let chain_var = match vars.first() {
+                Some(v) => *v,
+                None => {
+                    let v = self.fresh_var();
+                    close_over.push(v);
+                    v
+                }
+            };
+            // The place chained by is filled even if nothing is chained to the places
+            // before it, or to it.
+            vars = (0..places.max(chain_place as usize + 1))
+                .map(|i| {
+                    if i == chain_place as usize {
+                        chain_var
+                    } else {
+                        let v = self.fresh_var();
+                        close_over.push(v);
+                        v
+                    }
+                })
+                .collect();
+        } else {
+            for _ in 0..(places.saturating_sub(vars.len())) {
+                let v = self.fresh_var();
+                new_vars.push(v);
+                vars.push(v);
+            }
+        }
+        if let Exposure::Explicit(vec) = exposure {
+            for (i, (argument, chain_with)) in vec.iter().enumerate() {
+                let mut var = self.fresh_var();
+                if let Some(v) = vars.get_mut(i) {
+                    close_over.push(var);
+                    mem::swap(v, &mut var);
+                } else {
+                    new_vars.push(var);
+                }
+                let var = var;
+
+                let p = match argument {
+                    Argument::Word(word) => {
+                        let id = self.max_id;
+                        self.max_id += 1;
+                        self.symbol_table.entry(word.clone()).or_default().push(id);
+                        if *chain_with == PredicateChaining::Sharing {
+                            self.ki_vars.entry(word.clone()).or_default().push(var);
+                        }
+                        self.backend.exposure(word, id, var, *chain_with)
+                    }
+                    Argument::Number(count) => self.backend.cardinality(var, *count),
+                    Argument::Name(name) => {
+                        self.constants.insert(var);
+                        self.backend.name(var, name)
+                    }
+                    Argument::Quote(quote) => {
+                        let word = quote.leaf_name();
+                        let id = self.leaf_id(&word);
+                        self.backend.leaf(&word, id, vec![var])
+                    }
+                };
+                preds.push(p);
+            }
+        }
+        (vars, close_over)
+    }
+
+    /// Visits the trees chained to the places `vars` of a binding, returning the quantifiers of
+    /// those that are quantified, with their restrictors.
+    fn sharers(
+        &mut self,
+        sharers: Vec<Vec<(PredicateChaining, PredicateTree)>>,
+        vars: &[Var],
+        transparent: bool,
+        new_vars: &mut Vec<Var>,
+        preds: &mut Vec<B::Output>,
+    ) -> Vec<(Quantifier, Var, B::Output)> {
+        let mut quantifiers = Vec::new();
+        for (set, var) in sharers.into_iter().zip(vars.iter().copied()) {
+            for (chaining, pred_tree) in set {
+                match (chaining, pred_tree) {
+                    (
+                        PredicateChaining::Sharing,
+                        PredicateTree::Quantified { quantifier, tree },
+                    ) => {
+                        let r = self.restrictor(*tree, var, new_vars);
+                        quantifiers.push((quantifier, var, r));
+                    }
+                    (PredicateChaining::Sharing, pred_tree) => {
+                        self.visit(pred_tree, chaining, vec![var], new_vars, preds)
+                    }
+                    (PredicateChaining::Equivalence, pred_tree) => {
+                        self.equivalent(pred_tree, var, transparent, new_vars, preds)
+                    }
+                }
+            }
+        }
+        quantifiers
+    }
+
+    /// The restrictor of a quantified tree chained to `var` by a sharer.
+    fn restrictor(&mut self, tree: PredicateTree, var: Var, new_vars: &mut Vec<Var>) -> B::Output {
+        let mut restrictor = Vec::new();
+        self.visit(
+            tree,
+            PredicateChaining::Sharing,
+            vec![var],
+            new_vars,
+            &mut restrictor,
+        );
+        self.backend.conjunction(restrictor)
+    }
+
+    /// Visits a tree chained to `var` by equivalence. Through a transparent exposure, the places
+    /// of the tree are the binding's own.
+    fn equivalent(
+        &mut self,
+        tree: PredicateTree,
+        var: Var,
+        transparent: bool,
+        new_vars: &mut Vec<Var>,
+        preds: &mut Vec<B::Output>,
+    ) {
+        let mut equiv_preds = Vec::new();
+        if transparent {
+            self.visit(
+                tree,
+                PredicateChaining::Equivalence,
+                Vec::new(),
+                new_vars,
+                &mut equiv_preds,
+            );
+            let p = self.backend.conjunction(equiv_preds);
+            preds.push(self.backend.equivalent(var, p));
+        } else {
+            let mut new_vars = Vec::new();
+            self.visit(
+                tree,
+                PredicateChaining::Equivalence,
+                Vec::new(),
+                &mut new_vars,
+                &mut equiv_preds,
+            );
+            let p = self.backend.conjunction(equiv_preds);
+            new_vars.retain(|v| !self.constants.contains(v));
+            let p = if new_vars.is_empty() {
+                p
+            } else {
+                self.backend.lambda(new_vars, p)
+            };
+            preds.push(self.backend.equivalent(var, p));
+        }
+    }
+
+    /// Visits the trees attached by `tags` to the places `vars` of a binding.
+    fn adjuncts(
+        &mut self,
+        tags: Vec<(Tag, PredicateTree)>,
+        vars: &[Var],
+        preds: &mut Vec<B::Output>,
+    ) {
+        for (tag, tree) in tags {
+            let var = vars[tag.modified_place() as usize];
+            self.adjunct(&tag.tag, var, tree, preds);
+        }
+    }
+
+    /// Visits the tree an adjunct attaches. An adjunct is a predicate of the tag over the
+    /// modified place `var` and the first place of the tree it attaches, which a quantifier on
+    /// the tree ranges over.
+    fn adjunct(&mut self, tag: &str, var: Var, tree: PredicateTree, preds: &mut Vec<B::Output>) {
+        let arg = self.fresh_var();
+        let id = self.leaf_id(tag);
+        let tagged = self.backend.leaf(tag, id, vec![var, arg]);
+        let mut tag_vars = Vec::new();
+        let mut tag_preds = Vec::new();
+        let p = match tree {
+            PredicateTree::Quantified { quantifier, tree } => {
+                self.visit(
+                    *tree,
+                    PredicateChaining::Sharing,
+                    vec![arg],
+                    &mut tag_vars,
+                    &mut tag_preds,
+                );
+                let r = self.backend.conjunction(tag_preds);
+                self.backend.quantified(quantifier, arg, r, tagged)
+            }
+            tree => {
+                tag_vars.push(arg);
+                self.visit(
+                    tree,
+                    PredicateChaining::Sharing,
+                    vec![arg],
+                    &mut tag_vars,
+                    &mut tag_preds,
+                );
+                tag_preds.push(tagged);
+                self.backend.conjunction(tag_preds)
+            }
+        };
+        tag_vars.retain(|v| !self.constants.contains(v));
+        if tag_vars.is_empty() {
+            preds.push(p)
+        } else {
+            preds.push(self.backend.exists(tag_vars, p))
+        }
+    }
+
+    /// Visits the trees chained after a binding, each with places of its own.
+    fn chained(&mut self, and: Vec<PredicateTree>, preds: &mut Vec<B::Output>) {
+        for tree in and {
+            self.chained_tree(tree, preds);
+        }
+    }
+
+    /// Visits a tree chained after a binding.
+    fn chained_tree(&mut self, tree: PredicateTree, preds: &mut Vec<B::Output>) {
+        let mut new_vars = Vec::new();
+        let mut new_preds = Vec::new();
+        self.visit(
+            tree,
+            PredicateChaining::Equivalence,
+            Vec::new(),
+            &mut new_vars,
+            &mut new_preds,
+        );
+
+        let p = self.backend.conjunction(new_preds);
+        new_vars.retain(|v| !self.constants.contains(v));
+        if new_vars.is_empty() {
+            preds.push(p)
+        } else {
+            preds.push(self.backend.exists(new_vars, p))
+        }
+    }
+
+    /// Ends the scope of the words bound by an explicit exposure.
+    fn release(&mut self, exposure: &Exposure) {
+        let Exposure::Explicit(vec) = exposure else {
+            return;
+        };
+        for (argument, chain_with) in vec.iter() {
+            if let Argument::Word(word) = argument {
+                if let Some(ids) = self.symbol_table.get_mut(word) {
+                    ids.pop();
+                }
+                if *chain_with == PredicateChaining::Sharing {
+                    if let Some(vars) = self.ki_vars.get_mut(word) {
+                        vars.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    /// The predicates of a binding closed off: quantified by its quantified sharers, negated
+    /// and with the variables it closes over bound.
+    fn close(
+        &mut self,
+        negation: Negation,
+        quantifiers: Vec<(Quantifier, Var, B::Output)>,
+        mut new_new_vars: Vec<Var>,
+        new_preds: Vec<B::Output>,
+        orig_new_vars: &mut Vec<Var>,
+    ) -> B::Output {
+        let mut p = self.backend.conjunction(new_preds);
+        for (quantifier, var, restrictor) in quantifiers.into_iter().rev() {
+            new_new_vars.retain(|v| *v != var);
+            orig_new_vars.retain(|v| *v != var);
+            p = self.backend.quantified(quantifier, var, restrictor, p);
+        }
+
+        let p = if negation.short() {
+            self.backend.short_negation(p)
+        } else {
+            p
+        };
+        new_new_vars.retain(|v| !self.constants.contains(v));
+        let p = if !new_new_vars.is_empty() {
+            self.backend.exists(new_new_vars, p)
+        } else {
+            p
+        };
+        if negation.long() {
+            self.backend.long_negation(p)
+        } else {
+            p
+        }
+    }
+
+    /// Lowers a connective to conjunctions, disjunctions and short negations of its operands.
+    fn connective(&mut self, kind: ConnectiveKind, l: B::Output, r: B::Output) -> B::Output {
+        match kind {
+            ConnectiveKind::And => self.backend.conjunction(vec![l, r]),
+            ConnectiveKind::Or => self.backend.disjunction(vec![l, r]),
+            ConnectiveKind::Xor => {
+                let both = self.backend.conjunction(vec![l.clone(), r.clone()]);
+                let not_both = self.backend.short_negation(both);
+                let either = self.backend.disjunction(vec![l, r]);
+                self.backend.conjunction(vec![either, not_both])
+            }
+            ConnectiveKind::Implication => {
+                let not_l = self.backend.short_negation(l);
+                self.backend.disjunction(vec![not_l, r])
+            }
+            ConnectiveKind::Equivalence => {
+                let both = self.backend.conjunction(vec![l.clone(), r.clone()]);
+                let not_l = self.backend.short_negation(l);
+                let not_r = self.backend.short_negation(r);
+                let neither = self.backend.conjunction(vec![not_l, not_r]);
+                self.backend.disjunction(vec![both, neither])
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! "Did you mean" suggestions for misspelled words: the valid roots and particles closest to a
+//! word by edit distance.
+
+use std::collections::BTreeSet;
+
+use crate::{
+    dictionary::Dictionary,
+    lexer::{NON_SONORANT, SONORANT, VOWELS},
+    phonotactics::{is_valid_particle, is_valid_root},
+};
+
+/// The largest number of edits between a word and a suggestion for it.
+pub const MAX_DISTANCE: usize = 2;
+
+fn alphabet() -> impl Iterator<Item = char> {
+    VOWELS
+        .iter()
+        .chain(&NON_SONORANT)
+        .chain(&SONORANT)
+        .chain(&['h'])
+        .copied()
+}
+
+/// The words one deletion, substitution, insertion or transposition of adjacent letters away
+/// from `word`, using only letters of the alphabet.
+fn edits(word: &[char]) -> impl Iterator<Item = Vec<char>> + '_ {
+    let deletions = (0..word.len()).map(|i| {
+        let mut w = word.to_vec();
+        w.remove(i);
+        w
+    });
+    let substitutions = (0..word.len()).flat_map(move |i| {
+        alphabet().filter(move |c| word[i] != *c).map(move |c| {
+            let mut w = word.to_vec();
+            w[i] = c;
+            w
+        })
+    });
+    let insertions = (0..=word.len()).flat_map(move |i| {
+        alphabet().map(move |c| {
+            let mut w = word.to_vec();
+            w.insert(i, c);
+            w
+        })
+    });
+    let transpositions = (1..word.len())
+        .filter(|i| word[i - 1] != word[*i])
+        .map(|i| {
+            let mut w = word.to_vec();
+            w.swap(i - 1, i);
+            w
+        });
+    deletions
+        .chain(substitutions)
+        .chain(insertions)
+        .chain(transpositions)
+}
+
+/// The number of deletions, substitutions, insertions and transpositions of adjacent letters
+/// turning `a` into `b`, each letter being edited at most once.
+pub fn distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    // Three rows of the table, for the prefixes of `a` of length i - 2, i - 1 and i.
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (previous[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                if let Some(d) = before.get(j - 2) {
+                    row[j] = row[j].min(d + 1);
+                }
+            }
+        }
+        before = std::mem::replace(&mut previous, row);
+    }
+    previous.last().copied().unwrap_or(0)
+}
+
+fn is_valid(word: &str) -> bool {
+    is_valid_root(word).is_ok() || is_valid_particle(word).is_ok()
+}
+
+/// Up to `n` valid roots and particles at most [`MAX_DISTANCE`] edits away from `word`, closest
+/// first, then in alphabetical order. `word` itself is never suggested.
+pub fn suggest(word: &str, n: usize) -> Vec<String> {
+    suggest_ranked(word, n, None)
+}
+
+/// Like [`suggest`], ranking the words of `dictionary` before other words at the same distance.
+/// Words of the dictionary are suggested even if they are not valid roots or particles.
+pub fn suggest_in(word: &str, n: usize, dictionary: &Dictionary) -> Vec<String> {
+    suggest_ranked(word, n, Some(dictionary))
+}
+
+fn suggest_ranked(word: &str, n: usize, dictionary: Option<&Dictionary>) -> Vec<String> {
+    let word = word.to_lowercase();
+    let in_dictionary = |w: &str| dictionary.is_some_and(|d| d.get(w).is_some());
+
+    // Every word within reach, by breadth-first search so that each is found at its distance.
+    let mut seen = BTreeSet::from([word.chars().collect::<Vec<_>>()]);
+    let mut frontier = vec![word.chars().collect::<Vec<_>>()];
+    let mut candidates = BTreeSet::new();
+    for distance in 1..=MAX_DISTANCE {
+        let mut next = Vec::new();
+        for w in &frontier {
+            for edit in edits(w) {
+                if seen.insert(edit.clone()) {
+                    next.push(edit);
+                }
+            }
+        }
+        for w in &next {
+            let w: String = w.iter().collect();
+            if is_valid(&w) {
+                candidates.insert((distance, !in_dictionary(&w), w));
+            }
+        }
+        frontier = next;
+    }
+    if let Some(dictionary) = dictionary {
+        for entry in dictionary.iter() {
+            let d = distance(&word, &entry.word);
+            if (1..=MAX_DISTANCE).contains(&d) {
+                candidates.insert((d, false, entry.word.clone()));
+            }
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for (_, _, w) in candidates {
+        if suggestions.len() >= n {
+            break;
+        }
+        if !suggestions.contains(&w) {
+            suggestions.push(w);
+        }
+    }
+    suggestions
+}
@@ -0,0 +1,113 @@
+//! Frequencies of letters, syllables and word families over token streams.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    json::Value,
+    lexer::{ParticleFamily, Quote, Word},
+    phonotactics::syllabify,
+};
+
+/// Counts gathered over any number of token streams.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusStats {
+    pub words: usize,
+    pub letters: BTreeMap<char, usize>,
+    /// Syllables by their spelling, as split by [`syllabify`].
+    pub syllables: BTreeMap<String, usize>,
+    /// Particles by [`ParticleFamily::name`].
+    pub particle_families: BTreeMap<&'static str, usize>,
+    /// Predicate words by [`PredicateFamily::name`](crate::lexer::PredicateFamily::name).
+    pub predicate_families: BTreeMap<&'static str, usize>,
+}
+
+/// The eberban words spelled by a token: a quoted word counts along with its `ce`, while the
+/// delimiter and content of a foreign quote are not eberban and only its `cu` counts.
+fn spelled(word: &Word) -> Vec<String> {
+    match word {
+        Word::Particle(ParticleFamily::Quote(Quote::Word(quoted))) => {
+            vec!["ce".to_string(), quoted.clone()]
+        }
+        Word::Particle(ParticleFamily::Quote(Quote::Foreign { .. })) => vec!["cu".to_string()],
+        word => vec![word.text()],
+    }
+}
+
+impl CorpusStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The counts of a single token stream.
+    pub fn from_words(words: &[Word]) -> Self {
+        let mut stats = Self::new();
+        stats.add(words);
+        stats
+    }
+
+    /// Adds the counts of `words`.
+    pub fn add(&mut self, words: &[Word]) {
+        for word in words {
+            self.words += 1;
+            match word {
+                Word::Particle(p) => *self.particle_families.entry(p.name()).or_default() += 1,
+                Word::Predicate(_, family) => {
+                    *self.predicate_families.entry(family.name()).or_default() += 1
+                }
+            }
+            for spelled in spelled(word) {
+                for c in spelled.chars() {
+                    *self.letters.entry(c).or_default() += 1;
+                }
+                for syllable in syllabify(&spelled) {
+                    *self.syllables.entry(syllable.text()).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    /// Adds the counts of another corpus.
+    pub fn merge(&mut self, other: &CorpusStats) {
+        fn merge<K: Ord + Clone>(into: &mut BTreeMap<K, usize>, from: &BTreeMap<K, usize>) {
+            for (k, n) in from {
+                *into.entry(k.clone()).or_default() += n;
+            }
+        }
+        self.words += other.words;
+        merge(&mut self.letters, &other.letters);
+        merge(&mut self.syllables, &other.syllables);
+        merge(&mut self.particle_families, &other.particle_families);
+        merge(&mut self.predicate_families, &other.predicate_families);
+    }
+
+    /// The counts as a JSON object, each table sorted from most to least frequent, then by key.
+    pub fn to_json(&self) -> Value {
+        fn table<K: ToString>(counts: impl IntoIterator<Item = (K, usize)>) -> Value {
+            let mut counts: Vec<(String, usize)> = counts
+                .into_iter()
+                .map(|(k, n)| (k.to_string(), n))
+                .collect();
+            counts.sort_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+            Value::Object(counts.into_iter().map(|(k, n)| (k, n.into())).collect())
+        }
+        Value::Object(vec![
+            ("words".into(), self.words.into()),
+            (
+                "letters".into(),
+                table(self.letters.iter().map(|(k, n)| (k, *n))),
+            ),
+            (
+                "syllables".into(),
+                table(self.syllables.iter().map(|(k, n)| (k, *n))),
+            ),
+            (
+                "particle_families".into(),
+                table(self.particle_families.iter().map(|(k, n)| (k, *n))),
+            ),
+            (
+                "predicate_families".into(),
+                table(self.predicate_families.iter().map(|(k, n)| (k, *n))),
+            ),
+        ])
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    fmt,
+    io::{self, Read},
+    iter,
+    ops::Range,
+    str,
+};
+
+use chumsky::{error::Cheap, Parser, Stream};
+
+use crate::{
+    chunked::{chunks, Chunks, DEFAULT_CHUNK_SIZE},
+    lexer::{
+        assemble_numbers, assemble_spanned_numbers, lex, lex_spanned, lex_words, LexError,
+        ParticleFamily, Word,
+    },
+    parser::{self, sentence_parser, Sentence},
+    EberbanError,
+};
+
+/// Why a [`StreamLexer`] stopped.
+#[derive(Debug)]
+pub enum StreamError {
+    Lex(LexError),
+    /// Reading the input failed, or it is not valid UTF-8.
+    Io(io::Error),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Lex(e) => write!(f, "{e}"),
+            StreamError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<LexError> for StreamError {
+    fn from(e: LexError) -> Self {
+        StreamError::Lex(e)
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+/// The characters of a UTF-8 reader.
+pub struct Utf8Chars<R> {
+    reader: R,
+    bytes: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> Utf8Chars<R> {
+    pub fn new(reader: R) -> Self {
+        Utf8Chars {
+            reader,
+            bytes: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Reads until at least `n` bytes are buffered, returning false at the end of the input.
+    fn fill(&mut self, n: usize) -> io::Result<bool> {
+        self.bytes.drain(..self.pos);
+        self.pos = 0;
+        let mut buf = [0; 8 * 1024];
+        while self.bytes.len() < n {
+            match self.reader.read(&mut buf) {
+                Ok(0) => return Ok(false),
+                Ok(read) => self.bytes.extend_from_slice(&buf[..read]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for Utf8Chars<R> {
+    type Item = io::Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "stream is not valid UTF-8");
+        if self.pos >= self.bytes.len() {
+            match self.fill(1) {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let width = match self.bytes.get(self.pos) {
+            Some(b) if *b < 0x80 => 1,
+            Some(b) if *b >> 5 == 0b110 => 2,
+            Some(b) if *b >> 4 == 0b1110 => 3,
+            Some(b) if *b >> 3 == 0b11110 => 4,
+            _ => {
+                self.pos += 1;
+                return Some(Err(invalid()));
+            }
+        };
+        if self.pos + width > self.bytes.len() {
+            match self.fill(width) {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.pos = self.bytes.len();
+                    return Some(Err(invalid()));
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let c = self
+            .bytes
+            .get(self.pos..self.pos + width)
+            .and_then(|bytes| str::from_utf8(bytes).ok())
+            .and_then(|s| s.chars().next());
+        self.pos += width;
+        Some(c.ok_or_else(invalid))
+    }
+}
+
+/// Lexes a stream of characters, yielding words as soon as the text around them has been read.
+///
+/// Characters are buffered until at least `chunk_size` bytes end at a point where
+/// [`chunks`] may split the text; everything before that point is lexed, so only the last chunk
+/// and any quote still open are kept in memory. A number is held back until the word after it
+/// is lexed, as it may continue in the next chunk. Spans of errors index into the characters of
+/// the whole stream. The lexer stops at the first error, after yielding the words already lexed.
+pub struct StreamLexer<I> {
+    chars: I,
+    chunk_size: usize,
+    buffer: String,
+    /// How many characters were read before `buffer`.
+    offset: usize,
+    /// The length of `buffer` at which it is next split.
+    split_at: usize,
+    words: VecDeque<Word>,
+    error: Option<StreamError>,
+    finished: bool,
+}
+
+type CharResults<I> = iter::Map<I, fn(char) -> io::Result<char>>;
+
+impl<I: Iterator<Item = char>> StreamLexer<CharResults<I>> {
+    pub fn from_chars(chars: impl IntoIterator<IntoIter = I>) -> Self {
+        StreamLexer::new(chars.into_iter().map(Ok as fn(char) -> io::Result<char>))
+    }
+}
+
+impl<R: Read> StreamLexer<Utf8Chars<R>> {
+    pub fn from_reader(reader: R) -> Self {
+        StreamLexer::new(Utf8Chars::new(reader))
+    }
+}
+
+impl<I: Iterator<Item = io::Result<char>>> StreamLexer<I> {
+    pub fn new(chars: I) -> Self {
+        Self::with_chunk_size(chars, DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(chars: I, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.max(1);
+        StreamLexer {
+            chars,
+            chunk_size,
+            buffer: String::new(),
+            offset: 0,
+            split_at: chunk_size,
+            words: VecDeque::new(),
+            error: None,
+            finished: false,
+        }
+    }
+
+    /// Lexes `text`, which starts `offset` characters into the stream.
+    fn lex(&mut self, text: &str, offset: usize) -> Result<(), LexError> {
+        let words = lex_words(text).map_err(|errors| {
+            let span = errors.first().map_or(0..0, |e| e.span());
+            let error = LexError::classify(text, span);
+            LexError {
+                span: error.span.start + offset..error.span.end + offset,
+                ..error
+            }
+        })?;
+        let held = self.words.len().saturating_sub(1);
+        let mut tail: Vec<Word> = self.words.drain(held..).collect();
+        tail.extend(words);
+        self.words.extend(assemble_numbers(tail));
+        Ok(())
+    }
+
+    /// Reads and lexes at least one more chunk.
+    fn fill(&mut self) -> Result<(), StreamError> {
+        loop {
+            match self.chars.next().transpose()? {
+                Some(c) => {
+                    self.buffer.push(c);
+                    if self.buffer.len() < self.split_at || !c.is_whitespace() {
+                        continue;
+                    }
+                    let buffer = std::mem::take(&mut self.buffer);
+                    let mut chunks = chunks(&buffer, self.chunk_size);
+                    let last = chunks.pop().unwrap_or((0, ""));
+                    for (offset, chunk) in &chunks {
+                        self.lex(chunk, self.offset + offset)?;
+                    }
+                    self.offset += last.0;
+                    self.buffer = last.1.to_string();
+                    // Splitting again before another chunk is read would only rescan the same
+                    // open quote.
+                    self.split_at = self.buffer.len() + self.chunk_size;
+                    if !chunks.is_empty() {
+                        return Ok(());
+                    }
+                }
+                None => {
+                    self.finished = true;
+                    let buffer = std::mem::take(&mut self.buffer);
+                    return Ok(self.lex(&buffer, self.offset)?);
+                }
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = io::Result<char>>> Iterator for StreamLexer<I> {
+    type Item = Result<Word, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let held = matches!(
+                self.words.back(),
+                Some(Word::Particle(ParticleFamily::Number(_)))
+            ) && self.words.len() == 1;
+            if self.finished || !held {
+                if let Some(word) = self.words.pop_front() {
+                    return Some(Ok(word));
+                }
+            }
+            if self.finished {
+                return self.error.take().map(Err);
+            }
+            if let Err(e) = self.fill() {
+                self.finished = true;
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+/// The size in bytes of the chunks lexed at a time by [`Tokens`] when none is given: small, so
+/// that stopping early saves most of the work.
+pub const DEFAULT_TOKENS_CHUNK_SIZE: usize = 1024;
+
+/// The words of a text with their spans in its characters, lexed a chunk at a time as they are
+/// iterated over, so that a caller stopping early does not lex the rest of the text.
+///
+/// The text is split as by [`Chunks`], and a number is held back until the word after it is
+/// lexed, as it may continue in the next chunk. The words are the same as those of
+/// [`lex_spanned`] on the whole text. Iteration stops at the first error, after yielding the
+/// words before the chunk it is in.
+pub struct Tokens<'a> {
+    chunks: Chunks<'a>,
+    words: VecDeque<(Word, Range<usize>)>,
+    error: Option<LexError>,
+    finished: bool,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self::with_chunk_size(s, DEFAULT_TOKENS_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(s: &'a str, chunk_size: usize) -> Self {
+        Tokens {
+            chunks: Chunks::new(s, chunk_size.max(1)),
+            words: VecDeque::new(),
+            error: None,
+            finished: false,
+        }
+    }
+
+    /// Lexes the next chunk, if there is one.
+    fn fill(&mut self) {
+        let Some((offset, chunk)) = self.chunks.next() else {
+            self.finished = true;
+            return;
+        };
+        match lex_spanned(chunk) {
+            Ok(words) => {
+                let words = self
+                    .words
+                    .drain(..)
+                    .chain(
+                        words
+                            .into_iter()
+                            .map(|(word, span)| (word, span.start + offset..span.end + offset)),
+                    )
+                    .collect();
+                self.words.extend(assemble_spanned_numbers(words));
+            }
+            Err(errors) => {
+                let span = errors.first().map_or(0..0, |e| e.span());
+                let error = LexError::classify(chunk, span);
+                self.error = Some(LexError {
+                    span: error.span.start + offset..error.span.end + offset,
+                    ..error
+                });
+                self.finished = true;
+            }
+        }
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<(Word, Range<usize>), LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let held = matches!(
+                self.words.back(),
+                Some((Word::Particle(ParticleFamily::Number(_)), _))
+            ) && self.words.len() == 1;
+            if self.finished || !held {
+                if let Some(word) = self.words.pop_front() {
+                    return Some(Ok(word));
+                }
+            }
+            if self.finished {
+                return self.error.take().map(Err);
+            }
+            self.fill();
+        }
+    }
+}
+
+/// The words of `s` with their spans, lexed lazily by [`Tokens`].
+pub fn tokens(s: &str) -> Tokens<'_> {
+    Tokens::new(s)
+}
+
+/// Lexes and parses the sentence `s` in a single pass: the parser reads the words of [`Tokens`]
+/// as it needs them, so that parsing starts as soon as the first chunk is lexed, and the words
+/// are never collected before the parser sees them.
+///
+/// The result is that of [`parser::parse`] on the words of [`lex`]. Errors are explained as
+/// those do, by lexing and parsing the whole sentence again once it has failed.
+pub fn parse_sentence(s: &str) -> Result<Sentence, EberbanError> {
+    let len = s.chars().count();
+    let failed = Cell::new(false);
+    let words = tokens(s).map_while(|token| {
+        failed.set(failed.get() || token.is_err());
+        token.ok()
+    });
+    let parsed = sentence_parser::<Cheap<Word>>().parse(Stream::from_iter(len..len, words));
+    match parsed {
+        Ok(sentence) if !failed.get() => Ok(sentence),
+        _ => {
+            let words = lex(s).map_err(EberbanError::Lex)?;
+            parser::parse(words).map_err(EberbanError::Parse)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate::sentences, lexer::SpannedWords};
+    use proptest::{prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn tokens_are_the_spanned_words(
+            s in "(ce|cu|vi|fe|ie|mo|dona|mian|pe|pei|be|zi|ni|pa|re|ci|a|e|[a-z0-9]| |')*",
+            chunk_size in 1..16usize,
+        ) {
+            let tokens: Result<SpannedWords, _> = Tokens::with_chunk_size(&s, chunk_size).collect();
+            prop_assert_eq!(tokens.ok(), lex_spanned(&s).ok(), "{:?}", s);
+        }
+
+        #[test]
+        fn parses_as_lex_then_parse(s in sentences()) {
+            let parsed = lex(&s)
+                .map_err(EberbanError::Lex)
+                .and_then(|words| parser::parse(words).map_err(EberbanError::Parse));
+            prop_assert_eq!(parse_sentence(&s), parsed, "{:?}", s);
+        }
+    }
+}
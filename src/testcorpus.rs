@@ -0,0 +1,210 @@
+use std::fmt;
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    expr::{to_expr, Predicate},
+    lexer::lex_words,
+    parser::parser,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expectation {
+    Formula(String),
+    Rejected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusEntry {
+    /// The line of the sentence in the corpus file, starting from 1.
+    pub line: usize,
+    pub sentence: String,
+    pub expected: Expectation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CorpusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CorpusError {}
+
+/// Loads a golden corpus. A corpus is a sequence of entries separated by blank lines. The first
+/// line of an entry is the sentence; it is followed either by a line starting with `=` giving
+/// the expected formula, in the notation printed by [`Predicate`]'s `Display`, or by a line
+/// consisting of `!` if the sentence is expected to be rejected. Lines starting with `#` are
+/// comments.
+///
+/// Formulas are compared up to renaming of variables and leaf ids, so expectations do not need
+/// updating when unrelated changes shift the numbering.
+pub fn load(s: &str) -> Result<Vec<CorpusEntry>, CorpusError> {
+    let mut entries = Vec::new();
+    let mut sentence: Option<(usize, String)> = None;
+    for (i, line) in s.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            if let Some((line, _)) = sentence {
+                return Err(CorpusError {
+                    line,
+                    message: "sentence without an expectation".to_string(),
+                });
+            }
+            continue;
+        }
+        match sentence.take() {
+            None => sentence = Some((line_no, line.to_string())),
+            Some((sentence_line, s)) => {
+                entries.push(CorpusEntry {
+                    line: sentence_line,
+                    sentence: s,
+                    expected: expectation(line_no, line)?,
+                });
+            }
+        }
+    }
+    match sentence {
+        Some((line, _)) => Err(CorpusError {
+            line,
+            message: "sentence without an expectation".to_string(),
+        }),
+        None => Ok(entries),
+    }
+}
+
+fn expectation(line_no: usize, line: &str) -> Result<Expectation, CorpusError> {
+    if let Some(formula) = line.strip_prefix('=') {
+        Ok(Expectation::Formula(formula.trim().to_string()))
+    } else if line == "!" {
+        Ok(Expectation::Rejected)
+    } else {
+        Err(CorpusError {
+            line: line_no,
+            message: "expected a line starting with `=`, or `!`".to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    /// The sentence was analysed, but to a formula not alpha-equivalent to the expected one.
+    Mismatch {
+        actual: String,
+    },
+    /// The sentence was expected to be analysed, but was rejected.
+    UnexpectedRejection,
+    /// The sentence was expected to be rejected, but was analysed.
+    UnexpectedSuccess {
+        actual: String,
+    },
+    /// The expected formula could not be read.
+    InvalidExpectation {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryResult {
+    pub entry: CorpusEntry,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusReport {
+    pub results: Vec<EntryResult>,
+}
+
+impl CorpusReport {
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| r.outcome == Outcome::Pass)
+            .count()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &EntryResult> {
+        self.results.iter().filter(|r| r.outcome != Outcome::Pass)
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+impl fmt::Display for CorpusReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in self.failures() {
+            let entry = &result.entry;
+            write!(f, "line {}: `{}`: ", entry.line, entry.sentence)?;
+            match &result.outcome {
+                Outcome::Pass => writeln!(f, "passed")?,
+                Outcome::Mismatch { actual } => writeln!(f, "got {actual}")?,
+                Outcome::UnexpectedRejection => writeln!(f, "rejected")?,
+                Outcome::UnexpectedSuccess { actual } => {
+                    writeln!(f, "expected a rejection, got {actual}")?
+                }
+                Outcome::InvalidExpectation { message } => writeln!(f, "{message}")?,
+            }
+        }
+        writeln!(f, "{}/{} passed", self.passed(), self.results.len())
+    }
+}
+
+/// Analyses a sentence into the closed formula printed for it, or `None` if it is rejected.
+pub fn analyse(sentence: &str) -> Option<Predicate> {
+    let words = lex_words(sentence).ok()?;
+    let tree = parser::<Cheap<_>>().parse(words).ok()?;
+    let (pred, vars) = to_expr(tree);
+    Some(Predicate::Lambda {
+        vars,
+        pred: Box::new(pred),
+    })
+}
+
+/// Whether two formulas are equal up to renaming of variables and leaf ids.
+pub fn alpha_equivalent(a: &Predicate, b: &Predicate) -> bool {
+    a.clone().into_canonical() == b.clone().into_canonical()
+}
+
+pub fn check(entry: &CorpusEntry) -> Outcome {
+    let actual = analyse(&entry.sentence);
+    match (&entry.expected, actual) {
+        (Expectation::Rejected, None) => Outcome::Pass,
+        (Expectation::Rejected, Some(actual)) => Outcome::UnexpectedSuccess {
+            actual: actual.to_string(),
+        },
+        (Expectation::Formula(_), None) => Outcome::UnexpectedRejection,
+        (Expectation::Formula(expected), Some(actual)) => match expected.parse::<Predicate>() {
+            Err(e) => Outcome::InvalidExpectation {
+                message: e.to_string(),
+            },
+            Ok(expected) if alpha_equivalent(&expected, &actual) => Outcome::Pass,
+            Ok(_) => Outcome::Mismatch {
+                actual: actual.to_string(),
+            },
+        },
+    }
+}
+
+pub fn run(entries: impl IntoIterator<Item = CorpusEntry>) -> CorpusReport {
+    CorpusReport {
+        results: entries
+            .into_iter()
+            .map(|entry| EntryResult {
+                outcome: check(&entry),
+                entry,
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,132 @@
+//! Instrumentation of the pipeline, enabled by the `tracing` feature. Events are delivered to
+//! the [`Subscriber`] installed on the current thread by [`with_subscriber`].
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use chumsky::{prelude::empty, Error, Parser};
+
+use crate::expr::Var;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A grammar rule is being tried.
+    Enter { rule: &'static str },
+    /// A grammar rule matched. A rule that is entered but not exited failed, and the input it
+    /// consumed was backtracked over.
+    Exit { rule: &'static str },
+    /// The lexer produced a word.
+    Token { text: String },
+    /// A variable was allocated while converting a tree to a predicate.
+    VarAllocated { var: Var },
+    /// A repair was tried while recovering from an error.
+    Recovery { action: String, success: bool },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Enter { rule } => write!(f, "enter {rule}"),
+            Event::Exit { rule } => write!(f, "exit {rule}"),
+            Event::Token { text } => write!(f, "token `{text}`"),
+            Event::VarAllocated { var } => write!(f, "allocated variable {var}"),
+            Event::Recovery { action, success } => write!(
+                f,
+                "recovery: {action} ({})",
+                if *success { "succeeded" } else { "failed" }
+            ),
+        }
+    }
+}
+
+pub trait Subscriber {
+    fn event(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Subscriber for F {
+    fn event(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+/// A subscriber keeping every event. Clones share the same record.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    events: Rc<RefCell<Vec<Event>>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+}
+
+impl Subscriber for Recorder {
+    fn event(&mut self, event: &Event) {
+        self.events.borrow_mut().push(event.clone());
+    }
+}
+
+thread_local! {
+    static SUBSCRIBER: RefCell<Option<Box<dyn Subscriber>>> = RefCell::new(None);
+}
+
+/// Restores the previous subscriber, even if the traced code panics.
+struct Restore(Option<Box<dyn Subscriber>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        SUBSCRIBER.with(|s| {
+            if let Ok(mut s) = s.try_borrow_mut() {
+                *s = previous;
+            }
+        });
+    }
+}
+
+/// Runs `f` with `subscriber` receiving the events emitted on this thread. Subscribers nest;
+/// the previous one is restored when `f` returns. Called from within a subscriber, `f` runs
+/// untraced.
+pub fn with_subscriber<R>(subscriber: impl Subscriber + 'static, f: impl FnOnce() -> R) -> R {
+    let previous = SUBSCRIBER.with(|s| {
+        s.try_borrow_mut()
+            .map(|mut s| s.replace(Box::new(subscriber)))
+    });
+    match previous {
+        Ok(previous) => {
+            let _restore = Restore(previous);
+            f()
+        }
+        Err(_) => f(),
+    }
+}
+
+/// Delivers an event to the current subscriber, if any. The event is only built when there is
+/// one to receive it. Events emitted by the subscriber itself are dropped.
+pub(crate) fn emit(event: impl FnOnce() -> Event) {
+    SUBSCRIBER.with(|s| {
+        if let Ok(mut s) = s.try_borrow_mut() {
+            if let Some(s) = s.as_mut() {
+                s.event(&event());
+            }
+        }
+    });
+}
+
+/// Wraps a grammar rule so that trying it emits [`Event::Enter`] and matching it
+/// [`Event::Exit`].
+pub(crate) fn traced<I: Clone, O, E: Error<I>>(
+    rule: &'static str,
+    p: impl Parser<I, O, Error = E> + Clone,
+) -> impl Parser<I, O, Error = E> + Clone {
+    empty()
+        .map(move |()| emit(|| Event::Enter { rule }))
+        .ignore_then(p.map(move |o| {
+            emit(|| Event::Exit { rule });
+            o
+        }))
+}
@@ -0,0 +1,132 @@
+//! Adapting foreign names and words to freeform words: letters outside the alphabet are replaced
+//! by the nearest letters, vowels are inserted where consonants cannot follow each other, and a
+//! final non-sonorant is added where the word does not end in one.
+
+use crate::{
+    lexer::{
+        chaining_of, is_freeform, ChainingFamily, PhonologyTables, PredicateFamily, PredicateWord,
+        Word, NON_SONORANT, SONORANT, VOWELS,
+    },
+    normalize::{normalize, Strictness},
+};
+
+/// Spellings of other orthographies and the letters they are adapted to, tried longest first.
+pub const SPELLINGS: [(&str, &str); 15] = [
+    ("sch", "c"),
+    ("chr", "kr"),
+    ("sh", "c"),
+    ("ch", "tc"),
+    ("zh", "j"),
+    ("th", "t"),
+    ("ph", "f"),
+    ("ck", "k"),
+    ("qu", "kv"),
+    ("q", "k"),
+    ("x", "ks"),
+    ("w", "v"),
+    ("y", "i"),
+    ("ç", "s"),
+    ("ñ", "n"),
+];
+
+/// The vowel inserted between consonants that cannot follow each other, and into a word without
+/// a vowel.
+pub const EPENTHETIC_VOWEL: char = 'e';
+
+/// The consonant put after a word ending in a vowel or a sonorant, as freeform words end in a
+/// non-sonorant.
+pub const EPENTHETIC_CONSONANT: char = 's';
+
+fn is_consonant(c: char) -> bool {
+    NON_SONORANT.contains(&c) || SONORANT.contains(&c)
+}
+
+/// `text` with the spellings of [`SPELLINGS`] replaced.
+fn respell(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut respelled = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let spelling = SPELLINGS
+            .iter()
+            .map(|(from, to)| (from.chars().collect::<Vec<_>>(), to))
+            .find(|(from, _)| chars[i..].starts_with(from));
+        match spelling {
+            Some((from, to)) => {
+                respelled.push_str(to);
+                i += from.len();
+            }
+            None => {
+                respelled.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    respelled
+}
+
+/// The letters of the alphabet `foreign` is spelled with: lowered, with [`SPELLINGS`] replaced
+/// and diacritics stripped. Other characters are dropped, `h` is kept only between vowels, and
+/// repeated letters are merged.
+fn letters(foreign: &str) -> Vec<char> {
+    let (stripped, _) = normalize(&respell(&foreign.to_lowercase()), Strictness::Strip);
+    let mut letters: Vec<char> = respell(&stripped.to_lowercase())
+        .chars()
+        .filter(|c| is_consonant(*c) || VOWELS.contains(c) || *c == 'h')
+        .collect();
+    letters.dedup();
+    let is_vowel = |i: Option<usize>| {
+        i.and_then(|i| letters.get(i))
+            .is_some_and(|c| VOWELS.contains(c))
+    };
+    let mut kept: Vec<char> = (0..letters.len())
+        .filter(|&i| letters[i] != 'h' || (is_vowel(i.checked_sub(1)) && is_vowel(Some(i + 1))))
+        .map(|i| letters[i])
+        .collect();
+    kept.dedup();
+    kept
+}
+
+/// Adapts `foreign` to the nearest freeform word, spelled so that the lexer reads it as that word,
+/// or returns `None` if it has no letter to adapt.
+///
+/// [`EPENTHETIC_VOWEL`] is inserted between consonants that are not an initial pair at the start
+/// or a medial pair elsewhere, and into a word without a vowel, and a word not ending in a
+/// non-sonorant gets [`EPENTHETIC_CONSONANT`] after it. Like every freeform word, it chains by its
+/// first place.
+pub fn adapt(foreign: &str) -> Option<Word> {
+    let letters = letters(foreign);
+    if letters.is_empty() {
+        return None;
+    }
+    let tables = PhonologyTables::STANDARD;
+    let mut word: Vec<char> = Vec::new();
+    for c in letters {
+        if let Some(&p) = word.last().filter(|p| is_consonant(**p) && is_consonant(c)) {
+            let allowed = match word.len() {
+                1 => tables.is_initial_pair(p, c),
+                n => !is_consonant(word[n - 2]) && tables.is_medial_pair(p, c),
+            };
+            if !allowed {
+                word.push(EPENTHETIC_VOWEL);
+            }
+        }
+        word.push(c);
+    }
+    if !word.iter().any(|c| VOWELS.contains(c)) {
+        word.push(EPENTHETIC_VOWEL);
+    }
+    if !word.last().is_some_and(|c| NON_SONORANT.contains(c)) {
+        word.push(EPENTHETIC_CONSONANT);
+    }
+    let word: String = word.into_iter().collect();
+    is_freeform(&word).then(|| {
+        Word::Predicate(
+            PredicateWord {
+                chaining: chaining_of(&word, ChainingFamily::Freeform),
+                word,
+            },
+            PredicateFamily::Freeform,
+        )
+    })
+}
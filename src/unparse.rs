@@ -0,0 +1,474 @@
+//! Writing predicate trees back as eberban that parses to them.
+//!
+//! A tree is first written with every `vi` clause closed by `vei` and every group by `pei`, then
+//! the terminators whose removal leaves the parse unchanged are elided, last first.
+
+use chumsky::{error::Cheap, Parser};
+
+use crate::{
+    lexer::{
+        lex_words, to_text, Number, ParticleFamily, PredicateFamily, PredicateWord, QuantifierKind,
+        Quote, Word, ZiParticle,
+    },
+    parser::{parser, Modifier, PredicateTree},
+    Argument, ChainingBehavior, Exposure, Negation, PredicateChaining, Quantifier,
+};
+
+impl PredicateTree {
+    /// Words that parse to this tree, with the terminators that can be elided left out. A tree
+    /// the parser cannot produce, such as one with an explicit exposure at the top, is written
+    /// as closely as the grammar allows, with all its terminators and every sharer after `vi`.
+    pub fn to_words(&self) -> Vec<Word> {
+        let parser = parser::<Cheap<Word>>();
+        // Numbers written next to each other would be read back as one.
+        let parses = |words: &Vec<Word>| {
+            !words.windows(2).any(|pair| {
+                matches!(
+                    pair,
+                    [
+                        Word::Particle(ParticleFamily::Number(_)),
+                        Word::Particle(ParticleFamily::Number(_))
+                    ]
+                )
+            }) && parser.parse(words.clone()).as_ref() == Ok(self)
+        };
+        let written = |direct| {
+            let mut writer = Writer {
+                direct,
+                words: Vec::new(),
+            };
+            writer.connected(self);
+            writer.words
+        };
+        // Chaining the last sharer of a binding directly, without `vi`, is tried first.
+        let Some(mut words) = [written(true), written(false)]
+            .into_iter()
+            .find(|words| parses(words))
+        else {
+            return written(false);
+        };
+        for i in (0..words.len()).rev() {
+            if !matches!(
+                words[i],
+                Word::Particle(ParticleFamily::Vei | ParticleFamily::Pei)
+            ) {
+                continue;
+            }
+            let mut candidate = words.clone();
+            candidate.remove(i);
+            if parses(&candidate) {
+                words = candidate;
+            }
+        }
+        words
+    }
+
+    /// The text of [`to_words`](Self::to_words).
+    pub fn to_text(&self) -> String {
+        to_text(&self.to_words())
+    }
+}
+
+fn particle(family: ParticleFamily) -> Word {
+    Word::Particle(family)
+}
+
+/// The word a leaf was read from: the number or quote its name spells, or the word the lexer
+/// reads its spelling as.
+fn predicate(word: &PredicateWord) -> Word {
+    if let Some(digits) = word
+        .word
+        .strip_prefix("#«")
+        .and_then(|w| w.strip_suffix('»'))
+    {
+        return particle(ParticleFamily::Number(Number {
+            digits: digits.bytes().map(|d| d.wrapping_sub(b'0')).collect(),
+        }));
+    }
+    if let Some(content) = word
+        .word
+        .strip_prefix('«')
+        .and_then(|w| w.strip_suffix('»'))
+    {
+        let quote =
+            if !content.is_empty() && !content.contains(|c: char| c.is_whitespace() || c == '\'') {
+                Quote::Word(content.to_string())
+            } else {
+                let delimiter = (1..)
+                    .map(|n| "zoi".repeat(n))
+                    .find(|d| !content.contains(d.as_str()))
+                    .unwrap_or_default();
+                Quote::Foreign {
+                    delimiter,
+                    content: content.to_string(),
+                }
+            };
+        return particle(ParticleFamily::Quote(quote));
+    }
+    match lex_words(&word.word).as_deref() {
+        Ok([word]) => word.clone(),
+        _ => Word::Predicate(word.clone(), PredicateFamily::Root),
+    }
+}
+
+fn number(n: u64) -> Word {
+    particle(ParticleFamily::Number(Number {
+        digits: n.to_string().bytes().map(|d| d - b'0').collect(),
+    }))
+}
+
+fn quantifier(quantifier: Quantifier) -> Vec<Word> {
+    let kind = |kind| particle(ParticleFamily::Quantifier(kind));
+    match quantifier {
+        Quantifier::All => vec![kind(QuantifierKind::All)],
+        Quantifier::Some => vec![kind(QuantifierKind::Some)],
+        Quantifier::Most => vec![kind(QuantifierKind::Most)],
+        Quantifier::Exactly(n) => vec![kind(QuantifierKind::Exactly), number(n)],
+    }
+}
+
+/// The explicit exposure of a tree chained after `vi`, which is written before its quantifier.
+fn arguments(tree: &PredicateTree) -> Option<&[(Argument, PredicateChaining)]> {
+    match tree {
+        PredicateTree::Binding {
+            exposure: Exposure::Explicit(args),
+            ..
+        } => Some(args),
+        PredicateTree::Quantified { tree, .. } => arguments(tree),
+        _ => None,
+    }
+}
+
+/// The negation written before a tree, or before its root if it shares out places.
+fn negation(tree: &PredicateTree) -> Negation {
+    match tree {
+        PredicateTree::Leaf { negation, .. }
+        | PredicateTree::Number { negation, .. }
+        | PredicateTree::Name { negation, .. }
+        | PredicateTree::Abstraction { negation, .. }
+        | PredicateTree::Connective { negation, .. }
+        | PredicateTree::Binding { negation, .. } => *negation,
+        PredicateTree::Quantified { .. } => Negation::None,
+    }
+}
+
+struct Writer {
+    /// Whether the last sharer of a binding chaining with it is written without `vi`.
+    direct: bool,
+    words: Vec<Word>,
+}
+
+impl Writer {
+    fn push(&mut self, family: ParticleFamily) {
+        self.words.push(particle(family));
+    }
+
+    fn negations(&mut self, negation: Negation) {
+        if negation.long() {
+            self.push(ParticleFamily::Bi);
+        }
+        if negation.short() {
+            self.push(ParticleFamily::Zi(ZiParticle::Negation));
+        }
+    }
+
+    fn arguments(&mut self, args: &[(Argument, PredicateChaining)]) {
+        for (argument, chain_with) in args {
+            let word = match (argument, chain_with) {
+                (Argument::Word(word), PredicateChaining::Sharing) => {
+                    particle(ParticleFamily::Ki(word.clone()))
+                }
+                (Argument::Word(word), PredicateChaining::Equivalence) => {
+                    predicate(&PredicateWord {
+                        word: word.clone(),
+                        chaining: ChainingBehavior {
+                            var: 0,
+                            chain_with: PredicateChaining::Equivalence,
+                        },
+                    })
+                }
+                (Argument::Number(n), _) => number(*n),
+                (Argument::Quote(quote), _) => particle(ParticleFamily::Quote(quote.clone())),
+                (Argument::Name(name), _) => Word::Predicate(
+                    PredicateWord {
+                        word: name.clone(),
+                        chaining: ChainingBehavior {
+                            var: 0,
+                            chain_with: PredicateChaining::Sharing,
+                        },
+                    },
+                    PredicateFamily::Freeform,
+                ),
+            };
+            self.words.push(word);
+        }
+        self.push(ParticleFamily::Be);
+    }
+
+    /// The free modifiers after a word.
+    fn free(&mut self, modifiers: &[Modifier]) {
+        for (i, modifier) in modifiers.iter().enumerate() {
+            match modifier {
+                Modifier::Interjection(interjection) => {
+                    self.push(ParticleFamily::Interjection(interjection.clone()))
+                }
+                Modifier::Illocution(mood) => self.push(ParticleFamily::Illocution(*mood)),
+                Modifier::Vocative {
+                    word,
+                    free,
+                    addressee,
+                } => {
+                    self.push(ParticleFamily::Vocative(word.clone()));
+                    self.free(free);
+                    self.addressee(addressee, i + 1 == modifiers.len());
+                }
+            }
+        }
+    }
+
+    /// What a vocative addresses: a leaf, number or name, or else a group. Free modifiers after
+    /// a leaf would be read as its own, so one followed by more of them is written as a group.
+    fn addressee(&mut self, tree: &PredicateTree, last: bool) {
+        match tree {
+            _ if !last => self.group(tree),
+            PredicateTree::Leaf {
+                negation: Negation::None,
+                ..
+            }
+            | PredicateTree::Number {
+                negation: Negation::None,
+                ..
+            }
+            | PredicateTree::Name {
+                negation: Negation::None,
+                ..
+            } => self.element(tree),
+            tree => self.group(tree),
+        }
+    }
+
+    fn group(&mut self, tree: &PredicateTree) {
+        self.push(ParticleFamily::Pe);
+        self.connected(tree);
+        self.push(ParticleFamily::Pei);
+    }
+
+    /// A tree where a sentence or group allows trees joined by connectives.
+    fn connected(&mut self, tree: &PredicateTree) {
+        match tree {
+            PredicateTree::Connective {
+                kind,
+                negation: Negation::None,
+                left,
+                right,
+            } => {
+                // Connectives join elements, so operands that share out places are grouped.
+                self.connected_operand(left);
+                self.push(ParticleFamily::Connective(*kind));
+                self.element(right);
+            }
+            tree => self.chaining(tree),
+        }
+    }
+
+    fn connected_operand(&mut self, tree: &PredicateTree) {
+        match tree {
+            PredicateTree::Connective {
+                negation: Negation::None,
+                ..
+            } => self.connected(tree),
+            tree => self.element(tree),
+        }
+    }
+
+    /// A tree where a chain is expected, as after `vi`.
+    fn chaining(&mut self, tree: &PredicateTree) {
+        match tree {
+            PredicateTree::Binding {
+                chaining,
+                root,
+                negation,
+                exposure,
+                sharers,
+                and,
+                tags,
+            } => {
+                let bare = sharers.iter().all(Vec::is_empty) && and.is_empty() && tags.is_empty();
+                // Arguments given to an element carry its negation to the binding they make.
+                if let (Exposure::Explicit(_), true) = (exposure, bare) {
+                    if self::negation(root) == *negation {
+                        self.element(root);
+                        return;
+                    }
+                }
+                // A `zi` before a chain negates its first element, so a negated binding is
+                // grouped unless it is a lone element exposed by `si`.
+                let si = matches!(exposure, Exposure::Transparent | Exposure::Modified(_));
+                if negation.short() && !(bare && si) {
+                    self.negations(*negation);
+                    self.push(ParticleFamily::Pe);
+                    self.chaining(&tree.clone().negate(*negation));
+                    self.push(ParticleFamily::Pei);
+                    return;
+                }
+                if si {
+                    // `si` exposes its element with the element's own negation, which the `zi`
+                    // and `bi` before a lone element then negate. A negated element after `si`
+                    // is grouped, as `zi` and `bi` cannot follow it.
+                    let root_negation = self::negation(root);
+                    self.negations(if bare {
+                        *negation ^ root_negation
+                    } else {
+                        *negation
+                    });
+                    self.push(ParticleFamily::Si {
+                        exposure: exposure.clone(),
+                        chaining: *chaining,
+                    });
+                    if root_negation == Negation::None {
+                        self.element(root);
+                    } else {
+                        self.push(ParticleFamily::Pe);
+                        self.chaining(root);
+                        self.push(ParticleFamily::Pei);
+                    }
+                } else {
+                    self.negations(*negation);
+                    self.element(root);
+                }
+                let mut links: Vec<_> = sharers
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(place, set)| {
+                        set.iter()
+                            .map(move |(chain_with, p)| ((Some(place as u8), *chain_with), p))
+                    })
+                    .chain(and.iter().map(|p| ((None, PredicateChaining::Sharing), p)))
+                    .collect();
+                let direct = links
+                    .iter()
+                    .position(|(link, p)| {
+                        *link == (Some(chaining.var), chaining.chain_with) && arguments(p).is_none()
+                    })
+                    .filter(|_| self.direct)
+                    .map(|i| links.remove(i).1);
+                for ((var, chain_with), p) in links {
+                    self.push(ParticleFamily::Vi { var, chain_with });
+                    if let Some(args) = arguments(p) {
+                        self.arguments(args);
+                    }
+                    self.chaining(p);
+                    self.push(ParticleFamily::Vei);
+                }
+                for (tag, p) in tags {
+                    self.push(ParticleFamily::Tag(tag.clone()));
+                    self.chaining(p);
+                    self.push(ParticleFamily::Vei);
+                }
+                if let Some(p) = direct {
+                    self.chaining(p);
+                }
+            }
+            PredicateTree::Quantified {
+                quantifier: q,
+                tree,
+            } => {
+                self.words.extend(quantifier(*q));
+                self.chaining(tree);
+            }
+            tree => {
+                if negation(tree).long() {
+                    self.push(ParticleFamily::Bi);
+                    self.element(&tree.clone().negate(Negation::Long));
+                } else {
+                    self.element(tree);
+                }
+            }
+        }
+    }
+
+    /// A tree where a single element is expected, grouped by `pe` if it is more than one.
+    fn element(&mut self, tree: &PredicateTree) {
+        if negation(tree).long() {
+            self.push(ParticleFamily::Pe);
+            self.chaining(tree);
+            self.push(ParticleFamily::Pei);
+            return;
+        }
+        match tree {
+            PredicateTree::Leaf {
+                word,
+                negation,
+                free,
+            } => {
+                self.negations(*negation);
+                self.words.push(predicate(word));
+                self.free(free);
+            }
+            PredicateTree::Number {
+                value,
+                negation,
+                free,
+            } => {
+                self.negations(*negation);
+                self.words.push(number(*value));
+                self.free(free);
+            }
+            PredicateTree::Name {
+                word,
+                negation,
+                free,
+            } => {
+                self.negations(*negation);
+                self.words
+                    .push(Word::Predicate(word.clone(), PredicateFamily::Freeform));
+                self.free(free);
+            }
+            PredicateTree::Abstraction {
+                kind,
+                negation,
+                tree,
+            } => {
+                self.negations(*negation);
+                self.push(ParticleFamily::Subordinator(*kind));
+                self.connected(tree);
+                self.push(ParticleFamily::Pei);
+            }
+            PredicateTree::Connective { negation, .. } => {
+                self.negations(*negation);
+                self.push(ParticleFamily::Pe);
+                self.connected(&tree.clone().negate(*negation));
+                self.push(ParticleFamily::Pei);
+            }
+            PredicateTree::Binding { .. } | PredicateTree::Quantified { .. } => {
+                self.push(ParticleFamily::Pe);
+                self.chaining(tree);
+                self.push(ParticleFamily::Pei);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate::sentences, parse};
+    use proptest::{prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn trees_are_written_back(s in "( (mi|dona|mian|alis|vi|va|fi|fe|vei|be|pe|pei|zi|bi|ba|bo|da|de|du|ta|su|kie|gie|sia|si|ki|gi|a|'o|'na))*") {
+            if let Ok(tree) = parse(&s) {
+                let text = tree.to_text();
+                prop_assert_eq!(parse(&text).ok(), Some(tree), "{:?} written as {:?}", s, text);
+            }
+        }
+
+        #[test]
+        fn tagged_trees_are_written_back(s in sentences()) {
+            if let Ok(tree) = parse(&s) {
+                let text = tree.to_text();
+                prop_assert_eq!(parse(&text).ok(), Some(tree), "{:?} written as {:?}", s, text);
+            }
+        }
+    }
+}
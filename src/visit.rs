@@ -0,0 +1,204 @@
+//! Traversals of predicate trees, so that tools looking for words or rewriting subtrees need not
+//! follow the structure of bindings themselves.
+
+use crate::{parser::PredicateTree, PredicateChaining};
+
+/// Whether a traversal visits a tree before or after the trees nested in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Order {
+    Pre,
+    Post,
+}
+
+/// Where a tree sits in the binding it is nested in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Position {
+    /// The root, whose places the binding shares out.
+    Root,
+    /// A tree chained to a place of the root.
+    Sharer {
+        place: usize,
+        chain_with: PredicateChaining,
+    },
+    /// A tree joined to the whole binding by chaining after it.
+    And,
+    /// A tree attached to a place of the root by a tag.
+    Tag { place: usize },
+}
+
+/// A binding enclosing a tree, and where in it the tree sits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Context<'a> {
+    pub binding: &'a PredicateTree,
+    pub position: Position,
+}
+
+/// A tree with no trees nested in it: a leaf, a number or a name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeafInContext<'a> {
+    pub leaf: &'a PredicateTree,
+    /// The bindings enclosing the leaf, outermost first.
+    pub bindings: Vec<Context<'a>>,
+}
+
+impl PredicateTree {
+    /// The trees nested directly in this one: the root of a binding, then its sharers by place,
+    /// then the trees chained after it, then those its tags attach; or the operands of a connective; or the tree a
+    /// quantifier or subordinator applies to.
+    pub fn children(&self) -> Vec<&PredicateTree> {
+        match self {
+            PredicateTree::Leaf { .. }
+            | PredicateTree::Number { .. }
+            | PredicateTree::Name { .. } => Vec::new(),
+            PredicateTree::Binding {
+                root,
+                sharers,
+                and,
+                tags,
+                ..
+            } => std::iter::once(&**root)
+                .chain(sharers.iter().flatten().map(|(_, p)| p))
+                .chain(and)
+                .chain(tags.iter().map(|(_, p)| p))
+                .collect(),
+            PredicateTree::Connective { left, right, .. } => vec![left, right],
+            PredicateTree::Abstraction { tree, .. } | PredicateTree::Quantified { tree, .. } => {
+                vec![tree]
+            }
+        }
+    }
+
+    /// Calls `f` with this tree and every tree nested in it, each before or after the trees
+    /// nested in it as `order` says. The trees are kept on a stack of their own, so that however
+    /// deep they are nested, the walk does not recurse.
+    pub fn walk<'a>(&'a self, order: Order, f: &mut impl FnMut(&'a PredicateTree)) {
+        // Each tree, and whether the trees nested in it are already on the stack.
+        let mut stack = vec![(self, false)];
+        while let Some((tree, expanded)) = stack.pop() {
+            if expanded {
+                f(tree);
+                continue;
+            }
+            match order {
+                Order::Pre => f(tree),
+                Order::Post => stack.push((tree, true)),
+            }
+            stack.extend(
+                tree.children()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, false)),
+            );
+        }
+    }
+
+    /// Like [`walk`](Self::walk), letting `f` rewrite each tree. With [`Order::Pre`], the trees
+    /// nested in a tree are visited as `f` left them.
+    pub fn visit_mut(&mut self, order: Order, f: &mut impl FnMut(&mut PredicateTree)) {
+        if order == Order::Pre {
+            f(self);
+        }
+        match self {
+            PredicateTree::Leaf { .. }
+            | PredicateTree::Number { .. }
+            | PredicateTree::Name { .. } => {}
+            PredicateTree::Binding {
+                root,
+                sharers,
+                and,
+                tags,
+                ..
+            } => {
+                root.visit_mut(order, f);
+                for (_, p) in sharers.iter_mut().flatten() {
+                    p.visit_mut(order, f);
+                }
+                for p in and.iter_mut() {
+                    p.visit_mut(order, f);
+                }
+                for (_, p) in tags.iter_mut() {
+                    p.visit_mut(order, f);
+                }
+            }
+            PredicateTree::Connective { left, right, .. } => {
+                left.visit_mut(order, f);
+                right.visit_mut(order, f);
+            }
+            PredicateTree::Abstraction { tree, .. } | PredicateTree::Quantified { tree, .. } => {
+                tree.visit_mut(order, f)
+            }
+        }
+        if order == Order::Post {
+            f(self);
+        }
+    }
+
+    /// The trees with no trees nested in them, in the order of [`children`](Self::children), with
+    /// the bindings enclosing each.
+    pub fn leaves(&self) -> impl Iterator<Item = LeafInContext<'_>> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut Vec::new(), &mut leaves);
+        leaves.into_iter()
+    }
+
+    fn collect_leaves<'a>(
+        &'a self,
+        bindings: &mut Vec<Context<'a>>,
+        leaves: &mut Vec<LeafInContext<'a>>,
+    ) {
+        match self {
+            PredicateTree::Leaf { .. }
+            | PredicateTree::Number { .. }
+            | PredicateTree::Name { .. } => leaves.push(LeafInContext {
+                leaf: self,
+                bindings: bindings.clone(),
+            }),
+            PredicateTree::Binding {
+                root,
+                sharers,
+                and,
+                tags,
+                ..
+            } => {
+                let mut nested = |tree: &'a PredicateTree, position| {
+                    bindings.push(Context {
+                        binding: self,
+                        position,
+                    });
+                    tree.collect_leaves(bindings, leaves);
+                    bindings.pop();
+                };
+                nested(root, Position::Root);
+                for (place, set) in sharers.iter().enumerate() {
+                    for (chain_with, p) in set {
+                        nested(
+                            p,
+                            Position::Sharer {
+                                place,
+                                chain_with: *chain_with,
+                            },
+                        );
+                    }
+                }
+                for p in and {
+                    nested(p, Position::And);
+                }
+                for (tag, p) in tags {
+                    nested(
+                        p,
+                        Position::Tag {
+                            place: tag.modified_place() as usize,
+                        },
+                    );
+                }
+            }
+            PredicateTree::Connective { .. }
+            | PredicateTree::Abstraction { .. }
+            | PredicateTree::Quantified { .. } => {
+                for child in self.children() {
+                    child.collect_leaves(bindings, leaves);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::thread;
+
+use chumsky::{error::Cheap, Parser};
+use eberban::{
+    expr,
+    lexer::{lex, Word},
+    parser::{ParserOptions, PredicateTree},
+};
+
+/// The deepest sentence made of `unit` repeated and a last `mian` that parses however deep the
+/// parser is allowed to read.
+fn deepest(unit: &str) -> PredicateTree {
+    let parser = ParserOptions::new()
+        .max_depth(usize::MAX)
+        .parser::<Cheap<Word>>();
+    let parse = |n: usize| {
+        let words = lex(&format!("{}mian", unit.repeat(n))).unwrap();
+        parser.parse(words).ok()
+    };
+    let (mut shallow, mut deep) = (0, 1000);
+    while deep - shallow > 1 {
+        let n = (shallow + deep) / 2;
+        match parse(n) {
+            Some(_) => shallow = n,
+            None => deep = n,
+        }
+    }
+    parse(shallow).unwrap()
+}
+
+/// Runs `f` on a thread with the stack a spawned thread gets by default.
+fn on_small_stack(f: impl FnOnce() + Send + 'static) {
+    thread::Builder::new()
+        .stack_size(2 << 20)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn deepest_trees_can_be_walked() {
+    for unit in [
+        "mian vi ",
+        "mian vi du ",
+        "mian fi du mian vi ",
+        "dona fe du ",
+        "mian ba ",
+        "mian vi zi ",
+        "pe ",
+        "da ",
+    ] {
+        on_small_stack(move || {
+            let tree = deepest(unit);
+            let (pred, _) = expr::to_expr(tree.clone());
+            let _ = pred.to_string();
+            let _ = format!("{pred:?}");
+            let _ = expr::to_expr_canonical(tree.clone());
+            let _ = format!("{tree:?}");
+        });
+    }
+}
+
+#[test]
+fn deepest_trees_can_be_unparsed() {
+    for unit in ["mian vi du ", "mian ba ", "pe "] {
+        on_small_stack(move || {
+            let tree = deepest(unit);
+            assert_eq!(eberban::parse(&tree.to_text()).ok(), Some(tree));
+        });
+    }
+}
+
+#[test]
+fn max_depth_is_capped() {
+    on_small_stack(|| {
+        let words = lex(&format!("{}mian", "mian vi ".repeat(20000))).unwrap();
+        let parsed = ParserOptions::new()
+            .max_depth(usize::MAX)
+            .parser::<Cheap<Word>>()
+            .parse(words);
+        assert!(parsed.is_err());
+    });
+}
@@ -0,0 +1,66 @@
+use eberban::{
+    lexer::{Interjection, Mood},
+    parser::{Modifier, PredicateTree},
+    visit::Order,
+};
+
+/// The free modifiers kept by the leaf, number or name `word` of `tree`.
+fn free<'a>(tree: &'a PredicateTree, word: &str) -> Option<&'a [Modifier]> {
+    let mut found = None;
+    tree.walk(Order::Pre, &mut |tree| {
+        if let PredicateTree::Leaf { word: w, free, .. } = tree {
+            if w.word == word {
+                found = Some(free.as_slice());
+            }
+        }
+    });
+    found
+}
+
+#[test]
+fn free_modifiers_are_kept_on_the_word_they_follow() {
+    let tree = eberban::parse("'na mi dona 'o pe mian 'ahe pei a").unwrap();
+    assert_eq!(
+        free(&tree, "mi").unwrap(),
+        [Modifier::Interjection(Interjection::new("na".to_string()))]
+    );
+    let [Modifier::Vocative {
+        word,
+        free: vocative,
+        addressee,
+    }, Modifier::Illocution(Mood::Assertion)] = free(&tree, "dona").unwrap()
+    else {
+        panic!("{tree:?}");
+    };
+    assert_eq!(word, "o");
+    assert!(vocative.is_empty());
+    assert_eq!(
+        free(addressee, "mian").unwrap(),
+        [Modifier::Interjection(Interjection::new("ahe".to_string()))]
+    );
+}
+
+#[test]
+fn free_modifiers_are_written_back() {
+    for text in [
+        "mi dona mian a",
+        "mi na dona mian",
+        "mi o ahe mian na dona mian a",
+        "mi o pe mian pei o na dona ahe dona",
+        "mi o pe zi mian pei dona",
+    ] {
+        let tree = eberban::parse(text).unwrap();
+        assert_eq!(tree.to_text(), text);
+    }
+}
+
+#[cfg(feature = "handwritten-parser")]
+#[test]
+fn handwritten_parser_keeps_free_modifiers() {
+    let text = "'na mi 'o 'ahe pe mian na pei na dona 'o kie 'ahe vi 'na ki be mian a";
+    let words = eberban::lexer::lex(text).unwrap();
+    assert_eq!(
+        eberban::handwritten::parse_sentence(&words).unwrap(),
+        eberban::parse_sentence(text).unwrap()
+    );
+}
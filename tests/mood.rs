@@ -0,0 +1,11 @@
+use eberban::{expr::Predicate, lexer::Mood};
+
+#[test]
+fn mood_wraps_the_expression() {
+    let (expr, _) = eberban::parse_to_expr("mi dona mian u").unwrap();
+    assert!(matches!(expr, Predicate::Imperative(_)), "{expr:?}");
+    let (expr, _) = eberban::parse_to_expr("mi dona mian e").unwrap();
+    assert!(matches!(expr, Predicate::Interrogative(_)), "{expr:?}");
+    let sentence = eberban::parse_sentence("mi dona mian u").unwrap();
+    assert_eq!(sentence.mood, Some(Mood::Imperative));
+}
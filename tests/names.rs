@@ -0,0 +1,58 @@
+use eberban::{
+    lexer::{lex, PredicateFamily, Word},
+    parser::PredicateTree,
+    Argument, Exposure, PredicateChaining,
+};
+
+#[test]
+fn freeform_word_lexes_as_freeform() {
+    let words = lex("mi alis").unwrap();
+    assert!(matches!(
+        &words[..],
+        [_, Word::Predicate(pw, PredicateFamily::Freeform)] if pw.word == "alis"
+    ));
+}
+
+#[test]
+fn chained_name() {
+    let tree = eberban::parse("alis").unwrap();
+    assert!(matches!(tree, PredicateTree::Name { word, .. } if word.word == "alis"));
+}
+
+#[test]
+fn name_argument() {
+    let tree = eberban::parse("dona vi alis be mi").unwrap();
+    let PredicateTree::Binding { and, .. } = tree else {
+        panic!("not a binding: {tree:?}");
+    };
+    let [PredicateTree::Binding {
+        exposure: Exposure::Explicit(arguments),
+        ..
+    }] = &and[..]
+    else {
+        panic!("no argument list: {and:?}");
+    };
+    assert_eq!(
+        arguments,
+        &[(
+            Argument::Name("alis".to_string()),
+            PredicateChaining::Sharing
+        )]
+    );
+}
+
+#[test]
+fn name_is_a_constant() {
+    let (expr, _) = eberban::parse_to_expr("dona vi alis be mi").unwrap();
+    assert!(expr.to_string().contains("⟨alis⟩"));
+}
+
+#[cfg(feature = "handwritten-parser")]
+#[test]
+fn handwritten_parser_reads_names() {
+    let words = lex("dona vi alis be mi").unwrap();
+    assert_eq!(
+        eberban::handwritten::parse(&words).unwrap(),
+        eberban::parse("dona vi alis be mi").unwrap()
+    );
+}
@@ -0,0 +1,54 @@
+use chumsky::{error::Cheap, Parser};
+use eberban::{
+    expr,
+    lexer::{lex, Word},
+    parser::{ParserOptions, PredicateTree},
+    Negation,
+};
+
+fn parse(text: &str, normalize: bool) -> PredicateTree {
+    ParserOptions::new()
+        .normalize_negation(normalize)
+        .parser::<Cheap<Word>>()
+        .parse(lex(text).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn long_negation_is_hoisted_to_a_binding() {
+    let PredicateTree::Binding {
+        root,
+        negation: Negation::Long,
+        sharers,
+        ..
+    } = parse("bi zi mian", true)
+    else {
+        panic!("not a binding");
+    };
+    assert!(sharers.is_empty());
+    assert!(matches!(
+        *root,
+        PredicateTree::Leaf {
+            negation: Negation::Short,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn normalized_negation_keeps_the_expression() {
+    for text in [
+        "bi mian",
+        "bi zi mian",
+        "pe bi mian pei vi mi",
+        "bi pe zi mian pei vi mi",
+        "mian vi bi mi",
+        "bi mian ba dona",
+        "bi da kie",
+        "dona de bi do mi",
+    ] {
+        let (written, _) = expr::to_expr(parse(text, false));
+        let (normalized, _) = expr::to_expr(parse(text, true));
+        assert_eq!(normalized.to_string(), written.to_string(), "{text}");
+    }
+}
@@ -0,0 +1,23 @@
+use eberban::{
+    diagnostics::{check_with_dictionary, Code},
+    dictionary::Dictionary,
+};
+
+fn codes(text: &str) -> Vec<Code> {
+    let dictionary =
+        Dictionary::from_csv("word,family,definition\ndona,root,[E] loves [A].\n").unwrap();
+    check_with_dictionary(text, Some(&dictionary))
+        .into_iter()
+        .map(|d| d.code)
+        .collect()
+}
+
+#[test]
+fn place_past_the_dictionary_arity_is_extra() {
+    assert_eq!(codes("dona vo mi"), [Code::ExtraPlace]);
+    assert_eq!(
+        codes("pe dona pei vo mi"),
+        [Code::RedundantPei, Code::ExtraPlace]
+    );
+    assert!(codes("dona va mi").is_empty());
+}
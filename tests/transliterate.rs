@@ -0,0 +1,30 @@
+use eberban::{lexer::lex, transliterate::adapt};
+
+#[test]
+fn adapted_words_lex_as_themselves() {
+    for foreign in [
+        "Alice",
+        "John",
+        "Schmidt",
+        "Maria",
+        "Xi",
+        "Tchaikovsky",
+        "Zoë",
+        "Muñoz",
+        "Bob",
+        "Nguyen",
+        "Dvořák",
+        "Quentin",
+        "Ahmed",
+        "Wright",
+        "Oyelaran",
+    ] {
+        let word = adapt(foreign).unwrap();
+        assert_eq!(lex(&word.text()), Ok(vec![word]), "{foreign}");
+    }
+}
+
+#[test]
+fn nothing_to_adapt() {
+    assert_eq!(adapt("123 !?"), None);
+}